@@ -0,0 +1,543 @@
+use alignment::Alignment;
+use sequence::{Sequence, SequenceElement};
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+
+/// A single operation of a CIGAR string as defined by the SAM specification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CigarOperation {
+    /// Alignment match which can be a sequence match or mismatch (`M`)
+    Match,
+    /// Insertion into the template (`I`)
+    Insertion,
+    /// Deletion from the template (`D`)
+    Deletion,
+    /// Skipped template region like an intron (`N`)
+    Skip,
+    /// Soft clipped bases that are part of the read sequence (`S`)
+    SoftClip,
+    /// Hard clipped bases that are not part of the read sequence (`H`)
+    HardClip,
+    /// Silent deletion from a padded reference (`P`)
+    Padding,
+    /// Sequence match (`=`)
+    SequenceMatch,
+    /// Sequence mismatch (`X`)
+    SequenceMismatch,
+}
+
+impl CigarOperation {
+    /// Returns the operation for a CIGAR character
+    pub fn from_code(code: char) -> Option<Self> {
+        match code {
+            'M' => Some(CigarOperation::Match),
+            'I' => Some(CigarOperation::Insertion),
+            'D' => Some(CigarOperation::Deletion),
+            'N' => Some(CigarOperation::Skip),
+            'S' => Some(CigarOperation::SoftClip),
+            'H' => Some(CigarOperation::HardClip),
+            'P' => Some(CigarOperation::Padding),
+            '=' => Some(CigarOperation::SequenceMatch),
+            'X' => Some(CigarOperation::SequenceMismatch),
+            _ => None,
+        }
+    }
+
+    /// Returns the CIGAR character of the operation
+    pub fn code(&self) -> char {
+        match *self {
+            CigarOperation::Match => 'M',
+            CigarOperation::Insertion => 'I',
+            CigarOperation::Deletion => 'D',
+            CigarOperation::Skip => 'N',
+            CigarOperation::SoftClip => 'S',
+            CigarOperation::HardClip => 'H',
+            CigarOperation::Padding => 'P',
+            CigarOperation::SequenceMatch => '=',
+            CigarOperation::SequenceMismatch => 'X',
+        }
+    }
+
+    /// Returns `true` if the operation consumes bases of the read sequence
+    pub fn consumes_query(&self) -> bool {
+        "MIS=X".contains(self.code())
+    }
+
+    /// Returns `true` if the operation consumes template positions
+    pub fn consumes_reference(&self) -> bool {
+        "MDN=X".contains(self.code())
+    }
+
+    /// Returns `true` if read bases are aligned against template positions
+    pub fn is_aligned(&self) -> bool {
+        "M=X".contains(self.code())
+    }
+}
+
+/// The operations of an alignment as described by a CIGAR string like `8M2I4M`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cigar {
+    operations: Vec<(usize, CigarOperation)>,
+}
+
+impl Cigar {
+    /// Creates an empty CIGAR which is written as `*`
+    pub fn new() -> Self {
+        Cigar { operations: Vec::new() }
+    }
+
+    /// Appends an operation. Operations are not merged with the previous one.
+    pub fn with_operation(mut self, length: usize, operation: CigarOperation) -> Self {
+        self.operations.push((length, operation));
+        self
+    }
+
+    /// Appends an operation or extends the last operation if it is the same
+    fn push_merged(&mut self, length: usize, operation: CigarOperation) {
+        if length == 0 {
+            return;
+        }
+        match self.operations.last_mut() {
+            Some(last) if last.1 == operation => last.0 += length,
+            _ => self.operations.push((length, operation)),
+        }
+    }
+
+    /// Returns the operations with their lengths
+    pub fn operations(&self) -> &[(usize, CigarOperation)] {
+        &self.operations
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Returns the number of read bases that are consumed by the CIGAR
+    pub fn query_length(&self) -> usize {
+        self.operations
+            .iter()
+            .filter(|o| o.1.consumes_query())
+            .map(|o| o.0)
+            .sum()
+    }
+
+    /// Returns the number of template positions covered by the alignment
+    pub fn reference_length(&self) -> usize {
+        self.operations
+            .iter()
+            .filter(|o| o.1.consumes_reference())
+            .map(|o| o.0)
+            .sum()
+    }
+
+    /// Converts the CIGAR into an alignment of the sequence against the template
+    /// starting at the 0-based `template_offset`. Soft clips become unaligned
+    /// segments, skipped regions and clips that are not part of the sequence are
+    /// not represented.
+    pub fn alignment<E: SequenceElement, S: Sequence<E>>(
+        &self,
+        template: Option<S>,
+        sequence: S,
+        template_offset: usize,
+    ) -> Alignment<E, S> {
+        let mut alignment = Alignment::new(template, sequence);
+        let mut template_pos = template_offset;
+        let mut sequence_pos = 0usize;
+
+        for &(l, op) in self.operations.iter() {
+            match op {
+                CigarOperation::Match | CigarOperation::SequenceMatch | CigarOperation::SequenceMismatch => {
+                    alignment.add_segment_aligned(sequence_pos, l, template_pos, l, false);
+                    sequence_pos += l;
+                    template_pos += l;
+                }
+                CigarOperation::Insertion => {
+                    alignment.add_segment_aligned(sequence_pos, l, template_pos, 0, false);
+                    sequence_pos += l;
+                }
+                CigarOperation::Deletion => {
+                    alignment.add_segment_aligned(sequence_pos, 0, template_pos, l, false);
+                    template_pos += l;
+                }
+                CigarOperation::Skip => template_pos += l,
+                CigarOperation::SoftClip => {
+                    alignment.add_segment_unaligned(sequence_pos, l);
+                    sequence_pos += l;
+                }
+                CigarOperation::HardClip | CigarOperation::Padding => {}
+            }
+        }
+        alignment.canonicalize()
+    }
+}
+
+impl<'a, E: SequenceElement, S: Sequence<E>> From<&'a Alignment<E, S>> for Cigar {
+    /// Creates the CIGAR of an alignment. Unaligned segments are reported as
+    /// soft clips and segments that cover a different number of template and
+    /// sequence elements are split into a match and an insertion or deletion.
+    fn from(alignment: &'a Alignment<E, S>) -> Cigar {
+        let mut cigar = Cigar::new();
+        for segment in alignment.segments().iter() {
+            let sequence_length = segment.sequence_length();
+            match segment.template_length() {
+                Some(template_length) if segment.template_offset().is_some() => {
+                    let matched = cmp::min(template_length, sequence_length);
+                    cigar.push_merged(matched, CigarOperation::Match);
+                    cigar.push_merged(sequence_length - matched, CigarOperation::Insertion);
+                    cigar.push_merged(template_length - matched, CigarOperation::Deletion);
+                }
+                _ => cigar.push_merged(sequence_length, CigarOperation::SoftClip),
+            }
+        }
+        cigar
+    }
+}
+
+impl FromStr for Cigar {
+    type Err = String;
+
+    /// Parses a CIGAR string. The string `*` is parsed as empty CIGAR.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut cigar = Cigar::new();
+        if s == "*" {
+            return Ok(cigar);
+        }
+        let mut length = String::new();
+        for c in s.chars() {
+            if c.is_digit(10) {
+                length.push(c);
+                continue;
+            }
+            match CigarOperation::from_code(c) {
+                Some(op) if !length.is_empty() => {
+                    let l = length.parse::<usize>().map_err(|e| format!("Can not parse CIGAR '{}': {}", s, e))?;
+                    cigar = cigar.with_operation(l, op);
+                    length.clear();
+                }
+                _ => return Err(format!("Can not parse CIGAR '{}'", s)),
+            }
+        }
+        match length.is_empty() {
+            true => Ok(cigar),
+            false => Err(format!("Can not parse CIGAR '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Cigar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.operations.is_empty() {
+            return write!(f, "*");
+        }
+        for &(l, op) in self.operations.iter() {
+            write!(f, "{}{}", l, op.code())?;
+        }
+        Ok(())
+    }
+}
+
+
+/// A part of an MD tag
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MdOperation {
+    /// Number of template bases that match the read
+    Match(usize),
+    /// Template base that differs from the read
+    Mismatch(char),
+    /// Template bases that are deleted in the read
+    Deletion(String),
+}
+
+/// The MD tag describes the template bases at mismatches and deletions of an
+/// alignment. Together with the CIGAR and the read sequence it allows to
+/// reconstruct the aligned part of the template.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MdTag {
+    operations: Vec<MdOperation>,
+}
+
+impl MdTag {
+    /// Computes the MD tag of a read by comparing it to the template. The
+    /// `reference` is the template sequence starting at the alignment position
+    /// and covering the reference length of the CIGAR. Bases are compared case
+    /// insensitive.
+    pub fn generate(cigar: &Cigar, read: &str, reference: &str) -> Result<Self, String> {
+        let read: Vec<char> = read.chars().map(|c| c.to_ascii_uppercase()).collect();
+        let reference: Vec<char> = reference.chars().map(|c| c.to_ascii_uppercase()).collect();
+        if cigar.query_length() != read.len() || cigar.reference_length() > reference.len() {
+            return Err(format!(
+                "CIGAR {} does not match read length {} and reference length {}",
+                cigar,
+                read.len(),
+                reference.len()
+            ));
+        }
+
+        let mut md = MdTag::default();
+        let (mut q, mut r) = (0usize, 0usize);
+        for &(l, op) in cigar.operations() {
+            if op.is_aligned() {
+                for i in 0..l {
+                    match read[q + i] == reference[r + i] {
+                        true => md.push(MdOperation::Match(1)),
+                        false => md.push(MdOperation::Mismatch(reference[r + i])),
+                    }
+                }
+            } else if op == CigarOperation::Deletion {
+                md.push(MdOperation::Deletion(reference[r..r + l].iter().collect()));
+            }
+            if op.consumes_query() {
+                q += l;
+            }
+            if op.consumes_reference() {
+                r += l;
+            }
+        }
+        Ok(md)
+    }
+
+    /// Appends an operation and merges consecutive matches and deletions
+    fn push(&mut self, operation: MdOperation) {
+        match (self.operations.last_mut(), operation) {
+            (Some(&mut MdOperation::Match(ref mut n)), MdOperation::Match(m)) => *n += m,
+            (Some(&mut MdOperation::Deletion(ref mut s)), MdOperation::Deletion(ref t)) => s.push_str(t),
+            (_, operation) => self.operations.push(operation),
+        }
+    }
+
+    pub fn operations(&self) -> &[MdOperation] {
+        &self.operations
+    }
+
+    /// Returns the number of mismatching bases
+    pub fn mismatches(&self) -> usize {
+        self.operations
+            .iter()
+            .filter(|o| match **o {
+                MdOperation::Mismatch(_) => true,
+                _ => false,
+            })
+            .count()
+    }
+
+    /// Returns the edit distance to the template (the value of the NM tag),
+    /// which is the number of mismatches, inserted and deleted bases.
+    pub fn edit_distance(&self, cigar: &Cigar) -> usize {
+        let indels: usize = cigar
+            .operations()
+            .iter()
+            .filter(|o| o.1 == CigarOperation::Insertion || o.1 == CigarOperation::Deletion)
+            .map(|o| o.0)
+            .sum();
+        self.mismatches() + indels
+    }
+
+    /// Reconstructs the template sequence covered by the alignment from the
+    /// read and the MD tag. Skipped template regions (`N`) are not described by
+    /// the MD tag and are filled with `N`.
+    pub fn reference(&self, cigar: &Cigar, read: &str) -> Result<String, String> {
+        let read: Vec<char> = read.chars().collect();
+        if cigar.query_length() != read.len() {
+            return Err(format!("CIGAR {} does not match read length {}", cigar, read.len()));
+        }
+
+        // Expand the MD tag into one entry per template base with the flag that
+        // tells if the base is deleted and the base if it is not a match
+        let mut bases: Vec<(bool, Option<char>)> = Vec::new();
+        for operation in self.operations.iter() {
+            match *operation {
+                MdOperation::Match(n) => bases.extend((0..n).map(|_| (false, None))),
+                MdOperation::Mismatch(c) => bases.push((false, Some(c))),
+                MdOperation::Deletion(ref s) => bases.extend(s.chars().map(|c| (true, Some(c)))),
+            }
+        }
+        let mismatch = || format!("MD tag {} does not match CIGAR {}", self, cigar);
+
+        let mut reference = String::with_capacity(cigar.reference_length());
+        let mut bases = bases.into_iter();
+        let mut q = 0usize;
+        for &(l, op) in cigar.operations() {
+            match op {
+                _ if op.is_aligned() => {
+                    for i in 0..l {
+                        match bases.next() {
+                            Some((false, Some(c))) => reference.push(c),
+                            Some((false, None)) => reference.push(read[q + i]),
+                            _ => return Err(mismatch()),
+                        }
+                    }
+                }
+                CigarOperation::Deletion => {
+                    for _ in 0..l {
+                        match bases.next() {
+                            Some((true, Some(c))) => reference.push(c),
+                            _ => return Err(mismatch()),
+                        }
+                    }
+                }
+                CigarOperation::Skip => reference.extend((0..l).map(|_| 'N')),
+                _ => {}
+            }
+            if op.consumes_query() {
+                q += l;
+            }
+        }
+        match bases.next() {
+            None => Ok(reference),
+            Some(_) => Err(mismatch()),
+        }
+    }
+}
+
+impl FromStr for MdTag {
+    type Err = String;
+
+    /// Parses an MD tag like `10A5^AC6`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut md = MdTag::default();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_digit(10) {
+                let mut n = c.to_digit(10).unwrap() as usize;
+                while let Some(d) = chars.peek().and_then(|d| d.to_digit(10)) {
+                    n = n * 10 + d as usize;
+                    chars.next();
+                }
+                if n > 0 {
+                    md.push(MdOperation::Match(n));
+                }
+            } else if c == '^' {
+                let mut deleted = String::new();
+                while let Some(&d) = chars.peek() {
+                    if !d.is_alphabetic() {
+                        break;
+                    }
+                    deleted.push(d);
+                    chars.next();
+                }
+                if deleted.is_empty() {
+                    return Err(format!("Can not parse MD tag '{}'", s));
+                }
+                // Two deletions are never adjacent in a valid tag
+                md.operations.push(MdOperation::Deletion(deleted));
+            } else if c.is_alphabetic() {
+                md.operations.push(MdOperation::Mismatch(c));
+            } else {
+                return Err(format!("Can not parse MD tag '{}'", s));
+            }
+        }
+        Ok(md)
+    }
+}
+
+impl fmt::Display for MdTag {
+    /// Writes the tag with a (possibly zero) match count before, between and
+    /// after all mismatches and deletions as required by the SAM specification
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut after_match = false;
+        for operation in self.operations.iter() {
+            match *operation {
+                MdOperation::Match(n) => {
+                    write!(f, "{}", n)?;
+                    after_match = true;
+                    continue;
+                }
+                _ if !after_match => write!(f, "0")?,
+                _ => {}
+            }
+            match *operation {
+                MdOperation::Mismatch(c) => write!(f, "{}", c)?,
+                MdOperation::Deletion(ref s) => write!(f, "^{}", s)?,
+                MdOperation::Match(_) => {}
+            }
+            after_match = false;
+        }
+        match after_match {
+            true => Ok(()),
+            false => write!(f, "0"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alignment::{Alignment, Cigar, CigarOperation, MdTag};
+    use alignment::cigar::MdOperation;
+    use sequence::{DnaNucleotide, DnaSequence};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_cigar() {
+        let cigar = Cigar::from_str("5H2S3M2I2M1D3M14N2M").unwrap();
+        assert_eq!(cigar.len(), 9);
+        assert_eq!(cigar.operations()[0], (5, CigarOperation::HardClip));
+        assert_eq!((cigar.query_length(), cigar.reference_length()), (14, 25));
+        assert_eq!(cigar.to_string(), "5H2S3M2I2M1D3M14N2M");
+        assert_eq!(Cigar::from_str("1P1I1P1I").unwrap().to_string(), "1P1I1P1I");
+        assert!(Cigar::from_str("*").unwrap().is_empty());
+        assert_eq!(Cigar::new().to_string(), "*");
+
+        assert!(Cigar::from_str("4Q").is_err());
+        assert!(Cigar::from_str("M").is_err());
+        assert!(Cigar::from_str("4M3").is_err());
+    }
+
+    #[test]
+    fn test_alignment_conversion() {
+        let template = DnaSequence::from_str("TTAGAGACTAC").unwrap();
+        let sequence = DnaSequence::from_str("CCAGAGGGATAC").unwrap();
+        let cigar = Cigar::from_str("2S3M2I2M1D3M").unwrap();
+        let alignment: Alignment<DnaNucleotide, DnaSequence> = cigar.alignment(Some(template), sequence, 2);
+        let segments = alignment.segments();
+        assert_eq!(segments.len(), 6);
+        assert_eq!((segments[1].template_offset(), segments[1].sequence_offset()), (Some(2), 2));
+        assert!(segments[4].is_deletion());
+        assert_eq!(Cigar::from(&alignment), cigar);
+
+        let cigar = Cigar::from_str("3=1X2=").unwrap();
+        let alignment: Alignment<DnaNucleotide, DnaSequence> =
+            cigar.alignment(None, DnaSequence::from_str("ACGTAC").unwrap(), 0);
+        assert_eq!(Cigar::from(&alignment).to_string(), "6M");
+    }
+
+    #[test]
+    fn test_md_tag() {
+        let md = MdTag::from_str("3C3T1^GCTCAG0A4").unwrap();
+        assert_eq!(md.operations()[1], MdOperation::Mismatch('C'));
+        assert_eq!(md.operations()[5], MdOperation::Deletion("GCTCAG".to_string()));
+        assert_eq!(md.mismatches(), 3);
+        assert_eq!(md.to_string(), "3C3T1^GCTCAG0A4");
+        assert_eq!(MdTag::from_str("0A0^C0").unwrap().to_string(), "0A0^C0");
+        assert_eq!(MdTag::from_str("10").unwrap().to_string(), "10");
+        assert!(MdTag::from_str("3^2").is_err());
+        assert!(MdTag::from_str("3-2").is_err());
+    }
+
+    #[test]
+    fn test_generate_and_reconstruct() {
+        // r001 of the SAM specification example
+        let cigar = Cigar::from_str("8M4I4M1D3M").unwrap();
+        let read = "TTAGATAAAGAGGATACTG";
+        let reference = "TTAGATAAGATAGCTG";
+        let md = MdTag::generate(&cigar, read, &reference.to_lowercase()).unwrap();
+        assert_eq!(md.to_string(), "12^G3");
+        assert_eq!(md.edit_distance(&cigar), 5);
+        assert_eq!(md.reference(&cigar, read).unwrap(), reference);
+
+        let cigar = Cigar::from_str("2S3M2N2M").unwrap();
+        let md = MdTag::generate(&cigar, "AACGTAC", "CCTTTAG").unwrap();
+        assert_eq!(md.to_string(), "1C2G0");
+        assert_eq!(md.edit_distance(&cigar), 2);
+        assert_eq!(md.reference(&cigar, "AACGTAC").unwrap(), "CCTNNAG");
+
+        assert!(MdTag::generate(&cigar, "ACGT", "CCTTTAG").is_err());
+        assert!(MdTag::from_str("4").unwrap().reference(&cigar, "AACGTAC").is_err());
+        assert!(MdTag::from_str("6").unwrap().reference(&cigar, "AACGTAC").is_err());
+    }
+}
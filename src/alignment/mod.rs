@@ -1,3 +1,9 @@
+pub mod cigar;
+pub use self::cigar::{Cigar, CigarOperation, MdTag};
+pub mod pairwise;
+pub mod scoring;
+pub use self::scoring::ScoringMatrix;
+
 use sequence::*;
 use std::marker::PhantomData;
 
@@ -11,6 +17,7 @@ pub struct Alignment<E: SequenceElement, S: Sequence<E>> {
     template: Option<S>,
     sequence: S,
     segments: Vec<AlignmentSegment<E, S>>,
+    score: Option<i32>,
     _marker: PhantomData<E>,
 }
 
@@ -21,6 +28,7 @@ impl<E: SequenceElement, S: Sequence<E>> Alignment<E, S> {
             template: template,
             sequence: sequence,
             segments: Vec::new(),
+            score: None,
             _marker: PhantomData,
         }
     }
@@ -118,6 +126,22 @@ impl<E: SequenceElement, S: Sequence<E>> Alignment<E, S> {
         self.segments.clone()
     }
 
+    /// Returns the score of the alignment if it was computed by an aligner
+    pub fn score(&self) -> Option<i32> {
+        self.score
+    }
+
+    pub fn with_score(mut self, score: i32) -> Self {
+        self.score = Some(score);
+        self
+    }
+
+    /// Returns the CIGAR of the alignment. Unaligned segments are reported as
+    /// soft clips.
+    pub fn cigar(&self) -> Cigar {
+        Cigar::from(self)
+    }
+
 
     pub fn canonicalize(&self) -> Self {
         let mut new_segs = self.segments.clone();
@@ -157,6 +181,7 @@ impl<E: SequenceElement, S: Sequence<E>> Alignment<E, S> {
             template: self.template.clone(),
             sequence: self.sequence.clone(),
             segments: new_segs,
+            score: self.score,
             _marker: PhantomData,
         }
     }
@@ -219,14 +244,14 @@ impl<E: SequenceElement, S: Sequence<E>> AlignmentSegment<E, S> {
         self.template_length
     }
 
-    /// Returns the slice that is
-    pub fn template_slice(&self) -> Option<S> {
+    /// Returns the elements of the template that are covered by this segment
+    pub fn template_slice(&self) -> Option<Vec<E>> {
         match self.is_aligned() {
             true => {
                 Some(self.template().unwrap().subsequence(
                     self.template_offset().unwrap(),
                     self.template_length().unwrap(),
-                ))
+                ).vec())
             }
             false => None,
         }
@@ -248,20 +273,12 @@ impl<E: SequenceElement, S: Sequence<E>> AlignmentSegment<E, S> {
 
     /// Returns the aligned sequence slice. If the alignment `is_reverse()`
     /// the returned sequence is already reversed.
-    pub fn sequence_slice(&self) -> S {
-        match self.is_reverse() {
-            true => {
-                self.sequence()
-                    .subsequence(self.sequence_offset(), self.sequence_length())
-                    .reverse()
-            }
-            false => {
-                self.sequence().subsequence(
-                    self.sequence_offset(),
-                    self.sequence_length(),
-                )
-            }
+    pub fn sequence_slice(&self) -> Vec<E> {
+        let mut slice = self.sequence().subsequence(self.sequence_offset(), self.sequence_length()).vec();
+        if self.is_reverse() {
+            slice.reverse();
         }
+        slice
     }
 
     /// Returns `true` is this segment is truly aligned to the template,
@@ -0,0 +1,329 @@
+use alignment::{Alignment, ScoringMatrix};
+use sequence::{Sequence, SequenceElement};
+use std::i32;
+use util::matrix::{Matrix, VectorMatrix};
+
+/// Defines which parts of the sequences must be aligned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignmentMode {
+    /// Aligns both sequences end-to-end (Needleman-Wunsch)
+    Global,
+    /// Aligns the best matching parts of both sequences (Smith-Waterman)
+    Local,
+    /// Aligns the sequence end-to-end to any part of the template (glocal), as
+    /// used for aligning reads against a reference region
+    SemiGlobal,
+}
+
+/// Scores below this value are treated as impossible. The value leaves enough
+/// room to subtract penalties without overflowing.
+const IMPOSSIBLE: i32 = i32::MIN / 2;
+
+// Traceback pointers
+const STOP: u8 = 0;
+const DIAGONAL: u8 = 1;
+const FROM_DELETION: u8 = 2;
+const FROM_INSERTION: u8 = 3;
+const OPEN: u8 = 4;
+const EXTEND: u8 = 5;
+
+/// Computes optimal pairwise alignments with affine gap penalties. A gap of
+/// length `k` is penalized by `gap_open + (k - 1) * gap_extend`.
+pub struct PairwiseAligner<E: SequenceElement> {
+    mode: AlignmentMode,
+    substitution: Box<dyn Fn(&E, &E) -> i32>,
+    gap_open: i32,
+    gap_extend: i32,
+}
+
+impl<E: SequenceElement + 'static> PairwiseAligner<E> {
+    /// Creates an aligner that scores matches with 1, mismatches with -1, and uses
+    /// a gap open penalty of 2 and a gap extension penalty of 1.
+    pub fn new(mode: AlignmentMode) -> Self {
+        PairwiseAligner {
+            mode: mode,
+            substitution: Box::new(|a: &E, b: &E| if a == b { 1 } else { -1 }),
+            gap_open: 2,
+            gap_extend: 1,
+        }
+    }
+
+    pub fn global() -> Self {
+        PairwiseAligner::new(AlignmentMode::Global)
+    }
+
+    pub fn local() -> Self {
+        PairwiseAligner::new(AlignmentMode::Local)
+    }
+
+    pub fn semi_global() -> Self {
+        PairwiseAligner::new(AlignmentMode::SemiGlobal)
+    }
+
+    pub fn mode(&self) -> AlignmentMode {
+        self.mode
+    }
+
+    /// Scores identical elements with `match_score` and all others with `mismatch_score`
+    pub fn with_match_scores(mut self, match_score: i32, mismatch_score: i32) -> Self {
+        self.substitution = Box::new(move |a: &E, b: &E| if a == b { match_score } else { mismatch_score });
+        self
+    }
+
+    /// Scores aligned elements of the template and the sequence with the given function
+    pub fn with_substitution<F: Fn(&E, &E) -> i32 + 'static>(mut self, substitution: F) -> Self {
+        self.substitution = Box::new(substitution);
+        self
+    }
+
+    /// Scores aligned elements of the template and the sequence with a scoring
+    /// matrix like BLOSUM62
+    pub fn with_scoring_matrix<M: ScoringMatrix<E> + 'static>(mut self, matrix: M) -> Self {
+        self.substitution = Box::new(move |a: &E, b: &E| matrix.score(a, b));
+        self
+    }
+
+    /// Sets the (positive) penalties for opening and for extending a gap
+    pub fn with_gap_penalties(mut self, gap_open: i32, gap_extend: i32) -> Self {
+        self.gap_open = gap_open;
+        self.gap_extend = gap_extend;
+        self
+    }
+
+    /// Returns the penalty of a gap with the given length
+    fn gap_penalty(&self, length: usize) -> i32 {
+        match length {
+            0 => 0,
+            l => self.gap_open + (l as i32 - 1) * self.gap_extend,
+        }
+    }
+
+    /// Aligns the sequence against the template. The template offsets of the
+    /// returned alignment refer to the given template. Parts of the sequence that
+    /// are not aligned in local mode are reported as unaligned segments.
+    pub fn align<S: Sequence<E>>(&self, template: &S, sequence: &S) -> Alignment<E, S> {
+        let t = template.vec();
+        let s = sequence.vec();
+        let (n, m) = (s.len(), t.len());
+        let local = self.mode == AlignmentMode::Local;
+
+        // The best score ending in a cell in any state, ending with a deletion
+        // (template element aligned to a gap), and ending with an insertion
+        let mut best = VectorMatrix::new_with_default(n + 1, m + 1, IMPOSSIBLE);
+        let mut deletion = VectorMatrix::new_with_default(n + 1, m + 1, IMPOSSIBLE);
+        let mut insertion = VectorMatrix::new_with_default(n + 1, m + 1, IMPOSSIBLE);
+        let mut best_from = VectorMatrix::new_with_default(n + 1, m + 1, STOP);
+        let mut deletion_from = VectorMatrix::new_with_default(n + 1, m + 1, OPEN);
+        let mut insertion_from = VectorMatrix::new_with_default(n + 1, m + 1, OPEN);
+
+        best.set(0, 0, 0);
+        for j in 1..m + 1 {
+            match self.mode {
+                AlignmentMode::Global => {
+                    best.set(0, j, -self.gap_penalty(j));
+                    deletion.set(0, j, -self.gap_penalty(j));
+                    best_from.set(0, j, FROM_DELETION);
+                    deletion_from.set(0, j, if j == 1 { OPEN } else { EXTEND });
+                }
+                _ => best.set(0, j, 0),
+            }
+        }
+        for i in 1..n + 1 {
+            match self.mode {
+                AlignmentMode::Local => best.set(i, 0, 0),
+                _ => {
+                    best.set(i, 0, -self.gap_penalty(i));
+                    insertion.set(i, 0, -self.gap_penalty(i));
+                    best_from.set(i, 0, FROM_INSERTION);
+                    insertion_from.set(i, 0, if i == 1 { OPEN } else { EXTEND });
+                }
+            }
+        }
+
+        for i in 1..n + 1 {
+            for j in 1..m + 1 {
+                let open = *best.get(i, j - 1) - self.gap_open;
+                let extend = *deletion.get(i, j - 1) - self.gap_extend;
+                deletion.set(i, j, if open >= extend { open } else { extend });
+                deletion_from.set(i, j, if open >= extend { OPEN } else { EXTEND });
+
+                let open = *best.get(i - 1, j) - self.gap_open;
+                let extend = *insertion.get(i - 1, j) - self.gap_extend;
+                insertion.set(i, j, if open >= extend { open } else { extend });
+                insertion_from.set(i, j, if open >= extend { OPEN } else { EXTEND });
+
+                let mut score = *best.get(i - 1, j - 1) + (self.substitution)(&t[j - 1], &s[i - 1]);
+                let mut from = DIAGONAL;
+                if *deletion.get(i, j) > score {
+                    score = *deletion.get(i, j);
+                    from = FROM_DELETION;
+                }
+                if *insertion.get(i, j) > score {
+                    score = *insertion.get(i, j);
+                    from = FROM_INSERTION;
+                }
+                if local && score <= 0 {
+                    score = 0;
+                    from = STOP;
+                }
+                best.set(i, j, score);
+                best_from.set(i, j, from);
+            }
+        }
+
+        // Find the cell in which the alignment ends
+        let (mut i, mut j) = match self.mode {
+            AlignmentMode::Global => (n, m),
+            AlignmentMode::SemiGlobal => {
+                let mut end = (n, 0);
+                for j in 1..m + 1 {
+                    if *best.get(n, j) > *best.get(end.0, end.1) {
+                        end = (n, j);
+                    }
+                }
+                end
+            }
+            AlignmentMode::Local => {
+                let mut end = (0, 0);
+                for i in 1..n + 1 {
+                    for j in 1..m + 1 {
+                        if *best.get(i, j) > *best.get(end.0, end.1) {
+                            end = (i, j);
+                        }
+                    }
+                }
+                end
+            }
+        };
+        let score = *best.get(i, j);
+        let (sequence_end, template_end) = (i, j);
+
+        // Trace back the operations in reverse order
+        let mut operations = Vec::new();
+        let mut state = *best_from.get(i, j);
+        while i > 0 || j > 0 {
+            match state {
+                DIAGONAL => {
+                    operations.push('M');
+                    i -= 1;
+                    j -= 1;
+                    state = *best_from.get(i, j);
+                }
+                FROM_DELETION => {
+                    operations.push('D');
+                    let from = *deletion_from.get(i, j);
+                    j -= 1;
+                    state = if from == EXTEND { FROM_DELETION } else { *best_from.get(i, j) };
+                }
+                FROM_INSERTION => {
+                    operations.push('I');
+                    let from = *insertion_from.get(i, j);
+                    i -= 1;
+                    state = if from == EXTEND { FROM_INSERTION } else { *best_from.get(i, j) };
+                }
+                _ => break,
+            }
+        }
+        operations.reverse();
+
+        // Convert the operations into segments
+        let mut alignment = Alignment::new(Some(template.clone()), sequence.clone());
+        if i > 0 {
+            alignment.add_segment_unaligned(0, i);
+        }
+        let (mut sequence_pos, mut template_pos) = (i, j);
+        let mut k = 0;
+        while k < operations.len() {
+            let op = operations[k];
+            let length = operations[k..].iter().take_while(|o| **o == op).count();
+            match op {
+                'M' => {
+                    alignment.add_segment_aligned(sequence_pos, length, template_pos, length, false);
+                    sequence_pos += length;
+                    template_pos += length;
+                }
+                'I' => {
+                    alignment.add_segment_aligned(sequence_pos, length, template_pos, 0, false);
+                    sequence_pos += length;
+                }
+                _ => {
+                    alignment.add_segment_aligned(sequence_pos, 0, template_pos, length, false);
+                    template_pos += length;
+                }
+            }
+            k += length;
+        }
+        debug_assert_eq!((sequence_pos, template_pos), (sequence_end, template_end));
+        if sequence_end < n {
+            alignment.add_segment_unaligned(sequence_end, n - sequence_end);
+        }
+        alignment.with_score(score)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alignment::pairwise::{AlignmentMode, PairwiseAligner};
+    use io::fasta::{FastaReader, IndexedFastaFile};
+    use sequence::{Aminoacid, DnaNucleotide, DnaSequence, Peptide};
+    use std::str::FromStr;
+
+    fn dna(s: &str) -> DnaSequence {
+        DnaSequence::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_global() {
+        let aligner = PairwiseAligner::<DnaNucleotide>::global();
+        let alignment = aligner.align(&dna("ACGTACGT"), &dna("ACGTACGT"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(8), "8M".to_string()));
+
+        let alignment = aligner.align(&dna("ACGTTTACGT"), &dna("ACGTACGT"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(5), "3M2D5M".to_string()));
+
+        let alignment = aligner.align(&dna("ACGTACGT"), &dna("ACGTAAACGT"));
+        assert_eq!(alignment.cigar().to_string(), "4M2I4M");
+        assert!(alignment.segments()[1].is_insertion());
+
+        // Affine gaps prefer a single long gap over two short ones
+        let aligner = aligner.with_gap_penalties(5, 1);
+        let alignment = aligner.align(&dna("AAACCCGGGTTT"), &dna("AAAGGGTTT"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(2), "3M3D6M".to_string()));
+    }
+
+    #[test]
+    fn test_local() {
+        let aligner = PairwiseAligner::local().with_match_scores(2, -3);
+        assert_eq!(aligner.mode(), AlignmentMode::Local);
+        let alignment = aligner.align(&dna("TTTTTGATTACATTTTT"), &dna("CCGATTACACC"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(14), "2S7M2S".to_string()));
+        assert_eq!(alignment.segments()[1].template_offset(), Some(5));
+
+        let alignment = aligner.align(&dna("AAAA"), &dna("CCCC"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(0), "4S".to_string()));
+    }
+
+    #[test]
+    fn test_semi_global() {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let reference = fasta.search_region_as_dna("ref", 0, 45).unwrap();
+        let aligner = PairwiseAligner::semi_global().with_gap_penalties(3, 1);
+
+        let alignment = aligner.align(&reference, &dna("GTAGGCAGTCAG"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(12), "12M".to_string()));
+        assert_eq!(alignment.segments()[0].template_offset(), Some(27));
+        assert!(alignment.segments()[0].is_match());
+
+        let alignment = aligner.align(&reference, &dna("GTTAGATAAGTAGCTGTGC"));
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(16), "10M1D9M".to_string()));
+        assert_eq!(alignment.segments()[0].template_offset(), Some(5));
+    }
+
+    #[test]
+    fn test_peptides() {
+        let aligner = PairwiseAligner::<Aminoacid>::global();
+        let alignment = aligner.align(&Peptide::from_str("MLDKIA").unwrap(), &Peptide::from_str("MLKIA").unwrap());
+        assert_eq!((alignment.score(), alignment.cigar().to_string()), (Some(3), "2M1D3M".to_string()));
+    }
+}
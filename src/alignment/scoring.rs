@@ -0,0 +1,270 @@
+use sequence::{DnaNucleotide, SequenceElement};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Scores the alignment of two sequence elements. Higher scores indicate a
+/// more likely substitution.
+pub trait ScoringMatrix<E: SequenceElement> {
+    fn score(&self, a: &E, b: &E) -> i32;
+}
+
+/// A substitution matrix that scores pairs of single letter codes like the
+/// BLOSUM and PAM matrices for amino acids. Matrices are read from the text
+/// format used by NCBI: lines starting with `#` are comments, the first line
+/// lists the column letters, and each following line starts with the row letter
+/// followed by the scores.
+#[derive(Clone, Debug)]
+pub struct SubstitutionMatrix {
+    letters: BTreeMap<char, usize>,
+    scores: Vec<Vec<i32>>,
+    default_score: i32,
+}
+
+impl SubstitutionMatrix {
+    /// Parses a matrix in NCBI format. Letters are case insensitive.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|&(_, l)| !l.trim().is_empty() && !l.starts_with('#'));
+        let columns: Vec<char> = match lines.next() {
+            Some((i, l)) => {
+                let mut columns = Vec::new();
+                for cell in l.split_whitespace() {
+                    if cell.chars().count() != 1 {
+                        return Err(format!("Line {}: Expecting single letter column names but got '{}'", i + 1, cell));
+                    }
+                    columns.push(cell.chars().next().unwrap().to_ascii_uppercase());
+                }
+                columns
+            }
+            None => return Err("Missing header line of substitution matrix".to_string()),
+        };
+
+        let mut rows = BTreeMap::new();
+        for (i, line) in lines {
+            let cells: Vec<&str> = line.split_whitespace().collect();
+            if cells[0].chars().count() != 1 || cells.len() != columns.len() + 1 {
+                return Err(format!("Line {}: Expecting row letter and {} scores", i + 1, columns.len()));
+            }
+            let mut scores = Vec::with_capacity(columns.len());
+            for cell in &cells[1..] {
+                match cell.parse::<i32>() {
+                    Ok(s) => scores.push(s),
+                    Err(e) => return Err(format!("Line {}: Can not parse score '{}': {}", i + 1, cell, e)),
+                }
+            }
+            rows.insert(cells[0].chars().next().unwrap().to_ascii_uppercase(), scores);
+        }
+
+        // Store the rows in the order of the columns so that the same index is
+        // used for both letters of a pair
+        let mut scores: Vec<Vec<i32>> = Vec::with_capacity(columns.len());
+        for c in &columns {
+            match rows.remove(c) {
+                Some(r) => scores.push(r),
+                None => return Err(format!("Missing row for letter '{}'", c)),
+            }
+        }
+        let default_score = scores.iter().flat_map(|r| r.iter()).cloned().min().unwrap_or(0);
+        Ok(SubstitutionMatrix {
+            letters: columns.iter().enumerate().map(|(i, c)| (*c, i)).collect(),
+            scores: scores,
+            default_score: default_score,
+        })
+    }
+
+    /// Reads a matrix in NCBI format from a file
+    pub fn read_file<P: AsRef<Path>>(filename: &P) -> Result<Self, String> {
+        let mut text = String::new();
+        match File::open(filename).and_then(|mut f| f.read_to_string(&mut text)) {
+            Ok(_) => SubstitutionMatrix::parse(&text)
+                .map_err(|e| format!("Can not parse substitution matrix '{}': {}", filename.as_ref().display(), e)),
+            Err(e) => Err(format!("Can not read substitution matrix '{}': {}", filename.as_ref().display(), e)),
+        }
+    }
+
+    pub fn blosum45() -> Self {
+        SubstitutionMatrix::parse(include_str!("matrices/BLOSUM45")).unwrap()
+    }
+
+    pub fn blosum62() -> Self {
+        SubstitutionMatrix::parse(include_str!("matrices/BLOSUM62")).unwrap()
+    }
+
+    pub fn blosum80() -> Self {
+        SubstitutionMatrix::parse(include_str!("matrices/BLOSUM80")).unwrap()
+    }
+
+    pub fn pam30() -> Self {
+        SubstitutionMatrix::parse(include_str!("matrices/PAM30")).unwrap()
+    }
+
+    pub fn pam70() -> Self {
+        SubstitutionMatrix::parse(include_str!("matrices/PAM70")).unwrap()
+    }
+
+    pub fn pam250() -> Self {
+        SubstitutionMatrix::parse(include_str!("matrices/PAM250")).unwrap()
+    }
+
+    /// Returns the built-in matrix with the given name (e.g., `BLOSUM62`)
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "BLOSUM45" => Some(SubstitutionMatrix::blosum45()),
+            "BLOSUM62" => Some(SubstitutionMatrix::blosum62()),
+            "BLOSUM80" => Some(SubstitutionMatrix::blosum80()),
+            "PAM30" => Some(SubstitutionMatrix::pam30()),
+            "PAM70" => Some(SubstitutionMatrix::pam70()),
+            "PAM250" => Some(SubstitutionMatrix::pam250()),
+            _ => None,
+        }
+    }
+
+    /// Returns the letters of the matrix in the order of the header line
+    pub fn letters(&self) -> Vec<char> {
+        let mut letters: Vec<(char, usize)> = self.letters.iter().map(|(c, i)| (*c, *i)).collect();
+        letters.sort_by_key(|l| l.1);
+        letters.into_iter().map(|l| l.0).collect()
+    }
+
+    /// Returns the index of a letter. Letters that are not part of the matrix
+    /// are treated as the unknown letter `X` if the matrix defines it.
+    fn index(&self, c: char) -> Option<usize> {
+        self.letters
+            .get(&c.to_ascii_uppercase())
+            .or_else(|| self.letters.get(&'X'))
+            .cloned()
+    }
+
+    /// Returns the score for a pair of letters. Pairs that can not be looked
+    /// up are scored with the minimum score of the matrix.
+    pub fn score_letters(&self, a: char, b: char) -> i32 {
+        match (self.index(a), self.index(b)) {
+            (Some(i), Some(j)) => self.scores[i][j],
+            _ => self.default_score,
+        }
+    }
+}
+
+impl<E: SequenceElement> ScoringMatrix<E> for SubstitutionMatrix {
+    fn score(&self, a: &E, b: &E) -> i32 {
+        self.score_letters(a.clone().into(), b.clone().into())
+    }
+}
+
+
+/// A simple nucleotide scoring scheme that scores identical bases as match and
+/// all other pairs as mismatch. Pairs with an ambiguity code that may stand for
+/// the same base (e.g., `R` and `A`) get a separate score that defaults to 0.
+#[derive(Clone, Debug)]
+pub struct IupacScoring {
+    match_score: i32,
+    mismatch_score: i32,
+    ambiguous_score: i32,
+}
+
+impl IupacScoring {
+    pub fn new(match_score: i32, mismatch_score: i32) -> Self {
+        IupacScoring {
+            match_score: match_score,
+            mismatch_score: mismatch_score,
+            ambiguous_score: 0,
+        }
+    }
+
+    /// Sets the score for pairs that match only through an ambiguity code
+    pub fn with_ambiguous_score(mut self, ambiguous_score: i32) -> Self {
+        self.ambiguous_score = ambiguous_score;
+        self
+    }
+}
+
+impl ScoringMatrix<DnaNucleotide> for IupacScoring {
+    fn score(&self, a: &DnaNucleotide, b: &DnaNucleotide) -> i32 {
+        if a.is_ambiguous() || b.is_ambiguous() {
+            match a.matches(b) {
+                true => self.ambiguous_score,
+                false => self.mismatch_score,
+            }
+        } else if a == b {
+            self.match_score
+        } else {
+            self.mismatch_score
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alignment::ScoringMatrix;
+    use alignment::pairwise::PairwiseAligner;
+    use alignment::scoring::{IupacScoring, SubstitutionMatrix};
+    use sequence::{Aminoacid, DnaNucleotide, Peptide};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_builtin_matrices() {
+        let blosum62 = SubstitutionMatrix::blosum62();
+        assert_eq!(blosum62.score(&Aminoacid::W, &Aminoacid::W), 11);
+        assert_eq!(blosum62.score(&Aminoacid::A, &Aminoacid::R), -1);
+        assert_eq!(blosum62.score(&Aminoacid::E, &Aminoacid::Q), 2);
+        assert_eq!(blosum62.score(&Aminoacid::Stop, &Aminoacid::A), -4);
+        assert_eq!(blosum62.score(&Aminoacid::Unknown, &Aminoacid::S), 0);
+        assert_eq!(blosum62.score_letters('j', 'S'), 0);
+        assert_eq!(blosum62.letters().len(), 24);
+
+        assert_eq!(SubstitutionMatrix::pam250().score(&Aminoacid::W, &Aminoacid::W), 17);
+        assert_eq!(SubstitutionMatrix::by_name("blosum45").unwrap().score(&Aminoacid::C, &Aminoacid::C), 12);
+        for name in &["BLOSUM80", "PAM30", "PAM70"] {
+            let matrix = SubstitutionMatrix::by_name(name).unwrap();
+            let letters = matrix.letters();
+            for a in &letters {
+                for b in &letters {
+                    assert_eq!(matrix.score_letters(*a, *b), matrix.score_letters(*b, *a), "{} {}{}", name, a, b);
+                }
+            }
+        }
+        assert!(SubstitutionMatrix::by_name("BLOSUM50").is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let matrix = SubstitutionMatrix::parse("# custom\n   a  b\nB -1  2\nA  3 -1\n").unwrap();
+        assert_eq!(matrix.letters(), vec!['A', 'B']);
+        assert_eq!(matrix.score_letters('A', 'A'), 3);
+        assert_eq!(matrix.score_letters('b', 'B'), 2);
+        assert_eq!(matrix.score_letters('A', 'C'), -1);
+
+        assert!(SubstitutionMatrix::parse("").is_err());
+        assert!(SubstitutionMatrix::parse("  A B\nA 1\n").is_err());
+        assert!(SubstitutionMatrix::parse("  A B\nA 1 x\nB 1 1\n").is_err());
+        assert!(SubstitutionMatrix::parse("  A B\nA 1 0\n").is_err());
+        assert!(SubstitutionMatrix::read_file(&"testdata/missing.matrix").is_err());
+    }
+
+    #[test]
+    fn test_iupac_scoring() {
+        let scoring = IupacScoring::new(2, -3).with_ambiguous_score(1);
+        assert_eq!(scoring.score(&DnaNucleotide::A, &DnaNucleotide::A), 2);
+        assert_eq!(scoring.score(&DnaNucleotide::A, &DnaNucleotide::C), -3);
+        assert_eq!(scoring.score(&DnaNucleotide::R, &DnaNucleotide::G), 1);
+        assert_eq!(scoring.score(&DnaNucleotide::N, &DnaNucleotide::N), 1);
+        assert_eq!(scoring.score(&DnaNucleotide::Y, &DnaNucleotide::A), -3);
+    }
+
+    #[test]
+    fn test_align_with_matrix() {
+        let template = Peptide::from_str("HEAGAWGHEE").unwrap();
+        let sequence = Peptide::from_str("PAWHEAE").unwrap();
+        let aligner = PairwiseAligner::local()
+            .with_scoring_matrix(SubstitutionMatrix::blosum62())
+            .with_gap_penalties(10, 1);
+        let alignment = aligner.align(&template, &sequence);
+        assert_eq!(alignment.score(), Some(18));
+        assert_eq!(alignment.cigar().to_string(), "1S2M1D2M2S");
+    }
+}
@@ -0,0 +1,440 @@
+use io::vcf::{Genotype, VcfRecord};
+use model::{GenomicVariant, Variant};
+use pileup::{PileupBase, PileupColumn};
+use sequence::dna::{DnaNucleotide, DnaSequence};
+use sequence::quality::phred_to_probability;
+
+/// The highest reported genotype quality
+const MAX_GENOTYPE_QUALITY: f64 = 99f64;
+
+/// The highest reported variant quality
+const MAX_QUALITY: f64 = 999f64;
+
+/// A germline SNV or short indel called in a single diploid sample. Indels are
+/// reported like in VCF files, i.e., including the template base before them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariantCall {
+    template: String,
+    offset: usize,
+    reference: String,
+    alternative: String,
+    depth: usize,
+    allele_depths: (usize, usize),
+    genotype: Genotype,
+    genotype_quality: u8,
+    quality: f64,
+}
+
+impl VariantCall {
+    pub fn reference_allele(&self) -> String {
+        self.reference.clone()
+    }
+
+    pub fn alternative_allele(&self) -> String {
+        self.alternative.clone()
+    }
+
+    /// Returns the number of reads used for the call
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of reads supporting the reference and the alternative
+    /// allele. Reads with other alleles count towards the depth only.
+    pub fn allele_depths(&self) -> (usize, usize) {
+        self.allele_depths
+    }
+
+    /// Returns the fraction of reads supporting the alternative allele
+    pub fn allele_fraction(&self) -> f64 {
+        match self.depth {
+            0 => 0f64,
+            d => self.allele_depths.1 as f64 / d as f64,
+        }
+    }
+
+    /// Returns the most likely genotype, i.e., `0/1` or `1/1`
+    pub fn genotype(&self) -> Genotype {
+        self.genotype.clone()
+    }
+
+    /// Returns the Phred scaled probability that the genotype is wrong
+    pub fn genotype_quality(&self) -> u8 {
+        self.genotype_quality
+    }
+
+    /// Returns the Phred scaled probability that the sample is homozygous for
+    /// the reference allele
+    pub fn quality(&self) -> f64 {
+        self.quality
+    }
+
+    /// Converts the call into a VCF record with the `DP` and `AF` INFO fields and
+    /// the `GT:GQ:DP:AD` sample fields
+    pub fn to_vcf_record(&self) -> VcfRecord {
+        VcfRecord::new(
+            self.template.as_str(),
+            self.offset + 1,
+            self.reference.as_str(),
+            vec![self.alternative.clone()],
+        ).with_quality((self.quality * 100f64).round() / 100f64)
+            .with_info("DP", self.depth)
+            .with_info("AF", format!("{:.3}", self.allele_fraction()))
+            .with_format(vec!["GT".to_string(), "GQ".to_string(), "DP".to_string(), "AD".to_string()])
+            .with_sample(vec![
+                self.genotype.to_string(),
+                self.genotype_quality.to_string(),
+                self.depth.to_string(),
+                format!("{},{}", self.allele_depths.0, self.allele_depths.1),
+            ])
+    }
+}
+
+impl Variant<DnaNucleotide> for VariantCall {
+    type SequenceType = DnaSequence;
+
+    fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn reference(&self) -> DnaSequence {
+        self.reference.chars().map(|c| DnaNucleotide::from(c)).collect()
+    }
+
+    fn alternative(&self) -> DnaSequence {
+        self.alternative.chars().map(|c| DnaNucleotide::from(c)).collect()
+    }
+}
+
+impl GenomicVariant for VariantCall {}
+
+
+/// The alleles a read may show at a candidate variant
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Observation {
+    Reference,
+    Alternative,
+    Other,
+}
+
+/// Calls germline variants of a diploid sample from pileup columns. At each
+/// position, the most frequent alternative base and the most frequent indel are
+/// tested. The likelihoods of the genotypes `0/0`, `0/1` and `1/1` are computed
+/// from the base qualities (or a fixed indel quality) and mapping qualities of
+/// the reads and combined with a prior given by the heterozygosity.
+#[derive(Clone, Debug)]
+pub struct VariantCaller {
+    min_depth: usize,
+    min_alt_count: usize,
+    min_alt_fraction: f64,
+    min_quality: f64,
+    indel_quality: u8,
+    heterozygosity: f64,
+}
+
+impl VariantCaller {
+    pub fn new() -> Self {
+        VariantCaller {
+            min_depth: 3,
+            min_alt_count: 2,
+            min_alt_fraction: 0.1,
+            min_quality: 20f64,
+            indel_quality: 30,
+            heterozygosity: 0.001,
+        }
+    }
+
+    /// Skips positions with fewer reads
+    pub fn with_min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Skips alternative alleles supported by fewer reads
+    pub fn with_min_alt_count(mut self, count: usize) -> Self {
+        self.min_alt_count = count;
+        self
+    }
+
+    /// Skips alternative alleles supported by a lower fraction of the reads
+    pub fn with_min_alt_fraction(mut self, fraction: f64) -> Self {
+        self.min_alt_fraction = fraction;
+        self
+    }
+
+    /// Skips calls with a lower variant quality
+    pub fn with_min_quality(mut self, quality: f64) -> Self {
+        self.min_quality = quality;
+        self
+    }
+
+    /// Sets the Phred scaled probability that a read shows an indel by error
+    pub fn with_indel_quality(mut self, quality: u8) -> Self {
+        self.indel_quality = quality;
+        self
+    }
+
+    /// Sets the prior probability of a heterozygous variant at a position
+    pub fn with_heterozygosity(mut self, heterozygosity: f64) -> Self {
+        self.heterozygosity = heterozygosity;
+        self
+    }
+
+    /// Calls the SNV and the indel at the position of the column. Columns without
+    /// a reference base are skipped.
+    pub fn call(&self, column: &PileupColumn) -> Vec<VariantCall> {
+        let reference = match column.reference() {
+            Some(r) => r.to_ascii_uppercase(),
+            None => return Vec::new(),
+        };
+        // Only reads with a base aligned to the position are informative
+        let bases: Vec<&PileupBase> = column.bases().iter().filter(|b| !b.is_skip() && !b.is_deletion()).collect();
+        if bases.len() < self.min_depth {
+            return Vec::new();
+        }
+
+        let mut calls = Vec::new();
+        if reference != 'N' {
+            let alternative = VariantCaller::most_frequent(bases.iter().map(|b| b.base()).filter(|b| *b != reference && *b != 'N'));
+            if let Some(alternative) = alternative {
+                let observations: Vec<(Observation, f64)> = bases
+                    .iter()
+                    .map(|b| {
+                        let observation = match b.base() {
+                            c if c == reference => Observation::Reference,
+                            c if c == alternative => Observation::Alternative,
+                            _ => Observation::Other,
+                        };
+                        (observation, VariantCaller::error_probability(b.quality(), b.mapping_quality()))
+                    })
+                    .collect();
+                let call = self.genotype(column, reference.to_string(), alternative.to_string(), &observations);
+                calls.extend(call);
+            }
+        }
+
+        // Insertions and deletions are both reported after the reference base
+        let indel = |b: &PileupBase| match (b.insertion(), b.deletion()) {
+            (_, Some(d)) => Some((reference.to_string() + &d.to_uppercase(), reference.to_string())),
+            (Some(i), None) => Some((reference.to_string(), reference.to_string() + &i.to_uppercase())),
+            (None, None) => None,
+        };
+        if let Some((r, a)) = VariantCaller::most_frequent(bases.iter().filter_map(|b| indel(b))) {
+            let observations: Vec<(Observation, f64)> = bases
+                .iter()
+                .map(|b| {
+                    let observation = match indel(b) {
+                        Some(ref i) if i.0 == r && i.1 == a => Observation::Alternative,
+                        Some(_) => Observation::Other,
+                        None => Observation::Reference,
+                    };
+                    (observation, VariantCaller::error_probability(self.indel_quality, b.mapping_quality()))
+                })
+                .collect();
+            calls.extend(self.genotype(column, r, a, &observations));
+        }
+        calls
+    }
+
+    /// Calls the variants of all columns
+    pub fn call_all(&self, columns: &[PileupColumn]) -> Vec<VariantCall> {
+        columns.iter().flat_map(|c| self.call(c)).collect()
+    }
+
+    /// Returns the most frequent value. Ties are resolved by the first occurrence.
+    fn most_frequent<T: PartialEq, I: Iterator<Item = T>>(values: I) -> Option<T> {
+        let mut counts: Vec<(T, usize)> = Vec::new();
+        for v in values {
+            match counts.iter().position(|c| c.0 == v) {
+                Some(i) => counts[i].1 += 1,
+                None => counts.push((v, 1)),
+            }
+        }
+        let max = counts.iter().map(|c| c.1).max().unwrap_or(0);
+        counts.into_iter().find(|c| c.1 == max).map(|c| c.0)
+    }
+
+    /// Returns the probability that an observation is wrong because either the
+    /// base or the alignment is wrong. It is capped at the probability of a
+    /// random base.
+    fn error_probability(base_quality: u8, mapping_quality: u8) -> f64 {
+        let correct = (1f64 - phred_to_probability(base_quality)) * (1f64 - phred_to_probability(mapping_quality));
+        (1f64 - correct).min(0.75)
+    }
+
+    /// Returns the log10 likelihoods of the genotypes `0/0`, `0/1` and `1/1`.
+    /// An erroneous observation shows any of the three other alleles.
+    fn genotype_likelihoods(observations: &[(Observation, f64)]) -> [f64; 3] {
+        let mut likelihoods = [0f64; 3];
+        for &(observation, e) in observations.iter() {
+            let p = |allele: Observation| match allele == observation {
+                true => 1f64 - e,
+                false => e / 3f64,
+            };
+            likelihoods[0] += p(Observation::Reference).log10();
+            likelihoods[1] += (0.5 * p(Observation::Reference) + 0.5 * p(Observation::Alternative)).log10();
+            likelihoods[2] += p(Observation::Alternative).log10();
+        }
+        likelihoods
+    }
+
+    /// Genotypes the candidate allele and returns the call if it passes all
+    /// thresholds and the sample is not homozygous for the reference
+    fn genotype(
+        &self,
+        column: &PileupColumn,
+        reference: String,
+        alternative: String,
+        observations: &[(Observation, f64)],
+    ) -> Option<VariantCall> {
+        let depth = observations.len();
+        let count = |o: Observation| observations.iter().filter(|x| x.0 == o).count();
+        let allele_depths = (count(Observation::Reference), count(Observation::Alternative));
+        if depth < self.min_depth || allele_depths.1 < self.min_alt_count ||
+            (allele_depths.1 as f64) < self.min_alt_fraction * depth as f64
+        {
+            return None;
+        }
+
+        let priors = [1f64 - 1.5 * self.heterozygosity, self.heterozygosity, 0.5 * self.heterozygosity];
+        let likelihoods = VariantCaller::genotype_likelihoods(observations);
+        let mut posteriors = [0f64; 3];
+        for g in 0..3 {
+            posteriors[g] = likelihoods[g] + priors[g].log10();
+        }
+        // Normalizes the log10 posteriors without leaving log space
+        let max = posteriors.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let total = max + posteriors.iter().map(|p| 10f64.powf(p - max)).sum::<f64>().log10();
+        let log10_sum = |gs: &[usize]| max + gs.iter().map(|g| 10f64.powf(posteriors[*g] - max)).sum::<f64>().log10() - total;
+
+        // The posterior of a single genotype is computed directly as it may be
+        // too small to be represented in linear space at high depth
+        let quality = (-10f64 * (posteriors[0] - total)).min(MAX_QUALITY);
+        let best = (0..3).fold(0, |b, g| if posteriors[g] > posteriors[b] { g } else { b });
+        if best == 0 || quality < self.min_quality {
+            return None;
+        }
+        let others: Vec<usize> = (0..3).filter(|g| *g != best).collect();
+        let genotype_quality = (-10f64 * log10_sum(&others)).min(MAX_GENOTYPE_QUALITY).max(0f64).round() as u8;
+
+        Some(VariantCall {
+            template: column.template(),
+            offset: column.position(),
+            reference: reference,
+            alternative: alternative,
+            depth: depth,
+            allele_depths: allele_depths,
+            genotype: Genotype::new(vec![Some(best - 1), Some(1)], false),
+            genotype_quality: genotype_quality,
+            quality: quality,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use caller::VariantCaller;
+    use io::bam::{AlignmentRecord, IndexedBamReader};
+    use io::fasta::IndexedFastaFile;
+    use model::{SimpleRegion, Variant, VariantType};
+    use pileup::Pileup;
+    use sequence::quality::{PhredEncoding, QualitySequence};
+
+    #[test]
+    fn test_call_snv() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let columns = Pileup::new()
+            .from_bam(&mut bam, &SimpleRegion::new("ref2", 0, 40), Some(&mut fasta))
+            .unwrap();
+        let calls = VariantCaller::new().call_all(&columns);
+        // Three of six reads show a T instead of the C at position 14
+        let call = calls.iter().find(|c| c.offset() == 13).unwrap();
+        assert_eq!((call.reference_allele(), call.alternative_allele()), ("C".to_string(), "T".to_string()));
+        assert_eq!((call.depth(), call.allele_depths()), (6, (3, 3)));
+        assert_eq!(call.genotype().to_string(), "0/1");
+        assert!(call.quality() > 40f64);
+        assert_eq!(call.genotype_quality(), 47);
+        match call.variant_type() {
+            Some(VariantType::Substitution) => {}
+            _ => panic!("Expected a substitution"),
+        }
+        assert_eq!(
+            call.to_vcf_record().to_string(),
+            format!("ref2\t14\t.\tC\tT\t{}\t.\tDP=6;AF=0.500\tGT:GQ:DP:AD\t0/1:47:6:3,3", call.to_vcf_record().quality().unwrap())
+        );
+
+        // A single read with a C instead of the A at position 10 is more likely
+        // an error than a heterozygous variant
+        assert!(calls.iter().all(|c| c.offset() != 9));
+        let calls = VariantCaller::new().with_min_alt_count(1).call_all(&columns);
+        assert!(calls.iter().all(|c| c.offset() != 9));
+        let calls = VariantCaller::new().with_min_alt_count(1).with_heterozygosity(0.1).with_min_quality(5f64).call_all(&columns);
+        assert!(calls.iter().any(|c| c.offset() == 9));
+    }
+
+    #[test]
+    fn test_call_genotypes() {
+        let read = |name: &str, sequence: &str, cigar: &str| {
+            let quality = QualitySequence::from_ascii("I".repeat(sequence.len()), PhredEncoding::Phred33).unwrap();
+            AlignmentRecord::new(name)
+                .with_position("ref", 0)
+                .with_mapping_quality(60)
+                .with_cigar(cigar)
+                .unwrap()
+                .with_sequence(sequence, Some(quality))
+                .unwrap()
+        };
+        let reference = "ACGTACGT";
+        let region = SimpleRegion::new("ref", 0, 8);
+        let records = vec![
+            read("a", "ACCTACGT", "8M"),
+            read("b", "ACCTACGT", "8M"),
+            read("c", "ACCTAGGACGT", "5M3I3M"),
+            read("d", "ACCTAGGACGT", "5M3I3M"),
+            read("e", "ACCTAT", "5M2D1M"),
+        ];
+        let columns = Pileup::new().columns(&records, &region, Some((0, reference)));
+        let calls = VariantCaller::new().call_all(&columns);
+        assert_eq!(calls.len(), 2);
+        assert_eq!((calls[0].offset(), calls[0].genotype().to_string()), (2, "1/1".to_string()));
+        assert_eq!(calls[0].allele_depths(), (0, 5));
+        assert_eq!((calls[1].reference_allele(), calls[1].alternative_allele()), ("A".to_string(), "AGGA".to_string()));
+        assert_eq!(calls[1].genotype().to_string(), "0/1");
+        // The read with the deletion is neither reference nor the insertion
+        assert_eq!((calls[1].depth(), calls[1].allele_depths()), (5, (2, 2)));
+
+        let calls = VariantCaller::new().with_min_alt_count(1).call_all(&columns);
+        assert_eq!(calls.len(), 2);
+        let calls = VariantCaller::new().with_min_depth(6).call_all(&columns);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn test_call_deep_coverage() {
+        let quality = QualitySequence::from_ascii("IIII", PhredEncoding::Phred33).unwrap();
+        let records: Vec<AlignmentRecord> = (0..500)
+            .map(|i| {
+                AlignmentRecord::new(format!("r{}", i))
+                    .with_position("ref", 0)
+                    .with_mapping_quality(60)
+                    .with_cigar("4M")
+                    .unwrap()
+                    .with_sequence("ACCT", Some(quality.clone()))
+                    .unwrap()
+            })
+            .collect();
+        let columns = Pileup::new().columns(&records, &SimpleRegion::new("ref", 0, 4), Some((0, "ACGT")));
+        let calls = VariantCaller::new().call_all(&columns);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].genotype().to_string(), "1/1");
+        assert_eq!(calls[0].quality(), 999f64);
+        assert_eq!(calls[0].genotype_quality(), 99);
+        assert!(calls[0].to_vcf_record().to_string().starts_with("ref\t3\t.\tG\tC\t999\t"));
+    }
+}
@@ -0,0 +1,315 @@
+use io::bam::{AlignmentRecord, IndexedBamReader};
+use model::{Region, SimpleRegion};
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+
+/// Summary statistics of the per-base depth of one or more regions. The
+/// statistics are computed from a histogram of the depths, so summaries of
+/// different regions can be merged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CoverageSummary {
+    histogram: BTreeMap<u32, usize>,
+}
+
+impl CoverageSummary {
+    pub fn new() -> Self {
+        CoverageSummary::default()
+    }
+
+    /// Adds the positions of another summary
+    pub fn merge(&mut self, other: &CoverageSummary) {
+        for (depth, n) in other.histogram.iter() {
+            *self.histogram.entry(*depth).or_insert(0) += *n;
+        }
+    }
+
+    /// Returns the number of positions with each depth
+    pub fn histogram(&self) -> &BTreeMap<u32, usize> {
+        &self.histogram
+    }
+
+    /// Returns the number of positions
+    pub fn length(&self) -> usize {
+        self.histogram.values().sum()
+    }
+
+    /// Returns the mean depth or 0 for an empty region
+    pub fn mean(&self) -> f64 {
+        match self.length() {
+            0 => 0f64,
+            l => self.histogram.iter().map(|(d, n)| *d as f64 * *n as f64).sum::<f64>() / l as f64,
+        }
+    }
+
+    /// Returns the median depth or 0 for an empty region. For an even number of
+    /// positions, the median is the mean of the two middle depths.
+    pub fn median(&self) -> f64 {
+        let length = self.length();
+        if length == 0 {
+            return 0f64;
+        }
+        // Returns the depth at the given rank of the sorted depths
+        let nth = |rank: usize| {
+            let mut seen = 0;
+            for (depth, n) in self.histogram.iter() {
+                seen += *n;
+                if seen > rank {
+                    return *depth as f64;
+                }
+            }
+            0f64
+        };
+        match length % 2 {
+            1 => nth(length / 2),
+            _ => (nth(length / 2 - 1) + nth(length / 2)) / 2f64,
+        }
+    }
+
+    /// Returns the fraction of positions with a depth of at least `min_depth`
+    pub fn breadth(&self, min_depth: u32) -> f64 {
+        match self.length() {
+            0 => 0f64,
+            l => self.histogram.range(min_depth..).map(|(_, n)| *n).sum::<usize>() as f64 / l as f64,
+        }
+    }
+}
+
+
+/// The per-base depth of a region
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coverage {
+    template: String,
+    offset: usize,
+    depths: Vec<u32>,
+}
+
+impl Coverage {
+    /// Creates the coverage of a region without any reads
+    pub fn new<R: Region>(region: &R) -> Self {
+        Coverage {
+            template: region.template(),
+            offset: region.offset(),
+            depths: vec![0; region.length()],
+        }
+    }
+
+    /// Returns the depths of all positions of the region
+    pub fn depths(&self) -> &[u32] {
+        &self.depths
+    }
+
+    /// Returns the depth at the 0-based template position if it is part of the region
+    pub fn depth(&self, position: usize) -> Option<u32> {
+        match position >= self.offset {
+            true => self.depths.get(position - self.offset).cloned(),
+            false => None,
+        }
+    }
+
+    /// Increments the depth of the positions in `[offset, end)` that are part of the region
+    fn add(&mut self, offset: usize, end: usize) {
+        let start = cmp::max(offset, self.offset);
+        let end = cmp::min(end, self.end());
+        for p in start..cmp::max(start, end) {
+            self.depths[p - self.offset] += 1;
+        }
+    }
+
+    pub fn summary(&self) -> CoverageSummary {
+        let mut summary = CoverageSummary::new();
+        for d in self.depths.iter() {
+            *summary.histogram.entry(*d).or_insert(0) += 1;
+        }
+        summary
+    }
+
+    /// Returns the runs of positions with the same depth as regions with their
+    /// depth as used in bedGraph files
+    pub fn runs(&self) -> Vec<(SimpleRegion, u32)> {
+        let mut runs: Vec<(SimpleRegion, u32)> = Vec::new();
+        let mut start = 0;
+        for i in 1..self.depths.len() + 1 {
+            if i == self.depths.len() || self.depths[i] != self.depths[start] {
+                let region = SimpleRegion::new(self.template.as_str(), self.offset + start, i - start);
+                runs.push((region, self.depths[start]));
+                start = i;
+            }
+        }
+        runs
+    }
+}
+
+impl Region for Coverage {
+    fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn length(&self) -> usize {
+        self.depths.len()
+    }
+}
+
+
+/// Computes the depth of the reads over regions. Only positions with aligned
+/// read bases are counted, deletions and skipped regions are not. By default,
+/// reads that are unmapped, secondary, duplicates, or fail quality checks are
+/// skipped and positions where both mates of a pair overlap are counted once.
+#[derive(Clone, Debug)]
+pub struct CoverageCalculator {
+    min_mapping_quality: u8,
+    detect_overlaps: bool,
+}
+
+impl CoverageCalculator {
+    pub fn new() -> Self {
+        CoverageCalculator {
+            min_mapping_quality: 0,
+            detect_overlaps: true,
+        }
+    }
+
+    /// Ignores reads with a lower mapping quality
+    pub fn with_min_mapping_quality(mut self, quality: u8) -> Self {
+        self.min_mapping_quality = quality;
+        self
+    }
+
+    /// Enables or disables counting the overlap of both mates of a pair only once
+    pub fn with_overlap_detection(mut self, detect_overlaps: bool) -> Self {
+        self.detect_overlaps = detect_overlaps;
+        self
+    }
+
+    fn accepts(&self, record: &AlignmentRecord) -> bool {
+        !record.is_unmapped() && !record.is_secondary() && !record.is_duplicate() && !record.is_qc_fail() &&
+            record.position().is_some() && record.mapping_quality() >= self.min_mapping_quality
+    }
+
+    /// Returns the template intervals `[offset, end)` of the aligned read bases
+    fn aligned_blocks(record: &AlignmentRecord) -> Vec<(usize, usize)> {
+        let mut blocks: Vec<(usize, usize)> = Vec::new();
+        let mut p = record.offset();
+        for &(l, op) in record.cigar().operations() {
+            if op.is_aligned() {
+                match blocks.last_mut() {
+                    Some(last) if last.1 == p => last.1 += l,
+                    _ => blocks.push((p, p + l)),
+                }
+            }
+            if op.consumes_reference() {
+                p += l;
+            }
+        }
+        blocks
+    }
+
+    /// Computes the depth over the region from the records
+    pub fn coverage<R: Region>(&self, records: &[AlignmentRecord], region: &R) -> Coverage {
+        let mut coverage = Coverage::new(region);
+
+        // The aligned blocks of the first mates seen for pairs whose mates overlap
+        let mut mates: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        for record in records.iter().filter(|r| self.accepts(r) && r.template() == region.template()) {
+            let blocks = CoverageCalculator::aligned_blocks(record);
+            let overlaps_mate = self.detect_overlaps && record.is_paired() && !record.is_mate_unmapped() &&
+                record.mate_template_name() == Some(record.template()) &&
+                record.mate_position().map(|m| m < record.end() && m + record.length() > record.offset()) == Some(true);
+            if !overlaps_mate {
+                for &(s, e) in blocks.iter() {
+                    coverage.add(s, e);
+                }
+                continue;
+            }
+
+            match mates.remove(&record.name()) {
+                // Count only the positions not covered by the first mate
+                Some(first) => for &(s, e) in blocks.iter() {
+                    let mut start = s;
+                    for &(fs, fe) in first.iter().filter(|&&(fs, fe)| fe > s && fs < e) {
+                        coverage.add(start, cmp::max(start, fs));
+                        start = cmp::max(start, fe);
+                    }
+                    coverage.add(start, e);
+                },
+                None => {
+                    for &(s, e) in blocks.iter() {
+                        coverage.add(s, e);
+                    }
+                    mates.insert(record.name(), blocks);
+                }
+            }
+        }
+        coverage
+    }
+
+    /// Reads the records overlapping the region from the BAM file and computes
+    /// the depth. The region is clamped to the template.
+    pub fn from_bam<R: Region>(&self, bam: &mut IndexedBamReader, region: &R) -> Result<Coverage, String> {
+        let region = bam.dictionary().clamp(region)?;
+        let records = bam.query(&region)?;
+        Ok(self.coverage(&records, &region))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use coverage::{Coverage, CoverageCalculator, CoverageSummary};
+    use io::bam::{AlignmentRecord, IndexedBamReader};
+    use model::{Region, SimpleRegion};
+
+    #[test]
+    fn test_summary() {
+        let mut summary = CoverageSummary::new();
+        assert_eq!((summary.mean(), summary.median(), summary.breadth(1)), (0f64, 0f64, 0f64));
+
+        let mut coverage = Coverage::new(&SimpleRegion::new("ref", 10, 5));
+        coverage.add(8, 12);
+        coverage.add(11, 14);
+        coverage.add(12, 20);
+        assert_eq!(coverage.depths(), &[1, 2, 2, 2, 1]);
+        assert_eq!((coverage.depth(11), coverage.depth(9)), (Some(2), None));
+        summary.merge(&coverage.summary());
+        assert_eq!((summary.mean(), summary.median(), summary.breadth(2)), (1.6, 2f64, 0.6));
+        summary.merge(&Coverage::new(&SimpleRegion::new("ref", 0, 3)).summary());
+        assert_eq!((summary.length(), summary.median()), (8, 1f64));
+
+        let runs = coverage.runs();
+        assert_eq!(runs.len(), 3);
+        assert_eq!((runs[1].0.offset(), runs[1].0.end(), runs[1].1), (11, 14, 2));
+    }
+
+    #[test]
+    fn test_bam_coverage() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let coverage = CoverageCalculator::new().from_bam(&mut bam, &SimpleRegion::new("ref", 0, 100)).unwrap();
+        assert_eq!(coverage.length(), 45);
+        // r001 has a deletion at position 19 and r004 skips positions 22 to 35
+        assert_eq!(coverage.depth(17), Some(3));
+        assert_eq!(coverage.depth(18), Some(1));
+        assert_eq!(coverage.depth(25), Some(0));
+        assert_eq!(coverage.depth(36), Some(2));
+    }
+
+    #[test]
+    fn test_overlapping_mates() {
+        let read = |flags: u16, offset: usize, mate: usize, cigar: &str| {
+            AlignmentRecord::new("a")
+                .with_flags(flags)
+                .with_position("ref", offset)
+                .with_mate_position("ref", mate)
+                .with_cigar(cigar)
+                .unwrap()
+        };
+        let records = vec![read(0x1 | 0x40, 0, 4, "3M2N3M"), read(0x1 | 0x80 | 0x10, 4, 0, "6M")];
+        let region = SimpleRegion::new("ref", 0, 12);
+        let coverage = CoverageCalculator::new().coverage(&records, &region);
+        assert_eq!(coverage.depths(), &[1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 0, 0]);
+        let coverage = CoverageCalculator::new().with_overlap_detection(false).coverage(&records, &region);
+        assert_eq!(coverage.depths(), &[1, 1, 1, 0, 1, 2, 2, 2, 1, 1, 0, 0]);
+    }
+}
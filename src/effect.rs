@@ -0,0 +1,708 @@
+use io::fasta::FastaReader;
+use model::{CodingPosition, GenomicVariant, HgvsVariant, IntervalTree, Region, RegionIndex, SimpleRegion, Strand, Transcript, Variant};
+use sequence::{Aminoacid, DnaNucleotide, DnaSequence, GeneticCode, Sequence};
+use std::cmp;
+use std::fmt;
+
+/// The number of intron bases next to an exon that belong to the splice site
+const SPLICE_SITE_LENGTH: usize = 2;
+
+/// The number of intron bases next to an exon that belong to the splice region
+const SPLICE_REGION_INTRON_LENGTH: usize = 8;
+
+/// The number of exon bases next to an intron that belong to the splice region
+const SPLICE_REGION_EXON_LENGTH: usize = 3;
+
+/// The fields of the consequence annotations written to VCF files
+pub const CSQ_FORMAT: &'static str = "Allele|Consequence|IMPACT|Gene|Feature|Codons|Amino_acids|Protein_position|HGVSc|HGVSp";
+
+/// The impact of a consequence on the transcript, ordered from high to low
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    High,
+    Moderate,
+    Low,
+    Modifier,
+}
+
+impl fmt::Display for Impact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Impact::High => write!(f, "HIGH"),
+            Impact::Moderate => write!(f, "MODERATE"),
+            Impact::Low => write!(f, "LOW"),
+            Impact::Modifier => write!(f, "MODIFIER"),
+        }
+    }
+}
+
+/// The consequence of a variant on a transcript. The variants are ordered from
+/// the most to the least severe consequence and are displayed as Sequence
+/// Ontology terms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Consequence {
+    SpliceAcceptor,
+    SpliceDonor,
+    StopGained,
+    Frameshift,
+    StopLost,
+    StartLost,
+    InframeInsertion,
+    InframeDeletion,
+    Missense,
+    SpliceRegion,
+    StopRetained,
+    Synonymous,
+    CodingSequence,
+    FivePrimeUtr,
+    ThreePrimeUtr,
+    NonCodingTranscriptExon,
+    Intron,
+    Upstream,
+    Downstream,
+    Intergenic,
+}
+
+impl Consequence {
+    pub fn impact(&self) -> Impact {
+        match *self {
+            Consequence::SpliceAcceptor |
+            Consequence::SpliceDonor |
+            Consequence::StopGained |
+            Consequence::Frameshift |
+            Consequence::StopLost |
+            Consequence::StartLost => Impact::High,
+            Consequence::InframeInsertion | Consequence::InframeDeletion | Consequence::Missense => Impact::Moderate,
+            Consequence::SpliceRegion | Consequence::StopRetained | Consequence::Synonymous => Impact::Low,
+            _ => Impact::Modifier,
+        }
+    }
+}
+
+impl fmt::Display for Consequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let term = match *self {
+            Consequence::SpliceAcceptor => "splice_acceptor_variant",
+            Consequence::SpliceDonor => "splice_donor_variant",
+            Consequence::StopGained => "stop_gained",
+            Consequence::Frameshift => "frameshift_variant",
+            Consequence::StopLost => "stop_lost",
+            Consequence::StartLost => "start_lost",
+            Consequence::InframeInsertion => "inframe_insertion",
+            Consequence::InframeDeletion => "inframe_deletion",
+            Consequence::Missense => "missense_variant",
+            Consequence::SpliceRegion => "splice_region_variant",
+            Consequence::StopRetained => "stop_retained_variant",
+            Consequence::Synonymous => "synonymous_variant",
+            Consequence::CodingSequence => "coding_sequence_variant",
+            Consequence::FivePrimeUtr => "5_prime_UTR_variant",
+            Consequence::ThreePrimeUtr => "3_prime_UTR_variant",
+            Consequence::NonCodingTranscriptExon => "non_coding_transcript_exon_variant",
+            Consequence::Intron => "intron_variant",
+            Consequence::Upstream => "upstream_gene_variant",
+            Consequence::Downstream => "downstream_gene_variant",
+            Consequence::Intergenic => "intergenic_variant",
+        };
+        write!(f, "{}", term)
+    }
+}
+
+
+/// The effect of a variant on a single transcript. Codons are given in reading
+/// direction with the changed bases in upper case, e.g., `gAt/gCt`. Intergenic
+/// variants have no transcript.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariantEffect {
+    transcript: Option<String>,
+    gene: Option<String>,
+    consequences: Vec<Consequence>,
+    codons: Option<(String, String)>,
+    amino_acids: Option<(String, String)>,
+    protein_position: Option<(usize, usize)>,
+    hgvs_c: Option<HgvsVariant>,
+    hgvs_p: Option<HgvsVariant>,
+}
+
+impl VariantEffect {
+    fn new(transcript: Option<&Transcript>, consequences: Vec<Consequence>) -> Self {
+        let mut consequences = consequences;
+        consequences.sort();
+        consequences.dedup();
+        VariantEffect {
+            transcript: transcript.map(|t| t.id()),
+            gene: transcript.and_then(|t| t.gene_id()),
+            consequences: consequences,
+            codons: None,
+            amino_acids: None,
+            protein_position: None,
+            hgvs_c: None,
+            hgvs_p: None,
+        }
+    }
+
+    /// Returns the id of the affected transcript
+    pub fn transcript_id(&self) -> Option<String> {
+        self.transcript.clone()
+    }
+
+    /// Returns the id of the gene of the transcript
+    pub fn gene(&self) -> Option<String> {
+        self.gene.clone()
+    }
+
+    /// Returns the consequences ordered by severity
+    pub fn consequences(&self) -> Vec<Consequence> {
+        self.consequences.clone()
+    }
+
+    /// Returns the most severe consequence
+    pub fn consequence(&self) -> Consequence {
+        self.consequences[0]
+    }
+
+    pub fn impact(&self) -> Impact {
+        self.consequence().impact()
+    }
+
+    /// Returns the reference and alternative codons
+    pub fn codons(&self) -> Option<(String, String)> {
+        self.codons.clone()
+    }
+
+    /// Returns the reference and alternative amino acids in one letter code. An
+    /// empty side is written as `-`.
+    pub fn amino_acids(&self) -> Option<(String, String)> {
+        self.amino_acids.clone()
+    }
+
+    /// Returns the first and last affected (1-based) protein position
+    pub fn protein_position(&self) -> Option<(usize, usize)> {
+        self.protein_position
+    }
+
+    pub fn hgvs_c(&self) -> Option<HgvsVariant> {
+        self.hgvs_c.clone()
+    }
+
+    pub fn hgvs_p(&self) -> Option<HgvsVariant> {
+        self.hgvs_p.clone()
+    }
+
+    /// Returns the effect as a value of the VCF INFO field described by
+    /// `CSQ_FORMAT`. Reserved characters are percent-encoded.
+    pub fn to_vcf_value(&self, allele: &str) -> String {
+        let optional = |s: Option<String>| s.unwrap_or_default();
+        let pair = |p: Option<(String, String)>| p.map(|(r, a)| format!("{}/{}", r, a));
+        let fields = vec![
+            allele.to_string(),
+            self.consequences.iter().map(|c| c.to_string()).collect::<Vec<String>>().join("&"),
+            self.impact().to_string(),
+            optional(self.gene()),
+            optional(self.transcript_id()),
+            optional(pair(self.codons())),
+            optional(pair(self.amino_acids())),
+            optional(self.protein_position.map(|(s, e)| match s == e {
+                true => s.to_string(),
+                false => format!("{}-{}", s, e),
+            })),
+            optional(self.hgvs_c.as_ref().map(|h| h.to_string())),
+            optional(self.hgvs_p.as_ref().map(|h| h.to_string())),
+        ];
+        fields
+            .iter()
+            .map(|f| f.replace('%', "%25").replace(';', "%3B").replace('=', "%3D").replace(',', "%2C").replace(' ', "%20"))
+            .collect::<Vec<String>>()
+            .join("|")
+    }
+}
+
+
+/// A variant on the coding sequence of a transcript in reading direction
+struct CodingVariant {
+    transcript: String,
+    offset: usize,
+    reference: DnaSequence,
+    alternative: DnaSequence,
+}
+
+impl Variant<DnaNucleotide> for CodingVariant {
+    type SequenceType = DnaSequence;
+
+    fn template(&self) -> String {
+        self.transcript.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn reference(&self) -> DnaSequence {
+        self.reference.clone()
+    }
+
+    fn alternative(&self) -> DnaSequence {
+        self.alternative.clone()
+    }
+}
+
+
+/// Predicts the effects of genomic variants on the transcripts of an annotation.
+/// Variants are expected to be normalized, i.e., the affected interval is given
+/// by the reference and alternative after removing common bases.
+pub struct EffectPredictor {
+    transcripts: IntervalTree<Transcript>,
+    code: GeneticCode,
+    upstream_distance: usize,
+}
+
+impl EffectPredictor {
+    pub fn new(transcripts: Vec<Transcript>) -> Self {
+        EffectPredictor {
+            transcripts: IntervalTree::new(transcripts),
+            code: GeneticCode::standard(),
+            upstream_distance: 5000,
+        }
+    }
+
+    /// Sets the genetic code used for translation (defaults to the standard code)
+    pub fn with_genetic_code(mut self, code: GeneticCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Sets the maximal distance of up- and downstream variants to a transcript
+    /// (defaults to 5000)
+    pub fn with_upstream_distance(mut self, distance: usize) -> Self {
+        self.upstream_distance = distance;
+        self
+    }
+
+    /// Returns the effects on all transcripts near the variant ordered by transcript
+    /// id. Variants without nearby transcripts have a single intergenic effect.
+    pub fn predict<V: GenomicVariant, F: FastaReader>(&self, variant: &V, fasta: &mut F) -> Result<Vec<VariantEffect>, String> {
+        let (offset, reference, _) = variant.normalized_variation();
+        let start = offset.saturating_sub(self.upstream_distance + 1);
+        let query = SimpleRegion::new(variant.template(), start, offset + reference.length() + self.upstream_distance + 1 - start);
+        let mut transcripts = self.transcripts.overlapping(&query);
+        transcripts.sort_by_key(|t| t.id());
+
+        // A transcript that can not be evaluated, e.g., because its sequence is
+        // missing, is skipped and only fails the prediction if no other is left
+        let mut effects = Vec::new();
+        let mut error = None;
+        for t in transcripts.into_iter() {
+            match self.predict_on_transcript(variant, t, fasta) {
+                Ok(Some(e)) => effects.push(e),
+                Ok(None) => (),
+                Err(e) => {
+                    warn!("Skipping transcript '{}': {}", t.id(), e);
+                    error = Some(e);
+                }
+            }
+        }
+        if let (true, Some(e)) = (effects.is_empty(), error) {
+            return Err(e);
+        }
+        if effects.is_empty() {
+            effects.push(VariantEffect::new(None, vec![Consequence::Intergenic]));
+        }
+        Ok(effects)
+    }
+
+    /// Returns the effect on the transcript or `None` if the variant is too far away
+    pub fn predict_on_transcript<V: GenomicVariant, F: FastaReader>(
+        &self,
+        variant: &V,
+        transcript: &Transcript,
+        fasta: &mut F,
+    ) -> Result<Option<VariantEffect>, String> {
+        let (offset, reference, _) = variant.normalized_variation();
+        let (s, e) = (offset, offset + reference.length());
+        let forward = transcript.strand() == Strand::Forward;
+
+        if !overlaps(s, e, transcript.offset(), transcript.end()) {
+            let distance = match e <= transcript.offset() {
+                true => transcript.offset() - e,
+                false => s - transcript.end(),
+            };
+            if distance >= self.upstream_distance {
+                return Ok(None);
+            }
+            let upstream = (e <= transcript.offset()) == forward;
+            let consequence = if upstream { Consequence::Upstream } else { Consequence::Downstream };
+            return Ok(Some(VariantEffect::new(Some(transcript), vec![consequence])));
+        }
+
+        let mut consequences = Vec::new();
+        for intron in transcript.introns().iter() {
+            let (first, last) = (intron.offset(), intron.end());
+            let (left, right) = match forward {
+                true => (Consequence::SpliceDonor, Consequence::SpliceAcceptor),
+                false => (Consequence::SpliceAcceptor, Consequence::SpliceDonor),
+            };
+            let site = SPLICE_SITE_LENGTH;
+            if touches(s, e, first, cmp::min(first + site, last)) {
+                consequences.push(left);
+            } else if touches(s, e, cmp::max(last.saturating_sub(site), first), last) {
+                consequences.push(right);
+            } else if touches(s, e, first.saturating_sub(SPLICE_REGION_EXON_LENGTH), cmp::min(first + SPLICE_REGION_INTRON_LENGTH, last)) ||
+                touches(s, e, cmp::max(last.saturating_sub(SPLICE_REGION_INTRON_LENGTH), first), last + SPLICE_REGION_EXON_LENGTH)
+            {
+                consequences.push(Consequence::SpliceRegion);
+            }
+            if overlaps(s, e, first, last) {
+                consequences.push(Consequence::Intron);
+            }
+        }
+
+        let mut effect = VariantEffect::new(Some(transcript), Vec::new());
+        if transcript.is_coding() {
+            effect.hgvs_c = HgvsVariant::from_variant_on_transcript(variant, transcript, fasta).ok();
+        }
+        if transcript.exons().iter().any(|x| overlaps(s, e, x.offset(), x.end())) {
+            match transcript.is_coding() {
+                true => consequences.append(&mut self.coding_consequences(variant, transcript, fasta, &mut effect)?),
+                false => consequences.push(Consequence::NonCodingTranscriptExon),
+            }
+        }
+        if consequences.is_empty() {
+            // Insertions between an exon and an intron only touch the splice site
+            consequences.push(Consequence::SpliceRegion);
+        }
+        consequences.sort();
+        consequences.dedup();
+        effect.consequences = consequences;
+        Ok(Some(effect))
+    }
+
+    /// Returns the consequences of a variant overlapping an exon of a protein
+    /// coding transcript and sets the codons, amino acids and protein HGVS
+    /// description of the effect
+    fn coding_consequences<V: GenomicVariant, F: FastaReader>(
+        &self,
+        variant: &V,
+        transcript: &Transcript,
+        fasta: &mut F,
+        effect: &mut VariantEffect,
+    ) -> Result<Vec<Consequence>, String> {
+        let (offset, reference, alternative) = variant.normalized_variation();
+        let (reference, alternative) = (DnaSequence::from(reference.vec()), DnaSequence::from(alternative.vec()));
+        let (s, e) = (offset, offset + reference.length());
+        let cds_length = transcript.cds_length();
+
+        // The exonic bases of the variant or both bases next to an insertion
+        let bases = if s == e { s.saturating_sub(1)..e + 1 } else { s..e };
+        let mut positions = Vec::new();
+        for o in bases.clone() {
+            if transcript.spliced_position(o).is_some() {
+                positions.push(CodingPosition::from_template_offset(transcript, o)?);
+            }
+        }
+        let mut consequences = Vec::new();
+        if positions.iter().any(|p| !p.is_three_prime() && p.position() < 1) {
+            consequences.push(Consequence::FivePrimeUtr);
+        }
+        if positions.iter().any(|p| p.is_three_prime()) {
+            consequences.push(Consequence::ThreePrimeUtr);
+        }
+        if !positions.iter().any(|p| p.is_coding(cds_length)) {
+            return Ok(consequences);
+        }
+
+        let complete = transcript.cds()[0].phase() == 0 && positions.len() == bases.len() && positions.iter().all(|p| p.is_coding(cds_length));
+        if !complete {
+            // Variants that span the boundaries of the coding sequence
+            let positions: Vec<isize> = positions.iter().filter(|p| p.is_coding(cds_length)).map(|p| p.position()).collect();
+            // Insertions only change the start or stop codon if both neighboring bases
+            // belong to the codon, otherwise they lie in the UTR next to it
+            if s == e && positions.len() < 2 {
+                if consequences.is_empty() {
+                    consequences.push(Consequence::CodingSequence);
+                }
+                return Ok(consequences);
+            }
+            let in_start_codon = |p: &isize| *p <= 3;
+            let in_stop_codon = |p: &isize| *p > cds_length as isize - 3;
+            let (start_lost, stop_lost) = match s == e {
+                true => (positions.iter().all(in_start_codon), positions.iter().all(in_stop_codon)),
+                false => (positions.iter().any(in_start_codon), positions.iter().any(in_stop_codon)),
+            };
+            if start_lost {
+                consequences.push(Consequence::StartLost);
+            }
+            if stop_lost {
+                consequences.push(Consequence::StopLost);
+            }
+            consequences.push(Consequence::CodingSequence);
+            return Ok(consequences);
+        }
+        effect.hgvs_p = HgvsVariant::protein_consequence(variant, transcript, fasta, &self.code).ok();
+
+        // Applies the variant to the coding sequence in reading direction
+        let index = |p: &CodingPosition| p.position() as usize - 1;
+        let (cds_offset, reference, alternative) = match (transcript.strand(), s == e) {
+            (Strand::Forward, true) => (index(&positions[0]) + 1, reference, alternative),
+            (Strand::Forward, false) => (index(&positions[0]), reference, alternative),
+            (Strand::Backward, true) => (index(&positions[1]) + 1, reference.reverse_strand(), alternative.reverse_strand()),
+            (Strand::Backward, false) => (index(&positions[positions.len() - 1]), reference.reverse_strand(), alternative.reverse_strand()),
+        };
+        let coding = CodingVariant {
+            transcript: transcript.id(),
+            offset: cds_offset,
+            reference: reference,
+            alternative: alternative,
+        };
+        let cds = transcript.cds_sequence(fasta)?;
+        if !coding.check_variant_reference(&cds) {
+            return Err(format!(
+                "The reference allele of the variant at {}:{} does not match the coding sequence of '{}'",
+                variant.template(),
+                variant.offset() + 1,
+                transcript.id()
+            ));
+        }
+        let altered = coding.apply_variant(&cds);
+
+        // The affected codons of both sequences
+        let (reference_length, alternative_length) = (coding.reference.length(), coding.alternative.length());
+        let first_codon = cds_offset / 3;
+        let last_codon = cmp::min((cds_offset + reference_length + 2) / 3, (cds.length() + 2) / 3);
+        let reference_codons = cds.subsequence(first_codon * 3, cmp::min(last_codon * 3, cds.length()) - first_codon * 3);
+        let alternative_end = cmp::min(last_codon * 3 + alternative_length - reference_length, altered.length());
+        let alternative_codons = altered.subsequence(first_codon * 3, alternative_end - first_codon * 3);
+        let changed = cds_offset - first_codon * 3;
+        effect.codons = Some((
+            codon_string(&reference_codons, changed, reference_length),
+            codon_string(&alternative_codons, changed, alternative_length),
+        ));
+        effect.protein_position = Some(match last_codon > first_codon {
+            true => (first_codon + 1, last_codon),
+            false => (first_codon, first_codon + 1),
+        });
+
+        let translate = |s: &DnaSequence| -> Vec<Aminoacid> { s.codons().iter().map(|c| self.code.translate(c)).collect() };
+        let reference_aminoacids = translate(&reference_codons);
+        let start_lost = first_codon == 0 && reference_length > 0 && altered.subsequence(0, 3) != cds.subsequence(0, 3) &&
+            !altered.codons().first().map(|c| self.code.is_start(c)).unwrap_or(false);
+        if start_lost {
+            effect.amino_acids = Some((peptide_string(&reference_aminoacids), "-".to_string()));
+            consequences.push(Consequence::StartLost);
+            return Ok(consequences);
+        }
+        if (alternative_length as isize - reference_length as isize) % 3 != 0 {
+            effect.amino_acids = Some((peptide_string(&reference_aminoacids), "X".to_string()));
+            consequences.push(Consequence::Frameshift);
+            return Ok(consequences);
+        }
+
+        let alternative_aminoacids = translate(&alternative_codons);
+        let reference_stop = reference_aminoacids.contains(&Aminoacid::Stop);
+        let alternative_stop = alternative_aminoacids.contains(&Aminoacid::Stop);
+        consequences.push(match (reference_stop, alternative_stop) {
+            (false, true) => Consequence::StopGained,
+            (true, false) => Consequence::StopLost,
+            (true, true) if reference_aminoacids == alternative_aminoacids => Consequence::StopRetained,
+            _ if reference_aminoacids == alternative_aminoacids => Consequence::Synonymous,
+            _ if alternative_length > reference_length => Consequence::InframeInsertion,
+            _ if alternative_length < reference_length => Consequence::InframeDeletion,
+            _ => Consequence::Missense,
+        });
+        effect.amino_acids = Some(match reference_aminoacids == alternative_aminoacids {
+            true => (peptide_string(&reference_aminoacids), peptide_string(&reference_aminoacids)),
+            false => (peptide_string(&reference_aminoacids), peptide_string(&alternative_aminoacids)),
+        });
+        Ok(consequences)
+    }
+}
+
+/// Returns `true` if the interval `[s, e)` overlaps the region `[first, last)`.
+/// Insertions (`s == e`) overlap if both adjacent bases are in the region.
+fn overlaps(s: usize, e: usize, first: usize, last: usize) -> bool {
+    match s == e {
+        true => first < s && s < last,
+        false => s < last && e > first,
+    }
+}
+
+/// Returns `true` if the interval `[s, e)` overlaps the region `[first, last)`.
+/// Insertions (`s == e`) touch the region if one adjacent base is in it.
+fn touches(s: usize, e: usize, first: usize, last: usize) -> bool {
+    match s == e {
+        true => first < last && first <= s && s <= last,
+        false => overlaps(s, e, first, last),
+    }
+}
+
+/// Writes the codons in lower case with the `length` changed bases starting at
+/// `changed` in upper case. Empty codons are written as `-`.
+fn codon_string(codons: &DnaSequence, changed: usize, length: usize) -> String {
+    if codons.is_empty() {
+        return "-".to_string();
+    }
+    codons
+        .to_string()
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match i >= changed && i < changed + length {
+            true => c.to_ascii_uppercase(),
+            false => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+fn peptide_string(aminoacids: &[Aminoacid]) -> String {
+    match aminoacids.is_empty() {
+        true => "-".to_string(),
+        false => aminoacids.iter().map(|a| char::from(a)).collect(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use effect::{Consequence, EffectPredictor, Impact, VariantEffect};
+    use io::fasta::IndexedFastaFile;
+    use io::gtf::GtfStream;
+    use io::vcf::VcfRecord;
+    use model::{SimpleRegion, Strand, Transcript};
+
+    fn predictor() -> EffectPredictor {
+        let records = GtfStream::open(&"testdata/toy.gtf").unwrap().read_records().unwrap();
+        EffectPredictor::new(Transcript::from_gtf_records(&records).unwrap())
+    }
+
+    fn predict(predictor: &EffectPredictor, template: &str, position: usize, reference: &str, alternative: &str) -> Vec<VariantEffect> {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let variant = VcfRecord::new(template, position, reference, vec![alternative.to_string()]);
+        predictor.predict(&variant, &mut fasta).unwrap()
+    }
+
+    fn consequences(effect: &VariantEffect) -> String {
+        effect.consequences().iter().map(|c| c.to_string()).collect::<Vec<String>>().join("&")
+    }
+
+    #[test]
+    fn test_coding_effects() {
+        let p = predictor();
+        // The coding sequence of t1 is ATG TTA GAT AAG ATA GCG TAG
+        let effect = |position: usize, reference: &str, alternative: &str| {
+            let effects = predict(&p, "ref", position, reference, alternative);
+            assert_eq!(effects.len(), 1);
+            let e = effects[0].clone();
+            assert_eq!(e.transcript_id(), Some("t1".to_string()));
+            let pair = |p: Option<(String, String)>| p.map(|(r, a)| format!("{}/{}", r, a)).unwrap_or_default();
+            (consequences(&e), pair(e.codons()), pair(e.amino_acids()))
+        };
+        let c = |c: &str, codons: &str, aminoacids: &str| (c.to_string(), codons.to_string(), aminoacids.to_string());
+
+        assert_eq!(effect(10, "G", "C"), c("missense_variant", "Gat/Cat", "D/H"));
+        assert_eq!(effect(8, "T", "A"), c("stop_gained", "tTa/tAa", "L/*"));
+        assert_eq!(effect(28, "G", "A"), c("splice_region_variant&synonymous_variant", "gcG/gcA", "A/A"));
+        assert_eq!(effect(5, "T", "C"), c("start_lost", "aTg/aCg", "M/-"));
+        assert_eq!(effect(29, "T", "C"), c("stop_lost&splice_region_variant", "Tag/Cag", "*/Q"));
+        assert_eq!(effect(12, "TA", "T"), c("frameshift_variant", "Aag/ag", "K/X"));
+        assert_eq!(effect(12, "T", "TGAT"), c("inframe_insertion", "-/GAT", "-/D"));
+        assert_eq!(effect(9, "AGAT", "A"), c("inframe_deletion", "GAT/-", "D/-"));
+        // The last base of the first exon is in the splice region
+        assert_eq!(effect(20, "C", "T"), c("missense_variant&splice_region_variant", "gCg/gTg", "A/V"));
+
+        let effects = predict(&p, "ref", 10, "G", "C");
+        assert_eq!(effects[0].impact(), Impact::Moderate);
+        assert_eq!(effects[0].protein_position(), Some((3, 3)));
+        assert_eq!(effects[0].gene(), Some("g1".to_string()));
+        assert_eq!(effects[0].hgvs_c().unwrap().to_string(), "t1:c.7G>C");
+        assert_eq!(effects[0].hgvs_p().unwrap().to_string(), "p.(Asp3His)");
+        assert_eq!(
+            effects[0].to_vcf_value("C"),
+            "C|missense_variant|MODERATE|g1|t1|Gat/Cat|D/H|3|t1:c.7G>C|p.(Asp3His)"
+        );
+        let effects = predict(&p, "ref", 28, "G", "A");
+        assert_eq!(
+            effects[0].to_vcf_value("A"),
+            "A|splice_region_variant&synonymous_variant|LOW|g1|t1|gcG/gcA|A/A|6|t1:c.18G>A|p.(Ala6%3D)"
+        );
+    }
+
+    #[test]
+    fn test_non_coding_effects() {
+        let p = predictor();
+        let effect = |template: &str, position: usize, reference: &str, alternative: &str| {
+            predict(&p, template, position, reference, alternative)
+                .iter()
+                .map(|e| format!("{}:{}", e.transcript_id().unwrap_or_default(), consequences(e)))
+                .collect::<Vec<String>>()
+        };
+
+        // The intron of t1 is ref:21-27
+        assert_eq!(effect("ref", 22, "G", "A"), vec!["t1:splice_donor_variant&intron_variant"]);
+        let effects = predict(&p, "ref", 22, "G", "A");
+        assert_eq!(effects[0].hgvs_c().unwrap().to_string(), "t1:c.17+2G>A");
+        assert_eq!(effects[0].codons(), None);
+        assert_eq!(effect("ref", 27, "A", "G"), vec!["t1:splice_acceptor_variant&intron_variant"]);
+        assert_eq!(effect("ref", 24, "G", "A"), vec!["t1:splice_region_variant&intron_variant"]);
+        assert_eq!(effect("ref", 2, "G", "A"), vec!["t1:5_prime_UTR_variant"]);
+        assert_eq!(effect("ref", 36, "T", "A"), vec!["t1:3_prime_UTR_variant"]);
+        assert_eq!(effect("ref", 1, "A", "C"), vec!["t1:upstream_gene_variant"]);
+        assert_eq!(effect("ref", 45, "T", "C"), vec!["t1:downstream_gene_variant"]);
+        assert_eq!(effect("ref", 2, "GCA", "G"), vec!["t1:start_lost&coding_sequence_variant&5_prime_UTR_variant"]);
+        // Insertions right before the start codon and right after the stop codon keep them intact
+        assert_eq!(effect("ref", 3, "C", "CT"), vec!["t1:5_prime_UTR_variant"]);
+        assert_eq!(effect("ref", 31, "G", "GA"), vec!["t1:3_prime_UTR_variant"]);
+
+        // t2 and t3 are non-coding transcripts on the reverse strand of ref2
+        assert_eq!(
+            effect("ref2", 31, "C", "G"),
+            vec!["t2:non_coding_transcript_exon_variant", "t3:non_coding_transcript_exon_variant"]
+        );
+        assert_eq!(effect("ref2", 16, "A", "G"), vec!["t2:splice_region_variant&intron_variant", "t3:downstream_gene_variant"]);
+        assert_eq!(effect("ref2", 2, "G", "T"), vec!["t2:downstream_gene_variant", "t3:downstream_gene_variant"]);
+        assert_eq!(effect("ref2", 39, "C", "T"), vec!["t2:upstream_gene_variant", "t3:upstream_gene_variant"]);
+
+        let p = predictor().with_upstream_distance(2);
+        let effects = predict(&p, "ref", 45, "T", "C");
+        assert_eq!(effects.len(), 1);
+        assert_eq!(effects[0].transcript_id(), None);
+        assert_eq!(effects[0].consequence(), Consequence::Intergenic);
+        assert_eq!(effects[0].to_vcf_value("C"), "C|intergenic_variant|MODIFIER|||||||");
+    }
+
+    #[test]
+    fn test_failing_transcript() {
+        // The coding sequence of t5 extends beyond the end of the reference
+        let gtf = "ref\ttoy\ttranscript\t2\t60\t.\t+\t.\tgene_id \"g5\"; transcript_id \"t5\";\n\
+                   ref\ttoy\texon\t2\t60\t.\t+\t.\tgene_id \"g5\"; transcript_id \"t5\";\n\
+                   ref\ttoy\tCDS\t4\t60\t.\t+\t0\tgene_id \"g5\"; transcript_id \"t5\";\n";
+        let mut records = GtfStream::open(&"testdata/toy.gtf").unwrap().read_records().unwrap();
+        records.extend(GtfStream::new(gtf.as_bytes()).read_records().unwrap());
+        let p = EffectPredictor::new(Transcript::from_gtf_records(&records).unwrap());
+        let effects = predict(&p, "ref", 10, "G", "C");
+        assert_eq!(effects.len(), 1);
+        assert_eq!(effects[0].transcript_id(), Some("t1".to_string()));
+        assert_eq!(effects[0].consequence(), Consequence::Missense);
+
+        // The reference allele matches neither transcript, so the prediction fails
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let variant = VcfRecord::new("ref", 11, "G", vec!["C".to_string()]);
+        assert!(p.predict(&variant, &mut fasta).is_err());
+    }
+
+    #[test]
+    fn test_reverse_strand() {
+        // The coding sequence is AGC ACA GCT ATC TAA
+        let t = Transcript::new("t", "ref", Strand::Backward)
+            .with_exon(SimpleRegion::new("ref", 0, 10))
+            .with_exon(SimpleRegion::new("ref", 15, 15))
+            .with_cds(SimpleRegion::new("ref", 6, 4), 0)
+            .with_cds(SimpleRegion::new("ref", 15, 11), 0);
+        let p = EffectPredictor::new(vec![t]);
+        let effects = predict(&p, "ref", 20, "C", "T");
+        assert_eq!(consequences(&effects[0]), "missense_variant");
+        assert_eq!(effects[0].codons(), Some(("Gct".to_string(), "Act".to_string())));
+        assert_eq!(effects[0].amino_acids(), Some(("A".to_string(), "T".to_string())));
+        assert_eq!(effects[0].protein_position(), Some((3, 3)));
+        let effects = predict(&p, "ref", 8, "T", "G");
+        assert_eq!(effects[0].codons(), Some(("tAa".to_string(), "tCa".to_string())));
+        // The last three bases of the exon are in the splice region
+        assert_eq!(consequences(&effects[0]), "stop_lost&splice_region_variant");
+    }
+}
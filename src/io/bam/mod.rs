@@ -0,0 +1,8 @@
+mod record;
+#[allow(unused_imports)]
+pub use self::record::{AlignmentRecord, TagValue};
+mod reader;
+#[allow(unused_imports)]
+pub use self::reader::{BamReader, IndexedBamReader};
+mod sam;
+pub use self::sam::SamStream;
@@ -0,0 +1,163 @@
+extern crate rust_htslib;
+use self::rust_htslib::bam;
+use self::rust_htslib::bam::Read;
+use io::bam::AlignmentRecord;
+use model::{Region, SequenceDictionary};
+use std::path::Path;
+
+/// Reads the template names in the order of their ids and the sequence
+/// dictionary from the header of a BAM file
+fn read_header(header: &bam::HeaderView) -> Result<(Vec<String>, SequenceDictionary), String> {
+    let names: Vec<String> = header
+        .target_names()
+        .iter()
+        .map(|n| String::from_utf8_lossy(n).to_string())
+        .collect();
+    let mut dictionary = SequenceDictionary::new();
+    for (tid, name) in names.iter().enumerate() {
+        match header.target_len(tid as u32) {
+            Some(l) => dictionary = dictionary.with_sequence(name, l as usize),
+            None => return Err(format!("Missing length of template '{}' in BAM header", name)),
+        }
+    }
+    Ok((names, dictionary))
+}
+
+/// Reads the next record of a BAM reader or returns `None` at the end of the file
+fn read_next<R: Read>(reader: &R, template_names: &[String]) -> Option<Result<AlignmentRecord, String>> {
+    let mut record = bam::Record::new();
+    match reader.read(&mut record) {
+        Ok(_) => Some(AlignmentRecord::from_htslib(&record, template_names)),
+        Err(ref e) if e.is_eof() => None,
+        Err(e) => Some(Err(format!("Can not read BAM record: {}", e))),
+    }
+}
+
+
+/// A sequential reader for BAM files that yields all records in the order
+/// they are stored.
+pub struct BamReader {
+    reader: bam::Reader,
+    template_names: Vec<String>,
+    dictionary: SequenceDictionary,
+}
+
+impl BamReader {
+    pub fn open<P: AsRef<Path>>(path: &P) -> Result<BamReader, String> {
+        let reader = match bam::Reader::from_path(path) {
+            Ok(r) => r,
+            Err(e) => return Err(format!("Can not open BAM file '{}': {}", path.as_ref().display(), e)),
+        };
+        let (template_names, dictionary) = read_header(reader.header())?;
+        Ok(BamReader {
+            reader: reader,
+            template_names: template_names,
+            dictionary: dictionary,
+        })
+    }
+
+    /// Returns the templates defined in the header
+    pub fn dictionary(&self) -> &SequenceDictionary {
+        &self.dictionary
+    }
+}
+
+impl Iterator for BamReader {
+    type Item = Result<AlignmentRecord, String>;
+
+    fn next(&mut self) -> Option<Result<AlignmentRecord, String>> {
+        read_next(&self.reader, &self.template_names)
+    }
+}
+
+
+/// A BAM file with random access through its `.bai` index.
+pub struct IndexedBamReader {
+    reader: bam::IndexedReader,
+    template_names: Vec<String>,
+    dictionary: SequenceDictionary,
+}
+
+impl IndexedBamReader {
+    pub fn open<P: AsRef<Path>>(path: &P) -> Result<IndexedBamReader, String> {
+        let reader = match bam::IndexedReader::from_path(path) {
+            Ok(r) => r,
+            Err(e) => return Err(format!("Can not open indexed BAM file '{}': {}", path.as_ref().display(), e)),
+        };
+        let (template_names, dictionary) = read_header(&reader.header)?;
+        Ok(IndexedBamReader {
+            reader: reader,
+            template_names: template_names,
+            dictionary: dictionary,
+        })
+    }
+
+    /// Returns the templates defined in the header
+    pub fn dictionary(&self) -> &SequenceDictionary {
+        &self.dictionary
+    }
+
+    /// Returns all records overlapping the region. The region is clamped to the
+    /// template length and its template may be given by an alias like `chr1`
+    /// for `1`.
+    pub fn query<R: Region>(&mut self, region: &R) -> Result<Vec<AlignmentRecord>, String> {
+        let region = self.dictionary.clamp(region)?;
+        let tid = match self.template_names.iter().position(|n| *n == region.template()) {
+            Some(tid) => tid as u32,
+            None => return Err(format!("Can not find template '{}' in BAM", region.template())),
+        };
+        debug!("Extracting reads in range from {} to {}", region.offset(), region.end());
+        if let Err(e) = self.reader.seek(tid, region.offset() as u32, region.end() as u32) {
+            return Err(format!("Can not seek the region '{}': {}", region, e));
+        }
+
+        let mut records = Vec::new();
+        while let Some(record) = read_next(&self.reader, &self.template_names) {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::bam::{BamReader, IndexedBamReader};
+    use model::{Region, SimpleRegion};
+    use std::usize;
+
+    #[test]
+    pub fn test_bam_read() {
+        let records: Vec<_> = BamReader::open(&"testdata/toy.bam").unwrap().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 12);
+        assert_eq!(records[0].name(), "r001");
+        assert_eq!(records[0].cigar().to_string(), "8M4I4M1D3M");
+        assert_eq!(records[0].mate_position(), Some(36));
+        assert_eq!(records[0].tag("XX").unwrap().to_string(), "S,12561,2,20,112");
+        assert!(records[4].is_reverse());
+        assert_eq!(records[6].qualities().unwrap().scores()[0], 30);
+
+        assert!(BamReader::open(&"testdata/missing.bam").is_err());
+    }
+
+    #[test]
+    pub fn test_bam_open_region() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        assert_eq!(bam.dictionary().length("ref"), Some(45));
+
+        let region = SimpleRegion::new("ref".to_string(), 0, usize::MAX);
+        assert_eq!(bam.query(&region).unwrap().len(), 6, "Records on region '{}'", region);
+    }
+
+    #[test]
+    pub fn test_bam_open_region_range() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let region = SimpleRegion::new("ref", 8, 2);
+        let records = bam.query(&region).unwrap();
+        assert_eq!(records.len(), 3, "Records on region '{}'", region);
+        assert!(records.iter().all(|r| r.overlaps(&region)));
+
+        assert!(bam.query(&SimpleRegion::new("chrref2", 0, 5)).is_ok());
+        assert!(bam.query(&SimpleRegion::new("ref3", 0, 5)).is_err());
+    }
+}
@@ -0,0 +1,791 @@
+extern crate rust_htslib;
+
+use self::rust_htslib::bam::record::Record;
+use alignment::{Alignment, Cigar, CigarOperation, MdTag};
+use io::fasta::FastaReader;
+use model::Region;
+use sequence::Sequence;
+use sequence::dna::{DnaNucleotide, DnaSequence};
+use sequence::quality::{PhredEncoding, QualitySequence};
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+
+const FLAG_PAIRED: u16 = 0x1;
+const FLAG_PROPER_PAIR: u16 = 0x2;
+const FLAG_UNMAPPED: u16 = 0x4;
+const FLAG_MATE_UNMAPPED: u16 = 0x8;
+const FLAG_REVERSE: u16 = 0x10;
+const FLAG_MATE_REVERSE: u16 = 0x20;
+const FLAG_FIRST_IN_PAIR: u16 = 0x40;
+const FLAG_SECOND_IN_PAIR: u16 = 0x80;
+const FLAG_SECONDARY: u16 = 0x100;
+const FLAG_QC_FAIL: u16 = 0x200;
+const FLAG_DUPLICATE: u16 = 0x400;
+const FLAG_SUPPLEMENTARY: u16 = 0x800;
+
+/// The value of an optional field (tag) of an alignment record
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagValue {
+    Char(char),
+    Integer(i64),
+    Float(f32),
+    String(String),
+    Hex(String),
+    /// An array of integers with its SAM subtype (`c`, `C`, `s`, `S`, `i` or `I`)
+    IntegerArray(char, Vec<i64>),
+    FloatArray(Vec<f32>),
+}
+
+impl TagValue {
+    /// Returns the SAM type code of the value
+    pub fn type_code(&self) -> char {
+        match self {
+            &TagValue::Char(_) => 'A',
+            &TagValue::Integer(_) => 'i',
+            &TagValue::Float(_) => 'f',
+            &TagValue::String(_) => 'Z',
+            &TagValue::Hex(_) => 'H',
+            &TagValue::IntegerArray(_, _) | &TagValue::FloatArray(_) => 'B',
+        }
+    }
+
+    /// Parses the value of a tag in SAM notation
+    pub fn parse(type_code: &str, value: &str) -> Result<TagValue, String> {
+        let parse_integer = |v: &str| v.parse::<i64>().map_err(|e| format!("Can not parse integer '{}': {}", v, e));
+        let parse_float = |v: &str| v.parse::<f32>().map_err(|e| format!("Can not parse float '{}': {}", v, e));
+        match type_code {
+            "A" => match value.chars().count() {
+                1 => Ok(TagValue::Char(value.chars().next().unwrap())),
+                _ => Err(format!("Expecting a single character but found '{}'", value)),
+            },
+            "i" => parse_integer(value).map(TagValue::Integer),
+            "f" => parse_float(value).map(TagValue::Float),
+            "Z" => Ok(TagValue::String(value.to_string())),
+            "H" => Ok(TagValue::Hex(value.to_string())),
+            "B" => {
+                let mut cells = value.split(',');
+                match cells.next() {
+                    Some("f") => cells.map(parse_float).collect::<Result<Vec<f32>, String>>().map(TagValue::FloatArray),
+                    Some(s) if s.len() == 1 && "cCsSiI".contains(s) => cells
+                        .map(parse_integer)
+                        .collect::<Result<Vec<i64>, String>>()
+                        .map(|v| TagValue::IntegerArray(s.chars().next().unwrap(), v)),
+                    _ => Err(format!("Unknown array type in '{}'", value)),
+                }
+            }
+            _ => Err(format!("Unknown tag type '{}'", type_code)),
+        }
+    }
+
+    /// Returns the integer value or `None` for other types
+    pub fn integer(&self) -> Option<i64> {
+        match self {
+            &TagValue::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value or `None` for other types
+    pub fn string(&self) -> Option<String> {
+        match self {
+            &TagValue::String(ref s) | &TagValue::Hex(ref s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TagValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &TagValue::Char(c) => write!(f, "{}", c),
+            &TagValue::Integer(i) => write!(f, "{}", i),
+            &TagValue::Float(v) => write!(f, "{}", v),
+            &TagValue::String(ref s) | &TagValue::Hex(ref s) => write!(f, "{}", s),
+            &TagValue::IntegerArray(t, ref v) => {
+                write!(f, "{}", t)?;
+                v.iter().map(|i| write!(f, ",{}", i)).collect()
+            }
+            &TagValue::FloatArray(ref v) => {
+                write!(f, "f")?;
+                v.iter().map(|i| write!(f, ",{}", i)).collect()
+            }
+        }
+    }
+}
+
+
+/// A read aligned against a template as stored in SAM and BAM files. All
+/// positions are 0-based and the sequence is stored in the orientation of the
+/// template, i.e., reverse complemented for reads on the reverse strand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlignmentRecord {
+    name: String,
+    flags: u16,
+    template: Option<String>,
+    position: Option<usize>,
+    mapping_quality: u8,
+    cigar: Cigar,
+    mate_template: Option<String>,
+    mate_position: Option<usize>,
+    insert_size: i64,
+    sequence: String,
+    qualities: Option<QualitySequence>,
+    tags: Vec<(String, TagValue)>,
+}
+
+impl AlignmentRecord {
+    /// Creates an unmapped record without sequence
+    pub fn new<S: ToString>(name: S) -> Self {
+        AlignmentRecord {
+            name: name.to_string(),
+            flags: FLAG_UNMAPPED,
+            template: None,
+            position: None,
+            mapping_quality: 255,
+            cigar: Cigar::new(),
+            mate_template: None,
+            mate_position: None,
+            insert_size: 0,
+            sequence: String::new(),
+            qualities: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Converts a record read by `rust_htslib`. The `template_names` are the
+    /// target names of the BAM header used to resolve the template ids.
+    pub fn from_htslib(record: &Record, template_names: &[String]) -> Result<Self, String> {
+        let template_name = |tid: i32| match tid {
+            tid if tid < 0 => Ok(None),
+            tid => match template_names.get(tid as usize) {
+                Some(n) => Ok(Some(n.clone())),
+                None => Err(format!("Unknown template id {} for read '{}'", tid, String::from_utf8_lossy(record.qname()))),
+            },
+        };
+        let position = |pos: i32| match pos < 0 {
+            true => None,
+            false => Some(pos as usize),
+        };
+
+        let qualities = match record.qual().iter().all(|q| *q == 0xff) {
+            true => None,
+            false => Some(QualitySequence::from(record.qual().to_vec())),
+        };
+
+        // `Record::cigar` of rust_htslib reads the CIGAR through a `u32` pointer that is
+        // not necessarily aligned, so it is decoded from the bytes instead
+        let core = &record.inner().core;
+        let data = variable_data(record);
+        let cigar_offset = core.l_qname as usize;
+        let aux_offset = cigar_offset + core.n_cigar as usize * 4 + (core.l_qseq as usize + 1) / 2 + core.l_qseq as usize;
+        let cigar_bytes = match data.get(cigar_offset..cigar_offset + core.n_cigar as usize * 4) {
+            Some(b) => b,
+            None => return Err(format!("CIGAR of read '{}' is truncated", String::from_utf8_lossy(record.qname()))),
+        };
+        let mut cigar = Cigar::new();
+        for bytes in cigar_bytes.chunks(4) {
+            let c = bytes.iter().rev().fold(0u32, |v, b| (v << 8) | *b as u32);
+            match "MIDNSHP=X".chars().nth((c & 0xf) as usize).and_then(CigarOperation::from_code) {
+                Some(op) => cigar = cigar.with_operation((c >> 4) as usize, op),
+                None => return Err(format!("Unsupported CIGAR operation code {}", c & 0xf)),
+            }
+        }
+
+        Ok(AlignmentRecord {
+            name: String::from_utf8_lossy(record.qname()).to_string(),
+            flags: record.flags(),
+            template: template_name(record.tid())?,
+            position: position(record.pos()),
+            mapping_quality: record.mapq(),
+            cigar: cigar,
+            mate_template: template_name(record.mtid())?,
+            mate_position: position(record.mpos()),
+            insert_size: record.insert_size() as i64,
+            sequence: record.seq().as_bytes().iter().map(|b| *b as char).collect(),
+            qualities: qualities,
+            tags: parse_binary_tags(data.get(aux_offset..).unwrap_or(&[]))?,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the bitwise flags
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    pub fn with_flags(mut self, flags: u16) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn is_paired(&self) -> bool {
+        self.flags & FLAG_PAIRED > 0
+    }
+
+    pub fn is_proper_pair(&self) -> bool {
+        self.flags & FLAG_PROPER_PAIR > 0
+    }
+
+    pub fn is_unmapped(&self) -> bool {
+        self.flags & FLAG_UNMAPPED > 0 || self.template.is_none() || self.position.is_none()
+    }
+
+    pub fn is_mate_unmapped(&self) -> bool {
+        self.flags & FLAG_MATE_UNMAPPED > 0
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        self.flags & FLAG_REVERSE > 0
+    }
+
+    pub fn is_mate_reverse(&self) -> bool {
+        self.flags & FLAG_MATE_REVERSE > 0
+    }
+
+    pub fn is_first_in_pair(&self) -> bool {
+        self.flags & FLAG_FIRST_IN_PAIR > 0
+    }
+
+    pub fn is_second_in_pair(&self) -> bool {
+        self.flags & FLAG_SECOND_IN_PAIR > 0
+    }
+
+    pub fn is_secondary(&self) -> bool {
+        self.flags & FLAG_SECONDARY > 0
+    }
+
+    pub fn is_qc_fail(&self) -> bool {
+        self.flags & FLAG_QC_FAIL > 0
+    }
+
+    pub fn is_duplicate(&self) -> bool {
+        self.flags & FLAG_DUPLICATE > 0
+    }
+
+    pub fn is_supplementary(&self) -> bool {
+        self.flags & FLAG_SUPPLEMENTARY > 0
+    }
+
+    /// Returns the name of the template the read is aligned to
+    pub fn template_name(&self) -> Option<String> {
+        self.template.clone()
+    }
+
+    /// Returns the 0-based offset of the first aligned base
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Places the read at the given template offset and marks it as mapped
+    pub fn with_position<S: ToString>(mut self, template: S, offset: usize) -> Self {
+        self.template = Some(template.to_string());
+        self.position = Some(offset);
+        self.flags &= !FLAG_UNMAPPED;
+        self
+    }
+
+    /// Returns the mapping quality where 255 means that it is not available
+    pub fn mapping_quality(&self) -> u8 {
+        self.mapping_quality
+    }
+
+    pub fn with_mapping_quality(mut self, mapping_quality: u8) -> Self {
+        self.mapping_quality = mapping_quality;
+        self
+    }
+
+    /// Returns the CIGAR which is empty if it is not available
+    pub fn cigar(&self) -> Cigar {
+        self.cigar.clone()
+    }
+
+    /// Sets the CIGAR. Fails if the CIGAR string can not be parsed or does not
+    /// match the length of the sequence.
+    pub fn with_cigar(mut self, cigar: &str) -> Result<Self, String> {
+        self.cigar = Cigar::from_str(cigar)?;
+        self.check_lengths()?;
+        Ok(self)
+    }
+
+    /// Returns the template name of the mate
+    pub fn mate_template_name(&self) -> Option<String> {
+        self.mate_template.clone()
+    }
+
+    /// Returns the 0-based offset of the mate
+    pub fn mate_position(&self) -> Option<usize> {
+        self.mate_position
+    }
+
+    pub fn with_mate_position<S: ToString>(mut self, template: S, offset: usize) -> Self {
+        self.mate_template = Some(template.to_string());
+        self.mate_position = Some(offset);
+        self
+    }
+
+    /// Returns the observed template length (TLEN) which is negative for the
+    /// rightmost read of a pair
+    pub fn insert_size(&self) -> i64 {
+        self.insert_size
+    }
+
+    pub fn with_insert_size(mut self, insert_size: i64) -> Self {
+        self.insert_size = insert_size;
+        self
+    }
+
+    /// Returns the read sequence or an empty string if it is not stored
+    pub fn sequence(&self) -> String {
+        self.sequence.clone()
+    }
+
+    /// Converts the read sequence into a DNA sequence
+    pub fn as_dna(&self) -> DnaSequence {
+        self.sequence.chars().map(DnaNucleotide::from).collect()
+    }
+
+    /// Returns the base qualities if they are stored
+    pub fn qualities(&self) -> Option<QualitySequence> {
+        self.qualities.clone()
+    }
+
+    /// Sets the sequence and the base qualities. Fails if the lengths do not match
+    /// each other or the CIGAR.
+    pub fn with_sequence<S: ToString>(mut self, sequence: S, qualities: Option<QualitySequence>) -> Result<Self, String> {
+        self.sequence = sequence.to_string();
+        self.qualities = qualities;
+        self.check_lengths()?;
+        Ok(self)
+    }
+
+    /// Returns all optional fields in the order they are stored
+    pub fn tags(&self) -> Vec<(String, TagValue)> {
+        self.tags.clone()
+    }
+
+    /// Returns the value of the optional field with the given tag
+    pub fn tag(&self, tag: &str) -> Option<TagValue> {
+        self.tags.iter().find(|t| t.0 == tag).map(|t| t.1.clone())
+    }
+
+    /// Adds or replaces the optional field with the given tag
+    pub fn with_tag<S: ToString>(mut self, tag: S, value: TagValue) -> Self {
+        let tag = tag.to_string();
+        match self.tags.iter().position(|t| t.0 == tag) {
+            Some(i) => self.tags[i].1 = value,
+            None => self.tags.push((tag, value)),
+        }
+        self
+    }
+
+    /// Returns the number of read bases that are consumed by the CIGAR
+    pub fn query_length(&self) -> usize {
+        self.cigar.query_length()
+    }
+
+    /// Returns the number of template positions covered by the alignment
+    pub fn reference_length(&self) -> usize {
+        self.cigar.reference_length()
+    }
+
+    fn check_lengths(&self) -> Result<(), String> {
+        if let Some(ref q) = self.qualities {
+            if q.length() != self.sequence.len() {
+                return Err(format!(
+                    "Sequence length {} does not match quality length {} for read '{}'",
+                    self.sequence.len(),
+                    q.length(),
+                    self.name
+                ));
+            }
+        }
+        if !self.cigar.is_empty() && !self.sequence.is_empty() && self.query_length() != self.sequence.len() {
+            return Err(format!(
+                "CIGAR {} does not match sequence length {} for read '{}'",
+                self.cigar(),
+                self.sequence.len(),
+                self.name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Converts the record into an alignment of the read sequence against the
+    /// template. The `template` is the full sequence of the template such that
+    /// the segment offsets are template offsets. Unmapped reads are returned
+    /// as a single unaligned segment.
+    pub fn alignment(&self, template: Option<DnaSequence>) -> Alignment<DnaNucleotide, DnaSequence> {
+        let sequence = self.as_dna();
+        match (self.is_unmapped(), self.position) {
+            (false, Some(p)) => self.cigar.alignment(template, sequence, p),
+            _ => {
+                let length = sequence.length();
+                let mut alignment = Alignment::new(template, sequence);
+                alignment.add_segment_unaligned(0, length);
+                alignment
+            }
+        }
+    }
+
+    /// Returns the parsed MD tag if the record has one
+    pub fn md_tag(&self) -> Option<Result<MdTag, String>> {
+        match self.tag("MD") {
+            Some(TagValue::String(md)) => Some(MdTag::from_str(&md)),
+            Some(v) => Some(Err(format!("Expecting MD tag of type Z but found type {}", v.type_code()))),
+            None => None,
+        }
+    }
+
+    /// Reconstructs the template sequence covered by the alignment from the read
+    /// sequence and the MD tag.
+    pub fn reference_sequence(&self) -> Result<String, String> {
+        match self.md_tag() {
+            Some(md) => md?
+                .reference(&self.cigar, &self.sequence)
+                .map_err(|e| format!("Can not reconstruct reference of read '{}': {}", self.name, e)),
+            None => Err(format!("Missing MD tag for read '{}'", self.name)),
+        }
+    }
+
+    /// Computes the MD and the NM tag by comparing the read with the template
+    /// sequence read from the FASTA file. Existing tags are replaced. Unmapped
+    /// reads and reads without sequence are returned unchanged.
+    pub fn with_md_and_nm<F: FastaReader>(self, fasta: &mut F) -> Result<Self, String> {
+        let (template, position) = match (self.is_unmapped(), &self.template, self.position) {
+            (false, &Some(ref t), Some(p)) if !self.sequence.is_empty() => (t.clone(), p),
+            _ => return Ok(self),
+        };
+        let length = self.reference_length();
+        let reference = match fasta.search_region_as_sequence(&template, position, length) {
+            Some(r) => r,
+            None => return Err(format!("Can not find template '{}' in FASTA file", template)),
+        };
+        let md = MdTag::generate(&self.cigar, &self.sequence, &reference)
+            .map_err(|e| format!("Can not compute MD tag of read '{}': {}", self.name, e))?;
+        let nm = md.edit_distance(&self.cigar);
+        Ok(self
+            .with_tag("MD", TagValue::String(md.to_string()))
+            .with_tag("NM", TagValue::Integer(nm as i64)))
+    }
+}
+
+impl Region for AlignmentRecord {
+    /// Returns the template name or `*` for unmapped reads
+    fn template(&self) -> String {
+        self.template.clone().unwrap_or("*".to_string())
+    }
+
+    fn offset(&self) -> usize {
+        self.position.unwrap_or(0)
+    }
+
+    fn length(&self) -> usize {
+        self.reference_length()
+    }
+}
+
+impl FromStr for AlignmentRecord {
+    type Err = String;
+
+    /// Parses a record from an alignment line of a SAM file
+    fn from_str(s: &str) -> Result<Self, String> {
+        let cells: Vec<&str> = s.trim_end_matches(|c| c == '\n' || c == '\r').split('\t').collect();
+        if cells.len() < 11 {
+            return Err(format!("Expecting at least 11 columns but found {}", cells.len()));
+        }
+        let parse_position = |name: &str, v: &str| match v.parse::<usize>() {
+            Ok(0) => Ok(None),
+            Ok(p) => Ok(Some(p - 1)),
+            Err(e) => Err(format!("Can not parse {} '{}': {}", name, v, e)),
+        };
+        let optional = |v: &str| match v {
+            "*" => None,
+            _ => Some(v.to_string()),
+        };
+
+        let template = optional(cells[2]);
+        let mate_template = match cells[6] {
+            "=" => template.clone(),
+            v => optional(v),
+        };
+        let qualities = match cells[10] {
+            "*" => None,
+            q => Some(QualitySequence::from_ascii(q, PhredEncoding::Phred33)?),
+        };
+        let mut tags = Vec::new();
+        for cell in cells[11..].iter() {
+            let parts: Vec<&str> = cell.splitn(3, ':').collect();
+            if parts.len() != 3 || parts[0].len() != 2 {
+                return Err(format!("Can not parse optional field '{}'", cell));
+            }
+            tags.push((parts[0].to_string(), TagValue::parse(parts[1], parts[2])?));
+        }
+
+        let record = AlignmentRecord {
+            name: cells[0].to_string(),
+            flags: cells[1].parse::<u16>().map_err(|e| format!("Can not parse flag '{}': {}", cells[1], e))?,
+            template: template,
+            position: parse_position("position", cells[3])?,
+            mapping_quality: cells[4].parse::<u8>().map_err(|e| format!("Can not parse mapping quality '{}': {}", cells[4], e))?,
+            cigar: Cigar::from_str(cells[5])?,
+            mate_template: mate_template,
+            mate_position: parse_position("mate position", cells[7])?,
+            insert_size: cells[8].parse::<i64>().map_err(|e| format!("Can not parse template length '{}': {}", cells[8], e))?,
+            sequence: match cells[9] {
+                "*" => String::new(),
+                s => s.to_string(),
+            },
+            qualities: qualities,
+            tags: tags,
+        };
+        record.check_lengths()?;
+        Ok(record)
+    }
+}
+
+impl fmt::Display for AlignmentRecord {
+    /// Writes the record as SAM line
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let position = |p: Option<usize>| p.map(|p| p + 1).unwrap_or(0);
+        let mate_template = match (&self.mate_template, &self.template) {
+            (&Some(ref m), &Some(ref t)) if m == t => "=".to_string(),
+            (&Some(ref m), _) => m.clone(),
+            (&None, _) => "*".to_string(),
+        };
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.name,
+            self.flags,
+            self.template.clone().unwrap_or("*".to_string()),
+            position(self.position),
+            self.mapping_quality,
+            self.cigar(),
+            mate_template,
+            position(self.mate_position),
+            self.insert_size,
+            match self.sequence.is_empty() {
+                true => "*".to_string(),
+                false => self.sequence.clone(),
+            },
+            match self.qualities {
+                Some(ref q) => q.to_ascii(PhredEncoding::Phred33),
+                None => "*".to_string(),
+            }
+        )?;
+        for &(ref tag, ref value) in self.tags.iter() {
+            write!(f, "\t{}:{}:{}", tag, value.type_code(), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the variable length data of a record read by `rust_htslib`, i.e., the name,
+/// the CIGAR, the packed sequence, the qualities and the optional fields.
+fn variable_data(record: &Record) -> &[u8] {
+    let inner = record.inner();
+    // SAFETY: htslib keeps `l_data` initialized bytes at `data` for as long as the
+    // record lives, and the returned slice borrows the record.
+    unsafe { slice::from_raw_parts(inner.data as *const u8, inner.l_data as usize) }
+}
+
+/// Parses the optional fields from their binary BAM representation
+fn parse_binary_tags(data: &[u8]) -> Result<Vec<(String, TagValue)>, String> {
+    let truncated = || format!("Optional fields are truncated");
+    let read = |data: &[u8], pos: usize, n: usize| -> Result<u64, String> {
+        match data.get(pos..pos + n) {
+            Some(bytes) => Ok(bytes.iter().rev().fold(0u64, |v, b| (v << 8) | *b as u64)),
+            None => Err(truncated()),
+        }
+    };
+    let read_integer = |data: &[u8], pos: usize, type_code: u8| -> Result<(i64, usize), String> {
+        match type_code {
+            b'c' => read(data, pos, 1).map(|v| (v as u8 as i8 as i64, 1)),
+            b'C' => read(data, pos, 1).map(|v| (v as i64, 1)),
+            b's' => read(data, pos, 2).map(|v| (v as u16 as i16 as i64, 2)),
+            b'S' => read(data, pos, 2).map(|v| (v as i64, 2)),
+            b'i' => read(data, pos, 4).map(|v| (v as u32 as i32 as i64, 4)),
+            b'I' => read(data, pos, 4).map(|v| (v as i64, 4)),
+            t => Err(format!("Unknown integer type '{}'", t as char)),
+        }
+    };
+
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let header = match data.get(pos..pos + 3) {
+            Some(h) => h,
+            None => return Err(truncated()),
+        };
+        let tag = String::from_utf8_lossy(&header[0..2]).to_string();
+        pos += 3;
+        let value = match header[2] {
+            b'A' => {
+                pos += 1;
+                TagValue::Char(read(data, pos - 1, 1)? as u8 as char)
+            }
+            b'f' => {
+                pos += 4;
+                TagValue::Float(f32::from_bits(read(data, pos - 4, 4)? as u32))
+            }
+            b'Z' | b'H' => {
+                let end = match data[pos..].iter().position(|b| *b == 0) {
+                    Some(e) => pos + e,
+                    None => return Err(truncated()),
+                };
+                let s = String::from_utf8_lossy(&data[pos..end]).to_string();
+                pos = end + 1;
+                match header[2] {
+                    b'Z' => TagValue::String(s),
+                    _ => TagValue::Hex(s),
+                }
+            }
+            b'B' => {
+                let subtype = read(data, pos, 1)? as u8;
+                let count = read(data, pos + 1, 4)? as usize;
+                pos += 5;
+                let size = match subtype {
+                    b'c' | b'C' => 1,
+                    b's' | b'S' => 2,
+                    b'i' | b'I' | b'f' => 4,
+                    t => return Err(format!("Unknown array type '{}'", t as char)),
+                };
+                // The count is read from the file, so check it before allocating
+                if count.checked_mul(size).map_or(true, |n| n > data.len() - pos) {
+                    return Err(truncated());
+                }
+                match subtype {
+                    b'f' => {
+                        let mut values = Vec::with_capacity(count);
+                        for _ in 0..count {
+                            values.push(f32::from_bits(read(data, pos, 4)? as u32));
+                            pos += 4;
+                        }
+                        TagValue::FloatArray(values)
+                    }
+                    _ => {
+                        let mut values = Vec::with_capacity(count);
+                        for _ in 0..count {
+                            let (v, n) = read_integer(data, pos, subtype)?;
+                            values.push(v);
+                            pos += n;
+                        }
+                        TagValue::IntegerArray(subtype as char, values)
+                    }
+                }
+            }
+            t => {
+                let (v, n) = read_integer(data, pos, t)?;
+                pos += n;
+                TagValue::Integer(v)
+            }
+        };
+        tags.push((tag, value));
+    }
+    Ok(tags)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::bam::record::parse_binary_tags;
+    use io::bam::{AlignmentRecord, BamReader, SamStream, TagValue};
+    use io::fasta::IndexedFastaFile;
+    use model::Region;
+    use sequence::dna::DnaSequence;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_sam_line() {
+        let line = "r001\t163\tref\t7\t30\t8M4I4M1D3M\t=\t37\t39\tTTAGATAAAGAGGATACTG\t*\tXX:B:S,12561,2,20,112\tNM:i:5";
+        let record = AlignmentRecord::from_str(line).unwrap();
+        assert_eq!(record.name(), "r001");
+        assert!(record.is_paired() && record.is_proper_pair() && record.is_mate_reverse() && record.is_second_in_pair());
+        assert!(!record.is_reverse() && !record.is_unmapped());
+        assert_eq!((record.template(), record.offset(), record.end()), ("ref".to_string(), 6, 22));
+        assert_eq!(record.mate_template_name(), Some("ref".to_string()));
+        assert_eq!((record.mate_position(), record.insert_size()), (Some(36), 39));
+        assert_eq!(record.query_length(), 19);
+        assert_eq!(record.tag("XX"), Some(TagValue::IntegerArray('S', vec![12561, 2, 20, 112])));
+        assert_eq!(record.tag("NM").and_then(|t| t.integer()), Some(5));
+        assert_eq!(record.to_string(), line);
+
+        assert!(AlignmentRecord::from_str("r\t0\tref\t1\t30\t3M\t*\t0\t0\tACGT\t*").is_err());
+        assert!(AlignmentRecord::from_str("r\t0\tref\t1\t30\t4Q\t*\t0\t0\tACGT\t*").is_err());
+        assert!(AlignmentRecord::from_str("r\t0\tref\t1\t30\t4M\t*\t0\t0\tACGT\t??").is_err());
+    }
+
+    #[test]
+    fn test_unmapped_record() {
+        let record = AlignmentRecord::from_str("r\t4\t*\t0\t0\t*\t*\t0\t0\tACGT\t????").unwrap();
+        assert!(record.is_unmapped());
+        assert_eq!(record.qualities().unwrap().scores(), vec![30, 30, 30, 30]);
+        let segments = record.alignment(None).segments();
+        assert_eq!(segments.len(), 1);
+        assert!(!segments[0].is_aligned());
+        assert_eq!(record.to_string(), "r\t4\t*\t0\t0\t*\t*\t0\t0\tACGT\t????");
+    }
+
+    #[test]
+    fn test_alignment() {
+        let template = DnaSequence::from_str("TTAGAGACTAC").unwrap();
+        let record = AlignmentRecord::new("r")
+            .with_position("ref", 2)
+            .with_sequence("CCAGAGGGATAC", None)
+            .unwrap()
+            .with_cigar("2S3M2I2M1D3M")
+            .unwrap();
+        assert_eq!(record.reference_length(), 9);
+        let segments = record.alignment(Some(template)).segments();
+        assert_eq!(segments.len(), 6);
+        assert!(!segments[0].is_aligned());
+        assert!(segments[1].is_match());
+        assert!(segments[2].is_insertion());
+        assert_eq!(segments[3].template_offset(), Some(5));
+        assert!(segments[4].is_deletion());
+        assert_eq!((segments[5].template_offset(), segments[5].sequence_offset()), (Some(8), 9));
+        assert!(segments[5].is_match());
+    }
+
+    #[test]
+    fn test_md_and_nm() {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let records: Vec<AlignmentRecord> = SamStream::open(&"testdata/toy.sam").unwrap().map(|r| r.unwrap()).collect();
+        let record = records[0].clone().with_md_and_nm(&mut fasta).unwrap();
+        assert_eq!(record.tag("MD"), Some(TagValue::String("12^G3".to_string())));
+        assert_eq!(record.tag("NM").and_then(|t| t.integer()), Some(5));
+        assert_eq!(record.reference_sequence().unwrap(), "TTAGATAAGATAGCTG");
+
+        let record = records[8].clone().with_md_and_nm(&mut fasta).unwrap();
+        assert_eq!(record.tag("NM").and_then(|t| t.integer()), Some(4));
+        assert!(records[8].reference_sequence().is_err());
+
+        let unmapped = AlignmentRecord::new("r").with_md_and_nm(&mut fasta).unwrap();
+        assert!(unmapped.tag("MD").is_none());
+    }
+
+    #[test]
+    fn test_parse_binary_tags() {
+        let data = b"NMC\x05XAZab\x00XBBs\x02\x00\x00\x00\xff\xff\x01\x00";
+        let tags = parse_binary_tags(data).unwrap();
+        assert_eq!(tags[0], ("NM".to_string(), TagValue::Integer(5)));
+        assert_eq!(tags[1], ("XA".to_string(), TagValue::String("ab".to_string())));
+        assert_eq!(tags[2], ("XB".to_string(), TagValue::IntegerArray('s', vec![-1, 1])));
+        assert!(parse_binary_tags(b"NMi\x05").is_err());
+        assert!(parse_binary_tags(b"XBBi\xff\xff\xff\xff\x01\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_from_htslib() {
+        let records: Vec<_> = BamReader::open(&"testdata/tags.bam").unwrap().map(|r| r.unwrap()).collect();
+        assert_eq!(records[0].cigar().to_string(), "3M1I2M");
+        assert_eq!(records[0].tag("XA"), Some(TagValue::String("ab c".to_string())));
+        assert_eq!(records[0].tag("XB"), Some(TagValue::IntegerArray('s', vec![-1, 1])));
+        assert_eq!(records[0].tag("XF"), Some(TagValue::FloatArray(vec![0.5])));
+        assert_eq!(records[0].tag("NM"), Some(TagValue::Integer(1)));
+    }
+}
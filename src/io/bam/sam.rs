@@ -0,0 +1,114 @@
+use io::bam::AlignmentRecord;
+use io::compress::DecompressReader;
+use model::SequenceDictionary;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A stream reader for (possibly compressed) SAM files. The header is read
+/// when the stream is created and the records are read on demand.
+pub struct SamStream<R: Read> {
+    reader: BufReader<DecompressReader<R>>,
+    header: String,
+    dictionary: SequenceDictionary,
+    line: usize,
+}
+
+impl<R: Read> SamStream<R> {
+    pub fn new(r: R) -> Result<SamStream<R>, String> {
+        SamStream::from_decompressed(DecompressReader::new(r))
+    }
+
+    fn from_decompressed(r: DecompressReader<R>) -> Result<SamStream<R>, String> {
+        let mut reader = BufReader::new(r);
+        let mut header = String::new();
+        let mut line = 0;
+
+        // Read all lines starting with '@'
+        loop {
+            let is_header = match reader.fill_buf() {
+                Ok(buf) => buf.first() == Some(&b'@'),
+                Err(e) => return Err(format!("Can not read SAM header: {}", e)),
+            };
+            if !is_header {
+                break;
+            }
+            if let Err(e) = reader.read_line(&mut header) {
+                return Err(format!("Can not read SAM header: {}", e));
+            }
+            line += 1;
+        }
+
+        let dictionary = SequenceDictionary::from_sam_header(&header)?;
+        Ok(SamStream {
+            reader: reader,
+            header: header,
+            dictionary: dictionary,
+            line: line,
+        })
+    }
+
+    /// Returns the header lines
+    pub fn header(&self) -> String {
+        self.header.clone()
+    }
+
+    /// Returns the templates defined in the `@SQ` header lines
+    pub fn dictionary(&self) -> &SequenceDictionary {
+        &self.dictionary
+    }
+}
+
+impl SamStream<File> {
+    pub fn open<P: AsRef<Path>>(filename: &P) -> Result<SamStream<File>, String> {
+        match DecompressReader::open(filename) {
+            Ok(r) => SamStream::from_decompressed(r),
+            Err(e) => Err(format!("Can not open SAM file '{}': {}", filename.as_ref().display(), e)),
+        }
+    }
+}
+
+impl<R: Read> Iterator for SamStream<R> {
+    type Item = Result<AlignmentRecord, String>;
+
+    fn next(&mut self) -> Option<Result<AlignmentRecord, String>> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(format!("Can not read line {}: {}", self.line + 1, e))),
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(AlignmentRecord::from_str(&line).map_err(|e| format!("Line {}: {}", self.line, e)));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::bam::{AlignmentRecord, BamReader, SamStream};
+
+    #[test]
+    fn test_read_file() {
+        let stream = SamStream::open(&"testdata/toy.sam").unwrap();
+        assert_eq!(stream.dictionary().names(), vec!["ref".to_string(), "ref2".to_string()]);
+        let records: Vec<AlignmentRecord> = stream.map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 12);
+        assert_eq!(records[3].cigar().to_string(), "6M14N1I5M");
+
+        // The BAM file contains the same records
+        let bam: Vec<AlignmentRecord> = BamReader::open(&"testdata/toy.bam").unwrap().map(|r| r.unwrap()).collect();
+        for (s, b) in records.iter().zip(bam.iter()) {
+            assert_eq!((s.name(), s.position(), s.cigar(), s.flags()), (b.name(), b.position(), b.cigar(), b.flags()));
+        }
+
+        let stream = SamStream::new("@SQ\tSN:ref\tLN:45\nr\t0\tref\t1\t30\t2M\t*\t0\t0\tACG\t*\n".as_bytes()).unwrap();
+        let error = stream.map(|r| r.unwrap_err()).next().unwrap();
+        assert!(error.starts_with("Line 2:"), "{}", error);
+    }
+}
@@ -1,5 +1,5 @@
 
-use model::Region;
+use model::{Region, ZeroBasedInterval};
 use sketch::Color;
 use std::fmt;
 use std::str::FromStr;
@@ -63,6 +63,11 @@ impl BedRecord {
         self
     }
 
+    /// Returns the 0-based, half-open coordinates of the record
+    pub fn interval(&self) -> ZeroBasedInterval {
+        ZeroBasedInterval::from_region(self)
+    }
+
     
 
     pub fn has_name(&self) -> bool {
@@ -304,6 +309,10 @@ impl FromStr for BedRecord {
             Err(e) => return Err(format!("Can not parse '{}' as usize: {}", cells[2], e)),
         };
 
+        if chrom_end < chrom_start {
+            return Err(format!("Region end {} before start {}", chrom_end, chrom_start));
+        }
+
         let mut record = BedRecord::new(&cells[0], chrom_start, chrom_end);
 
         if cells.len() >= 4 {
@@ -1,6 +1,7 @@
 
 
 use io::bed::BedRecord;
+use io::compress::DecompressReader;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Read;
@@ -11,15 +12,16 @@ use std::path::Path;
 use std::fs::File;
 use std::io;
 
-use model::Region;
+use model::{IntervalTree, Region};
 
+/// A stream of BED records. Gzip and BGZF compressed input is decompressed transparently.
 pub struct BedStream<R: Read> {
-    inner: BufReader<R>,
+    inner: BufReader<DecompressReader<R>>,
 }
 
 impl<R: Read> BedStream<R> {
     pub fn new(src: R) -> Self {
-        BedStream { inner: BufReader::new(src) }
+        BedStream { inner: BufReader::new(DecompressReader::new(src)) }
     }
 
     pub fn read_records(&mut self) -> Vec<BedRecord> {
@@ -29,12 +31,17 @@ impl<R: Read> BedStream<R> {
     pub fn read_records_in_region<RE: Region>(&mut self, region: &RE) -> Vec<BedRecord> {
         self.filter(|r| region.overlaps(r)).collect()
     }
+
+    /// Reads all records into an interval tree for repeated region queries
+    pub fn read_index(&mut self) -> IntervalTree<BedRecord> {
+        self.collect()
+    }
 }
 
 impl BedStream<File> {
 	pub fn open<A: AsRef<Path>>(filename: A) -> Result<Self,io::Error> {
-		match File::open(filename) {
-			Ok(fh) => Ok(Self::new(fh)),
+		match DecompressReader::open(&filename) {
+			Ok(r) => Ok(BedStream { inner: BufReader::new(r) }),
 			Err(e) => Err(e)
 		}
 	}
@@ -59,14 +66,19 @@ impl<R: Read> Iterator for BedStream<R> {
 
         loop {
             let mut line = String::new();
-            let read_result = self.inner.read_line(&mut line);
-            if read_result.is_err() {
-                warn!("Can not read from BED stream: {:?}", read_result.err());
-                return None;
+            match self.inner.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Can not read from BED stream: {}", e);
+                    return None;
+                }
             }
 
-            if !line.starts_with('#') {
-                match BedRecord::from_str(&line) {
+            let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+            let is_comment = line.starts_with('#') || line.starts_with("track") || line.starts_with("browser");
+            if !is_comment && !line.is_empty() {
+                match BedRecord::from_str(line) {
                     Ok(r) => return Some(r),
                     Err(e) => {
                         warn!("Can not parse record from BED stream: {}", e);
@@ -82,6 +94,7 @@ impl<R: Read> Iterator for BedStream<R> {
 #[cfg(test)]
 mod tests {
 	use io::bed::stream::BedStream;
+	use model::{RegionIndex, SimpleRegion};
 	
 	#[test]
 	pub fn test_read_file(){
@@ -94,5 +107,23 @@ mod tests {
 		assert_eq!(records.len(), 4usize);
 	}
 
+	#[test]
+	pub fn test_read_bgzf_file(){
+		let mut reader = match BedStream::open(&"testdata/toy.bed.gz") {
+			Ok(r) => r,
+			Err(e) => panic!("Can not open BedStream: {}", e)
+		};
+		let records = reader.read_records();
+
+		assert_eq!(records.len(), 4usize);
+	}
+
 
-}
\ No newline at end of file
+	#[test]
+	pub fn test_read_index(){
+		let index = BedStream::open(&"testdata/toy.bed").unwrap().read_index();
+		let found = index.overlapping(&SimpleRegion::new("ref", 30, 5));
+		assert_eq!(found.len(), 2);
+		assert_eq!(found[0].name(), Some("GeneA_Exon2".to_string()));
+	}
+}
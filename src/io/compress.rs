@@ -0,0 +1,421 @@
+use flate2::bufread::MultiGzDecoder;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The compression of an input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    Uncompressed,
+    Gzip,
+    /// Blocked gzip as written by `bgzip`
+    Bgzf,
+}
+
+impl Compression {
+    /// Detects the compression from the first bytes of the reader without consuming them.
+    pub fn detect<R: BufRead>(reader: &mut R) -> io::Result<Compression> {
+        let buf = reader.fill_buf()?;
+        if buf.len() < 2 || buf[0] != 0x1f || buf[1] != 0x8b {
+            return Ok(Compression::Uncompressed);
+        }
+        // BGZF sets FLG.FEXTRA and stores the block size in an extra subfield 'BC'
+        if buf.len() >= 16 && buf[3] & 0x04 != 0 && buf[12] == b'B' && buf[13] == b'C' {
+            return Ok(Compression::Bgzf);
+        }
+        Ok(Compression::Gzip)
+    }
+}
+
+
+/// The index of a BGZF file mapping compressed block offsets to uncompressed offsets
+/// as written by `bgzip -i` (see http://www.htslib.org/doc/bgzip.html).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GziIndex {
+    /// Pairs of (compressed offset, uncompressed offset) sorted by offset
+    entries: Vec<(u64, u64)>,
+}
+
+impl GziIndex {
+    /// Parses a `.gzi` index: the number of entries followed by the offset pairs,
+    /// all stored as little-endian 64-bit integers.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<GziIndex> {
+        let n = read_u64(&mut reader)?;
+        let mut entries = Vec::new();
+        for _ in 0..n {
+            let compressed = read_u64(&mut reader)?;
+            let uncompressed = read_u64(&mut reader)?;
+            entries.push((compressed, uncompressed));
+        }
+        Ok(GziIndex { entries: entries })
+    }
+
+    pub fn read_gzi<P: AsRef<Path>>(gzi_filename: &P) -> io::Result<GziIndex> {
+        GziIndex::read(BufReader::new(File::open(gzi_filename)?))
+    }
+
+    /// Builds the index by scanning the block headers of the raw (compressed) BGZF data.
+    pub fn build<R: Read>(reader: R) -> io::Result<GziIndex> {
+        let mut reader = BufReader::new(reader);
+        let mut entries = Vec::new();
+        let mut compressed = 0u64;
+        let mut uncompressed = 0u64;
+
+        while !reader.fill_buf()?.is_empty() {
+            let (block_size, header_size) = read_bgzf_header(&mut reader)?;
+            // Skip the compressed data and the CRC32 up to the ISIZE field of the gzip footer
+            let data_size = block_size - header_size - 4;
+            let skipped = io::copy(&mut reader.by_ref().take(data_size), &mut io::sink())?;
+            if skipped < data_size {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated BGZF block"));
+            }
+            let mut isize_buf = [0u8; 4];
+            reader.read_exact(&mut isize_buf)?;
+            let isize = u32::from_le_bytes(isize_buf) as u64;
+
+            if compressed > 0 && isize > 0 {
+                entries.push((compressed, uncompressed));
+            }
+            compressed += block_size;
+            uncompressed += isize;
+        }
+
+        Ok(GziIndex { entries: entries })
+    }
+
+    /// Builds the index for a BGZF file.
+    pub fn build_for<P: AsRef<Path>>(bgzf_filename: &P) -> io::Result<GziIndex> {
+        GziIndex::build(File::open(bgzf_filename)?)
+    }
+
+    pub fn write_gzi<P: AsRef<Path>>(&self, gzi_filename: &P) -> io::Result<usize> {
+        let mut wr = BufWriter::new(File::create(gzi_filename)?);
+        wr.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for &(compressed, uncompressed) in &self.entries {
+            wr.write_all(&compressed.to_le_bytes())?;
+            wr.write_all(&uncompressed.to_le_bytes())?;
+        }
+        wr.flush()?;
+        Ok(self.entries.len())
+    }
+
+    /// Tries to discover and read the `.gzi` index for a given BGZF file.
+    pub fn discover<P: AsRef<Path>>(bgzf_filename: &P) -> Option<GziIndex> {
+        let mut gzi_path = bgzf_filename.as_ref().as_os_str().to_owned();
+        gzi_path.push(".gzi");
+
+        if !Path::new(&gzi_path).exists() {
+            return None;
+        }
+        match GziIndex::read_gzi(&gzi_path) {
+            Ok(index) => {
+                debug!("Found gzi index with {} entries at: {:?}", index.num_entries(), gzi_path);
+                Some(index)
+            }
+            Err(e) => {
+                warn!("Can not read existing gzi index {:?}: {}", gzi_path, e);
+                None
+            }
+        }
+    }
+
+    /// Returns the number of entries. The implicit first block at offset zero is not counted.
+    pub fn num_entries(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the (compressed, uncompressed) offsets of the block containing the
+    /// given uncompressed offset.
+    pub fn find_block(&self, uncompressed_offset: u64) -> (u64, u64) {
+        self.entries
+            .iter()
+            .take_while(|e| e.1 <= uncompressed_offset)
+            .last()
+            .cloned()
+            .unwrap_or((0, 0))
+    }
+}
+
+/// Reads the header of a BGZF block and returns the total block size and the header
+/// size. The reader is positioned at the start of the compressed data afterwards.
+fn read_bgzf_header<R: Read>(reader: &mut R) -> io::Result<(u64, u64)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut header = [0u8; 12];
+    reader.read_exact(&mut header)?;
+    if header[0] != 0x1f || header[1] != 0x8b || header[3] & 0x04 == 0 {
+        return Err(invalid("Not a BGZF block"));
+    }
+    let xlen = (header[10] as usize) | ((header[11] as usize) << 8);
+    let mut extra = vec![0u8; xlen];
+    reader.read_exact(&mut extra)?;
+
+    // Search the 'BC' subfield holding the block size minus one
+    let mut i = 0;
+    while i + 4 <= xlen {
+        let slen = (extra[i + 2] as usize) | ((extra[i + 3] as usize) << 8);
+        if extra[i] == b'B' && extra[i + 1] == b'C' && slen == 2 && i + 6 <= xlen {
+            let block_size = ((extra[i + 4] as u64) | ((extra[i + 5] as u64) << 8)) + 1;
+            let header_size = 12 + xlen as u64;
+            if block_size < header_size + 8 {
+                return Err(invalid("Invalid BGZF block size"));
+            }
+            return Ok((block_size, header_size));
+        }
+        i += 4 + slen;
+    }
+    Err(invalid("Missing BGZF block size"))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+
+enum Inner<R: Read> {
+    Plain(BufReader<R>),
+    Gzip(MultiGzDecoder<BufReader<R>>),
+}
+
+/// A reader that transparently decompresses gzip and BGZF input and passes
+/// uncompressed input through.
+///
+/// If the underlying reader is seekable, so is the decompressed stream. Seeking in
+/// compressed input restarts decoding at the closest preceding BGZF block known from
+/// the `.gzi` index (or at the beginning of the file without an index).
+pub struct DecompressReader<R: Read> {
+    // Only `None` while the decoder is rebuilt during a seek
+    inner: Option<Inner<R>>,
+    compression: Compression,
+    index: GziIndex,
+    // The current uncompressed position for compressed input
+    position: u64,
+}
+
+impl<R: Read> DecompressReader<R> {
+    pub fn new(reader: R) -> DecompressReader<R> {
+        let mut buffered = BufReader::new(reader);
+        let compression = match Compression::detect(&mut buffered) {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("Can not detect compression of input: {}", e);
+                Compression::Uncompressed
+            }
+        };
+        let inner = match compression {
+            Compression::Uncompressed => Inner::Plain(buffered),
+            _ => Inner::Gzip(MultiGzDecoder::new(buffered)),
+        };
+
+        DecompressReader {
+            inner: Some(inner),
+            compression: compression,
+            index: GziIndex::default(),
+            position: 0,
+        }
+    }
+
+    /// Sets the `.gzi` index used for random access into BGZF input.
+    pub fn with_index(mut self, index: GziIndex) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compression != Compression::Uncompressed
+    }
+}
+
+impl DecompressReader<File> {
+    /// Opens a possibly compressed file. For BGZF files, an existing `.gzi` index is
+    /// loaded for random access.
+    pub fn open<P: AsRef<Path>>(filename: &P) -> io::Result<DecompressReader<File>> {
+        let reader = DecompressReader::new(File::open(filename)?);
+        if reader.compression() == Compression::Bgzf {
+            if let Some(index) = GziIndex::discover(filename) {
+                return Ok(reader.with_index(index));
+            }
+        }
+        Ok(reader)
+    }
+}
+
+impl<R: Read> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner {
+            Some(Inner::Plain(ref mut r)) => r.read(buf),
+            Some(Inner::Gzip(ref mut r)) => {
+                let n = r.read(buf)?;
+                self.position += n as u64;
+                Ok(n)
+            }
+            None => Err(io::Error::new(io::ErrorKind::Other, "Decompression stream is broken")),
+        }
+    }
+}
+
+impl<R: Read + Seek> DecompressReader<R> {
+    /// Restarts decoding at the given (compressed, uncompressed) block offsets.
+    fn restart_at(&mut self, block: (u64, u64)) -> io::Result<()> {
+        let mut reader = match self.inner.take() {
+            Some(Inner::Gzip(r)) => r.into_inner().into_inner(),
+            Some(Inner::Plain(r)) => r.into_inner(),
+            None => return Err(io::Error::new(io::ErrorKind::Other, "Decompression stream is broken")),
+        };
+        reader.seek(SeekFrom::Start(block.0))?;
+        self.inner = Some(Inner::Gzip(MultiGzDecoder::new(BufReader::new(reader))));
+        self.position = block.1;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Seek for DecompressReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if let Some(Inner::Plain(ref mut r)) = self.inner {
+            return r.seek(pos);
+        }
+
+        let target = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::Current(d) if d >= 0 => self.position + d as u64,
+            SeekFrom::Current(d) if (-d) as u64 <= self.position => self.position - (-d) as u64,
+            SeekFrom::Current(_) => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Seeking before start of stream"))
+            }
+            SeekFrom::End(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Seeking from the end is not supported for compressed input",
+                ))
+            }
+        };
+
+        // Restart at a block unless we can simply continue decoding from the current position
+        let block = self.index.find_block(target);
+        if target < self.position || block.1 > self.position {
+            self.restart_at(block)?;
+        }
+
+        let skip = target - self.position;
+        let skipped = io::copy(&mut self.by_ref().take(skip), &mut io::sink())?;
+        if skipped < skip {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Seeking beyond end of stream"));
+        }
+        Ok(self.position)
+    }
+}
+
+impl<R: Read> fmt::Debug for DecompressReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DecompressReader[{:?}]", self.compression)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use flate2::Compression as Level;
+    use flate2::write::GzEncoder;
+    use io::compress::*;
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    fn read_to_string<R: Read>(mut r: R) -> String {
+        let mut s = String::new();
+        r.read_to_string(&mut s).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(DecompressReader::open(&"testdata/toy.fasta").unwrap().compression(), Compression::Uncompressed);
+        assert_eq!(DecompressReader::open(&"testdata/toy.fasta.gz").unwrap().compression(), Compression::Bgzf);
+    }
+
+    #[test]
+    fn test_read_bgzf() {
+        let plain = read_to_string(File::open("testdata/toy.fasta").unwrap());
+        let bgzf = read_to_string(DecompressReader::open(&"testdata/toy.fasta.gz").unwrap());
+        assert_eq!(plain, bgzf);
+    }
+
+    #[test]
+    fn test_read_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+        encoder.write_all(b"ref\t0\t10\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let reader = DecompressReader::new(&compressed[..]);
+        assert_eq!(reader.compression(), Compression::Gzip);
+        assert_eq!(read_to_string(reader), "ref\t0\t10\n");
+    }
+
+    #[test]
+    fn test_read_gzi() {
+        let index = GziIndex::read_gzi(&"testdata/toy.fasta.gz.gzi").unwrap();
+        assert_eq!(index.num_entries(), 0);
+        assert_eq!(index.find_block(100), (0, 0));
+
+        let data: Vec<u8> = vec![2u64, 100, 1000, 250, 3000]
+            .into_iter()
+            .flat_map(|v| (0..8).map(move |i| (v >> (8 * i)) as u8))
+            .collect();
+        let index = GziIndex::read(&data[..]).unwrap();
+        assert_eq!(index.find_block(999), (0, 0));
+        assert_eq!(index.find_block(1000), (100, 1000));
+        assert_eq!(index.find_block(5000), (250, 3000));
+    }
+
+    #[test]
+    fn test_build_gzi() {
+        assert_eq!(
+            GziIndex::build_for(&"testdata/toy.fasta.gz").unwrap(),
+            GziIndex::read_gzi(&"testdata/toy.fasta.gz.gzi").unwrap()
+        );
+        assert_eq!(
+            GziIndex::build_for(&"testdata/toy_blocks.fasta.gz").unwrap(),
+            GziIndex::read_gzi(&"testdata/toy_blocks.fasta.gz.gzi").unwrap()
+        );
+        assert!(GziIndex::build_for(&"testdata/toy.fasta").is_err());
+    }
+
+    #[test]
+    fn test_seek_bgzf() {
+        let mut reader = DecompressReader::open(&"testdata/toy.fasta.gz").unwrap();
+        let mut buf = [0u8; 4];
+
+        assert_eq!(reader.seek(SeekFrom::Start(58)).unwrap(), 58);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"aggt");
+
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap(), 5);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"AGCA");
+
+        assert_eq!(reader.seek(SeekFrom::Current(1)).unwrap(), 10);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"GTTA");
+    }
+
+    #[test]
+    fn test_seek_bgzf_blocks() {
+        let mut reader = DecompressReader::open(&"testdata/toy_blocks.fasta.gz").unwrap();
+        assert_eq!(reader.index.num_entries(), 3);
+        let mut buf = [0u8; 4];
+
+        assert_eq!(reader.seek(SeekFrom::Start(72)).unwrap(), 72);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"caat");
+
+        assert_eq!(reader.seek(SeekFrom::Start(30)).unwrap(), 30);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"CTAG");
+    }
+}
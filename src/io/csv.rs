@@ -1,9 +1,10 @@
+use io::compress::DecompressReader;
 use std::io::{BufReader, Read};
 use std::io::BufRead;
 use util;
 
 pub struct Csv<R: Read> {
-    reader: BufReader<R>,
+    reader: BufReader<DecompressReader<R>>,
     record_separator: char,
     cell_separator: char,
 }
@@ -52,7 +53,7 @@ impl<R: Read> Iterator for Csv<R> {
 impl<R: Read> From<R> for Csv<R> {
     fn from(r: R) -> Csv<R> {
         Csv {
-            reader: BufReader::new(r),
+            reader: BufReader::new(DecompressReader::new(r)),
             record_separator: '\n',
             cell_separator: '\t',
         }
@@ -1,9 +1,11 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io;
-use std::io::{Write,BufWriter,BufRead,BufReader};
+use std::io::{Write,BufWriter,BufRead,BufReader,Read};
 use std::path;
 use std::str::FromStr;
 
+use io::compress::DecompressReader;
 use io::fai::FaiRecord;
 
 /// A fasta index represents all the records within a FAI file
@@ -43,7 +45,7 @@ impl FaiIndex {
     }
 
     /// Tries to discover a fasta index file for a given FASTA file.
-    pub fn discover<P: AsRef<path::Path>>(fasta_filename: &P) -> Option<File> {
+    pub fn discover<P: AsRef<path::Path>>(fasta_filename: &P) -> Option<DecompressReader<File>> {
         // Check if the FASTA file exists
         let fasta_path = fasta_filename.as_ref();
         if !fasta_path.exists() {
@@ -65,7 +67,7 @@ impl FaiIndex {
                 fasta_path,
                 faidx_with_suffix
             );
-            match File::open(faidx_with_suffix) {
+            match DecompressReader::open(&faidx_with_suffix) {
                 Ok(fh) => return Some(fh),
                 Err(e) => warn!("Can not read existing FASTA index: {}", e),
             }
@@ -73,9 +75,73 @@ impl FaiIndex {
         None
     }
 
+    /// Builds the index by scanning a (possibly compressed) FASTA file.
+    pub fn build_for<P: AsRef<path::Path>>(fasta_filename: &P) -> Result<FaiIndex, String> {
+        match DecompressReader::open(fasta_filename) {
+            Ok(r) => FaiIndex::build(r),
+            Err(e) => Err(format!("Can not open FASTA file '{}': {}", fasta_filename.as_ref().display(), e)),
+        }
+    }
+
+    /// Builds the index by scanning the uncompressed FASTA content. All lines of a
+    /// sequence except the last one must contain the same number of bases.
+    pub fn build<R: Read>(input: R) -> Result<FaiIndex, String> {
+        let mut reader = BufReader::new(input);
+        let mut records: Vec<FaiRecord> = Vec::new();
+        let mut names: HashSet<String> = HashSet::new();
+        let mut current: Option<FaiBuilder> = None;
+        let mut file_offset = 0usize;
+        let mut line_number = 0usize;
+
+        loop {
+            let mut line = Vec::new();
+            let width = match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(l) => l,
+                Err(e) => return Err(format!("Can not read line {}: {}", line_number + 1, e)),
+            };
+            line_number += 1;
+            file_offset += width;
+
+            if line.first() == Some(&b'>') {
+                if let Some(b) = current.take() {
+                    records.push(b.finish());
+                }
+                let name: String = String::from_utf8_lossy(&line[1..])
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+                    .to_string();
+                if name.is_empty() {
+                    return Err(format!("Missing sequence name in header at line {}", line_number));
+                }
+                if !names.insert(name.clone()) {
+                    return Err(format!("Duplicate sequence name '{}' at line {}", name, line_number));
+                }
+                current = Some(FaiBuilder::new(name, file_offset));
+                continue;
+            }
+
+            let bases = line.iter().filter(|c| !(**c as char).is_whitespace()).count();
+            match current {
+                Some(ref mut b) => b.add_line(bases, width, line_number)?,
+                None => {
+                    if bases > 0 {
+                        return Err(format!("Expected a FASTA header at line {}", line_number));
+                    }
+                }
+            }
+        }
+
+        if let Some(b) = current.take() {
+            records.push(b.finish());
+        }
+        Ok(FaiIndex { records: records })
+    }
+
     /// Tries to discover a fasta index file for a given FASTA file.
     pub fn read_fai<P: AsRef<path::Path>>(fai_filename: &P) -> Result<FaiIndex, io::Error> {
-        match File::open(fai_filename) {
+        match DecompressReader::open(fai_filename) {
             Ok(fh) => Ok(FaiIndex::from(BufReader::new(fh))),
             Err(e) => Err(e)
         }
@@ -95,6 +161,62 @@ impl FaiIndex {
     }
 }
 
+/// Collects the line layout of a single sequence while building an index
+struct FaiBuilder {
+    name: String,
+    offset: usize,
+    length: usize,
+    linebases: usize,
+    linewidth: usize,
+    // Set after a line shorter than `linebases`; only the last line may be shorter
+    last_line_seen: bool,
+}
+
+impl FaiBuilder {
+    fn new(name: String, offset: usize) -> FaiBuilder {
+        FaiBuilder {
+            name: name,
+            offset: offset,
+            length: 0,
+            linebases: 0,
+            linewidth: 0,
+            last_line_seen: false,
+        }
+    }
+
+    fn add_line(&mut self, bases: usize, width: usize, line_number: usize) -> Result<(), String> {
+        if bases == 0 {
+            self.last_line_seen = true;
+            return Ok(());
+        }
+        if self.length == 0 {
+            self.linebases = bases;
+            self.linewidth = width;
+        } else if self.last_line_seen || bases > self.linebases {
+            return Err(format!(
+                "Different line length in sequence '{}' at line {}",
+                self.name,
+                line_number
+            ));
+        } else if bases == self.linebases && width != self.linewidth {
+            return Err(format!(
+                "Inconsistent line endings in sequence '{}' at line {}",
+                self.name,
+                line_number
+            ));
+        }
+        if bases < self.linebases {
+            self.last_line_seen = true;
+        }
+        self.length += bases;
+        Ok(())
+    }
+
+    fn finish(self) -> FaiRecord {
+        FaiRecord::new(self.name, self.length, self.offset, self.linebases, self.linewidth)
+    }
+}
+
 impl<R: BufRead> From<R> for FaiIndex {
     fn from(input: R) -> FaiIndex {
         let mut records = Vec::new();
@@ -124,6 +246,27 @@ mod tests {
     pub fn test_discovery() {
         assert!(FaiIndex::discover(&"testdata/toy.fasta").is_some());
         assert!(FaiIndex::discover(&"testdata/toy.fa").is_none());
+        assert!(FaiIndex::discover(&"testdata/toy.fasta.gz").is_some());
+    }
+
+    #[test]
+    pub fn test_build() {
+        let index = FaiIndex::read_fai(&"testdata/toy.fasta.fai").unwrap();
+        assert_eq!(FaiIndex::build_for(&"testdata/toy.fasta").unwrap().records(), index.records());
+        assert_eq!(FaiIndex::build_for(&"testdata/toy.fasta.gz").unwrap().records(), index.records());
+    }
+
+    #[test]
+    pub fn test_build_validation() {
+        let index = FaiIndex::build(&b">a desc\r\nAC\r\nG\r\n>b\nACGT\n\n"[..]).unwrap();
+        assert_eq!(index.records()[0], FaiRecord::new("a", 3, 9, 2, 4));
+        assert_eq!(index.records()[1], FaiRecord::new("b", 4, 19, 4, 5));
+
+        assert!(FaiIndex::build(&b">a\nACG\nA\nCG\n"[..]).is_err(), "Short line within sequence");
+        assert!(FaiIndex::build(&b">a\nAC\nACG\n"[..]).is_err(), "Long line within sequence");
+        assert!(FaiIndex::build(&b">a\nAC\n\nAC\n"[..]).is_err(), "Blank line within sequence");
+        assert!(FaiIndex::build(&b">a\nAC\n>a\nAC\n"[..]).is_err(), "Duplicate name");
+        assert!(FaiIndex::build(&b"AC\n"[..]).is_err(), "Missing header");
     }
 
     #[test]
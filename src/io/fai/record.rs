@@ -11,6 +11,16 @@ pub struct FaiRecord {
 }
 
 impl FaiRecord {
+    pub fn new<S: ToString>(name: S, length: usize, offset: usize, linebases: usize, linewidth: usize) -> FaiRecord {
+        FaiRecord {
+            name: name.to_string(),
+            length: length,
+            offset: offset,
+            linebases: linebases,
+            linewidth: linewidth,
+        }
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
@@ -1,8 +1,9 @@
 
+use io::compress::DecompressReader;
 use io::fai::FaiRecord;
 use sequence::*;
 use std::fmt;
-use std::fs::File;
+use std::io::BufReader;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
@@ -40,7 +41,7 @@ impl Sequence<DnaNucleotide> for FaiSequence {
         let n_bases = offset - (n_lines * self.record.linebases());
         let file_offset = self.record.offset() + n_lines * self.record.linewidth() + n_bases;
 
-        let mut fh = match File::open(&self.filename) {
+        let mut fh = match DecompressReader::open(&self.filename) {
             Err(_) => return DnaSequence::default(),
             Ok(fh) => fh,
         };
@@ -50,7 +51,7 @@ impl Sequence<DnaNucleotide> for FaiSequence {
         }
 
 
-        let sequence: Vec<DnaNucleotide> = fh.bytes()
+        let sequence: Vec<DnaNucleotide> = BufReader::new(fh).bytes()
             .map(|b| b.unwrap() as char)
             .take_while(|c| *c != '>') // Break at new record
             .filter(|c| ! c.is_whitespace() ) // drop whitespaces
@@ -94,4 +95,13 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_bgzf(){
+        let index = FaiIndex::read_fai(&"testdata/toy.fasta.gz.fai").unwrap();
+        let record = index.find_record(&"ref2").expect(&"Expected to find a record with name 'ref2'");
+        let chrom = FaiSequence::new(record, &"testdata/toy.fasta.gz");
+
+        assert_eq!(chrom.subsequence(10,6).to_string(), "AAACAA");
+    }
+
 }
\ No newline at end of file
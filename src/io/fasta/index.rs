@@ -1,4 +1,7 @@
+use io::compress::DecompressReader;
+use io::fai::FaiIndex;
 use io::fasta::{FastaReader,FastaRecord};
+use std::cmp;
 use std::fmt::Display;
 use std::io::{Seek, SeekFrom};
 use std::io::BufRead;
@@ -121,7 +124,7 @@ impl FastaIndex {
     }
 
     /// Tries to discover a fasta index file for a given FASTA file.
-    pub fn find_for<P: AsRef<Path>>(fasta_filename: &P) -> Option<File> {
+    pub fn find_for<P: AsRef<Path>>(fasta_filename: &P) -> Option<DecompressReader<File>> {
         // Check if the FASTA file exists
         let fasta_path = fasta_filename.as_ref();
         if !fasta_path.exists() {
@@ -143,7 +146,7 @@ impl FastaIndex {
                 fasta_path,
                 faidx_with_suffix
             );
-            match File::open(faidx_with_suffix) {
+            match DecompressReader::open(&faidx_with_suffix) {
                 Ok(fh) => return Some(fh),
                 Err(e) => warn!("Can not read existing FASTA index: {}", e),
             }
@@ -152,6 +155,22 @@ impl FastaIndex {
     }
 }
 
+impl<'a> From<&'a FaiIndex> for FastaIndex {
+    fn from(index: &FaiIndex) -> FastaIndex {
+        let records = index.records()
+            .into_iter()
+            .map(|r| FastaIndexRecord {
+                name: r.name(),
+                length: r.length(),
+                offset: r.offset(),
+                linebases: r.linebases(),
+                linewidth: r.linewidth(),
+            })
+            .collect();
+        FastaIndex { records: records }
+    }
+}
+
 impl<R: BufRead> From<R> for FastaIndex {
     fn from(input: R) -> FastaIndex {
         let mut records = Vec::new();
@@ -173,25 +192,30 @@ impl<R: BufRead> From<R> for FastaIndex {
 }
 
 
+/// A FASTA file with random access through its index. BGZF compressed
+/// files are supported through their `.gzi` index.
 #[derive(Debug)]
 pub struct IndexedFastaFile {
     index: FastaIndex,
-    fh: File,
+    fh: DecompressReader<File>,
 }
 
 impl IndexedFastaFile {
+    /// Opens a FASTA file using its index. If no index file exists, the index
+    /// is built in memory by scanning the file once.
     pub fn open<P: AsRef<Path> + Display>(fasta_filename: &P) -> Result<Self, String> {
         let index = match FastaIndex::find_for(fasta_filename) {
             Some(fh) => FastaIndex::from(BufReader::new(fh)),
             None => {
-                return Err(format!(
-                    "Can not find FASTA index file for: {}",
-                    fasta_filename
-                ))
+                debug!("Can not find FASTA index file for '{}', building index", fasta_filename);
+                match FaiIndex::build_for(fasta_filename) {
+                    Ok(index) => FastaIndex::from(&index),
+                    Err(e) => return Err(format!("Can not build FASTA index for '{}': {}", fasta_filename, e)),
+                }
             }
         };
 
-        let fasta_fh = match File::open(fasta_filename) {
+        let fasta_fh = match DecompressReader::open(fasta_filename) {
             Ok(fh) => fh,
             Err(e) => {
                 return Err(format!(
@@ -226,6 +250,12 @@ impl FastaReader for IndexedFastaFile {
             Some(record) => record,
         };
 
+        // Never read beyond the end of the sequence
+        let length = match offset < record.length() {
+            true => cmp::min(length, record.length() - offset),
+            false => 0,
+        };
+
         let file_offset = record.offset_region(offset);
         match self.fh.seek(SeekFrom::Start(file_offset as u64)) {
             Err(e) => {
@@ -274,6 +304,8 @@ impl FastaReader for IndexedFastaFile {
 #[cfg(test)]
 mod tests {
     use io::fasta::index::*;
+    use std::env;
+    use std::fs;
     use std::io::BufReader;
 
     #[test]
@@ -372,4 +404,36 @@ mod tests {
             "Beyond end of second sequence"
         );
     }
+
+    #[test]
+    pub fn test_read_fasta_without_index() {
+        let dir = env::temp_dir().join("ngstk_test_read_fasta_without_index");
+        fs::create_dir_all(&dir).unwrap();
+        let fasta = dir.join("noindex.fasta");
+        fs::copy("testdata/toy.fasta", &fasta).unwrap();
+
+        let mut reader = IndexedFastaFile::open(&fasta.to_str().unwrap()).unwrap();
+        assert_eq!(reader.search_region("ref2", 8, 6), Some(FastaRecord::new("ref2", "taaaac")));
+        assert_eq!(reader.search_region("ref2", 40, 3), Some(FastaRecord::new("ref2", "")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn test_read_fasta_bgzf() {
+        for filename in &["testdata/toy.fasta.gz", "testdata/toy_blocks.fasta.gz"] {
+            let reader_result = IndexedFastaFile::open(filename);
+            assert!(reader_result.is_ok());
+            let mut reader = reader_result.unwrap();
+
+            assert_eq!(reader.search_region("ref", 0, 2), Some(FastaRecord::new("ref","AG")));
+            assert_eq!(reader.search_region("ref", 42, 3), Some(FastaRecord::new("ref","CAT")));
+            assert_eq!(
+                reader.search_region("ref2", 8, 6),
+                Some(FastaRecord::new("ref2", "taaaac")),
+                "One line to the next"
+            );
+            assert_eq!(reader.search_region("ref", 20, 5), Some(FastaRecord::new("ref", "TGTGC")));
+            assert_eq!(reader.search("ref2"), Some(FastaRecord::new("ref2", "aggttttataaaacaattaagtctacagagcaactacgcg")));
+        }
+    }
 }
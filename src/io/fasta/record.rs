@@ -62,8 +62,7 @@ impl FastaRecord {
 
 impl<'a> From<&'a FastaRecord> for DnaSequence {
     fn from(r: &FastaRecord) -> DnaSequence {
-        let nucs : Vec<DnaNucleotide> = r.sequence().chars().map(|c| DnaNucleotide::from(c)).collect();
-        DnaSequence::from(nucs)
+        r.sequence.chars().map(|c| DnaNucleotide::from(c)).collect()
     }
 }
 
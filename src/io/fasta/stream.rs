@@ -1,13 +1,24 @@
+use io::compress::DecompressReader;
 use io::fasta::{FastaReader,FastaRecord};
+use std::fs::File;
+use std::io;
 use std::io::{BufReader, Read};
 use std::io::BufRead;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::iter::Iterator;
+use std::path::Path;
 
-/// A stream reader may process only once.
+/// A stream reader may process only once. Gzip and BGZF compressed input
+/// is decompressed transparently.
 pub struct FastaStream<R: Read> {
-    reader: BufReader<R>,
+    reader: BufReader<DecompressReader<R>>,
+}
+
+impl FastaStream<File> {
+    pub fn open<P: AsRef<Path>>(filename: &P) -> Result<Self, io::Error> {
+        Ok(FastaStream::from_decompressed(DecompressReader::open(filename)?))
+    }
 }
 
 impl<R: Read> Iterator for FastaStream<R> {
@@ -85,8 +96,8 @@ impl<R: Read> FastaReader for FastaStream<R> {
     }
 }
 
-impl<R: Read> From<R> for FastaStream<R> {
-    fn from(r: R) -> FastaStream<R> {
+impl<R: Read> FastaStream<R> {
+    fn from_decompressed(r: DecompressReader<R>) -> FastaStream<R> {
         let mut fr = FastaStream { reader: BufReader::new(r) };
         let mut s = Vec::new();
         fr.reader.read_until('>' as u8, &mut s);
@@ -94,6 +105,12 @@ impl<R: Read> From<R> for FastaStream<R> {
     }
 }
 
+impl<R: Read> From<R> for FastaStream<R> {
+    fn from(r: R) -> FastaStream<R> {
+        FastaStream::from_decompressed(DecompressReader::new(r))
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -123,6 +140,17 @@ mod tests {
         assert!(read_opt.is_none());
     }
 
+    #[test]
+    pub fn test_next_bgzf() {
+        let file = File::open("testdata/toy.fasta.gz");
+        assert!(file.is_ok(), "Creating file");
+
+        let records: Vec<FastaRecord> = FastaStream::from(file.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], FastaRecord::new("ref", "AGCATGTTAGATAAGATAGCTGTGCTAGTAGGCAGTCAGCGCCAT"));
+        assert_eq!(records[1], FastaRecord::new("ref2", "aggttttataaaacaattaagtctacagagcaactacgcg"));
+    }
+
     #[test]
     pub fn test_search_first_entry() {
         let file = File::open("testdata/toy.fasta");
@@ -0,0 +1,14 @@
+mod record;
+mod stream;
+mod writer;
+
+pub use self::record::FastqRecord;
+pub use self::stream::FastqStream;
+#[allow(unused_imports)]
+pub use self::writer::FastqWriter;
+
+use std::io::Read;
+
+pub fn read_stream<R: Read>(input: R) -> FastqStream<R> {
+    FastqStream::from(input)
+}
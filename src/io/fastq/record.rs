@@ -0,0 +1,92 @@
+use sequence::dna::{DnaNucleotide, DnaSequence};
+use sequence::quality::QualitySequence;
+
+/// Struct for resembling records from a FASTQ file, i.e., a header,
+/// the sequence and the base qualities.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FastqRecord {
+    header: String,
+    sequence: String,
+    quality: QualitySequence,
+}
+
+impl FastqRecord {
+    /// Create a new record. Fails if the sequence and the qualities differ in length.
+    pub fn new<H: ToString, S: ToString>(
+        header: H,
+        sequence: S,
+        quality: QualitySequence,
+    ) -> Result<FastqRecord, String> {
+        let sequence: String = sequence.to_string().chars().filter(|c| !c.is_whitespace()).collect();
+        if sequence.chars().count() != quality.length() {
+            return Err(format!(
+                "Sequence length {} does not match quality length {} for record '{}'",
+                sequence.chars().count(),
+                quality.length(),
+                header.to_string()
+            ));
+        }
+        Ok(FastqRecord {
+            header: header.to_string(),
+            sequence: sequence,
+            quality: quality,
+        })
+    }
+
+    /// Return the header part
+    pub fn header(&self) -> String {
+        self.header.clone()
+    }
+
+    /// Return the first non-whitespace part of the header
+    pub fn name(&self) -> String {
+        self.header.chars().take_while(|c| !c.is_whitespace()).collect()
+    }
+
+    /// Returns the sequence part
+    pub fn sequence(&self) -> String {
+        self.sequence.clone()
+    }
+
+    /// Returns the base qualities
+    pub fn quality(&self) -> QualitySequence {
+        self.quality.clone()
+    }
+
+    /// Converts the sequence into a DNA sequence
+    pub fn as_dna(&self) -> DnaSequence {
+        DnaSequence::from(self)
+    }
+
+    pub fn subsequence(&self, offset: usize, length: usize) -> Self {
+        FastqRecord {
+            header: self.header.clone(),
+            sequence: self.sequence.chars().skip(offset).take(length).collect::<String>(),
+            quality: self.quality.subsequence(offset, length),
+        }
+    }
+}
+
+impl<'a> From<&'a FastqRecord> for DnaSequence {
+    fn from(r: &FastqRecord) -> DnaSequence {
+        r.sequence.chars().map(|c| DnaNucleotide::from(c)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fastq::FastqRecord;
+    use sequence::quality::{PhredEncoding, QualitySequence};
+
+    #[test]
+    fn test_new() {
+        let q = QualitySequence::from_ascii("IIII", PhredEncoding::Phred33).unwrap();
+        let r = FastqRecord::new("read1 extra", "ACGT", q.clone()).unwrap();
+        assert_eq!(r.name(), "read1");
+        assert_eq!(r.as_dna().to_string(), "ACGT");
+        assert_eq!(r.subsequence(1, 2).sequence(), "CG");
+        assert_eq!(r.subsequence(1, 2).quality().length(), 2);
+        assert!(FastqRecord::new("read1", "ACG", q).is_err());
+    }
+}
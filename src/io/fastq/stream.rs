@@ -0,0 +1,203 @@
+use io::compress::DecompressReader;
+use io::fastq::FastqRecord;
+use sequence::quality::{PhredEncoding, QualitySequence};
+use std::io::{BufRead, BufReader, Read};
+use std::iter::Iterator;
+
+/// A stream reader for FASTQ files that expects each record on exactly four lines.
+/// Malformed records are reported as errors that include the line number. Gzip and
+/// BGZF compressed input is decompressed transparently.
+pub struct FastqStream<R: Read> {
+    reader: BufReader<DecompressReader<R>>,
+    encoding: PhredEncoding,
+    line: usize,
+}
+
+impl<R: Read> FastqStream<R> {
+    /// Sets the encoding of the quality lines (defaults to Phred+33).
+    pub fn with_encoding(mut self, encoding: PhredEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn encoding(&self) -> PhredEncoding {
+        self.encoding
+    }
+
+    /// Reads the next line without the trailing line break. Returns `None` at
+    /// the end of the input.
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        let mut buf = String::new();
+        match self.reader.read_line(&mut buf) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.line += 1;
+                Ok(Some(buf.trim_end_matches(|c| c == '\n' || c == '\r').to_string()))
+            }
+            Err(e) => Err(format!("Can not read line {}: {}", self.line + 1, e)),
+        }
+    }
+
+    /// Reads a line that is required to complete the current record.
+    fn read_required_line(&mut self, what: &str, header: &str) -> Result<String, String> {
+        match self.read_line()? {
+            Some(l) => Ok(l),
+            None => Err(format!(
+                "Unexpected end of input: missing {} of record '{}'",
+                what,
+                header
+            )),
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Option<FastqRecord>, String> {
+        // Skip blank lines between records
+        let header = loop {
+            match self.read_line()? {
+                None => return Ok(None),
+                Some(ref l) if l.trim().is_empty() => continue,
+                Some(l) => break l,
+            }
+        };
+        if !header.starts_with('@') {
+            return Err(format!(
+                "Line {}: expected record header starting with '@' but found '{}'",
+                self.line,
+                header
+            ));
+        }
+        let header = header[1..].trim().to_string();
+
+        let sequence = self.read_required_line("sequence", &header)?;
+
+        let separator = self.read_required_line("'+' separator", &header)?;
+        if !separator.starts_with('+') {
+            if separator.starts_with('@') {
+                return Err(format!(
+                    "Line {}: expected '+' separator for record '{}' but found '{}'",
+                    self.line,
+                    header,
+                    separator
+                ));
+            }
+            return Err(format!(
+                "Line {}: multi-line sequences are not supported (record '{}' continues with '{}' instead of '+')",
+                self.line,
+                header,
+                separator
+            ));
+        }
+        let repeated = separator[1..].trim();
+        if !repeated.is_empty() && repeated != header {
+            return Err(format!(
+                "Line {}: separator header '{}' does not match record header '{}'",
+                self.line,
+                repeated,
+                header
+            ));
+        }
+
+        let quality_line = self.read_required_line("quality line", &header)?;
+        if quality_line.len() != sequence.len() {
+            return Err(format!(
+                "Line {}: quality length {} does not match sequence length {} of record '{}'{}",
+                self.line,
+                quality_line.len(),
+                sequence.len(),
+                header,
+                if quality_line.len() < sequence.len() {
+                    " (multi-line records are not supported)"
+                } else {
+                    ""
+                }
+            ));
+        }
+        let quality = match QualitySequence::from_ascii(&quality_line, self.encoding) {
+            Ok(q) => q,
+            Err(e) => return Err(format!("Line {}: {}", self.line, e)),
+        };
+
+        FastqRecord::new(header, sequence, quality).map(|r| Some(r))
+    }
+}
+
+impl<R: Read> Iterator for FastqStream<R> {
+    type Item = Result<FastqRecord, String>;
+
+    fn next(&mut self) -> Option<Result<FastqRecord, String>> {
+        match self.read_record() {
+            Ok(Some(r)) => Some(Ok(r)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<R: Read> From<R> for FastqStream<R> {
+    fn from(r: R) -> FastqStream<R> {
+        FastqStream {
+            reader: BufReader::new(DecompressReader::new(r)),
+            encoding: PhredEncoding::default(),
+            line: 0,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fastq::FastqStream;
+    use sequence::quality::PhredEncoding;
+    use std::fs::File;
+
+    #[test]
+    pub fn test_next() {
+        let file = File::open("testdata/toy.fastq");
+        assert!(file.is_ok(), "Opening file");
+
+        let mut reader = FastqStream::from(file.unwrap());
+        let r1 = reader.next().unwrap().unwrap();
+        assert_eq!(r1.name(), "read1");
+        assert_eq!(r1.sequence(), "AGCATGTTAGATAAGATAGC");
+        assert_eq!(r1.quality().score(0), Some(40));
+
+        let r2 = reader.next().unwrap().unwrap();
+        assert_eq!(r2.header(), "read2 second read");
+        assert_eq!(r2.sequence(), "aggttttataaaacaatt");
+
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    pub fn test_phred64() {
+        let input = "@r\nACGT\n+\n@JT^\n";
+        let mut reader = FastqStream::from(input.as_bytes()).with_encoding(PhredEncoding::Phred64);
+        let r = reader.next().unwrap().unwrap();
+        assert_eq!(r.quality().scores(), vec![0, 10, 20, 30]);
+    }
+
+    #[test]
+    pub fn test_multiline_record() {
+        let input = "@r\nACGT\nACGT\n+\nIIIIIIII\n";
+        let mut reader = FastqStream::from(input.as_bytes());
+        let e = reader.next().unwrap().unwrap_err();
+        assert!(e.contains("Line 3"), "{}", e);
+        assert!(e.contains("multi-line"), "{}", e);
+    }
+
+    #[test]
+    pub fn test_malformed_records() {
+        let missing_header = "r\nACGT\n+\nIIII\n";
+        assert!(FastqStream::from(missing_header.as_bytes()).next().unwrap().is_err());
+
+        let truncated = "@r\nACGT\n+\n";
+        let e = FastqStream::from(truncated.as_bytes()).next().unwrap().unwrap_err();
+        assert!(e.contains("missing quality line"), "{}", e);
+
+        let length_mismatch = "@r\nACGT\n+\nIII\n";
+        assert!(FastqStream::from(length_mismatch.as_bytes()).next().unwrap().is_err());
+
+        let bad_quality = "@r\nACGT\n+\nII I\n";
+        assert!(FastqStream::from(bad_quality.as_bytes()).next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use io::fastq::FastqRecord;
+use sequence::quality::{PhredEncoding, QualitySequence};
+use std::io::BufWriter;
+use std::io::Error;
+use std::io::Write;
+
+pub struct FastqWriter<W: Write> {
+    inner: BufWriter<W>,
+    encoding: PhredEncoding,
+}
+
+
+impl<W: Write> FastqWriter<W> {
+    pub fn set_encoding(&mut self, new_encoding: PhredEncoding) {
+        self.encoding = new_encoding
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
+    pub fn append<A: ToString, B: ToString>(
+        &mut self,
+        header: A,
+        body: B,
+        quality: &QualitySequence,
+    ) -> Result<usize, Error> {
+        let header = header.to_string();
+        let body = body.to_string();
+        let quality = quality.to_ascii(self.encoding);
+
+        write!(self.inner, "@{}\n{}\n+\n{}\n", header, body, quality)?;
+        Ok(header.len() + body.len() + quality.len() + 6)
+    }
+
+    pub fn append_record(&mut self, record: &FastqRecord) -> Result<usize, Error> {
+        self.append(record.header(), record.sequence(), &record.quality())
+    }
+}
+
+impl<W: Write> From<W> for FastqWriter<W> {
+    fn from(inner: W) -> FastqWriter<W> {
+        FastqWriter {
+            inner: BufWriter::new(inner),
+            encoding: PhredEncoding::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use io::fastq::{FastqStream, FastqWriter};
+    use sequence::quality::{PhredEncoding, QualitySequence};
+
+    #[test]
+    fn test_write_two() {
+        let q = QualitySequence::from(vec![0, 10, 20, 30]);
+        let mut writer = FastqWriter::from(Vec::new());
+        assert!(writer.append("r1", "ACGT", &q).is_ok());
+        assert!(writer.append("r2", "TGCA", &q).is_ok());
+        writer.flush().unwrap();
+
+        let result = writer.get_ref();
+        assert_eq!(
+            result.iter().map(|u| *u as char).collect::<String>(),
+            "@r1\nACGT\n+\n!+5?\n@r2\nTGCA\n+\n!+5?\n"
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_phred64() {
+        let input = "@r desc\nACGT\n+\n!+5?\n";
+        let record = FastqStream::from(input.as_bytes()).next().unwrap().unwrap();
+
+        let mut writer = FastqWriter::from(Vec::new());
+        writer.set_encoding(PhredEncoding::Phred64);
+        assert!(writer.append_record(&record).is_ok());
+        writer.flush().unwrap();
+        assert_eq!(
+            writer.get_ref().iter().map(|u| *u as char).collect::<String>(),
+            "@r desc\nACGT\n+\n@JT^\n"
+        );
+    }
+}
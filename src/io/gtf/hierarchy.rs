@@ -0,0 +1,148 @@
+use io::gtf::GtfRecord;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Resolves the parent/child relationships between GTF or GFF3 records.
+///
+/// For GFF3, records are linked by their `ID` and `Parent` attributes. For GTF, the
+/// relationships are derived from `gene_id` and `transcript_id` (see
+/// `GtfRecord::id()` and `GtfRecord::parent_ids()`). Multiple records may share the
+/// same identifier, e.g., the CDS parts of a GFF3 protein.
+#[derive(Clone,Debug)]
+pub struct GtfHierarchy {
+	records: Vec<GtfRecord>,
+	ids: BTreeMap<String, Vec<usize>>,
+	children: BTreeMap<String, Vec<usize>>
+}
+
+impl GtfHierarchy {
+	pub fn new(records: Vec<GtfRecord>) -> GtfHierarchy {
+		let mut ids: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+		let mut children: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+		for (i, record) in records.iter().enumerate() {
+			if let Some(id) = record.id() {
+				ids.entry(id).or_insert(Vec::new()).push(i);
+			}
+			for parent in record.parent_ids() {
+				children.entry(parent).or_insert(Vec::new()).push(i);
+			}
+		}
+
+		GtfHierarchy {
+			records: records,
+			ids: ids,
+			children: children
+		}
+	}
+
+	pub fn records(&self) -> &Vec<GtfRecord> {
+		&self.records
+	}
+
+	/// Returns all records with the given identifier
+	pub fn find<S: ToString>(&self, id: S) -> Vec<&GtfRecord> {
+		self.resolve(self.ids.get(&id.to_string()))
+	}
+
+	/// Returns the records that name the given identifier as parent
+	pub fn children<S: ToString>(&self, id: S) -> Vec<&GtfRecord> {
+		self.resolve(self.children.get(&id.to_string()))
+	}
+
+	/// Returns the parent records of the given record
+	pub fn parents(&self, record: &GtfRecord) -> Vec<&GtfRecord> {
+		record.parent_ids().iter()
+			.flat_map(|p| self.find(p))
+			.collect()
+	}
+
+	/// Returns the records without a parent, e.g., the genes. Records with a parent
+	/// that is not part of the hierarchy are not included (see `missing_parents()`).
+	pub fn roots(&self) -> Vec<&GtfRecord> {
+		self.records.iter()
+			.filter(|r| r.parent_ids().is_empty())
+			.collect()
+	}
+
+	/// Returns all direct and indirect children of the given identifier in
+	/// depth-first order. Each record is reported only once.
+	pub fn descendants<S: ToString>(&self, id: S) -> Vec<&GtfRecord> {
+		let mut visited = BTreeSet::new();
+		let mut descendants = Vec::new();
+		self.collect_descendants(&id.to_string(), &mut visited, &mut descendants);
+		descendants.into_iter().map(|i| &self.records[i]).collect()
+	}
+
+	fn collect_descendants(&self, id: &String, visited: &mut BTreeSet<usize>, descendants: &mut Vec<usize>) {
+		let children = match self.children.get(id) {
+			Some(c) => c,
+			None => return
+		};
+		for &i in children {
+			if !visited.insert(i) {
+				continue;
+			}
+			descendants.push(i);
+			if let Some(child_id) = self.records[i].id() {
+				self.collect_descendants(&child_id, visited, descendants);
+			}
+		}
+	}
+
+	/// Returns the identifiers that are referenced as parent but are not defined by
+	/// any record. This is common for GTF files without gene and transcript lines.
+	pub fn missing_parents(&self) -> Vec<String> {
+		self.children.keys()
+			.filter(|p| !self.ids.contains_key(*p))
+			.cloned()
+			.collect()
+	}
+
+	fn resolve(&self, indices: Option<&Vec<usize>>) -> Vec<&GtfRecord> {
+		match indices {
+			Some(l) => l.iter().map(|&i| &self.records[i]).collect(),
+			None => Vec::new()
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use io::gtf::{GtfFeature, GtfHierarchy, GtfStream};
+
+	fn load(filename: &str) -> GtfHierarchy {
+		GtfHierarchy::new(GtfStream::open(&filename).unwrap().read_records().unwrap())
+	}
+
+	#[test]
+	fn test_gtf_hierarchy() {
+		let h = load("testdata/toy.gtf");
+		assert_eq!(h.roots().len(), 2);
+		assert!(h.missing_parents().is_empty());
+
+		let transcripts = h.children("g1");
+		assert_eq!(transcripts.len(), 1);
+		assert_eq!(transcripts[0].feature(), Some(GtfFeature::Transcript));
+		assert_eq!(h.children("t1").len(), 8);
+		assert_eq!(h.descendants("g1").len(), 9);
+		assert_eq!(h.parents(transcripts[0])[0].feature(), Some(GtfFeature::Gene));
+		assert_eq!(h.children("g2").len(), 2);
+	}
+
+	#[test]
+	fn test_gff3_hierarchy() {
+		let h = load("testdata/toy.gff3");
+		assert_eq!(h.roots().len(), 2);
+		assert!(h.missing_parents().is_empty());
+		assert_eq!(h.find("cds1").len(), 2);
+		assert_eq!(h.children("t1").len(), 6);
+		assert_eq!(h.children("t2").len(), 2);
+		assert_eq!(h.children("t3").len(), 1);
+
+		// The shared exon is reported only once
+		assert_eq!(h.descendants("g2").len(), 4);
+		let exon = h.find("e3")[0];
+		assert_eq!(h.parents(exon).len(), 2);
+	}
+}
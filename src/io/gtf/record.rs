@@ -0,0 +1,672 @@
+use std::fmt;
+use std::str::FromStr;
+use model::{OneBasedInterval, Region, Strand, ZeroBasedInterval};
+use util;
+
+/// The feature type of a record (column 3). Feature types not known to the
+/// toolkit are kept as they are.
+#[derive(Clone,Debug,PartialEq)]
+pub enum GtfFeature {
+	StartCodon, StopCodon, Exon, CDS, Intron, Gene, Transcript,
+	UTR, FivePrimeUTR, ThreePrimeUTR, Selenocysteine,
+	Other(String)
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum GtfAnnotation {
+	GeneId(String),
+	TranscriptId(String),
+	ExonNumber(usize),
+	/// The GFF3 `ID` attribute
+	Id(String),
+	/// A single value of the GFF3 `Parent` attribute
+	Parent(String),
+	Unknown(String,String)
+}
+
+/// The syntax of the attribute column (column 9).
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum GtfFormat {
+	/// GTF / GFF2 attributes like `gene_id "g1"; transcript_id "t1";`
+	Gtf,
+	/// GFF3 attributes like `ID=t1;Parent=g1`
+	Gff3
+}
+
+#[derive(Clone,Debug)]
+pub struct GtfRecord {
+	seqname: String,
+	source: Option<String>,
+	feature: Option<GtfFeature>,
+	start: u64,
+	end: u64,
+	score: Option<f64>,
+	strand: Option<Strand>,
+	frame: Option<usize>,
+	annotations: Vec<GtfAnnotation>,
+	format: GtfFormat
+}
+
+impl GtfRecord {
+	pub fn new<TN: ToString>(template_name: &TN, start: u64, end: u64) -> GtfRecord {
+		assert!(start >      0);
+		assert!(end   >= start);
+		GtfRecord {
+			seqname: template_name.to_string(),
+			start: start,
+			end: end,
+			source: None,
+			feature: None,
+			score: None,
+			strand: None,
+			frame: None,
+			annotations: Vec::new(),
+			format: GtfFormat::Gtf
+		}
+	}
+
+	pub fn seqname(&self) -> &str {
+		self.seqname.as_ref()
+	}
+
+	pub fn with_seqname<T: ToString>(mut self, new_seqname: &T) -> Self {
+		self.seqname = new_seqname.to_string();
+		self
+	}
+
+	pub fn start(&self) -> u64 {
+		self.start
+	}
+
+	pub fn with_start(mut self, new_start: u64) -> Self {
+		assert!(new_start > 0);
+		self.start = new_start;
+		self
+	}
+
+	pub fn end(&self) -> u64 {
+		self.end
+	}
+
+	pub fn with_end(mut self, new_end: u64) -> Self {
+		assert!(new_end >= self.start);
+		self.end = new_end;
+		self
+	}
+
+	/// Returns the 1-based, closed coordinates of the record
+	pub fn interval(&self) -> OneBasedInterval {
+		OneBasedInterval::new(self.start as usize, self.end as usize).unwrap()
+	}
+
+	pub fn has_source(&self) -> bool {
+		self.source.is_some()
+	}
+	pub fn source(&self) -> Option<String> {
+		self.source.clone()
+	}
+	pub fn with_source<T: ToString>(mut self, new_source: &T) -> Self {
+		self.source = Some(new_source.to_string());
+		self
+	}
+	pub fn without_source(mut self) -> Self {
+		self.source = None;
+		self
+	}
+
+
+	pub fn has_feature(&self) -> bool {
+		self.feature.is_some()
+	}
+	pub fn feature(&self) -> Option<GtfFeature> {
+		self.feature.clone()
+	}
+	pub fn with_feature(mut self, new_feature: GtfFeature) -> Self {
+		self.feature = Some(new_feature);
+		self
+	}
+	pub fn without_feature(mut self) -> Self {
+		self.feature = None;
+		self
+	}
+
+
+	pub fn has_score(&self) -> bool {
+		self.score.is_some()
+	}
+	pub fn score(&self) -> Option<f64> {
+		self.score.clone()
+	}
+	pub fn with_score(mut self, new_score: f64) -> Self {
+		self.score = Some(new_score);
+		self
+	}
+	pub fn without_score(mut self) -> Self {
+		self.score = None;
+		self
+	}
+
+	
+	pub fn has_strand(&self) -> bool {
+		self.strand.is_some()
+	}
+	pub fn strand(&self) -> Option<Strand> {
+		self.strand.clone()
+	}
+	pub fn with_strand(mut self, new_strand: Strand) -> Self {
+		self.strand = Some(new_strand);
+		self
+	}
+	pub fn without_strand(mut self) -> Self {
+		self.strand = None;
+		self
+	}
+
+
+	pub fn has_frame(&self) -> bool {
+		self.frame.is_some()
+	}
+	pub fn frame(&self) -> Option<usize> {
+		self.frame.clone()
+	}
+	pub fn with_frame(mut self, new_frame: usize) -> Self {
+		self.frame = Some(new_frame);
+		self
+	}
+	pub fn without_frame(mut self) -> Self {
+		self.frame = None;
+		self
+	}
+
+
+	pub fn has_annotations(&self) -> bool {
+		self.annotations.len() > 0
+	}
+	pub fn annotations(&self) -> Vec<GtfAnnotation> {
+		self.annotations.clone()
+	}
+	pub fn with_annotations(mut self, new_annotations: Vec<GtfAnnotation>) -> Self {
+		self.annotations = new_annotations;
+		self
+	}
+	pub fn without_annotations(mut self) -> Self {
+		self.annotations.clear();
+		self
+	}
+
+	pub fn add_annotation(mut self, new_annotation: GtfAnnotation) -> Self {
+		self.annotations.push(new_annotation);
+		self
+	}
+
+	pub fn remove_annotation(mut self, annotation_to_remove: GtfAnnotation) -> Self {
+		let p = self.annotations.iter().position(|item| *item == annotation_to_remove);
+		if p.is_some() {
+			self.annotations.remove(p.unwrap());
+		}
+		self
+	}
+
+	/// Returns the value of the first annotation with the given key, ignoring the case
+	pub fn annotation<K: ToString>(&self, key: K) -> Option<String> {
+		let key = key.to_string().to_lowercase();
+		self.annotations.iter()
+			.find(|a| a.key().to_lowercase() == key)
+			.map(|a| a.value())
+	}
+
+	pub fn gene_id(&self) -> Option<String> {
+		self.annotations.iter().filter_map(|a| match *a {
+			GtfAnnotation::GeneId(ref s) => Some(s.clone()),
+			_ => None
+		}).next()
+	}
+
+	pub fn transcript_id(&self) -> Option<String> {
+		self.annotations.iter().filter_map(|a| match *a {
+			GtfAnnotation::TranscriptId(ref s) => Some(s.clone()),
+			_ => None
+		}).next()
+	}
+
+	/// Returns the identifier of the record. This is the `ID` attribute for GFF3. For
+	/// GTF, the identifiers of genes and transcripts are given by `gene_id` and
+	/// `transcript_id`, respectively.
+	pub fn id(&self) -> Option<String> {
+		let id = self.annotations.iter().filter_map(|a| match *a {
+			GtfAnnotation::Id(ref s) => Some(s.clone()),
+			_ => None
+		}).next();
+		if id.is_some() || self.format == GtfFormat::Gff3 {
+			return id;
+		}
+		match self.feature {
+			Some(GtfFeature::Gene) => self.gene_id(),
+			Some(GtfFeature::Transcript) => self.transcript_id(),
+			_ => None
+		}
+	}
+
+	/// Returns the identifiers of the parent records. These are the `Parent` attributes
+	/// for GFF3. For GTF, transcripts belong to their `gene_id` and all other features
+	/// except genes to their `transcript_id`.
+	pub fn parent_ids(&self) -> Vec<String> {
+		let parents: Vec<String> = self.annotations.iter().filter_map(|a| match *a {
+			GtfAnnotation::Parent(ref s) => Some(s.clone()),
+			_ => None
+		}).collect();
+		if !parents.is_empty() || self.format == GtfFormat::Gff3 {
+			return parents;
+		}
+		let parent = match self.feature {
+			Some(GtfFeature::Gene) => None,
+			Some(GtfFeature::Transcript) => self.gene_id(),
+			_ => self.transcript_id().or(self.gene_id())
+		};
+		parent.into_iter().collect()
+	}
+
+	pub fn format(&self) -> GtfFormat {
+		self.format
+	}
+	pub fn with_format(mut self, new_format: GtfFormat) -> Self {
+		self.format = new_format;
+		self
+	}
+
+	/// Formats the annotations as GFF3 attributes. Consecutive values with the same
+	/// key (e.g., multiple parents) are joined by a comma.
+	fn gff3_attributes(&self) -> String {
+		let mut attributes: Vec<(String, Vec<String>)> = Vec::new();
+		for annotation in self.annotations.iter() {
+			let value = gff3_escape(&annotation.value());
+			let key = annotation.key();
+			let is_same_key = match attributes.last() {
+				Some(&(ref k, _)) => *k == key,
+				None => false
+			};
+			if is_same_key {
+				attributes.last_mut().unwrap().1.push(value);
+			} else {
+				attributes.push((key, vec![value]));
+			}
+		}
+		util::join(attributes.into_iter().map(|(k, v)| format!("{}={}", k, v.join(","))).collect(), ";")
+	}
+}
+
+impl Region for GtfRecord {
+	fn template(&self) -> String {
+		self.seqname.clone()
+	}
+
+	fn offset(&self) -> usize {
+		ZeroBasedInterval::from(self.interval()).start()
+	}
+
+	fn length(&self) -> usize {
+		self.interval().length()
+	}
+}
+
+impl FromStr for GtfFeature {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<GtfFeature, String> {
+		match s.to_lowercase().as_ref() {
+			"gene" => Ok(GtfFeature::Gene),
+			"transcript" => Ok(GtfFeature::Transcript),
+			"cds" => Ok(GtfFeature::CDS),
+			"exon" => Ok(GtfFeature::Exon),
+			"intron" => Ok(GtfFeature::Intron),
+			"start_codon" => Ok(GtfFeature::StartCodon),
+			"stop_codon" => Ok(GtfFeature::StopCodon),
+			"utr" => Ok(GtfFeature::UTR),
+			"five_prime_utr" | "5utr" => Ok(GtfFeature::FivePrimeUTR),
+			"three_prime_utr" | "3utr" => Ok(GtfFeature::ThreePrimeUTR),
+			"selenocysteine" => Ok(GtfFeature::Selenocysteine),
+			"" | "." => Err(format!("Missing feature")),
+			_ => Ok(GtfFeature::Other(s.to_string()))
+		}
+	}
+}
+impl fmt::Display for GtfFeature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    	match self.clone() {
+    		GtfFeature::Gene => write!(f, "gene"),
+    		GtfFeature::Transcript => write!(f, "transcript"),
+    		GtfFeature::CDS => write!(f, "CDS"),
+    		GtfFeature::Exon => write!(f, "exon"),
+    		GtfFeature::Intron => write!(f, "intron"),
+    		GtfFeature::StartCodon => write!(f, "start_codon"),
+    		GtfFeature::StopCodon => write!(f, "stop_codon"),
+    		GtfFeature::UTR => write!(f, "UTR"),
+    		GtfFeature::FivePrimeUTR => write!(f, "five_prime_utr"),
+    		GtfFeature::ThreePrimeUTR => write!(f, "three_prime_utr"),
+    		GtfFeature::Selenocysteine => write!(f, "Selenocysteine"),
+    		GtfFeature::Other(s) => write!(f, "{}", s)
+    	}
+	}
+}
+impl GtfAnnotation {
+	/// Creates the annotation for a key and an (unquoted) value. Known keys are
+	/// matched regardless of their case.
+	pub fn new<K: ToString, V: ToString>(key: K, value: V) -> Result<GtfAnnotation, String> {
+		let key = key.to_string();
+		let value = value.to_string();
+		match key.to_lowercase().as_ref() {
+			"gene_id" => Ok(GtfAnnotation::GeneId(value)),
+			"transcript_id" => Ok(GtfAnnotation::TranscriptId(value)),
+			"exon_number" => match value.parse::<usize>() {
+				Ok(n) => Ok(GtfAnnotation::ExonNumber(n)),
+				Err(e) => Err(format!("Can not parse exon number '{}': {}", value, e))
+			},
+			"id" => Ok(GtfAnnotation::Id(value)),
+			"parent" => Ok(GtfAnnotation::Parent(value)),
+			_ => Ok(GtfAnnotation::Unknown(key, value))
+		}
+	}
+
+	pub fn key(&self) -> String {
+		match *self {
+			GtfAnnotation::GeneId(_) => "gene_id".to_string(),
+			GtfAnnotation::TranscriptId(_) => "transcript_id".to_string(),
+			GtfAnnotation::ExonNumber(_) => "exon_number".to_string(),
+			GtfAnnotation::Id(_) => "ID".to_string(),
+			GtfAnnotation::Parent(_) => "Parent".to_string(),
+			GtfAnnotation::Unknown(ref k, _) => k.clone()
+		}
+	}
+
+	pub fn value(&self) -> String {
+		match *self {
+			GtfAnnotation::GeneId(ref s) | GtfAnnotation::TranscriptId(ref s) |
+			GtfAnnotation::Id(ref s) | GtfAnnotation::Parent(ref s) |
+			GtfAnnotation::Unknown(_, ref s) => s.clone(),
+			GtfAnnotation::ExonNumber(n) => n.to_string()
+		}
+	}
+
+	/// Parses the attribute column in either GTF or GFF3 syntax. Multiple values of
+	/// a GFF3 attribute (e.g., `Parent=t1,t2`) result in one annotation per value.
+	pub fn parse_attributes(s: &str, format: GtfFormat) -> Result<Vec<GtfAnnotation>, String> {
+		let mut annotations = Vec::new();
+		// GFF3 values escape semicolons, GTF values may contain them within quotes
+		let attributes = match format {
+			GtfFormat::Gtf => split_quoted(s),
+			GtfFormat::Gff3 => s.split(';').collect()
+		};
+		for attribute in attributes.into_iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+			match format {
+				GtfFormat::Gtf => annotations.push(attribute.parse::<GtfAnnotation>()?),
+				GtfFormat::Gff3 => {
+					let (key, values) = match attribute.find('=') {
+						Some(pos) => (&attribute[..pos], &attribute[pos + 1..]),
+						None => return Err(format!("Expecting 'key=value' but found '{}'", attribute))
+					};
+					for value in values.split(',') {
+						annotations.push(GtfAnnotation::new(key, gff3_unescape(value))?);
+					}
+				}
+			}
+		}
+		Ok(annotations)
+	}
+
+	/// Detects the syntax of the attribute column
+	pub fn detect_format(s: &str) -> GtfFormat {
+		let first_token = s.trim().split(|c: char| c.is_whitespace() || c == ';').next().unwrap_or("");
+		match first_token.contains('=') {
+			true => GtfFormat::Gff3,
+			false => GtfFormat::Gtf
+		}
+	}
+}
+
+/// Splits GTF attributes at the semicolons that are not part of a quoted value
+fn split_quoted(s: &str) -> Vec<&str> {
+	let mut attributes = Vec::new();
+	let mut quoted = false;
+	let mut start = 0;
+	for (i, c) in s.char_indices() {
+		match c {
+			'"' => quoted = !quoted,
+			';' if !quoted => {
+				attributes.push(&s[start..i]);
+				start = i + 1;
+			},
+			_ => {}
+		}
+	}
+	attributes.push(&s[start..]);
+	attributes
+}
+
+/// Decodes the percent encoding of GFF3 attribute values
+fn gff3_unescape(s: &str) -> String {
+	let bytes = s.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%' && i + 2 < bytes.len() {
+			let digit = |b: u8| (b as char).to_digit(16);
+			if let (Some(h), Some(l)) = (digit(bytes[i + 1]), digit(bytes[i + 2])) {
+				decoded.push((h * 16 + l) as u8);
+				i += 3;
+				continue;
+			}
+		}
+		decoded.push(bytes[i]);
+		i += 1;
+	}
+	String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Encodes the characters with a special meaning in GFF3 attribute values
+fn gff3_escape(s: &str) -> String {
+	s.chars().map(|c| match c {
+		';' | '=' | '&' | ',' | '%' | '\t' | '\n' => format!("%{:02X}", c as u8),
+		_ => c.to_string()
+	}).collect()
+}
+
+/// Parses a single GTF attribute like `gene_id "g1"`
+impl FromStr for GtfAnnotation {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<GtfAnnotation, String> {
+		let s = s.trim();
+		let (key, value) = match s.find(char::is_whitespace) {
+			Some(pos) => (&s[..pos], s[pos..].trim()),
+			None => return Err(format!("Expecting key and value in attribute '{}'", s))
+		};
+		let value = match value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+			true => &value[1..value.len() - 1],
+			false => value
+		};
+		GtfAnnotation::new(key, value)
+	}
+}
+
+impl fmt::Display for GtfAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    	match self.clone() {
+    		GtfAnnotation::GeneId(s) => write!(f, "gene_id \"{}\"", s),
+    		GtfAnnotation::TranscriptId(s) => write!(f, "transcript_id \"{}\"", s),
+    		GtfAnnotation::ExonNumber(s) => write!(f, "exon_number {}", s),
+    		GtfAnnotation::Id(s) => write!(f, "ID \"{}\"", s),
+    		GtfAnnotation::Parent(s) => write!(f, "Parent \"{}\"", s),
+    		GtfAnnotation::Unknown(k,v) => write!(f, "{} \"{}\"", k, v)
+    	}
+	}	
+}
+
+impl FromStr for GtfRecord {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<GtfRecord, String> {
+		let parts = util::split(s.trim_end_matches(|c| c == '\n' || c == '\r'), '\t');
+		if parts.len() != 9 {
+			return Err(format!("Expected 9 cells separated by tab but found {}", parts.len()))
+		}
+
+		let mut record = match parts[3].parse::<u64>() {
+			Err(e) => return Err(format!("Can not parse cell 4 as start position: {}", e)),
+			Ok(start) => match parts[4].parse::<u64>() {
+				Err(e) => return Err(format!("Can not parse cell 5 as end position: {}", e)),
+				Ok(_) if start == 0 => return Err(format!("Start position must be at least 1")),
+				Ok(end) if end < start => return Err(format!("End position {} before start position {}", end, start)),
+				Ok(end) => GtfRecord::new(&parts[0], start, end)
+			}
+		};
+
+		if parts[1] != "." {
+			record = record.with_source(&parts[1]);
+		}
+
+		match parts[2].parse::<GtfFeature>() {
+			Ok(f) => record = record.with_feature(f),
+			Err(e) => return Err(format!("Can not parse cell 3 (feature): {}", e))
+		}
+
+		if parts[5] != "." {
+			match parts[5].parse::<f64>() {
+				Ok(f) => record = record.with_score(f),
+				Err(e) => return Err(format!("Can not parse cell 6 (score): {}", e))
+			}
+		}
+
+		if parts[6] != "." && parts[6] != "?" {
+			match parts[6].parse::<Strand>() {
+				Ok(f) => record = record.with_strand(f),
+				Err(e) => return Err(format!("Can not parse cell 7 (strand): {}", e))
+			}
+		}
+
+		if parts[7] != "." {
+			match parts[7].parse::<usize>() {
+				Ok(f) => record = record.with_frame(f),
+				Err(e) => return Err(format!("Can not parse cell 8 (frame): {}", e))
+			}
+		}
+
+		if parts[8] != "." {
+			let format = GtfAnnotation::detect_format(&parts[8]);
+			match GtfAnnotation::parse_attributes(&parts[8], format) {
+				Ok(a) => record = record.with_annotations(a).with_format(format),
+				Err(e) => return Err(format!("Can not parse cell 9 (attributes): {}", e))
+			}
+		}
+
+		Ok(record)
+	}
+}
+
+
+impl fmt::Display for GtfRecord { 
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    	let mut cells = Vec::new();
+
+    	cells.push(self.seqname().to_string());
+    	match self.source() {
+    		Some(s) => cells.push(s),
+    		None => cells.push(".".to_string())
+    	};
+    	match self.feature() {
+    		Some(s) => cells.push(s.to_string()),
+    		None => cells.push(".".to_string())
+    	}
+    	cells.push(format!("{}", self.start()));
+    	cells.push(format!("{}", self.end()));
+    	match self.score() {
+    		Some(s) => cells.push(format!("{}", s)),
+    		None => cells.push(".".to_string())
+    	}
+    	match self.strand() {
+    		Some(s) => match s {
+    			Strand::Forward  => cells.push("+".to_string()),
+    			Strand::Backward => cells.push("-".to_string())
+    		},
+    		None => cells.push(".".to_string())
+    	}
+    	match self.frame() {
+    		Some(s) => cells.push(format!("{}", s)),
+    		None => cells.push(".".to_string())
+    	}
+    	
+    	let annots = match self.format {
+    		GtfFormat::Gtf => util::join(self.annotations().iter().map(|x| x.to_string()).collect(), "; "),
+    		GtfFormat::Gff3 => self.gff3_attributes()
+    	};
+    	cells.push(if annots.is_empty() { ".".to_string() } else { annots });
+
+    	write!(f, "{}", util::join(cells, "\t"))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use io::gtf::{GtfFeature, GtfFormat, GtfRecord};
+	use std::str::FromStr;
+
+	#[test]
+	fn test_from_and_to_string(){
+		let orig = "chr1\tprocessed_transcript\ttranscript\t11869\t14409\t.\t+\t.\tgene_id \"ENSG00000223972\"; transcript_id \"ENST00000456328\"; gene_name \"DDX11L1\"; gene_source \"havana\"; gene_biotype \"transcribed_unprocessed_pseudogene\"; transcript_name \"DDX11L1-002\"; transcript_source \"havana\"";
+		let record = match GtfRecord::from_str(orig) {
+			Ok(r) => {
+				assert_eq!(r.seqname(), "chr1".to_string());
+				assert_eq!(r.start(), 11869u64);
+				assert_eq!(r.end(), 14409u64);
+			},
+			Err(e) => assert!(false, e)
+		};
+	}
+
+	#[test]
+	fn test_gtf_attributes(){
+		let r = GtfRecord::from_str("ref\ttoy\texon\t2\t20\t.\t?\t.\tgene_id \"g1\"; transcript_id \"t1\"; exon_number 2; note \"a b\";\n").unwrap();
+		assert_eq!(r.strand(), None);
+		assert_eq!(r.gene_id(), Some("g1".to_string()));
+		assert_eq!(r.annotation("exon_number"), Some("2".to_string()));
+		assert_eq!(r.annotation("note"), Some("a b".to_string()));
+		assert_eq!(r.id(), None);
+		assert_eq!(r.parent_ids(), vec!["t1".to_string()]);
+
+		let r = GtfRecord::from_str("ref\ttoy\texon\t2\t20\t.\t+\t.\tGene_Id \"g1\"; note \"a; b\"; Transcript_ID \"t1\"").unwrap();
+		assert_eq!(r.gene_id(), Some("g1".to_string()));
+		assert_eq!(r.transcript_id(), Some("t1".to_string()));
+		assert_eq!(r.annotation("note"), Some("a; b".to_string()));
+		assert_eq!(r.annotation("NOTE"), Some("a; b".to_string()));
+	}
+
+	#[test]
+	fn test_with_end(){
+		let r = GtfRecord::new(&"ref", 10, 20).with_end(30);
+		assert_eq!(r.start(), 10);
+		assert_eq!(r.end(), 30);
+	}
+
+	#[test]
+	fn test_gff3_attributes(){
+		let orig = "ref\ttoy\texon\t20\t38\t.\t-\t.\tID=e3;Parent=t2,t3;Note=a%3Bb";
+		let r = GtfRecord::from_str(orig).unwrap();
+		assert_eq!(r.format(), GtfFormat::Gff3);
+		assert_eq!(r.feature(), Some(GtfFeature::Exon));
+		assert_eq!(r.id(), Some("e3".to_string()));
+		assert_eq!(r.parent_ids(), vec!["t2".to_string(), "t3".to_string()]);
+		assert_eq!(r.annotation("Note"), Some("a;b".to_string()));
+		assert_eq!(r.to_string(), orig);
+
+		let r = GtfRecord::from_str("ref\ttoy\tgene\t1\t9\t.\t+\t.\tID=g1;Name=%aé;Note=caf%C3%A9").unwrap();
+		assert_eq!(r.annotation("Name"), Some("%aé".to_string()));
+		assert_eq!(r.annotation("Note"), Some("café".to_string()));
+	}
+
+	#[test]
+	fn test_invalid_coordinates(){
+		assert!(GtfRecord::from_str("ref\ttoy\texon\t0\t20\t.\t+\t.\tgene_id \"g1\"").is_err());
+		assert!(GtfRecord::from_str("ref\ttoy\texon\t20\t10\t.\t+\t.\tgene_id \"g1\"").is_err());
+	}
+}
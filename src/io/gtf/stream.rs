@@ -0,0 +1,118 @@
+use io::compress::DecompressReader;
+use io::gtf::GtfRecord;
+use model::IntervalTree;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A stream of GTF or GFF3 records. Comments and directives are skipped and the stream
+/// ends at a GFF3 `##FASTA` section. Gzip and BGZF compressed input is decompressed
+/// transparently.
+pub struct GtfStream<R: Read> {
+	reader: BufReader<DecompressReader<R>>,
+	line: usize,
+	finished: bool
+}
+
+impl<R: Read> GtfStream<R> {
+	pub fn new(r: R) -> GtfStream<R> {
+		GtfStream::from_decompressed(DecompressReader::new(r))
+	}
+
+	fn from_decompressed(r: DecompressReader<R>) -> GtfStream<R> {
+		GtfStream {
+			reader: BufReader::new(r),
+			line: 0,
+			finished: false
+		}
+	}
+
+	/// Reads all records and fails on the first malformed one
+	pub fn read_records(&mut self) -> Result<Vec<GtfRecord>, String> {
+		self.collect()
+	}
+
+	/// Reads all records into an interval tree for repeated region queries
+	pub fn read_index(&mut self) -> Result<IntervalTree<GtfRecord>, String> {
+		self.read_records().map(IntervalTree::from)
+	}
+}
+
+impl GtfStream<File> {
+	pub fn open<P: AsRef<Path>>(filename: &P) -> Result<GtfStream<File>, String> {
+		match DecompressReader::open(filename) {
+			Ok(r) => Ok(GtfStream::from_decompressed(r)),
+			Err(e) => Err(format!("Can not open GTF file '{}': {}", filename.as_ref().display(), e))
+		}
+	}
+}
+
+impl<R: Read> Iterator for GtfStream<R> {
+	type Item = Result<GtfRecord, String>;
+
+	fn next(&mut self) -> Option<Result<GtfRecord, String>> {
+		while !self.finished {
+			let mut line = String::new();
+			match self.reader.read_line(&mut line) {
+				Ok(0) => self.finished = true,
+				Ok(_) => self.line += 1,
+				Err(e) => return Some(Err(format!("Can not read line {}: {}", self.line + 1, e)))
+			}
+
+			if line.starts_with("##FASTA") {
+				self.finished = true;
+			}
+			if self.finished || line.starts_with('#') || line.trim().is_empty() {
+				continue;
+			}
+
+			return Some(GtfRecord::from_str(&line).map_err(|e| format!("Line {}: {}", self.line, e)));
+		}
+		None
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use io::gtf::{GtfFeature, GtfFormat, GtfRecord, GtfStream};
+	use model::{RegionIndex, SimpleRegion};
+
+	#[test]
+	fn test_read_gtf() {
+		let records = GtfStream::open(&"testdata/toy.gtf").unwrap().read_records().unwrap();
+		assert_eq!(records.len(), 16);
+		assert_eq!(records[0].feature(), Some(GtfFeature::Gene));
+		assert_eq!(records[0].format(), GtfFormat::Gtf);
+		assert_eq!(records[2].annotation("exon_id"), Some("e1".to_string()));
+		assert_eq!(records[9].feature(), Some(GtfFeature::ThreePrimeUTR));
+	}
+
+	#[test]
+	fn test_read_gff3() {
+		let records: Vec<GtfRecord> = GtfStream::open(&"testdata/toy.gff3").unwrap()
+			.map(|r| r.unwrap())
+			.collect();
+		assert_eq!(records.len(), 13);
+		assert_eq!(records[1].feature(), Some(GtfFeature::Other("mRNA".to_string())));
+		assert_eq!(records[1].annotation("Name"), Some("GeneA, transcript 1".to_string()));
+		assert_eq!(records[12].id(), Some("e4".to_string()));
+	}
+
+	#[test]
+	fn test_read_index() {
+		let index = GtfStream::open(&"testdata/toy.gtf").unwrap().read_index().unwrap();
+		let found = index.containing(&SimpleRegion::new("ref2", 20, 5));
+		assert_eq!(found.len(), 5);
+		assert!(found.iter().all(|r| r.gene_id() == Some("g2".to_string())));
+	}
+
+	#[test]
+	fn test_malformed_line() {
+		let data = "# comment\nref\ttoy\texon\t2\t20\t.\t+\n";
+		let mut stream = GtfStream::new(data.as_bytes());
+		let e = stream.next().unwrap().unwrap_err();
+		assert!(e.starts_with("Line 2:"), "{}", e);
+	}
+}
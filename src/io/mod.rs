@@ -1,6 +1,10 @@
 pub mod fasta;
-//pub mod bam;
+pub mod fastq;
+pub mod bam;
 pub mod csv;
 pub mod bed;
 pub mod gtf;
-pub mod fai;
\ No newline at end of file
+pub mod fai;
+pub mod compress;
+pub mod tabix;
+pub mod vcf;
@@ -0,0 +1,370 @@
+use flate2::bufread::MultiGzDecoder;
+use io::compress::DecompressReader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// The data format of a tabix indexed file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TabixFormat {
+    Generic,
+    Sam,
+    Vcf,
+}
+
+/// The binning and linear index of a single reference sequence.
+#[derive(Clone, Debug, Default)]
+struct TabixReference {
+    bins: HashMap<u32, Vec<(u64, u64)>>,
+    intervals: Vec<u64>,
+}
+
+/// A tabix index (`.tbi`) for a BGZF compressed and position sorted text file
+/// as defined by https://samtools.github.io/hts-specs/tabix.pdf
+#[derive(Clone, Debug)]
+pub struct TabixIndex {
+    format: TabixFormat,
+    zero_based: bool,
+    col_seq: usize,
+    col_beg: usize,
+    col_end: usize,
+    meta: char,
+    skip: usize,
+    names: Vec<String>,
+    references: Vec<TabixReference>,
+}
+
+impl TabixIndex {
+    /// Parses the (BGZF compressed) content of a `.tbi` file.
+    pub fn read<R: Read>(reader: R) -> io::Result<TabixIndex> {
+        let mut r = DecompressReader::new(reader);
+
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != b"TBI\x01" {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a tabix index"));
+        }
+
+        let n_ref = read_i32(&mut r)? as usize;
+        let format = read_i32(&mut r)?;
+        let col_seq = read_i32(&mut r)? as usize;
+        let col_beg = read_i32(&mut r)? as usize;
+        let col_end = read_i32(&mut r)? as usize;
+        let meta = read_i32(&mut r)? as u8 as char;
+        let skip = read_i32(&mut r)? as usize;
+
+        let l_nm = read_i32(&mut r)? as usize;
+        let mut names_buf = vec![0u8; l_nm];
+        r.read_exact(&mut names_buf)?;
+        let names: Vec<String> = names_buf
+            .split(|b| *b == 0)
+            .filter(|n| !n.is_empty())
+            .map(|n| String::from_utf8_lossy(n).to_string())
+            .collect();
+
+        let mut references = Vec::with_capacity(n_ref);
+        for _ in 0..n_ref {
+            let mut reference = TabixReference::default();
+            let n_bin = read_i32(&mut r)?;
+            for _ in 0..n_bin {
+                let bin = read_u32(&mut r)?;
+                let n_chunk = read_i32(&mut r)?;
+                let mut chunks = Vec::new();
+                for _ in 0..n_chunk {
+                    let beg = read_u64(&mut r)?;
+                    let end = read_u64(&mut r)?;
+                    chunks.push((beg, end));
+                }
+                reference.bins.insert(bin, chunks);
+            }
+            let n_intv = read_i32(&mut r)?;
+            for _ in 0..n_intv {
+                reference.intervals.push(read_u64(&mut r)?);
+            }
+            references.push(reference);
+        }
+
+        Ok(TabixIndex {
+            format: match format & 0xffff {
+                1 => TabixFormat::Sam,
+                2 => TabixFormat::Vcf,
+                _ => TabixFormat::Generic,
+            },
+            zero_based: format & 0x10000 != 0,
+            col_seq: col_seq,
+            col_beg: col_beg,
+            col_end: col_end,
+            meta: meta,
+            skip: skip,
+            names: names,
+            references: references,
+        })
+    }
+
+    pub fn read_tbi<P: AsRef<Path>>(tbi_filename: &P) -> io::Result<TabixIndex> {
+        TabixIndex::read(File::open(tbi_filename)?)
+    }
+
+    /// Tries to discover and read the `.tbi` index for a given BGZF file.
+    pub fn discover<P: AsRef<Path>>(filename: &P) -> Option<TabixIndex> {
+        let mut tbi_path = filename.as_ref().as_os_str().to_owned();
+        tbi_path.push(".tbi");
+
+        if !Path::new(&tbi_path).exists() {
+            return None;
+        }
+        match TabixIndex::read_tbi(&tbi_path) {
+            Ok(index) => Some(index),
+            Err(e) => {
+                warn!("Can not read existing tabix index {:?}: {}", tbi_path, e);
+                None
+            }
+        }
+    }
+
+    pub fn format(&self) -> TabixFormat {
+        self.format
+    }
+
+    /// Returns the names of the indexed reference sequences
+    pub fn names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+
+    /// Returns the virtual file offset from where on records overlapping the given
+    /// region (0-based, half-open) may be found. Returns `None` if no record is located
+    /// in the region's bins.
+    pub fn query_offset<S: ToString>(&self, name: S, offset: usize, end: usize) -> Option<u64> {
+        let tid = match self.names.iter().position(|n| *n == name.to_string()) {
+            Some(t) => t,
+            None => return None,
+        };
+        let reference = &self.references[tid];
+
+        // Chunks ending before the linear index offset can not contain overlapping records
+        let min_offset = reference
+            .intervals
+            .get(offset >> 14)
+            .or(reference.intervals.last())
+            .cloned()
+            .unwrap_or(0);
+
+        region_to_bins(offset, end)
+            .iter()
+            .filter_map(|bin| reference.bins.get(bin))
+            .flat_map(|chunks| chunks.iter())
+            .filter(|&&(_, chunk_end)| chunk_end > min_offset)
+            .map(|&(chunk_beg, _)| chunk_beg)
+            .min()
+    }
+
+    /// Parses the template and the interval (0-based, half-open) of a data line.
+    fn line_interval(&self, line: &str) -> Option<(String, usize, usize)> {
+        let cells: Vec<&str> = line.split('\t').collect();
+        if cells.len() < self.col_seq || cells.len() < self.col_beg || self.col_seq == 0 || self.col_beg == 0 {
+            return None;
+        }
+        let begin = match cells[self.col_beg - 1].parse::<usize>() {
+            Ok(b) if self.zero_based => b,
+            Ok(b) if b > 0 => b - 1,
+            _ => return None,
+        };
+
+        let end = match self.format {
+            TabixFormat::Vcf => {
+                let info_end = cells.get(7).and_then(|info| {
+                    info.split(';')
+                        .find(|kv| kv.starts_with("END="))
+                        .and_then(|kv| kv[4..].parse::<usize>().ok())
+                });
+                match info_end {
+                    Some(e) => e,
+                    None => begin + cells.get(3).map(|r| r.len()).unwrap_or(1),
+                }
+            }
+            _ if self.col_end > 0 && self.col_end <= cells.len() => {
+                match cells[self.col_end - 1].parse::<usize>() {
+                    Ok(e) => e,
+                    Err(_) => begin + 1,
+                }
+            }
+            _ => begin + 1,
+        };
+
+        Some((cells[self.col_seq - 1].to_string(), begin, end))
+    }
+
+    /// Returns `true` if the line is a header line that has to be skipped.
+    fn is_meta_line(&self, line: &str) -> bool {
+        line.starts_with(self.meta)
+    }
+}
+
+
+/// A BGZF compressed file that is queried via its tabix index.
+#[derive(Debug)]
+pub struct TabixFile {
+    filename: PathBuf,
+    index: TabixIndex,
+}
+
+impl TabixFile {
+    pub fn open<P: AsRef<Path>>(filename: &P) -> Result<TabixFile, String> {
+        match TabixIndex::discover(filename) {
+            Some(index) => Ok(TabixFile {
+                filename: filename.as_ref().to_path_buf(),
+                index: index,
+            }),
+            None => Err(format!("Can not find tabix index for: {}", filename.as_ref().display())),
+        }
+    }
+
+    pub fn index(&self) -> &TabixIndex {
+        &self.index
+    }
+
+    /// Reads all header lines from the beginning of the file.
+    pub fn header_lines(&self) -> Result<Vec<String>, String> {
+        let reader = match DecompressReader::open(&self.filename) {
+            Ok(r) => BufReader::new(r),
+            Err(e) => return Err(format!("Can not open '{}': {}", self.filename.display(), e)),
+        };
+
+        let mut lines = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            match line {
+                Ok(l) => {
+                    if i >= self.index.skip && !self.index.is_meta_line(&l) {
+                        break;
+                    }
+                    lines.push(l);
+                }
+                Err(e) => return Err(format!("Can not read '{}': {}", self.filename.display(), e)),
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Returns all data lines overlapping the given region (0-based offset and length).
+    pub fn query<S: ToString>(&self, name: S, offset: usize, length: usize) -> Result<Vec<String>, String> {
+        let name = name.to_string();
+        let end = offset + length;
+        let voffset = match self.index.query_offset(&name, offset, end) {
+            Some(v) => v,
+            None => return Ok(Vec::new()),
+        };
+
+        let reader = match self.open_at(voffset) {
+            Ok(r) => r,
+            Err(e) => return Err(format!("Can not read '{}': {}", self.filename.display(), e)),
+        };
+
+        // Records are sorted, so stop at the first record behind the region
+        let mut lines = Vec::new();
+        for line in reader.lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => return Err(format!("Can not read '{}': {}", self.filename.display(), e)),
+            };
+            if self.index.is_meta_line(&line) {
+                continue;
+            }
+            match self.index.line_interval(&line) {
+                Some((ref template, begin, _)) if *template != name || begin >= end => break,
+                Some((_, begin, line_end)) if begin < end && line_end > offset => lines.push(line),
+                Some(_) => {}
+                None => warn!("Can not parse position from line: {}", line),
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Opens the file at the given virtual offset, i.e., the offset of the BGZF block
+    /// shifted by 16 bits combined with the offset within the uncompressed block.
+    fn open_at(&self, voffset: u64) -> io::Result<BufReader<MultiGzDecoder<BufReader<File>>>> {
+        let mut fh = File::open(&self.filename)?;
+        fh.seek(SeekFrom::Start(voffset >> 16))?;
+        let mut reader = BufReader::new(MultiGzDecoder::new(BufReader::new(fh)));
+
+        let within_block = voffset & 0xffff;
+        let skipped = io::copy(&mut reader.by_ref().take(within_block), &mut io::sink())?;
+        if skipped < within_block {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Invalid virtual offset"));
+        }
+        Ok(reader)
+    }
+}
+
+
+/// Calculates the bins that may contain records overlapping the region
+/// (0-based, half-open) as given in the SAM specification.
+fn region_to_bins(offset: usize, end: usize) -> Vec<u32> {
+    let end = if end > offset { end - 1 } else { offset };
+    let mut bins = vec![0u32];
+    for &(first, shift) in [(1u32, 26), (9, 23), (73, 20), (585, 17), (4681, 14)].iter() {
+        for k in (first + (offset >> shift) as u32)..(first + (end >> shift) as u32 + 1) {
+            bins.push(k);
+        }
+    }
+    bins
+}
+
+fn read_i32<R: Read>(reader: &mut R) -> io::Result<i32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::tabix::*;
+
+    #[test]
+    fn test_read_tbi() {
+        let index = TabixIndex::read_tbi(&"testdata/toy.vcf.gz.tbi").unwrap();
+        assert_eq!(index.format(), TabixFormat::Vcf);
+        assert_eq!(index.names(), vec!["ref".to_string(), "ref2".to_string()]);
+        assert!(index.query_offset("ref", 0, 10).is_some());
+        assert!(index.query_offset("ref3", 0, 10).is_none());
+    }
+
+    #[test]
+    fn test_region_to_bins() {
+        assert_eq!(region_to_bins(0, 1), vec![0, 1, 9, 73, 585, 4681]);
+        assert_eq!(region_to_bins(16384, 16385), vec![0, 1, 9, 73, 585, 4682]);
+    }
+
+    #[test]
+    fn test_query() {
+        let file = TabixFile::open(&"testdata/toy.vcf.gz").unwrap();
+        assert_eq!(file.header_lines().unwrap().len(), 13);
+
+        let lines = file.query("ref", 10, 15).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("ref\t12\t"));
+        assert!(lines[1].starts_with("ref\t20\t"));
+
+        // The deletion at position 20 spans until position 22
+        assert_eq!(file.query("ref", 21, 1).unwrap().len(), 1);
+        assert_eq!(file.query("ref", 22, 5).unwrap().len(), 0);
+
+        // The structural variant extends through its END annotation
+        assert_eq!(file.query("ref2", 32, 2).unwrap().len(), 1);
+        assert_eq!(file.query("ref3", 0, 10).unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,408 @@
+use std::fmt;
+
+/// The definition of an INFO, FORMAT, or FILTER field in the VCF header, e.g.,
+/// `##INFO=<ID=DP,Number=1,Type=Integer,Description="Total depth">`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VcfField {
+    id: String,
+    number: String,
+    value_type: String,
+    description: String,
+}
+
+impl VcfField {
+    pub fn new<I: ToString, N: ToString, T: ToString, D: ToString>(
+        id: I,
+        number: N,
+        value_type: T,
+        description: D,
+    ) -> VcfField {
+        VcfField {
+            id: id.to_string(),
+            number: number.to_string(),
+            value_type: value_type.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Returns the number of values, i.e., an integer, `A` (one per alternative
+    /// allele), `R` (one per allele), `G` (one per genotype) or `.` (unknown).
+    pub fn number(&self) -> String {
+        self.number.clone()
+    }
+
+    /// Returns the type of the values (`Integer`, `Float`, `Flag`, `Character`, or `String`)
+    pub fn value_type(&self) -> String {
+        self.value_type.clone()
+    }
+
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn from_attributes(attributes: &Vec<(String, String)>) -> Result<VcfField, String> {
+        let get = |key: &str| attributes.iter().find(|a| a.0 == key).map(|a| a.1.clone());
+        match get("ID") {
+            Some(id) => Ok(VcfField {
+                id: id,
+                number: get("Number").unwrap_or(".".to_string()),
+                value_type: get("Type").unwrap_or("String".to_string()),
+                description: get("Description").unwrap_or(String::new()),
+            }),
+            None => Err("Missing ID in header field definition".to_string()),
+        }
+    }
+}
+
+
+/// A contig definition of the VCF header, e.g., `##contig=<ID=chr1,length=248956422>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VcfContig {
+    id: String,
+    length: Option<usize>,
+}
+
+impl VcfContig {
+    pub fn new<S: ToString>(id: S, length: Option<usize>) -> VcfContig {
+        VcfContig {
+            id: id.to_string(),
+            length: length,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn length(&self) -> Option<usize> {
+        self.length
+    }
+}
+
+
+/// The header of a VCF file. All meta lines are kept in their original order
+/// such that writing the header reproduces the input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VcfHeader {
+    meta_lines: Vec<String>,
+    infos: Vec<VcfField>,
+    formats: Vec<VcfField>,
+    filters: Vec<VcfField>,
+    contigs: Vec<VcfContig>,
+    samples: Vec<String>,
+}
+
+impl VcfHeader {
+    /// Create a new header for VCF version 4.2 without any definitions
+    pub fn new() -> VcfHeader {
+        VcfHeader {
+            meta_lines: vec!["##fileformat=VCFv4.2".to_string()],
+            infos: Vec::new(),
+            formats: Vec::new(),
+            filters: Vec::new(),
+            contigs: Vec::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Parses the header from its lines, i.e., all meta lines (starting with `##`)
+    /// followed by the column header line (starting with `#CHROM`).
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Result<VcfHeader, String> {
+        let mut header = VcfHeader {
+            meta_lines: Vec::new(),
+            infos: Vec::new(),
+            formats: Vec::new(),
+            filters: Vec::new(),
+            contigs: Vec::new(),
+            samples: Vec::new(),
+        };
+        let mut has_column_line = false;
+
+        for line in lines {
+            let line = line.as_ref().trim_end();
+            if line.starts_with("##") {
+                header.add_meta_line(line)?;
+            } else if line.starts_with("#CHROM") {
+                let columns: Vec<&str> = line.split('\t').collect();
+                if columns.len() < 8 {
+                    return Err(format!("Expecting at least 8 columns in header line but found {}", columns.len()));
+                }
+                header.samples = columns.iter().skip(9).map(|s| s.to_string()).collect();
+                has_column_line = true;
+            } else if !line.is_empty() {
+                return Err(format!("Unexpected line in VCF header: {}", line));
+            }
+        }
+
+        if !has_column_line {
+            return Err("Missing '#CHROM' line in VCF header".to_string());
+        }
+        if !header.meta_lines.first().map(|l| l.starts_with("##fileformat=")).unwrap_or(false) {
+            warn!("VCF header does not start with a fileformat line");
+        }
+        Ok(header)
+    }
+
+    /// Appends a meta line (e.g., `##INFO=<...>`) and parses the contained definition.
+    pub fn add_meta_line<S: ToString>(&mut self, line: S) -> Result<(), String> {
+        let line = line.to_string();
+        if !line.starts_with("##") {
+            return Err(format!("Meta line must start with '##': {}", line));
+        }
+
+        let (key, value) = match line[2..].find('=') {
+            Some(pos) => (&line[2..pos + 2], &line[pos + 3..]),
+            None => (&line[2..], ""),
+        };
+        let result = match key {
+            "INFO" => parse_attributes(value).and_then(|a| VcfField::from_attributes(&a)).map(|f| self.infos.push(f)),
+            "FORMAT" => parse_attributes(value).and_then(|a| VcfField::from_attributes(&a)).map(|f| self.formats.push(f)),
+            "FILTER" => parse_attributes(value).and_then(|a| VcfField::from_attributes(&a)).map(|f| self.filters.push(f)),
+            "contig" => parse_attributes(value).and_then(|a| {
+                match a.iter().find(|a| a.0 == "ID") {
+                    Some(id) => {
+                        let length = a.iter().find(|a| a.0 == "length").and_then(|l| l.1.parse::<usize>().ok());
+                        self.contigs.push(VcfContig::new(id.1.clone(), length));
+                        Ok(())
+                    }
+                    None => Err("Missing ID in contig definition".to_string()),
+                }
+            }),
+            _ => Ok(()),
+        };
+
+        match result {
+            Ok(_) => {
+                self.meta_lines.push(line.clone());
+                Ok(())
+            }
+            Err(e) => Err(format!("{}: {}", e, line)),
+        }
+    }
+
+    pub fn with_info(mut self, field: VcfField) -> Self {
+        let line = format!("##INFO={}", field_line(&field, true));
+        self.meta_lines.push(line);
+        self.infos.push(field);
+        self
+    }
+
+    pub fn with_format(mut self, field: VcfField) -> Self {
+        let line = format!("##FORMAT={}", field_line(&field, true));
+        self.meta_lines.push(line);
+        self.formats.push(field);
+        self
+    }
+
+    pub fn with_filter(mut self, field: VcfField) -> Self {
+        let line = format!("##FILTER={}", field_line(&field, false));
+        self.meta_lines.push(line);
+        self.filters.push(field);
+        self
+    }
+
+    pub fn with_contig(mut self, contig: VcfContig) -> Self {
+        let line = match contig.length {
+            Some(l) => format!("##contig=<ID={},length={}>", contig.id, l),
+            None => format!("##contig=<ID={}>", contig.id),
+        };
+        self.meta_lines.push(line);
+        self.contigs.push(contig);
+        self
+    }
+
+    pub fn with_sample<S: ToString>(mut self, name: S) -> Self {
+        self.samples.push(name.to_string());
+        self
+    }
+
+    /// Returns the VCF version (e.g., `VCFv4.2`) if given
+    pub fn fileformat(&self) -> Option<String> {
+        self.meta_lines
+            .iter()
+            .find(|l| l.starts_with("##fileformat="))
+            .map(|l| l["##fileformat=".len()..].to_string())
+    }
+
+    pub fn meta_lines(&self) -> Vec<String> {
+        self.meta_lines.clone()
+    }
+
+    pub fn infos(&self) -> Vec<VcfField> {
+        self.infos.clone()
+    }
+
+    pub fn info<S: ToString>(&self, id: S) -> Option<VcfField> {
+        let id = id.to_string();
+        self.infos.iter().find(|f| f.id == id).cloned()
+    }
+
+    pub fn formats(&self) -> Vec<VcfField> {
+        self.formats.clone()
+    }
+
+    pub fn format<S: ToString>(&self, id: S) -> Option<VcfField> {
+        let id = id.to_string();
+        self.formats.iter().find(|f| f.id == id).cloned()
+    }
+
+    pub fn filters(&self) -> Vec<VcfField> {
+        self.filters.clone()
+    }
+
+    pub fn contigs(&self) -> Vec<VcfContig> {
+        self.contigs.clone()
+    }
+
+    pub fn contig<S: ToString>(&self, id: S) -> Option<VcfContig> {
+        let id = id.to_string();
+        self.contigs.iter().find(|c| c.id == id).cloned()
+    }
+
+    /// Returns the sample names in the order of the genotype columns
+    pub fn samples(&self) -> Vec<String> {
+        self.samples.clone()
+    }
+
+    pub fn sample_index<S: ToString>(&self, name: S) -> Option<usize> {
+        let name = name.to_string();
+        self.samples.iter().position(|s| *s == name)
+    }
+}
+
+impl Default for VcfHeader {
+    fn default() -> VcfHeader {
+        VcfHeader::new()
+    }
+}
+
+impl fmt::Display for VcfHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.meta_lines {
+            write!(f, "{}\n", line)?;
+        }
+        write!(f, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
+        if !self.samples.is_empty() {
+            write!(f, "\tFORMAT")?;
+            for s in &self.samples {
+                write!(f, "\t{}", s)?;
+            }
+        }
+        write!(f, "\n")
+    }
+}
+
+fn field_line(field: &VcfField, with_type: bool) -> String {
+    match with_type {
+        true => format!(
+            "<ID={},Number={},Type={},Description=\"{}\">",
+            field.id,
+            field.number,
+            field.value_type,
+            field.description
+        ),
+        false => format!("<ID={},Description=\"{}\">", field.id, field.description),
+    }
+}
+
+/// Parses a structured meta value like `<ID=DP,Number=1,Description="A, B">` into
+/// key-value pairs. Quotes are removed from the values.
+fn parse_attributes(s: &str) -> Result<Vec<(String, String)>, String> {
+    if !s.starts_with('<') || !s.ends_with('>') {
+        return Err("Expecting structured value enclosed in '<' and '>'".to_string());
+    }
+
+    let mut attributes = Vec::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in s[1..s.len() - 1].chars() {
+        if escaped {
+            value.push(c);
+            escaped = false;
+        } else if in_quotes {
+            match c {
+                '\\' => escaped = true,
+                '"' => in_quotes = false,
+                _ => value.push(c),
+            }
+        } else if c == ',' {
+            attributes.push((key.trim().to_string(), value.clone()));
+            key.clear();
+            value.clear();
+            in_value = false;
+        } else if c == '=' && !in_value {
+            in_value = true;
+        } else if c == '"' && in_value {
+            in_quotes = true;
+        } else if in_value {
+            value.push(c);
+        } else {
+            key.push(c);
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted value".to_string());
+    }
+    if !key.is_empty() {
+        attributes.push((key.trim().to_string(), value));
+    }
+    Ok(attributes)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::vcf::header::*;
+
+    #[test]
+    fn test_parse_attributes() {
+        let a = parse_attributes("<ID=AF,Number=A,Type=Float,Description=\"Allele frequency, one per ALT\">").unwrap();
+        assert_eq!(a.len(), 4);
+        assert_eq!(a[0], ("ID".to_string(), "AF".to_string()));
+        assert_eq!(a[3], ("Description".to_string(), "Allele frequency, one per ALT".to_string()));
+        assert!(parse_attributes("<ID=AF,Description=\"open>").is_err());
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let lines = vec![
+            "##fileformat=VCFv4.2",
+            "##contig=<ID=ref,length=45>",
+            "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total depth\">",
+            "##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">",
+            "##source=test",
+            "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\ts2",
+        ];
+        let header = VcfHeader::parse(&lines).unwrap();
+        assert_eq!(header.fileformat(), Some("VCFv4.2".to_string()));
+        assert_eq!(header.contig("ref"), Some(VcfContig::new("ref", Some(45))));
+        assert_eq!(header.info("DP").unwrap().value_type(), "Integer");
+        assert_eq!(header.format("GT").unwrap().number(), "1");
+        assert_eq!(header.samples(), vec!["s1".to_string(), "s2".to_string()]);
+        assert_eq!(header.to_string(), lines.join("\n") + "\n");
+
+        assert!(VcfHeader::parse(&lines[0..4]).is_err(), "Missing column line");
+    }
+
+    #[test]
+    fn test_build_header() {
+        let header = VcfHeader::new()
+            .with_contig(VcfContig::new("ref", Some(45)))
+            .with_info(VcfField::new("DP", 1, "Integer", "Total depth"))
+            .with_sample("s1");
+        assert_eq!(
+            header.to_string(),
+            "##fileformat=VCFv4.2\n##contig=<ID=ref,length=45>\n\
+             ##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total depth\">\n\
+             #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\n"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use io::tabix::TabixFile;
+use io::vcf::{VcfHeader, VcfRecord};
+use model::Region;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A bgzip compressed VCF file with random access through its tabix index.
+#[derive(Debug)]
+pub struct IndexedVcfFile {
+    file: TabixFile,
+    header: VcfHeader,
+}
+
+impl IndexedVcfFile {
+    pub fn open<P: AsRef<Path>>(filename: &P) -> Result<IndexedVcfFile, String> {
+        let file = TabixFile::open(filename)?;
+        let header = VcfHeader::parse(&file.header_lines()?)?;
+        Ok(IndexedVcfFile {
+            file: file,
+            header: header,
+        })
+    }
+
+    pub fn header(&self) -> &VcfHeader {
+        &self.header
+    }
+
+    /// Returns all records overlapping the region
+    pub fn query<R: Region>(&self, region: &R) -> Result<Vec<VcfRecord>, String> {
+        let lines = self.file.query(region.template(), region.offset(), region.length())?;
+        lines.iter().map(|l| VcfRecord::from_str(l)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::vcf::IndexedVcfFile;
+    use model::SimpleRegion;
+
+    #[test]
+    fn test_query() {
+        let vcf = IndexedVcfFile::open(&"testdata/toy.vcf.gz").unwrap();
+        assert_eq!(vcf.header().samples().len(), 2);
+
+        let records = vcf.query(&SimpleRegion::new("ref", 0, 20)).unwrap();
+        assert_eq!(records.iter().map(|r| r.position()).collect::<Vec<usize>>(), vec![5, 12, 20]);
+
+        let records = vcf.query(&SimpleRegion::new("ref2", 30, 5)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].alternative_alleles(), vec!["<DEL>".to_string()]);
+
+        assert!(IndexedVcfFile::open(&"testdata/toy.vcf").is_err(), "No tabix index");
+    }
+}
@@ -0,0 +1,13 @@
+mod header;
+mod record;
+mod stream;
+mod writer;
+mod index;
+mod normalize;
+
+pub use self::header::{VcfContig, VcfField, VcfHeader};
+pub use self::record::{Genotype, VcfRecord};
+pub use self::stream::VcfStream;
+pub use self::writer::VcfWriter;
+pub use self::index::IndexedVcfFile;
+pub use self::normalize::VcfNormalizer;
@@ -0,0 +1,281 @@
+use io::fasta::FastaReader;
+use io::vcf::{Genotype, VcfHeader, VcfRecord};
+use std::collections::HashMap;
+
+/// The number of template bases before a record that are read at once for
+/// left-aligning it. Longer shifts read more bases.
+const WINDOW_SIZE: usize = 100;
+
+/// Normalizes VCF records against the reference sequence as described by
+/// Tan et al. (2015), "Unified representation of genetic variants":
+///
+/// * the reference allele is checked against the template sequence,
+/// * indels are shifted to the leftmost position of a repeat,
+/// * the alleles are trimmed to the shortest representation keeping a
+///   single anchor base for indels (the base after the event for indels at
+///   the first template position), and
+/// * multi-allelic records are optionally split into one record per
+///   alternative allele.
+///
+/// Records with symbolic alleles are not changed.
+pub struct VcfNormalizer<F: FastaReader> {
+    fasta: F,
+    info_numbers: HashMap<String, String>,
+    format_numbers: HashMap<String, String>,
+    split_multiallelic: bool,
+}
+
+impl<F: FastaReader> VcfNormalizer<F> {
+    /// Creates a normalizer for the records of a VCF file with the given header.
+    /// The header defines how INFO and FORMAT values are split.
+    pub fn new(fasta: F, header: &VcfHeader) -> Self {
+        VcfNormalizer {
+            fasta: fasta,
+            info_numbers: header.infos().into_iter().map(|f| (f.id(), f.number())).collect(),
+            format_numbers: header.formats().into_iter().map(|f| (f.id(), f.number())).collect(),
+            split_multiallelic: false,
+        }
+    }
+
+    /// Enables or disables splitting multi-allelic records
+    pub fn with_multiallelic_splitting(mut self, split: bool) -> Self {
+        self.split_multiallelic = split;
+        self
+    }
+
+    /// Returns the normalized records of a record. Fails if the reference allele
+    /// does not match the template sequence.
+    pub fn normalize(&mut self, record: &VcfRecord) -> Result<Vec<VcfRecord>, String> {
+        let records = match self.split_multiallelic && record.is_multiallelic() {
+            true => self.split(record),
+            false => vec![record.clone()],
+        };
+        let mut normalized = Vec::with_capacity(records.len());
+        for r in records.into_iter() {
+            normalized.push(self.normalize_record(r)?);
+        }
+        Ok(normalized)
+    }
+
+    fn normalize_record(&mut self, record: VcfRecord) -> Result<VcfRecord, String> {
+        let alleles: Vec<String> = record.alleles().iter().map(|a| a.to_uppercase()).collect();
+        if alleles.iter().any(|a| VcfRecord::is_symbolic_allele(a)) {
+            return Ok(record);
+        }
+
+        let template = record.chrom();
+        let offset = record.position() - 1;
+        let mut window = WINDOW_SIZE;
+        loop {
+            // The template bases before the record, of the reference allele and
+            // of the base after it
+            let start = offset.saturating_sub(window);
+            let length = offset - start + alleles[0].len() + 1;
+            let sequence: Vec<char> = match self.fasta.search_region_as_sequence(template.as_str(), start, length) {
+                Some(s) => s.to_uppercase().chars().collect(),
+                None => return Err(format!("Can not find template '{}' in FASTA file", template)),
+            };
+            let found: String = sequence.iter().skip(offset - start).take(alleles[0].len()).collect();
+            if found != alleles[0] {
+                return Err(format!(
+                    "The reference allele '{}' at {}:{} does not match the reference sequence '{}'",
+                    record.reference_allele(),
+                    template,
+                    record.position(),
+                    found
+                ));
+            }
+
+            if let Some((offset, alleles)) = VcfNormalizer::<F>::left_align(&sequence, start, offset, &alleles) {
+                let mut alternatives = alleles;
+                let reference = alternatives.remove(0);
+                return Ok(record
+                    .with_position(offset + 1)
+                    .with_reference_allele(reference)
+                    .with_alternative_alleles(alternatives));
+            }
+            window *= 10;
+        }
+    }
+
+    /// Left-aligns and trims the alleles at the template offset given the template
+    /// sequence starting at `start`. Returns `None` if the alleles are shifted
+    /// beyond the start of the sequence.
+    fn left_align(sequence: &[char], start: usize, offset: usize, alleles: &[String]) -> Option<(usize, Vec<String>)> {
+        let mut alleles: Vec<Vec<char>> = alleles.iter().map(|a| a.chars().collect()).collect();
+        if alleles.iter().all(|a| *a == alleles[0]) {
+            return Some((offset, alleles.into_iter().map(|a| a.into_iter().collect()).collect()));
+        }
+
+        let mut offset = offset;
+        loop {
+            let mut changed = false;
+            let last = alleles[0].last().cloned();
+            if alleles.iter().all(|a| !a.is_empty() && a.last().cloned() == last) {
+                for a in alleles.iter_mut() {
+                    a.pop();
+                }
+                changed = true;
+            }
+            if alleles.iter().any(|a| a.is_empty()) {
+                if offset == 0 {
+                    // Indels at the template start are anchored to the base after them
+                    let anchor = sequence.get(alleles[0].len()).cloned().unwrap_or('N');
+                    for a in alleles.iter_mut() {
+                        a.push(anchor);
+                    }
+                    break;
+                }
+                if offset == start {
+                    return None;
+                }
+                offset -= 1;
+                for a in alleles.iter_mut() {
+                    a.insert(0, sequence[offset - start]);
+                }
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // Removes the common leading bases but keeps at least one base per allele
+        while alleles.iter().all(|a| a.len() > 1 && a[0] == alleles[0][0]) {
+            for a in alleles.iter_mut() {
+                a.remove(0);
+            }
+            offset += 1;
+        }
+        Some((offset, alleles.into_iter().map(|a| a.into_iter().collect()).collect()))
+    }
+
+    /// Splits a multi-allelic record into one record per alternative allele.
+    /// INFO and FORMAT values with one value per allele or genotype are split
+    /// according to the header, genotypes refer to the reference allele for the
+    /// other alternative alleles.
+    pub fn split(&self, record: &VcfRecord) -> Vec<VcfRecord> {
+        let alternatives = record.alternative_alleles();
+        let format = record.format();
+        (1..alternatives.len() + 1)
+            .map(|i| {
+                let mut split = record.clone().with_alternative_alleles(vec![alternatives[i - 1].clone()]);
+                for (key, value) in record.info_entries().into_iter() {
+                    if let (Some(number), Some(value)) = (self.info_numbers.get(&key), value) {
+                        split = split.with_info(key.as_str(), VcfNormalizer::<F>::split_values(&value, number, alternatives.len(), i));
+                    }
+                }
+                let samples: Vec<Vec<String>> = record
+                    .samples()
+                    .iter()
+                    .map(|values| {
+                        values
+                            .iter()
+                            .zip(format.iter())
+                            .map(|(value, key)| match (key.as_str(), self.format_numbers.get(key)) {
+                                ("GT", _) => VcfNormalizer::<F>::split_genotype(value, i),
+                                (_, Some(number)) => VcfNormalizer::<F>::split_values(value, number, alternatives.len(), i),
+                                (_, None) => value.clone(),
+                            })
+                            .collect()
+                    })
+                    .collect();
+                split.with_samples(samples)
+            })
+            .collect()
+    }
+
+    /// Returns the values for the `allele`th alternative allele of comma separated
+    /// values with the given VCF number. Values of unexpected length are kept.
+    fn split_values(value: &str, number: &str, alternatives: usize, allele: usize) -> String {
+        let values: Vec<&str> = value.split(',').collect();
+        // The indices of the genotypes 0/0, 0/i and i/i for diploid samples or 0
+        // and i for haploid samples
+        let diploid = (alternatives + 1) * (alternatives + 2) / 2;
+        let indices = match number {
+            "A" if values.len() == alternatives => vec![allele - 1],
+            "R" if values.len() == alternatives + 1 => vec![0, allele],
+            "G" if values.len() == diploid => vec![0, allele * (allele + 1) / 2, allele * (allele + 1) / 2 + allele],
+            "G" if values.len() == alternatives + 1 => vec![0, allele],
+            _ => return value.to_string(),
+        };
+        let split: Vec<&str> = indices.into_iter().map(|i| values[i]).collect();
+        split.join(",")
+    }
+
+    /// Returns the genotype for the `allele`th alternative allele
+    fn split_genotype(value: &str, allele: usize) -> String {
+        match value.parse::<Genotype>() {
+            Ok(g) => Genotype::new(
+                g.alleles()
+                    .into_iter()
+                    .map(|a| a.map(|a| if a == allele { 1 } else { 0 }))
+                    .collect(),
+                g.is_phased(),
+            ).to_string(),
+            Err(_) => value.to_string(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fasta::IndexedFastaFile;
+    use io::vcf::{VcfField, VcfHeader, VcfNormalizer, VcfRecord};
+    use std::str::FromStr;
+
+    fn normalizer(split: bool) -> VcfNormalizer<IndexedFastaFile> {
+        let header = VcfHeader::new()
+            .with_info(VcfField::new("AF", "A", "Float", "Allele frequency"))
+            .with_format(VcfField::new("GT", 1, "String", "Genotype"))
+            .with_format(VcfField::new("AD", "R", "Integer", "Allele depths"))
+            .with_format(VcfField::new("PL", "G", "Integer", "Genotype likelihoods"))
+            .with_sample("a");
+        let fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        VcfNormalizer::new(fasta, &header).with_multiallelic_splitting(split)
+    }
+
+    fn normalize(position: usize, reference: &str, alternatives: &[&str]) -> Result<(usize, String, String), String> {
+        let alternatives: Vec<String> = alternatives.iter().map(|a| a.to_string()).collect();
+        let record = VcfRecord::new("ref", position, reference, alternatives);
+        let normalized = normalizer(false).normalize(&record)?;
+        assert_eq!(normalized.len(), 1);
+        let r = &normalized[0];
+        Ok((r.position(), r.reference_allele(), r.alternative_alleles().join(",")))
+    }
+
+    #[test]
+    fn test_left_align() {
+        let n = |p: usize, r: &str, a: &str| (p, r.to_string(), a.to_string());
+        // The template starts with AGCATGTTAGATAAGATAGCTGTGCTAG
+        assert_eq!(normalize(14, "AG", &["G"]), Ok(n(12, "TA", "T")));
+        assert_eq!(normalize(22, "GTG", &["G"]), Ok(n(20, "CTG", "C")));
+        assert_eq!(normalize(22, "G", &["GTG"]), Ok(n(20, "C", "CTG")));
+        assert_eq!(normalize(21, "TGTGC", &["TGC"]), Ok(n(20, "CTG", "C")));
+        assert_eq!(normalize(17, "TAG", &["TCG"]), Ok(n(18, "A", "C")));
+        assert_eq!(normalize(13, "AAG", &["AG", "ACG"]), Ok(n(13, "AA", "A,AC")));
+        assert_eq!(normalize(5, "tg", &["tg"]), Ok(n(5, "TG", "TG")));
+        assert_eq!(normalize(1, "<DEL>", &["A"]), Ok(n(1, "<DEL>", "A")));
+        // Indels at the template start are anchored to the following base
+        assert_eq!(normalize(1, "AGC", &["GC"]), Ok(n(1, "AG", "G")));
+        assert_eq!(normalize(2, "G", &["AG"]), Ok(n(1, "A", "AA")));
+
+        assert!(normalize(1, "C", &["T"]).is_err());
+        assert!(normalize(45, "TA", &["T"]).is_err());
+        assert!(normalizer(false).normalize(&VcfRecord::new("chrX", 1, "A", vec!["C".to_string()])).is_err());
+    }
+
+    #[test]
+    fn test_split() {
+        let record = VcfRecord::from_str("ref\t13\trs1\tAAG\tAG,ACG\t50\tPASS\tAF=0.1,0.2;DB\tGT:AD:PL\t1/2:5,3,2:1,2,3,4,5,6").unwrap();
+        let normalized = normalizer(true).normalize(&record).unwrap();
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(normalized[0].to_string(), "ref\t12\trs1\tTA\tT\t50\tPASS\tAF=0.1;DB\tGT:AD:PL\t1/0:5,3:1,2,3");
+        assert_eq!(normalized[1].to_string(), "ref\t14\trs1\tA\tC\t50\tPASS\tAF=0.2;DB\tGT:AD:PL\t0/1:5,2:1,4,6");
+
+        let normalized = normalizer(false).normalize(&record).unwrap();
+        assert_eq!(normalized.len(), 1);
+        assert_eq!(normalized[0].alternative_alleles(), vec!["A".to_string(), "AC".to_string()]);
+    }
+}
@@ -0,0 +1,496 @@
+use model::{GenomicVariant, Region, Variant};
+use sequence::dna::{DnaNucleotide, DnaSequence};
+use std::fmt;
+use std::str::FromStr;
+
+/// The genotype of a sample given as allele indices (0 is the reference allele).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Genotype {
+    alleles: Vec<Option<usize>>,
+    phased: bool,
+}
+
+impl Genotype {
+    pub fn new(alleles: Vec<Option<usize>>, phased: bool) -> Genotype {
+        Genotype {
+            alleles: alleles,
+            phased: phased,
+        }
+    }
+
+    /// Returns the allele indices where `None` denotes a missing call
+    pub fn alleles(&self) -> Vec<Option<usize>> {
+        self.alleles.clone()
+    }
+
+    pub fn is_phased(&self) -> bool {
+        self.phased
+    }
+
+    /// Returns `true` if no allele is called
+    pub fn is_missing(&self) -> bool {
+        self.alleles.iter().all(|a| a.is_none())
+    }
+
+    /// Returns `true` if all called alleles are identical
+    pub fn is_homozygous(&self) -> bool {
+        let called: Vec<usize> = self.alleles.iter().filter_map(|a| *a).collect();
+        !called.is_empty() && called.iter().all(|a| *a == called[0])
+    }
+
+    pub fn is_heterozygous(&self) -> bool {
+        let called: Vec<usize> = self.alleles.iter().filter_map(|a| *a).collect();
+        called.iter().any(|a| *a != called[0])
+    }
+}
+
+impl FromStr for Genotype {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Genotype, Self::Err> {
+        let phased = s.contains('|');
+        let mut alleles = Vec::new();
+        for a in s.split(|c| c == '/' || c == '|') {
+            match a {
+                "." => alleles.push(None),
+                _ => match a.parse::<usize>() {
+                    Ok(i) => alleles.push(Some(i)),
+                    Err(e) => return Err(format!("Can not parse allele '{}' of genotype '{}': {}", a, s, e)),
+                },
+            }
+        }
+        Ok(Genotype::new(alleles, phased))
+    }
+}
+
+impl fmt::Display for Genotype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sep = if self.phased { "|" } else { "/" };
+        let alleles: Vec<String> = self.alleles
+            .iter()
+            .map(|a| match *a {
+                Some(i) => i.to_string(),
+                None => ".".to_string(),
+            })
+            .collect();
+        write!(f, "{}", alleles.join(sep))
+    }
+}
+
+
+/// A data line of a VCF file as defined by https://samtools.github.io/hts-specs/VCFv4.2.pdf
+#[derive(Clone, Debug, PartialEq)]
+pub struct VcfRecord {
+    chrom: String,
+    position: usize,
+    ids: Vec<String>,
+    reference: String,
+    alternatives: Vec<String>,
+    quality: Option<f64>,
+    filters: Vec<String>,
+    info: Vec<(String, Option<String>)>,
+    format: Vec<String>,
+    samples: Vec<Vec<String>>,
+}
+
+impl VcfRecord {
+    /// Create a new record at the given 1-based position.
+    pub fn new<C: ToString, R: ToString>(chrom: C, position: usize, reference: R, alternatives: Vec<String>) -> VcfRecord {
+        assert!(position > 0, "VCF positions are 1-based");
+        VcfRecord {
+            chrom: chrom.to_string(),
+            position: position,
+            ids: Vec::new(),
+            reference: reference.to_string(),
+            alternatives: alternatives,
+            quality: None,
+            filters: Vec::new(),
+            info: Vec::new(),
+            format: Vec::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn chrom(&self) -> String {
+        self.chrom.clone()
+    }
+
+    /// Returns the 1-based position of the first reference base
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn with_position(mut self, new_position: usize) -> Self {
+        assert!(new_position > 0, "VCF positions are 1-based");
+        self.position = new_position;
+        self
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.ids.clone()
+    }
+
+    pub fn with_id<S: ToString>(mut self, id: S) -> Self {
+        self.ids.push(id.to_string());
+        self
+    }
+
+    /// Returns the reference allele
+    pub fn reference_allele(&self) -> String {
+        self.reference.clone()
+    }
+
+    pub fn with_reference_allele<S: ToString>(mut self, new_reference: S) -> Self {
+        self.reference = new_reference.to_string();
+        self
+    }
+
+    /// Returns the alternative alleles
+    pub fn alternative_alleles(&self) -> Vec<String> {
+        self.alternatives.clone()
+    }
+
+    pub fn with_alternative_alleles(mut self, new_alternatives: Vec<String>) -> Self {
+        self.alternatives = new_alternatives;
+        self
+    }
+
+    /// Returns all alleles, i.e., the reference allele followed by the alternatives.
+    pub fn alleles(&self) -> Vec<String> {
+        let mut alleles = vec![self.reference.clone()];
+        alleles.extend(self.alternatives.iter().cloned());
+        alleles
+    }
+
+    pub fn is_multiallelic(&self) -> bool {
+        self.alternatives.len() > 1
+    }
+
+    pub fn quality(&self) -> Option<f64> {
+        self.quality
+    }
+
+    pub fn with_quality(mut self, new_quality: f64) -> Self {
+        self.quality = Some(new_quality);
+        self
+    }
+
+    /// Returns the filters. An empty list denotes that no filters were applied.
+    pub fn filters(&self) -> Vec<String> {
+        self.filters.clone()
+    }
+
+    pub fn with_filter<S: ToString>(mut self, filter: S) -> Self {
+        self.filters.push(filter.to_string());
+        self
+    }
+
+    pub fn is_pass(&self) -> bool {
+        self.filters.len() == 1 && self.filters[0] == "PASS"
+    }
+
+    /// Returns the INFO entries in their original order. Flags have no value.
+    pub fn info_entries(&self) -> Vec<(String, Option<String>)> {
+        self.info.clone()
+    }
+
+    pub fn has_info<S: ToString>(&self, key: S) -> bool {
+        let key = key.to_string();
+        self.info.iter().any(|i| i.0 == key)
+    }
+
+    /// Returns the value of an INFO entry. Flags return `None`, use `has_info()` instead.
+    pub fn info<S: ToString>(&self, key: S) -> Option<String> {
+        let key = key.to_string();
+        self.info.iter().find(|i| i.0 == key).and_then(|i| i.1.clone())
+    }
+
+    /// Returns the comma separated values of an INFO entry, e.g., one per alternative allele.
+    pub fn info_values<S: ToString>(&self, key: S) -> Vec<String> {
+        match self.info(key) {
+            Some(v) => v.split(',').map(|s| s.to_string()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Sets an INFO entry, replacing an existing entry with the same key.
+    pub fn with_info<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.set_info(key.to_string(), Some(value.to_string()));
+        self
+    }
+
+    pub fn with_flag<K: ToString>(mut self, key: K) -> Self {
+        self.set_info(key.to_string(), None);
+        self
+    }
+
+    fn set_info(&mut self, key: String, value: Option<String>) {
+        match self.info.iter().position(|i| i.0 == key) {
+            Some(pos) => self.info[pos].1 = value,
+            None => self.info.push((key, value)),
+        }
+    }
+
+    /// Returns the keys of the per-sample values
+    pub fn format(&self) -> Vec<String> {
+        self.format.clone()
+    }
+
+    pub fn with_format(mut self, keys: Vec<String>) -> Self {
+        self.format = keys;
+        self
+    }
+
+    /// Appends the values of a sample in the order given by `format()`.
+    pub fn with_sample(mut self, values: Vec<String>) -> Self {
+        self.samples.push(values);
+        self
+    }
+
+    /// Returns the values of all samples in the order given by `format()`
+    pub fn samples(&self) -> Vec<Vec<String>> {
+        self.samples.clone()
+    }
+
+    /// Replaces the values of all samples
+    pub fn with_samples(mut self, samples: Vec<Vec<String>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns the value of a sample for the given FORMAT key. Trailing values may be
+    /// omitted in VCF and are treated as missing.
+    pub fn sample_value<S: ToString>(&self, sample: usize, key: S) -> Option<String> {
+        let key = key.to_string();
+        match self.format.iter().position(|k| *k == key) {
+            Some(pos) => self.samples
+                .get(sample)
+                .and_then(|values| values.get(pos))
+                .filter(|v| *v != ".")
+                .cloned(),
+            None => None,
+        }
+    }
+
+    /// Returns the genotype (`GT`) of the sample
+    pub fn genotype(&self, sample: usize) -> Option<Genotype> {
+        match self.sample_value(sample, "GT") {
+            Some(gt) => match Genotype::from_str(&gt) {
+                Ok(g) => Some(g),
+                Err(e) => {
+                    warn!("{}", e);
+                    None
+                }
+            },
+            None => None,
+        }
+    }
+
+    /// Returns `true` if the allele is symbolic (e.g., `<DEL>`), a breakend,
+    /// the overlapping deletion `*` or missing.
+    pub fn is_symbolic_allele(allele: &str) -> bool {
+        allele.is_empty() || allele == "." || allele == "*" || allele.contains(|c| c == '<' || c == '[' || c == ']')
+    }
+}
+
+impl FromStr for VcfRecord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<VcfRecord, Self::Err> {
+        let cells: Vec<&str> = s.trim_end_matches(|c| c == '\n' || c == '\r').split('\t').collect();
+        if cells.len() < 8 {
+            return Err(format!("Expecting at least 8 columns but found {}", cells.len()));
+        }
+
+        let position = match cells[1].parse::<usize>() {
+            Ok(p) if p > 0 => p,
+            Ok(_) => return Err("Position must be larger than zero".to_string()),
+            Err(e) => return Err(format!("Can not parse position '{}': {}", cells[1], e)),
+        };
+        let list = |cell: &str, sep: char| -> Vec<String> {
+            match cell {
+                "." | "" => Vec::new(),
+                _ => cell.split(sep).map(|s| s.to_string()).collect(),
+            }
+        };
+        let quality = match cells[5] {
+            "." => None,
+            q => match q.parse::<f64>() {
+                Ok(v) => Some(v),
+                Err(e) => return Err(format!("Can not parse quality '{}': {}", q, e)),
+            },
+        };
+        let info = list(cells[7], ';')
+            .into_iter()
+            .map(|kv| match kv.find('=') {
+                Some(pos) => (kv[..pos].to_string(), Some(kv[pos + 1..].to_string())),
+                None => (kv, None),
+            })
+            .collect();
+
+        let format = match cells.len() > 8 {
+            true => list(cells[8], ':'),
+            false => Vec::new(),
+        };
+        let samples = cells
+            .iter()
+            .skip(9)
+            .map(|s| s.split(':').map(|v| v.to_string()).collect())
+            .collect();
+
+        Ok(VcfRecord {
+            chrom: cells[0].to_string(),
+            position: position,
+            ids: list(cells[2], ';'),
+            reference: cells[3].to_string(),
+            alternatives: list(cells[4], ','),
+            quality: quality,
+            filters: list(cells[6], ';'),
+            info: info,
+            format: format,
+            samples: samples,
+        })
+    }
+}
+
+impl fmt::Display for VcfRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |values: &Vec<String>, sep: &str| match values.is_empty() {
+            true => ".".to_string(),
+            false => values.join(sep),
+        };
+        let info: Vec<String> = self.info
+            .iter()
+            .map(|&(ref k, ref v)| match *v {
+                Some(ref v) => format!("{}={}", k, v),
+                None => k.clone(),
+            })
+            .collect();
+
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.chrom,
+            self.position,
+            list(&self.ids, ";"),
+            self.reference,
+            list(&self.alternatives, ","),
+            match self.quality {
+                Some(q) => q.to_string(),
+                None => ".".to_string(),
+            },
+            list(&self.filters, ";"),
+            list(&info, ";")
+        )?;
+
+        if !self.format.is_empty() {
+            write!(f, "\t{}", self.format.join(":"))?;
+            for s in &self.samples {
+                write!(f, "\t{}", s.join(":"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The region covered by the reference allele
+impl Region for VcfRecord {
+    fn template(&self) -> String {
+        self.chrom.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.position - 1
+    }
+
+    fn length(&self) -> usize {
+        self.reference.len()
+    }
+}
+
+/// A VCF record is a variant of its first alternative allele. Symbolic alleles
+/// can not be expressed as sequence and the variant then equals the reference.
+impl Variant<DnaNucleotide> for VcfRecord {
+    type SequenceType = DnaSequence;
+
+    fn template(&self) -> String {
+        self.chrom.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.position - 1
+    }
+
+    fn reference(&self) -> DnaSequence {
+        self.reference.chars().map(|c| DnaNucleotide::from(c)).collect()
+    }
+
+    fn alternative(&self) -> DnaSequence {
+        match self.alternatives.first() {
+            Some(a) if !VcfRecord::is_symbolic_allele(a) => a.chars().map(|c| DnaNucleotide::from(c)).collect(),
+            _ => self.reference(),
+        }
+    }
+}
+
+impl GenomicVariant for VcfRecord {}
+
+
+#[cfg(test)]
+mod tests {
+    use io::vcf::{Genotype, VcfRecord};
+    use model::{Region, Variant};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_record() {
+        let line = "ref\t33\trs2;rs3\tC\tA,G\t60\tPASS\tDP=30;AF=0.3,0.2;DB\tGT:DP\t1/2:15\t0|1";
+        let r = VcfRecord::from_str(line).unwrap();
+        assert_eq!(r.chrom(), "ref");
+        assert_eq!(r.position(), 33);
+        assert_eq!(r.ids(), vec!["rs2".to_string(), "rs3".to_string()]);
+        assert_eq!(r.alleles(), vec!["C".to_string(), "A".to_string(), "G".to_string()]);
+        assert!(r.is_multiallelic());
+        assert_eq!(r.quality(), Some(60f64));
+        assert!(r.is_pass());
+        assert_eq!(r.info("DP"), Some("30".to_string()));
+        assert_eq!(r.info_values("AF"), vec!["0.3".to_string(), "0.2".to_string()]);
+        assert!(r.has_info("DB"));
+        assert_eq!(r.info("DB"), None);
+        assert_eq!(r.sample_value(0, "DP"), Some("15".to_string()));
+        assert_eq!(r.sample_value(1, "DP"), None, "Omitted trailing value");
+        assert_eq!(r.genotype(0), Some(Genotype::new(vec![Some(1), Some(2)], false)));
+        assert!(r.genotype(1).unwrap().is_phased());
+        assert_eq!(r.to_string(), line);
+
+        assert!(VcfRecord::from_str("ref\t0\t.\tA\tC\t.\t.\t.").is_err());
+        assert!(VcfRecord::from_str("ref\t1\t.\tA\tC\t.\t.").is_err());
+    }
+
+    #[test]
+    fn test_genotype() {
+        let gt = Genotype::from_str("0/1").unwrap();
+        assert!(gt.is_heterozygous());
+        assert!(!gt.is_homozygous());
+        assert!(Genotype::from_str("1|1").unwrap().is_homozygous());
+        assert!(Genotype::from_str("./.").unwrap().is_missing());
+        assert_eq!(Genotype::from_str("./1").unwrap().to_string(), "./1");
+        assert!(Genotype::from_str("0/x").is_err());
+    }
+
+    #[test]
+    fn test_variant() {
+        let r = VcfRecord::new("ref", 20, "CTG", vec!["C".to_string()]).with_quality(9.5);
+        assert_eq!(r.to_string(), "ref\t20\t.\tCTG\tC\t9.5\t.\t.");
+        assert_eq!(Region::offset(&r), 19);
+        assert_eq!(r.length(), 3);
+        assert_eq!(r.hgvs(), "21_22delTG");
+
+        let sv = VcfRecord::new("ref", 30, "G", vec!["<DEL>".to_string()]);
+        assert!(sv.variant_type().is_none());
+    }
+}
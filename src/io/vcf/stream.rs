@@ -0,0 +1,141 @@
+use io::compress::DecompressReader;
+use io::vcf::{VcfHeader, VcfRecord};
+use model::IntervalTree;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A stream reader for (possibly compressed) VCF files. The header is parsed
+/// when the stream is created and the records are read on demand.
+pub struct VcfStream<R: Read> {
+    reader: BufReader<DecompressReader<R>>,
+    header: VcfHeader,
+    line: usize,
+}
+
+impl<R: Read> VcfStream<R> {
+    pub fn new(r: R) -> Result<VcfStream<R>, String> {
+        VcfStream::from_decompressed(DecompressReader::new(r))
+    }
+
+    fn from_decompressed(r: DecompressReader<R>) -> Result<VcfStream<R>, String> {
+        let mut reader = BufReader::new(r);
+        let mut lines = Vec::new();
+
+        // Read all lines up to and including the '#CHROM' line
+        loop {
+            let is_header = match reader.fill_buf() {
+                Ok(buf) => buf.first() == Some(&b'#'),
+                Err(e) => return Err(format!("Can not read VCF header: {}", e)),
+            };
+            if !is_header {
+                break;
+            }
+            let mut line = String::new();
+            if let Err(e) = reader.read_line(&mut line) {
+                return Err(format!("Can not read VCF header: {}", e));
+            }
+            lines.push(line);
+        }
+
+        let header = VcfHeader::parse(&lines)?;
+        Ok(VcfStream {
+            reader: reader,
+            header: header,
+            line: lines.len(),
+        })
+    }
+
+    pub fn header(&self) -> &VcfHeader {
+        &self.header
+    }
+
+    /// Reads all remaining records into an interval tree for repeated region queries
+    pub fn read_index(&mut self) -> Result<IntervalTree<VcfRecord>, String> {
+        self.collect::<Result<Vec<VcfRecord>, String>>().map(IntervalTree::from)
+    }
+}
+
+impl VcfStream<File> {
+    pub fn open<P: AsRef<Path>>(filename: &P) -> Result<VcfStream<File>, String> {
+        match DecompressReader::open(filename) {
+            Ok(r) => VcfStream::from_decompressed(r),
+            Err(e) => Err(format!("Can not open VCF file '{}': {}", filename.as_ref().display(), e)),
+        }
+    }
+}
+
+impl<R: Read> Iterator for VcfStream<R> {
+    type Item = Result<VcfRecord, String>;
+
+    fn next(&mut self) -> Option<Result<VcfRecord, String>> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(format!("Can not read line {}: {}", self.line + 1, e))),
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record = VcfRecord::from_str(&line).and_then(|r| {
+                match r.num_samples() == self.header.samples().len() || r.format().is_empty() {
+                    true => Ok(r),
+                    false => Err(format!(
+                        "Expecting {} samples but found {}",
+                        self.header.samples().len(),
+                        r.num_samples()
+                    )),
+                }
+            });
+            return Some(record.map_err(|e| format!("Line {}: {}", self.line, e)));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::vcf::{VcfRecord, VcfStream};
+    use model::{RegionIndex, SimpleRegion};
+
+    #[test]
+    fn test_read_file() {
+        for filename in &["testdata/toy.vcf", "testdata/toy.vcf.gz"] {
+            let stream = VcfStream::open(filename).unwrap();
+            assert_eq!(stream.header().samples(), vec!["sample1".to_string(), "sample2".to_string()]);
+            assert_eq!(stream.header().contigs().len(), 2);
+            assert_eq!(stream.header().infos().len(), 5);
+            assert_eq!(stream.header().formats().len(), 3);
+            assert_eq!(stream.header().filters().len(), 1);
+
+            let records: Vec<VcfRecord> = stream.map(|r| r.unwrap()).collect();
+            assert_eq!(records.len(), 6);
+            assert_eq!(records[3].alternative_alleles(), vec!["A".to_string(), "G".to_string()]);
+            assert_eq!(records[5].info("END"), Some("35".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_read_index() {
+        let index = VcfStream::open(&"testdata/toy.vcf.gz").unwrap().read_index().unwrap();
+        assert_eq!(index.len(), 6);
+        let found = index.overlapping(&SimpleRegion::new("ref", 10, 12));
+        assert_eq!(found.iter().map(|r| r.position()).collect::<Vec<usize>>(), vec![12, 20]);
+        assert_eq!(index.nearest(&SimpleRegion::new("ref2", 0, 1)).unwrap().position(), 10);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let missing_header = "ref\t1\t.\tA\tC\t.\t.\t.\n";
+        assert!(VcfStream::new(missing_header.as_bytes()).is_err());
+
+        let wrong_samples = "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\n\
+                             ref\t1\t.\tA\tC\t.\t.\t.\tGT\t0/1\t1/1\n";
+        let e = VcfStream::new(wrong_samples.as_bytes()).unwrap().next().unwrap().unwrap_err();
+        assert!(e.starts_with("Line 3:"), "{}", e);
+    }
+}
@@ -0,0 +1,55 @@
+use io::vcf::{VcfHeader, VcfRecord};
+use std::io::BufWriter;
+use std::io::Error;
+use std::io::Write;
+
+pub struct VcfWriter<W: Write> {
+    inner: BufWriter<W>,
+}
+
+impl<W: Write> VcfWriter<W> {
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
+    pub fn write_header(&mut self, header: &VcfHeader) -> Result<(), Error> {
+        write!(self.inner, "{}", header)
+    }
+
+    pub fn append(&mut self, record: &VcfRecord) -> Result<(), Error> {
+        write!(self.inner, "{}\n", record)
+    }
+}
+
+impl<W: Write> From<W> for VcfWriter<W> {
+    fn from(inner: W) -> VcfWriter<W> {
+        VcfWriter { inner: BufWriter::new(inner) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use io::vcf::{VcfStream, VcfWriter};
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut expected = String::new();
+        File::open("testdata/toy.vcf").unwrap().read_to_string(&mut expected).unwrap();
+
+        let stream = VcfStream::open(&"testdata/toy.vcf.gz").unwrap();
+        let mut writer = VcfWriter::from(Vec::new());
+        writer.write_header(&stream.header().clone()).unwrap();
+        for record in stream {
+            writer.append(&record.unwrap()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(String::from_utf8(writer.get_ref().clone()).unwrap(), expected);
+    }
+}
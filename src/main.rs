@@ -4,12 +4,17 @@ extern crate log;
 extern crate pretty_env_logger;
 
 extern crate clap;
+extern crate flate2;
 
 mod io;
 mod util;
 mod model;
 mod sequence;
-//mod alignment;
+mod alignment;
+mod pileup;
+mod coverage;
+mod caller;
+mod effect;
 mod sketch;
 mod tool;
 use tool::Tool;
@@ -33,11 +38,29 @@ fn main() {
     app = tool::Translate::subcommand("translate", app);
     app = tool::Sketch::subcommand("sketch", app);
     app = tool::FastaFormat::subcommand("fasta-format", app);
+    app = tool::Orfs::subcommand("orfs", app);
+    app = tool::Faidx::subcommand("faidx", app);
+    app = tool::ExtractTranscripts::subcommand("extract-transcripts", app);
+    app = tool::Bed::subcommand("bed", app);
+    app = tool::Pileup::subcommand("pileup", app);
+    app = tool::Coverage::subcommand("coverage", app);
+    app = tool::Call::subcommand("call", app);
+    app = tool::Norm::subcommand("norm", app);
+    app = tool::Annotate::subcommand("annotate", app);
 
     match app.get_matches().subcommand() {
         ("translate", Some(sub_m)) => tool::Translate::run(sub_m),
         ("sketch", Some(sub_m)) => tool::Sketch::run(sub_m),
         ("fasta-format", Some(sub_m)) => tool::FastaFormat::run(sub_m),
+        ("orfs", Some(sub_m)) => tool::Orfs::run(sub_m),
+        ("faidx", Some(sub_m)) => tool::Faidx::run(sub_m),
+        ("extract-transcripts", Some(sub_m)) => tool::ExtractTranscripts::run(sub_m),
+        ("bed", Some(sub_m)) => tool::Bed::run(sub_m),
+        ("pileup", Some(sub_m)) => tool::Pileup::run(sub_m),
+        ("coverage", Some(sub_m)) => tool::Coverage::run(sub_m),
+        ("call", Some(sub_m)) => tool::Call::run(sub_m),
+        ("norm", Some(sub_m)) => tool::Norm::run(sub_m),
+        ("annotate", Some(sub_m)) => tool::Annotate::run(sub_m),
         _ => {}
     }
 }
@@ -0,0 +1,140 @@
+use model::Region;
+use std::fmt;
+
+/// An interval in 0-based, half-open coordinates as used by BED files and by
+/// `Region`: `start` is the offset of the first position and `end` is the offset
+/// of the first position after the interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZeroBasedInterval {
+    start: usize,
+    end: usize,
+}
+
+impl ZeroBasedInterval {
+    pub fn new(start: usize, end: usize) -> Result<Self, String> {
+        match start <= end {
+            true => Ok(ZeroBasedInterval { start: start, end: end }),
+            false => Err(format!("Interval end {} before start {}", end, start)),
+        }
+    }
+
+    pub fn from_region<R: Region + ?Sized>(region: &R) -> Self {
+        ZeroBasedInterval {
+            start: region.offset(),
+            end: region.end(),
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn length(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+impl From<OneBasedInterval> for ZeroBasedInterval {
+    fn from(interval: OneBasedInterval) -> Self {
+        ZeroBasedInterval {
+            start: interval.start - 1,
+            end: interval.end,
+        }
+    }
+}
+
+impl fmt::Display for ZeroBasedInterval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+
+/// An interval in 1-based, closed coordinates as used by GTF and VCF files and
+/// by region strings like `chr1:100-200`: `start` and `end` are the positions of
+/// the first and the last element. An empty interval ends one position before
+/// its start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OneBasedInterval {
+    start: usize,
+    end: usize,
+}
+
+impl OneBasedInterval {
+    pub fn new(start: usize, end: usize) -> Result<Self, String> {
+        if start == 0 {
+            return Err(format!("Position 0 is not valid in 1-based coordinates"));
+        }
+        match start - 1 <= end {
+            true => Ok(OneBasedInterval { start: start, end: end }),
+            false => Err(format!("Interval end {} before start {}", end, start)),
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn length(&self) -> usize {
+        self.end - (self.start - 1)
+    }
+}
+
+impl From<ZeroBasedInterval> for OneBasedInterval {
+    fn from(interval: ZeroBasedInterval) -> Self {
+        OneBasedInterval {
+            start: interval.start + 1,
+            end: interval.end,
+        }
+    }
+}
+
+impl fmt::Display for OneBasedInterval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use model::coordinates::*;
+    use model::SimpleRegion;
+    use std::usize;
+
+    #[test]
+    fn test_conversion() {
+        let zero = ZeroBasedInterval::new(9, 20).unwrap();
+        let one = OneBasedInterval::from(zero);
+        assert_eq!((one.start(), one.end()), (10, 20));
+        assert_eq!(one.length(), zero.length());
+        assert_eq!(ZeroBasedInterval::from(one), zero);
+
+        let region = SimpleRegion::new("ref", 9, 11);
+        assert_eq!(ZeroBasedInterval::from_region(&region), zero);
+
+        // Regions up to the end of a template are unbounded
+        let one = OneBasedInterval::new(1, usize::MAX).unwrap();
+        assert_eq!(one.length(), usize::MAX);
+        assert_eq!(ZeroBasedInterval::from(one).length(), usize::MAX);
+    }
+
+    #[test]
+    fn test_empty_intervals() {
+        let one = OneBasedInterval::new(10, 9).unwrap();
+        assert_eq!(one.length(), 0);
+        assert_eq!(ZeroBasedInterval::from(one), ZeroBasedInterval::new(9, 9).unwrap());
+
+        assert!(OneBasedInterval::new(0, 10).is_err());
+        assert!(OneBasedInterval::new(10, 8).is_err());
+        assert!(ZeroBasedInterval::new(10, 9).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use io::gtf::{GtfFeature, GtfRecord};
+use model::{Region, Strand, Transcript};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A gene groups the transcripts that share the same gene identifier. The region of
+/// the gene spans all of its transcripts.
+#[derive(Clone, Debug)]
+pub struct Gene {
+    id: String,
+    name: Option<String>,
+    template: String,
+    strand: Strand,
+    transcripts: Vec<Transcript>,
+}
+
+impl Gene {
+    pub fn new<S: ToString, T: ToString>(id: S, template: T, strand: Strand) -> Self {
+        Gene {
+            id: id.to_string(),
+            name: None,
+            template: template.to_string(),
+            strand: strand,
+            transcripts: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn with_name<S: ToString>(mut self, name: S) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn strand(&self) -> Strand {
+        self.strand.clone()
+    }
+
+    pub fn transcripts(&self) -> &Vec<Transcript> {
+        &self.transcripts
+    }
+
+    /// Returns the transcript with the given identifier
+    pub fn transcript<S: ToString>(&self, id: S) -> Option<&Transcript> {
+        let id = id.to_string();
+        self.transcripts.iter().find(|t| t.id() == id)
+    }
+
+    pub fn with_transcript(mut self, transcript: Transcript) -> Result<Self, String> {
+        if transcript.template() != self.template || transcript.strand() != self.strand {
+            return Err(format!(
+                "Transcript '{}' is not located on the template and strand of gene '{}'",
+                transcript.id(),
+                self.id
+            ));
+        }
+        self.transcripts.push(transcript);
+        Ok(self)
+    }
+
+    /// Assembles the genes from GTF or GFF3 records. The transcripts are grouped by
+    /// their gene identifier; transcripts without gene form a gene on their own.
+    /// Genes are returned in the order of their first transcript.
+    pub fn from_gtf_records(records: &[GtfRecord]) -> Result<Vec<Gene>, String> {
+        let gene_records: BTreeMap<String, &GtfRecord> = records
+            .iter()
+            .filter(|r| r.parent_ids().is_empty())
+            .filter_map(|r| r.id().map(|id| (id, r)))
+            .collect();
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<(String, Vec<Transcript>)> = Vec::new();
+        for transcript in Transcript::from_gtf_records(records)? {
+            let gene_id = transcript.gene_id().unwrap_or(transcript.id());
+            match index.get(&gene_id) {
+                Some(&i) => groups[i].1.push(transcript),
+                None => {
+                    index.insert(gene_id.clone(), groups.len());
+                    groups.push((gene_id, vec![transcript]));
+                }
+            }
+        }
+
+        let mut genes: Vec<Gene> = Vec::with_capacity(groups.len());
+        for (gene_id, transcripts) in groups {
+            let mut gene = Gene::new(&gene_id, transcripts[0].template(), transcripts[0].strand());
+            if let Some(n) = gene_records.get(&gene_id).and_then(|r| gene_name(r)) {
+                gene = gene.with_name(n);
+            }
+            for transcript in transcripts {
+                gene = gene.with_transcript(transcript)?;
+            }
+            genes.push(gene);
+        }
+        Ok(genes)
+    }
+}
+
+fn gene_name(record: &GtfRecord) -> Option<String> {
+    match record.feature() {
+        Some(GtfFeature::Gene) | Some(GtfFeature::Other(_)) => record.annotation("gene_name").or(record.annotation("Name")),
+        _ => None,
+    }
+}
+
+impl Region for Gene {
+    fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.transcripts.iter().map(|t| t.offset()).min().unwrap_or(0)
+    }
+
+    fn length(&self) -> usize {
+        match self.transcripts.iter().map(|t| t.end()).max() {
+            Some(end) => end - self.offset(),
+            None => 0,
+        }
+    }
+}
+
+impl fmt::Display for Gene {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}:{}-{}({})", self.id, self.template(), self.offset() + 1, self.end(), self.strand)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::gtf::GtfStream;
+    use model::Region;
+    use model::gene::Gene;
+
+    #[test]
+    fn test_from_gtf_records() {
+        for filename in &["testdata/toy.gtf", "testdata/toy.gff3"] {
+            let records = GtfStream::open(filename).unwrap().read_records().unwrap();
+            let genes = Gene::from_gtf_records(&records).unwrap();
+            assert_eq!(genes.len(), 2);
+            assert_eq!(genes[0].name(), Some("GeneA".to_string()));
+            assert_eq!(genes[0].transcripts().len(), 1);
+
+            assert_eq!(genes[1].id(), "g2");
+            assert_eq!(genes[1].transcripts().len(), 2);
+            assert!(genes[1].transcript("t3").is_some());
+            assert_eq!((genes[1].offset(), genes[1].end()), (4, 38));
+        }
+    }
+}
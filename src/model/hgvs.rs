@@ -0,0 +1,1157 @@
+use io::fasta::FastaReader;
+use io::vcf::VcfRecord;
+use model::{GenomicVariant, Region, Strand, Transcript};
+use sequence::{Aminoacid, Aminoacid3, DnaNucleotide, DnaSequence, GeneticCode};
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of template bases read at once while shifting indels
+const SHIFT_WINDOW: usize = 100;
+
+/// A position in coding DNA coordinates (`c.`). Position 1 is the first base
+/// of the start codon, positions before it are negative (5' UTR) and positions
+/// after the stop codon are counted from its last base (`c.*1`). Intronic
+/// positions have an offset to the nearest exon base, e.g., `c.123+5`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CodingPosition {
+    position: isize,
+    three_prime: bool,
+    intron_offset: isize,
+}
+
+impl CodingPosition {
+    /// Creates a position in the coding sequence (`c.123`) or before it (`c.-14`)
+    pub fn new(position: isize) -> Self {
+        assert!(position != 0, "There is no coding position 0");
+        CodingPosition {
+            position: position,
+            three_prime: false,
+            intron_offset: 0,
+        }
+    }
+
+    /// Creates a position after the stop codon (`c.*37`)
+    pub fn three_prime(position: usize) -> Self {
+        assert!(position > 0, "There is no coding position *0");
+        CodingPosition {
+            position: position as isize,
+            three_prime: true,
+            intron_offset: 0,
+        }
+    }
+
+    /// Sets the distance to the exon base, positive after and negative before it
+    pub fn with_intron_offset(mut self, offset: isize) -> Self {
+        self.intron_offset = offset;
+        self
+    }
+
+    /// Returns the position relative to the start codon or, for positions after
+    /// the stop codon, relative to its last base
+    pub fn position(&self) -> isize {
+        self.position
+    }
+
+    /// Returns `true` for positions after the stop codon
+    pub fn is_three_prime(&self) -> bool {
+        self.three_prime
+    }
+
+    pub fn intron_offset(&self) -> isize {
+        self.intron_offset
+    }
+
+    /// Returns `true` if the position is an exonic base of the coding sequence
+    /// with the given length (including the stop codon)
+    pub fn is_coding(&self, cds_length: usize) -> bool {
+        !self.three_prime && self.intron_offset == 0 && self.position >= 1 && self.position <= cds_length as isize
+    }
+
+    /// Returns a key that orders positions in transcription direction
+    fn order(&self) -> (bool, isize, isize) {
+        (self.three_prime, self.position, self.intron_offset)
+    }
+
+    /// Returns the position of the template base on a protein coding transcript
+    pub fn from_template_offset(transcript: &Transcript, offset: usize) -> Result<Self, String> {
+        let (cds_start, cds_end) = coding_bounds(transcript)?;
+        let from_spliced = |s: isize| match s {
+            _ if s < cds_start => CodingPosition::new(s - cds_start),
+            _ if s <= cds_end => CodingPosition::new(s - cds_start + 1),
+            _ => CodingPosition::three_prime((s - cds_end) as usize),
+        };
+        if let Some(s) = transcript.spliced_position(offset) {
+            return Ok(from_spliced(s as isize));
+        }
+
+        // Positions before and after the transcript continue the numbering
+        let last = transcript.spliced_length() as isize - 1;
+        let (first_base, last_base) = (transcript.offset() as isize, transcript.end() as isize - 1);
+        let o = offset as isize;
+        match transcript.strand() {
+            Strand::Backward if o > last_base => return Ok(from_spliced(last_base - o)),
+            Strand::Backward if o < first_base => return Ok(from_spliced(last + first_base - o)),
+            Strand::Forward if o < first_base => return Ok(from_spliced(o - first_base)),
+            Strand::Forward if o > last_base => return Ok(from_spliced(last + o - last_base)),
+            _ => {}
+        }
+
+        // Intronic positions refer to the nearest exon base. The central base of
+        // an intron with odd length is described relative to the upstream exon.
+        let exons = transcript.exons();
+        for w in exons.windows(2) {
+            let (upstream, downstream) = match transcript.strand() {
+                Strand::Backward => (w[0].offset() as isize, w[1].end() as isize - 1),
+                _ => (w[0].end() as isize - 1, w[1].offset() as isize),
+            };
+            if cmp::min(upstream, downstream) < o && o < cmp::max(upstream, downstream) {
+                let (to_upstream, to_downstream) = ((o - upstream).abs(), (downstream - o).abs());
+                let spliced = |b: isize| transcript.spliced_position(b as usize).unwrap() as isize;
+                return Ok(match to_upstream <= to_downstream {
+                    true => from_spliced(spliced(upstream)).with_intron_offset(to_upstream),
+                    false => from_spliced(spliced(downstream)).with_intron_offset(-to_downstream),
+                });
+            }
+        }
+        Err(format!("Can not locate offset {} on transcript '{}'", offset, transcript.id()))
+    }
+
+    /// Returns the template offset of the position on a protein coding transcript.
+    /// This is the inverse of `from_template_offset()`.
+    pub fn template_offset(&self, transcript: &Transcript) -> Result<usize, String> {
+        let (cds_start, cds_end) = coding_bounds(transcript)?;
+        let spliced = match (self.three_prime, self.position < 0) {
+            (true, _) => cds_end + self.position,
+            (false, true) => cds_start + self.position,
+            (false, false) => cds_start + self.position - 1,
+        };
+        let length = transcript.spliced_length() as isize;
+        let (first_base, last_base) = (transcript.offset() as isize, transcript.end() as isize - 1);
+        let forward = transcript.strand() == Strand::Forward;
+        let base = match spliced {
+            s if s < 0 && forward => first_base + s,
+            s if s < 0 => last_base - s,
+            s if s >= length && forward => last_base + s - length + 1,
+            s if s >= length => first_base - (s - length + 1),
+            s => transcript.template_offset(s as usize).unwrap() as isize,
+        };
+        let offset = match forward {
+            true => base + self.intron_offset,
+            false => base - self.intron_offset,
+        };
+        if offset < 0 {
+            return Err(format!("Position c.{} is located before the template start", self));
+        }
+        if self.intron_offset != 0 && transcript.spliced_position(offset as usize).is_some() {
+            return Err(format!("Position c.{} is not located in an intron of '{}'", self, transcript.id()));
+        }
+        Ok(offset as usize)
+    }
+}
+
+impl fmt::Display for CodingPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.three_prime {
+            write!(f, "*")?;
+        }
+        write!(f, "{}", self.position)?;
+        match self.intron_offset {
+            0 => Ok(()),
+            o if o > 0 => write!(f, "+{}", o),
+            o => write!(f, "{}", o),
+        }
+    }
+}
+
+/// Returns the spliced positions of the first and the last coding base
+fn coding_bounds(transcript: &Transcript) -> Result<(isize, isize), String> {
+    let cds = transcript.cds();
+    let (first, last) = match (cds.first(), cds.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return Err(format!("Transcript '{}' is not protein coding", transcript.id())),
+    };
+    let (start, end) = match transcript.strand() {
+        Strand::Backward => (first.end() - 1, last.offset()),
+        _ => (first.offset(), last.end() - 1),
+    };
+    match (transcript.spliced_position(start), transcript.spliced_position(end)) {
+        (Some(s), Some(e)) => Ok((s as isize, e as isize)),
+        _ => Err(format!("The coding sequence of transcript '{}' is not located on its exons", transcript.id())),
+    }
+}
+
+
+/// The change of the nucleotides at a range of positions
+#[derive(Clone, Debug, PartialEq)]
+pub enum NucleotideEdit {
+    /// The reference base and the alternative base, e.g., `G>A`
+    Substitution(char, char),
+    Deletion,
+    Duplication,
+    /// The inserted sequence between the two positions, e.g., `insTTA`
+    Insertion(String),
+    /// The sequence replacing the positions, e.g., `delinsAT`
+    Delins(String),
+    Inversion,
+    /// No change, e.g., `g.123=`
+    Identity,
+}
+
+impl NucleotideEdit {
+    /// Returns the edit on the reverse strand
+    fn reverse_complement(&self) -> NucleotideEdit {
+        match *self {
+            NucleotideEdit::Substitution(r, a) => NucleotideEdit::Substitution(complement(r), complement(a)),
+            NucleotideEdit::Insertion(ref s) => NucleotideEdit::Insertion(reverse_complement(s)),
+            NucleotideEdit::Delins(ref s) => NucleotideEdit::Delins(reverse_complement(s)),
+            ref e => e.clone(),
+        }
+    }
+}
+
+impl fmt::Display for NucleotideEdit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NucleotideEdit::Substitution(r, a) => write!(f, "{}>{}", r, a),
+            NucleotideEdit::Deletion => write!(f, "del"),
+            NucleotideEdit::Duplication => write!(f, "dup"),
+            NucleotideEdit::Insertion(ref s) => write!(f, "ins{}", s),
+            NucleotideEdit::Delins(ref s) => write!(f, "delins{}", s),
+            NucleotideEdit::Inversion => write!(f, "inv"),
+            NucleotideEdit::Identity => write!(f, "="),
+        }
+    }
+}
+
+fn complement(c: char) -> char {
+    char::from(DnaNucleotide::from(c).complement())
+}
+
+fn reverse_complement(s: &str) -> String {
+    s.chars().rev().map(complement).collect()
+}
+
+
+/// An amino acid and its 1-based position in the protein
+pub type Residue = (Aminoacid3, usize);
+
+/// The change of a protein
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProteinEdit {
+    /// A missense or nonsense substitution, e.g., `Arg97Cys` or `Trp24Ter`
+    Substitution(Aminoacid3, usize, Aminoacid3),
+    /// A silent change, e.g., `Leu54=`
+    Synonymous(Aminoacid3, usize),
+    Deletion(Residue, Residue),
+    Duplication(Residue, Residue),
+    /// The inserted amino acids between two residues
+    Insertion(Residue, Residue, Vec<Aminoacid3>),
+    Delins(Residue, Residue, Vec<Aminoacid3>),
+    /// The first changed residue, the new amino acid at its position and the
+    /// position of the new stop codon counted from the first changed residue,
+    /// e.g., `Arg97ProfsTer23`. The short form `Arg97fs` has no new amino acid.
+    Frameshift(Aminoacid3, usize, Option<Aminoacid3>, Option<usize>),
+    /// The position of the lost stop codon, the new amino acid and the position
+    /// of the new stop codon counted from the lost one, e.g., `Ter110GlnextTer17`
+    Extension(usize, Aminoacid3, Option<usize>),
+    /// The start codon is lost with an unknown consequence (`Met1?`)
+    StartLoss,
+    /// No change of the protein (`=`)
+    NoChange,
+    /// An unknown change of the protein (`?`)
+    Unknown,
+}
+
+impl fmt::Display for ProteinEdit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let range = |start: &Residue, end: &Residue| match start == end {
+            true => format!("{}{}", start.0, start.1),
+            false => format!("{}{}_{}{}", start.0, start.1, end.0, end.1),
+        };
+        let sequence = |s: &Vec<Aminoacid3>| s.iter().map(|a| a.to_string()).collect::<Vec<String>>().concat();
+        let position = |p: Option<usize>| p.map(|p| p.to_string()).unwrap_or("?".to_string());
+        match *self {
+            ProteinEdit::Substitution(r, p, a) => write!(f, "{}{}{}", r, p, a),
+            ProteinEdit::Synonymous(r, p) => write!(f, "{}{}=", r, p),
+            ProteinEdit::Deletion(ref s, ref e) => write!(f, "{}del", range(s, e)),
+            ProteinEdit::Duplication(ref s, ref e) => write!(f, "{}dup", range(s, e)),
+            ProteinEdit::Insertion(ref s, ref e, ref i) => write!(f, "{}ins{}", range(s, e), sequence(i)),
+            ProteinEdit::Delins(ref s, ref e, ref i) => write!(f, "{}delins{}", range(s, e), sequence(i)),
+            ProteinEdit::Frameshift(r, p, Some(a), ter) => write!(f, "{}{}{}fsTer{}", r, p, a, position(ter)),
+            ProteinEdit::Frameshift(r, p, None, _) => write!(f, "{}{}fs", r, p),
+            ProteinEdit::Extension(p, a, ter) => write!(f, "Ter{}{}extTer{}", p, a, position(ter)),
+            ProteinEdit::StartLoss => write!(f, "Met1?"),
+            ProteinEdit::NoChange => write!(f, "="),
+            ProteinEdit::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+
+/// A sequence variant as described by the Human Genome Variation Society
+/// (https://varnomen.hgvs.org), e.g., `NC_000023.11:g.32389644G>A`,
+/// `NM_004006.2:c.123+5G>A`, or `NP_003997.1:p.(Arg97Cys)`. The accession is
+/// optional. Protein changes predicted from DNA variants are enclosed in
+/// parentheses.
+///
+/// Genomic variants of templates use the template name as accession and coding
+/// variants use the transcript id. Indels are shifted to the most 3' position
+/// in the direction of the transcript and insertions of a copy of the preceding
+/// sequence are described as duplications.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HgvsVariant {
+    Genomic {
+        accession: Option<String>,
+        start: usize,
+        end: usize,
+        edit: NucleotideEdit,
+    },
+    Coding {
+        accession: Option<String>,
+        start: CodingPosition,
+        end: CodingPosition,
+        edit: NucleotideEdit,
+    },
+    Protein {
+        accession: Option<String>,
+        edit: ProteinEdit,
+        predicted: bool,
+    },
+}
+
+impl HgvsVariant {
+    pub fn accession(&self) -> Option<String> {
+        match *self {
+            HgvsVariant::Genomic { ref accession, .. } |
+            HgvsVariant::Coding { ref accession, .. } |
+            HgvsVariant::Protein { ref accession, .. } => accession.clone(),
+        }
+    }
+
+    pub fn with_accession<S: ToString>(mut self, new_accession: S) -> Self {
+        match self {
+            HgvsVariant::Genomic { ref mut accession, .. } |
+            HgvsVariant::Coding { ref mut accession, .. } |
+            HgvsVariant::Protein { ref mut accession, .. } => *accession = Some(new_accession.to_string()),
+        }
+        self
+    }
+
+    /// Describes the variant on the genomic template
+    pub fn from_variant<V: GenomicVariant, F: FastaReader>(variant: &V, fasta: &mut F) -> Result<Self, String> {
+        let (start, end, edit) = genomic_edit(variant, fasta, true)?;
+        Ok(HgvsVariant::Genomic {
+            accession: Some(variant.template()),
+            start: start + 1,
+            end: end,
+            edit: edit,
+        })
+    }
+
+    /// Describes the variant on a protein coding transcript
+    pub fn from_variant_on_transcript<V: GenomicVariant, F: FastaReader>(
+        variant: &V,
+        transcript: &Transcript,
+        fasta: &mut F,
+    ) -> Result<Self, String> {
+        if variant.template() != transcript.template() {
+            return Err(format!("Variant and transcript '{}' are located on different templates", transcript.id()));
+        }
+        let forward = transcript.strand() == Strand::Forward;
+        let (start, end, edit) = genomic_edit(variant, fasta, forward)?;
+        let position = |o: usize| CodingPosition::from_template_offset(transcript, o);
+        let (start, end, edit) = match forward {
+            true => (position(start)?, position(end - 1)?, edit),
+            false => (position(end - 1)?, position(start)?, edit.reverse_complement()),
+        };
+        Ok(HgvsVariant::Coding {
+            accession: Some(transcript.id()),
+            start: start,
+            end: end,
+            edit: edit,
+        })
+    }
+
+    /// Predicts the change of the protein encoded by the transcript. Changes of
+    /// introns or of the start codon have an unknown consequence.
+    pub fn protein_consequence<V: GenomicVariant, F: FastaReader>(
+        variant: &V,
+        transcript: &Transcript,
+        fasta: &mut F,
+        code: &GeneticCode,
+    ) -> Result<Self, String> {
+        let predicted = |edit: ProteinEdit| {
+            Ok(HgvsVariant::Protein {
+                accession: None,
+                edit: edit,
+                predicted: true,
+            })
+        };
+        let (start, end, edit) = match HgvsVariant::from_variant_on_transcript(variant, transcript, fasta)? {
+            HgvsVariant::Coding { start, end, edit, .. } => (start, end, edit),
+            _ => unreachable!(),
+        };
+        if transcript.cds()[0].phase() != 0 {
+            return Err(format!("The coding sequence of transcript '{}' is incomplete", transcript.id()));
+        }
+        let cds_length = transcript.cds_length();
+        if start.intron_offset() != 0 || end.intron_offset() != 0 {
+            return predicted(ProteinEdit::Unknown);
+        }
+        let before = |p: &CodingPosition| !p.is_three_prime() && p.position() < 1;
+        let after = |p: &CodingPosition| p.is_three_prime() || p.position() > cds_length as isize;
+        let outside = match edit {
+            // Insertions next to the coding sequence do not change it
+            NucleotideEdit::Insertion(_) => !start.is_coding(cds_length) || !end.is_coding(cds_length),
+            _ => (before(&start) && before(&end)) || (after(&start) && after(&end)),
+        };
+        if outside {
+            return predicted(ProteinEdit::NoChange);
+        }
+        if !start.is_coding(cds_length) || !end.is_coding(cds_length) {
+            return predicted(ProteinEdit::Unknown);
+        }
+
+        // The coding sequence followed by the 3' UTR, such that lost stop codons
+        // and frameshifts can be translated beyond the original stop codon
+        let (cds_start, _) = coding_bounds(transcript)?;
+        let spliced = transcript.spliced_sequence(fasta)?.to_string();
+        let reference: Vec<char> = spliced.chars().skip(cds_start as usize).collect();
+        let (s, e) = (start.position() as usize - 1, end.position() as usize);
+        let mut alternative = reference.clone();
+        match edit {
+            NucleotideEdit::Substitution(_, a) => alternative[s] = a,
+            NucleotideEdit::Deletion => {
+                alternative.drain(s..e);
+            }
+            NucleotideEdit::Duplication => {
+                let copy: Vec<char> = reference[s..e].to_vec();
+                alternative.splice(e..e, copy);
+            }
+            NucleotideEdit::Insertion(ref i) => {
+                alternative.splice(s + 1..s + 1, i.chars());
+            }
+            NucleotideEdit::Delins(ref i) => {
+                alternative.splice(s..e, i.chars());
+            }
+            NucleotideEdit::Inversion => {
+                let inverted: Vec<char> = reverse_complement(&reference[s..e].iter().collect::<String>()).chars().collect();
+                alternative.splice(s..e, inverted);
+            }
+            NucleotideEdit::Identity => return predicted(ProteinEdit::NoChange),
+        }
+        let frameshift = (alternative.len() as isize - reference.len() as isize) % 3 != 0;
+        predicted(protein_edit(&translate(&reference, code)?, &translate(&alternative, code)?, s / 3, frameshift))
+    }
+
+    /// Returns the genomic description of a coding variant on the transcript.
+    /// Genomic variants are returned unchanged.
+    pub fn to_genomic(&self, transcript: &Transcript) -> Result<HgvsVariant, String> {
+        match *self {
+            HgvsVariant::Genomic { .. } => Ok(self.clone()),
+            HgvsVariant::Coding { ref start, ref end, ref edit, .. } => {
+                let (start, end) = (start.template_offset(transcript)?, end.template_offset(transcript)?);
+                let edit = match transcript.strand() {
+                    Strand::Backward => edit.reverse_complement(),
+                    _ => edit.clone(),
+                };
+                Ok(HgvsVariant::Genomic {
+                    accession: Some(transcript.template()),
+                    start: cmp::min(start, end) + 1,
+                    end: cmp::max(start, end) + 1,
+                    edit: edit,
+                })
+            }
+            HgvsVariant::Protein { .. } => Err("Can not map a protein variant to the genome".to_string()),
+        }
+    }
+
+    /// Describes a genomic variant on a protein coding transcript
+    pub fn to_coding<F: FastaReader>(&self, transcript: &Transcript, fasta: &mut F) -> Result<HgvsVariant, String> {
+        let record = self.to_vcf_record(fasta)?;
+        HgvsVariant::from_variant_on_transcript(&record, transcript, fasta)
+    }
+
+    /// Converts a genomic variant into a VCF record on the template given by the
+    /// accession. Indels are anchored to the base before them.
+    pub fn to_vcf_record<F: FastaReader>(&self, fasta: &mut F) -> Result<VcfRecord, String> {
+        let (accession, start, end, edit) = match *self {
+            HgvsVariant::Genomic { ref accession, start, end, ref edit } => match *accession {
+                Some(ref a) => (a.clone(), start, end, edit),
+                None => return Err(format!("Variant '{}' has no accession", self)),
+            },
+            _ => return Err(format!("Variant '{}' is not a genomic variant", self)),
+        };
+        let mut fetch = |offset: usize, length: usize| fetch(fasta, &accession, offset, length);
+        let affected = fetch(start - 1, end + 1 - start)?;
+        let record = |position: usize, reference: String, alternative: String| {
+            VcfRecord::new(accession.as_str(), position, reference, vec![alternative])
+        };
+        match *edit {
+            NucleotideEdit::Substitution(r, a) => {
+                if affected != r.to_string() {
+                    return Err(format!("The reference base of '{}' does not match the template base {}", self, affected));
+                }
+                Ok(record(start, affected, a.to_string()))
+            }
+            NucleotideEdit::Deletion if start > 1 => {
+                let anchor = fetch(start - 2, 1)?;
+                Ok(record(start - 1, anchor.clone() + &affected, anchor))
+            }
+            NucleotideEdit::Deletion => {
+                let anchor = fetch(end, 1)?;
+                Ok(record(start, affected + &anchor, anchor))
+            }
+            NucleotideEdit::Duplication => {
+                let anchor = fetch(end - 1, 1)?;
+                Ok(record(end, anchor.clone(), anchor + &affected))
+            }
+            NucleotideEdit::Insertion(ref i) => {
+                let anchor = fetch(start - 1, 1)?;
+                Ok(record(start, anchor.clone(), anchor + i))
+            }
+            NucleotideEdit::Delins(ref i) => Ok(record(start, affected, i.clone())),
+            NucleotideEdit::Inversion => {
+                let inverted = reverse_complement(&affected);
+                Ok(record(start, affected, inverted))
+            }
+            NucleotideEdit::Identity => Ok(VcfRecord::new(accession.as_str(), start, affected, Vec::new())),
+        }
+    }
+}
+
+impl fmt::Display for HgvsVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(accession) = self.accession() {
+            write!(f, "{}:", accession)?;
+        }
+        match *self {
+            HgvsVariant::Genomic { start, end, ref edit, .. } if start == end => write!(f, "g.{}{}", start, edit),
+            HgvsVariant::Genomic { start, end, ref edit, .. } => write!(f, "g.{}_{}{}", start, end, edit),
+            HgvsVariant::Coding { start, end, ref edit, .. } if start == end => write!(f, "c.{}{}", start, edit),
+            HgvsVariant::Coding { start, end, ref edit, .. } => write!(f, "c.{}_{}{}", start, end, edit),
+            HgvsVariant::Protein { edit: ProteinEdit::Unknown, .. } => write!(f, "p.?"),
+            HgvsVariant::Protein { ref edit, predicted: true, .. } => write!(f, "p.({})", edit),
+            HgvsVariant::Protein { ref edit, predicted: false, .. } => write!(f, "p.{}", edit),
+        }
+    }
+}
+
+impl FromStr for HgvsVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<HgvsVariant, Self::Err> {
+        let (accession, description) = match s.find(':') {
+            Some(i) => (Some(s[..i].to_string()), &s[i + 1..]),
+            None => (None, s),
+        };
+        let mut parser = Parser::new(description);
+        let variant = match (parser.next(), parser.next()) {
+            (Some('g'), Some('.')) => {
+                let start = parser.number()?;
+                let end = match parser.consume("_") {
+                    true => parser.number()?,
+                    false => start,
+                };
+                HgvsVariant::Genomic {
+                    accession: accession,
+                    start: start,
+                    end: end,
+                    edit: parser.nucleotide_edit()?,
+                }
+            }
+            (Some('c'), Some('.')) => {
+                let start = parser.coding_position()?;
+                let end = match parser.consume("_") {
+                    true => parser.coding_position()?,
+                    false => start,
+                };
+                HgvsVariant::Coding {
+                    accession: accession,
+                    start: start,
+                    end: end,
+                    edit: parser.nucleotide_edit()?,
+                }
+            }
+            (Some('p'), Some('.')) => {
+                let predicted = parser.consume("(");
+                let edit = parser.protein_edit()?;
+                if predicted && !parser.consume(")") {
+                    return Err(format!("Missing ')' in HGVS description '{}'", s));
+                }
+                HgvsVariant::Protein {
+                    accession: accession,
+                    edit: edit,
+                    predicted: predicted,
+                }
+            }
+            _ => return Err(format!("Expecting 'g.', 'c.' or 'p.' in HGVS description '{}'", s)),
+        };
+        if !parser.is_done() {
+            return Err(format!("Unexpected '{}' in HGVS description '{}'", parser.rest(), s));
+        }
+        match variant {
+            HgvsVariant::Genomic { start, end, ref edit, .. } if start == 0 || end < start || !edit_fits(edit, end - start + 1) => {
+                Err(format!("Invalid positions in HGVS description '{}'", s))
+            }
+            HgvsVariant::Coding { start, end, ref edit, .. } if end.order() < start.order() || !edit_fits(edit, if start == end { 1 } else { 2 }) => {
+                Err(format!("Invalid positions in HGVS description '{}'", s))
+            }
+            v => Ok(v),
+        }
+    }
+}
+
+/// Returns `true` if the edit can be applied to the given number of positions.
+/// Coding ranges can span introns, so their length is only checked to exceed one.
+fn edit_fits(edit: &NucleotideEdit, length: usize) -> bool {
+    match *edit {
+        NucleotideEdit::Substitution(_, _) => length == 1,
+        NucleotideEdit::Insertion(_) => length == 2,
+        _ => true,
+    }
+}
+
+
+/// A simple recursive descent parser for HGVS descriptions
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn new(s: &str) -> Self {
+        Parser {
+            chars: s.chars().collect(),
+            index: 0,
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.index).cloned();
+        self.index += 1;
+        c
+    }
+
+    fn is_done(&self) -> bool {
+        self.index >= self.chars.len()
+    }
+
+    fn rest(&self) -> String {
+        self.chars.iter().skip(self.index).collect()
+    }
+
+    /// Skips the prefix and returns `true` if the remaining input starts with it
+    fn consume(&mut self, prefix: &str) -> bool {
+        let n = prefix.chars().count();
+        let matches = self.chars.len() >= self.index + n && self.chars[self.index..self.index + n].iter().cloned().eq(prefix.chars());
+        if matches {
+            self.index += n;
+        }
+        matches
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let digits: String = self.chars[cmp::min(self.index, self.chars.len())..].iter().take_while(|c| c.is_ascii_digit()).collect();
+        self.index += digits.len();
+        digits.parse::<usize>().map_err(|_| format!("Expecting a number at '{}'", self.rest()))
+    }
+
+    fn coding_position(&mut self) -> Result<CodingPosition, String> {
+        let (three_prime, upstream) = match self.consume("*") {
+            true => (true, false),
+            false => (false, self.consume("-")),
+        };
+        let mut position = match (self.number()?, three_prime, upstream) {
+            (0, true, _) => return Err("There is no coding position *0".to_string()),
+            (0, false, true) => return Err("There is no coding position -0".to_string()),
+            (0, false, false) => return Err("There is no coding position 0".to_string()),
+            (n, true, _) => CodingPosition::three_prime(n),
+            (n, false, true) => CodingPosition::new(-(n as isize)),
+            (n, false, false) => CodingPosition::new(n as isize),
+        };
+        if self.consume("+") {
+            position = position.with_intron_offset(self.number()? as isize);
+        } else if self.consume("-") {
+            position = position.with_intron_offset(-(self.number()? as isize));
+        }
+        Ok(position)
+    }
+
+    /// Reads a (possibly empty) nucleotide sequence
+    fn nucleotides(&mut self) -> String {
+        let sequence: String = self.chars[cmp::min(self.index, self.chars.len())..].iter().take_while(|c| c.is_ascii_uppercase()).collect();
+        self.index += sequence.len();
+        sequence
+    }
+
+    fn nucleotide_edit(&mut self) -> Result<NucleotideEdit, String> {
+        if self.consume("delins") {
+            return match self.nucleotides() {
+                ref s if s.is_empty() => Err("Expecting the inserted sequence after 'delins'".to_string()),
+                s => Ok(NucleotideEdit::Delins(s)),
+            };
+        }
+        if self.consume("del") {
+            // The deleted sequence is optional and not kept
+            self.nucleotides();
+            return Ok(NucleotideEdit::Deletion);
+        }
+        if self.consume("dup") {
+            self.nucleotides();
+            return Ok(NucleotideEdit::Duplication);
+        }
+        if self.consume("ins") {
+            return match self.nucleotides() {
+                ref s if s.is_empty() => Err("Expecting the inserted sequence after 'ins'".to_string()),
+                s => Ok(NucleotideEdit::Insertion(s)),
+            };
+        }
+        if self.consume("inv") {
+            return Ok(NucleotideEdit::Inversion);
+        }
+        if self.consume("=") {
+            return Ok(NucleotideEdit::Identity);
+        }
+        match (self.next(), self.next(), self.next()) {
+            (Some(r), Some('>'), Some(a)) if r.is_ascii_uppercase() && a.is_ascii_uppercase() => Ok(NucleotideEdit::Substitution(r, a)),
+            _ => Err("Expecting a substitution, 'del', 'dup', 'ins', 'delins', 'inv' or '='".to_string()),
+        }
+    }
+
+    /// Reads an amino acid in three letter or one letter code
+    fn aminoacid(&mut self) -> Result<Aminoacid3, String> {
+        let three: String = self.chars.iter().skip(self.index).take(3).collect();
+        if let Ok(a) = three.parse::<Aminoacid3>() {
+            if three != "*" {
+                self.index += 3;
+                return Ok(a);
+            }
+        }
+        match self.chars.get(self.index).cloned() {
+            Some(c) if c == '*' || (c.is_ascii_uppercase() && Aminoacid::from(c) != Aminoacid::Unknown) || c == 'X' => {
+                self.index += 1;
+                Ok(Aminoacid3::from(Aminoacid::from(c)))
+            }
+            _ => Err(format!("Expecting an amino acid at '{}'", self.rest())),
+        }
+    }
+
+    fn aminoacids(&mut self) -> Result<Vec<Aminoacid3>, String> {
+        let mut sequence = vec![self.aminoacid()?];
+        while let Ok(a) = self.aminoacid() {
+            sequence.push(a);
+        }
+        Ok(sequence)
+    }
+
+    /// Reads the position of the new stop codon after `Ter` or `*`
+    fn stop_position(&mut self) -> Result<Option<usize>, String> {
+        if !self.consume("Ter") && !self.consume("*") {
+            return Err(format!("Expecting 'Ter' at '{}'", self.rest()));
+        }
+        match self.consume("?") {
+            true => Ok(None),
+            false => self.number().map(Some),
+        }
+    }
+
+    fn protein_edit(&mut self) -> Result<ProteinEdit, String> {
+        if self.consume("?") {
+            return Ok(ProteinEdit::Unknown);
+        }
+        if self.consume("=") {
+            return Ok(ProteinEdit::NoChange);
+        }
+        if self.consume("Met1?") || self.consume("M1?") {
+            return Ok(ProteinEdit::StartLoss);
+        }
+
+        let first = (self.aminoacid()?, self.number()?);
+        let last = match self.consume("_") {
+            true => (self.aminoacid()?, self.number()?),
+            false => first,
+        };
+        if self.consume("delins") {
+            return Ok(ProteinEdit::Delins(first, last, self.aminoacids()?));
+        }
+        if self.consume("del") {
+            return Ok(ProteinEdit::Deletion(first, last));
+        }
+        if self.consume("dup") {
+            return Ok(ProteinEdit::Duplication(first, last));
+        }
+        if self.consume("ins") {
+            return Ok(ProteinEdit::Insertion(first, last, self.aminoacids()?));
+        }
+        if first != last {
+            return Err(format!("Expecting 'del', 'dup', 'ins' or 'delins' at '{}'", self.rest()));
+        }
+        if self.consume("=") {
+            return Ok(ProteinEdit::Synonymous(first.0, first.1));
+        }
+        if self.consume("fs") {
+            return Ok(ProteinEdit::Frameshift(first.0, first.1, None, None));
+        }
+        let alternative = self.aminoacid()?;
+        if self.consume("fs") {
+            let ter = match self.is_done() || self.chars[self.index] == ')' {
+                true => None,
+                false => self.stop_position()?,
+            };
+            return Ok(ProteinEdit::Frameshift(first.0, first.1, Some(alternative), ter));
+        }
+        if self.consume("ext") {
+            if first.0 != Aminoacid3::Stop {
+                return Err("Extensions must start at the stop codon".to_string());
+            }
+            return Ok(ProteinEdit::Extension(first.1, alternative, self.stop_position()?));
+        }
+        Ok(ProteinEdit::Substitution(first.0, first.1, alternative))
+    }
+}
+
+
+/// Fetches the upper case template sequence and fails if it is incomplete
+fn fetch<F: FastaReader>(fasta: &mut F, template: &str, offset: usize, length: usize) -> Result<String, String> {
+    match fasta.search_region_as_sequence(template, offset, length) {
+        Some(ref s) if s.len() == length => Ok(s.to_uppercase()),
+        Some(_) => Err(format!("Region {}:{}-{} exceeds the template sequence", template, offset + 1, offset + length)),
+        None => Err(format!("Can not find template '{}' in FASTA file", template)),
+    }
+}
+
+/// Returns the affected template interval `[start, end)` and the edit of the
+/// variant on the forward strand. Insertions are described by the two flanking
+/// bases. Indels are shifted in the given direction as far as possible.
+fn genomic_edit<V: GenomicVariant, F: FastaReader>(variant: &V, fasta: &mut F, forward: bool) -> Result<(usize, usize, NucleotideEdit), String> {
+    let (offset, reference, alternative) = variant.normalized_variation();
+    let (reference, alternative) = (reference.to_string().to_uppercase(), alternative.to_string().to_uppercase());
+    let template = variant.template();
+
+    if reference.is_empty() && alternative.is_empty() {
+        return Err(format!("The variant at {}:{} does not change the sequence", template, offset + 1));
+    }
+    if reference.len() == 1 && alternative.len() == 1 {
+        let r = reference.chars().next().unwrap();
+        return Ok((offset, offset + 1, NucleotideEdit::Substitution(r, alternative.chars().next().unwrap())));
+    }
+    if reference.is_empty() {
+        let (offset, inserted) = shift(fasta, &template, offset, &alternative, false, forward)?;
+        let length = inserted.len();
+        // A copy of the sequence before (or after, when shifting backwards) the insertion
+        let copied = match forward {
+            true if offset >= length => Some(offset - length),
+            false => Some(offset),
+            _ => None,
+        };
+        if let Some(c) = copied {
+            if fetch(fasta, &template, c, length).ok() == Some(inserted.clone()) {
+                return Ok((c, c + length, NucleotideEdit::Duplication));
+            }
+        }
+        if offset == 0 {
+            return Err(format!("Can not describe the insertion before the start of '{}'", template));
+        }
+        return Ok((offset - 1, offset + 1, NucleotideEdit::Insertion(inserted)));
+    }
+    if alternative.is_empty() {
+        let (offset, deleted) = shift(fasta, &template, offset, &reference, true, forward)?;
+        return Ok((offset, offset + deleted.len(), NucleotideEdit::Deletion));
+    }
+    if reference.len() > 1 && alternative == reverse_complement(&reference) {
+        return Ok((offset, offset + reference.len(), NucleotideEdit::Inversion));
+    }
+    Ok((offset, offset + reference.len(), NucleotideEdit::Delins(alternative)))
+}
+
+/// Shifts an inserted or deleted sequence at the template offset to the right
+/// (`forward`) or left while the shifted sequence is equivalent. Returns the new
+/// offset and the rotated sequence.
+fn shift<F: FastaReader>(
+    fasta: &mut F,
+    template: &str,
+    offset: usize,
+    sequence: &str,
+    deletion: bool,
+    forward: bool,
+) -> Result<(usize, String), String> {
+    let mut sequence: Vec<char> = sequence.chars().collect();
+    let mut offset = offset;
+    loop {
+        // The bases next to the indel in the shift direction
+        let window = match forward {
+            true => {
+                let next = offset + if deletion { sequence.len() } else { 0 };
+                match fasta.search_region_as_sequence(template, next, SHIFT_WINDOW) {
+                    Some(s) => s.to_uppercase().chars().collect::<Vec<char>>(),
+                    None => return Err(format!("Can not find template '{}' in FASTA file", template)),
+                }
+            }
+            false => {
+                let start = offset.saturating_sub(SHIFT_WINDOW);
+                fetch(fasta, template, start, offset - start)?.chars().rev().collect()
+            }
+        };
+        let mut shifted = 0;
+        for c in window.iter() {
+            match forward {
+                true if *c == sequence[0] => {
+                    sequence.rotate_left(1);
+                    offset += 1;
+                }
+                false if *c == sequence[sequence.len() - 1] => {
+                    sequence.rotate_right(1);
+                    offset -= 1;
+                }
+                _ => break,
+            }
+            shifted += 1;
+        }
+        if window.is_empty() || shifted < window.len() {
+            return Ok((offset, sequence.into_iter().collect()));
+        }
+    }
+}
+
+/// Translates the sequence up to and including the first stop codon
+fn translate(sequence: &[char], code: &GeneticCode) -> Result<Vec<Aminoacid>, String> {
+    let sequence = sequence.iter().collect::<String>().parse::<DnaSequence>()?;
+    let mut peptide = Vec::new();
+    for codon in sequence.codons().iter() {
+        let aminoacid = code.translate(codon);
+        peptide.push(aminoacid.clone());
+        if aminoacid == Aminoacid::Stop {
+            break;
+        }
+    }
+    Ok(peptide)
+}
+
+/// Describes the difference of the translated alternative sequence to the
+/// reference protein. The `codon` is the index of the first changed codon.
+fn protein_edit(reference: &[Aminoacid], alternative: &[Aminoacid], codon: usize, frameshift: bool) -> ProteinEdit {
+    let three = |a: &Aminoacid| Aminoacid3::from(a.clone());
+    let first = match (0..cmp::max(reference.len(), alternative.len())).find(|i| reference.get(*i) != alternative.get(*i)) {
+        Some(i) => i,
+        None => return match reference.get(codon) {
+            Some(a) => ProteinEdit::Synonymous(three(a), codon + 1),
+            None => ProteinEdit::NoChange,
+        },
+    };
+    // The position of the stop codon of the alternative counted from the first change
+    let stop = |from: usize| match alternative.last() {
+        Some(&Aminoacid::Stop) if alternative.len() > from => Some(alternative.len() - from),
+        _ => None,
+    };
+    let (r, a) = match (reference.get(first), alternative.get(first)) {
+        (Some(r), Some(a)) => (r, a),
+        _ => return ProteinEdit::Unknown,
+    };
+    if first == 0 {
+        return ProteinEdit::StartLoss;
+    }
+    if *a == Aminoacid::Stop {
+        return ProteinEdit::Substitution(three(r), first + 1, Aminoacid3::Stop);
+    }
+    if *r == Aminoacid::Stop {
+        return ProteinEdit::Extension(first + 1, three(a), stop(first + 1).map(|s| s - 1).filter(|s| *s > 0).or(stop(first + 1)));
+    }
+    if frameshift {
+        return ProteinEdit::Frameshift(three(r), first + 1, Some(three(a)), stop(first));
+    }
+
+    // In-frame changes are trimmed from both sides, the common prefix first such
+    // that the change is described at the most C-terminal position
+    let suffix = (0..cmp::min(reference.len(), alternative.len()) - first)
+        .take_while(|i| reference[reference.len() - 1 - i] == alternative[alternative.len() - 1 - i])
+        .count();
+    let deleted = &reference[first..reference.len() - suffix];
+    let inserted: Vec<Aminoacid3> = alternative[first..alternative.len() - suffix].iter().map(&three).collect();
+    let residue = |i: usize| (three(&reference[i]), i + 1);
+    match (deleted.len(), inserted.len()) {
+        (1, 1) => ProteinEdit::Substitution(three(r), first + 1, inserted[0]),
+        (_, 0) => ProteinEdit::Deletion(residue(first), residue(first + deleted.len() - 1)),
+        (0, n) if first >= n && reference[first - n..first].iter().map(&three).collect::<Vec<Aminoacid3>>() == inserted => {
+            ProteinEdit::Duplication(residue(first - n), residue(first - 1))
+        }
+        (0, _) => ProteinEdit::Insertion(residue(first - 1), residue(first), inserted),
+        (_, _) => ProteinEdit::Delins(residue(first), residue(first + deleted.len() - 1), inserted),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fasta::IndexedFastaFile;
+    use io::gtf::GtfStream;
+    use io::vcf::VcfRecord;
+    use model::{CodingPosition, HgvsVariant, SimpleRegion, Strand, Transcript};
+    use model::hgvs::{NucleotideEdit, ProteinEdit};
+    use sequence::{Aminoacid3, GeneticCode};
+    use std::str::FromStr;
+
+    fn transcript() -> Transcript {
+        let records = GtfStream::open(&"testdata/toy.gtf").unwrap().read_records().unwrap();
+        Transcript::from_gtf_records(&records).unwrap().remove(0)
+    }
+
+    fn variant(position: usize, reference: &str, alternative: &str) -> VcfRecord {
+        VcfRecord::new("ref", position, reference, vec![alternative.to_string()])
+    }
+
+    #[test]
+    fn test_parse() {
+        let descriptions = [
+            "NM_004006.2:c.4375C>T",
+            "c.123+5G>A",
+            "c.124-3_124-1del",
+            "c.-14_-12del",
+            "c.*37dup",
+            "c.88_89insTTA",
+            "NC_000023.11:g.32389644_32389645delinsAT",
+            "g.10_15inv",
+            "g.12=",
+            "p.Arg97Cys",
+            "NP_003997.1:p.(Arg97Cys)",
+            "p.Trp24Ter",
+            "p.Leu54=",
+            "p.Lys23_Val25del",
+            "p.Lys23dup",
+            "p.His4_Gln5insAlaLys",
+            "p.Cys28delinsTrpVal",
+            "p.Arg97ProfsTer23",
+            "p.Arg97fs",
+            "p.Ter110GlnextTer17",
+            "p.(Met1?)",
+            "p.(=)",
+            "p.?",
+        ];
+        for d in descriptions.iter() {
+            assert_eq!(HgvsVariant::from_str(d).map(|v| v.to_string()), Ok(d.to_string()));
+        }
+
+        let v = HgvsVariant::from_str("NM_004006.2:c.123+5G>A").unwrap();
+        assert_eq!(v.accession(), Some("NM_004006.2".to_string()));
+        match v {
+            HgvsVariant::Coding { start, edit, .. } => {
+                assert_eq!((start.position(), start.intron_offset()), (123, 5));
+                assert_eq!(edit, NucleotideEdit::Substitution('G', 'A'));
+            }
+            _ => panic!("Expected a coding variant"),
+        }
+        // Deleted sequences and one letter codes are accepted but not written
+        assert_eq!(HgvsVariant::from_str("g.5delA").unwrap().to_string(), "g.5del");
+        assert_eq!(HgvsVariant::from_str("p.R97*").unwrap().to_string(), "p.Arg97Ter");
+        assert_eq!(
+            HgvsVariant::from_str("p.R97C").unwrap(),
+            HgvsVariant::Protein { accession: None, edit: ProteinEdit::Substitution(Aminoacid3::Arg, 97, Aminoacid3::Cys), predicted: false }
+        );
+
+        for d in ["x.12A>G", "g.12A", "g.12_13A>G", "g.0del", "g.12ins", "c.0A>G", "c.-0G>A", "c.*0G>A", "c.*0_*2del", "c.12+A>G", "c.13_12del", "c.*1_20del", "c.12_13G>A", "c.12insA", "p.(Arg97Cys", "p.Arg97Xyz", "g.12delx"].iter() {
+            assert!(HgvsVariant::from_str(d).is_err(), "{}", d);
+        }
+    }
+
+    #[test]
+    fn test_coding_positions() {
+        let t = transcript();
+        let position = |o: usize| CodingPosition::from_template_offset(&t, o).unwrap().to_string();
+        // The exons of t1 are ref:2-20 and ref:28-40 with the CDS from 4 to 31
+        assert_eq!(position(3), "1");
+        assert_eq!(position(2), "-1");
+        assert_eq!(position(0), "-3");
+        assert_eq!(position(27), "18");
+        assert_eq!(position(30), "21");
+        assert_eq!(position(31), "*1");
+        assert_eq!(position(40), "*10");
+        assert_eq!(position(21), "17+2");
+        assert_eq!(position(23), "17+4");
+        assert_eq!(position(25), "18-2");
+        for o in 0..45 {
+            assert_eq!(CodingPosition::from_template_offset(&t, o).unwrap().template_offset(&t), Ok(o));
+        }
+        assert!(CodingPosition::new(5).with_intron_offset(1).template_offset(&t).is_err());
+    }
+
+    #[test]
+    fn test_describe_variants() {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let t = transcript();
+        let code = GeneticCode::standard();
+        let describe = |v: &VcfRecord, fasta: &mut IndexedFastaFile| {
+            (
+                HgvsVariant::from_variant(v, fasta).unwrap().to_string(),
+                HgvsVariant::from_variant_on_transcript(v, &t, fasta).unwrap().to_string(),
+                HgvsVariant::protein_consequence(v, &t, fasta, &code).unwrap().to_string(),
+            )
+        };
+        let d = |g: &str, c: &str, p: &str| (g.to_string(), c.to_string(), p.to_string());
+
+        // The coding sequence is ATG TTA GAT AAG ATA GCG TAG
+        assert_eq!(describe(&variant(10, "G", "C"), &mut fasta), d("ref:g.10G>C", "t1:c.7G>C", "p.(Asp3His)"));
+        assert_eq!(describe(&variant(8, "T", "A"), &mut fasta), d("ref:g.8T>A", "t1:c.5T>A", "p.(Leu2Ter)"));
+        assert_eq!(describe(&variant(28, "G", "A"), &mut fasta), d("ref:g.28G>A", "t1:c.18G>A", "p.(Ala6=)"));
+        assert_eq!(describe(&variant(5, "T", "C"), &mut fasta), d("ref:g.5T>C", "t1:c.2T>C", "p.(Met1?)"));
+        assert_eq!(describe(&variant(29, "T", "C"), &mut fasta), d("ref:g.29T>C", "t1:c.19T>C", "p.(Ter7GlnextTer?)"));
+        assert_eq!(describe(&variant(22, "G", "A"), &mut fasta), d("ref:g.22G>A", "t1:c.17+2G>A", "p.?"));
+        assert_eq!(describe(&variant(2, "G", "T"), &mut fasta), d("ref:g.2G>T", "t1:c.-2G>T", "p.(=)"));
+        // The deletion of one A of AA is shifted to the 3' end
+        assert_eq!(describe(&variant(12, "TA", "T"), &mut fasta), d("ref:g.14del", "t1:c.11del", "p.(Lys4ArgfsTer2)"));
+        assert_eq!(describe(&variant(12, "T", "TGAT"), &mut fasta), d("ref:g.10_12dup", "t1:c.7_9dup", "p.(Asp3dup)"));
+        assert_eq!(describe(&variant(12, "T", "TCCC"), &mut fasta), d("ref:g.12_13insCCC", "t1:c.9_10insCCC", "p.(Asp3_Lys4insPro)"));
+        assert_eq!(describe(&variant(9, "AGAT", "A"), &mut fasta), d("ref:g.10_12del", "t1:c.7_9del", "p.(Asp3del)"));
+        assert_eq!(describe(&variant(10, "GA", "TC"), &mut fasta), d("ref:g.10_11inv", "t1:c.7_8inv", "p.(Asp3Ser)"));
+        assert_eq!(describe(&variant(10, "GAT", "TGG"), &mut fasta), d("ref:g.10_12delinsTGG", "t1:c.7_9delinsTGG", "p.(Asp3Trp)"));
+    }
+
+    #[test]
+    fn test_reverse_strand() {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        // The reverse complement of ref:4-27 is CACAGCTATCTTATCTTATCTAACAT
+        let t = Transcript::new("t", "ref", Strand::Backward)
+            .with_exon(SimpleRegion::new("ref", 0, 10))
+            .with_exon(SimpleRegion::new("ref", 15, 15))
+            .with_cds(SimpleRegion::new("ref", 6, 4), 0)
+            .with_cds(SimpleRegion::new("ref", 15, 11), 0);
+        let coding = |v: &VcfRecord, fasta: &mut IndexedFastaFile| HgvsVariant::from_variant_on_transcript(v, &t, fasta).unwrap();
+
+        let c = coding(&variant(26, "C", "A"), &mut fasta);
+        assert_eq!(c.to_string(), "t:c.1G>T");
+        assert_eq!(c.to_genomic(&t).unwrap().to_string(), "ref:g.26C>A");
+        let c = coding(&variant(13, "A", "G"), &mut fasta);
+        assert_eq!(c.to_string(), "t:c.11+3T>C");
+        assert_eq!(c.to_genomic(&t).unwrap().to_string(), "ref:g.13A>G");
+        // The deletion of one A of AA is shifted to its 3' end in the transcript
+        let c = coding(&variant(13, "AA", "A"), &mut fasta);
+        assert_eq!(c.to_string(), "t:c.11+3del");
+        assert_eq!(c.to_genomic(&t).unwrap().to_string(), "ref:g.13del");
+
+        let g = HgvsVariant::from_str("ref:g.20_21insCA").unwrap();
+        let c = g.to_coding(&t, &mut fasta).unwrap();
+        assert_eq!(c.to_string(), "t:c.6_7insTG");
+        assert_eq!(c.to_genomic(&t).unwrap(), g);
+    }
+
+    #[test]
+    fn test_to_vcf_record() {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let record = |d: &str, fasta: &mut IndexedFastaFile| {
+            let r = HgvsVariant::from_str(d).unwrap().to_vcf_record(fasta).unwrap();
+            (r.position(), r.reference_allele(), r.alternative_alleles().join(","))
+        };
+        let r = |p: usize, reference: &str, alternative: &str| (p, reference.to_string(), alternative.to_string());
+        assert_eq!(record("ref:g.10G>C", &mut fasta), r(10, "G", "C"));
+        assert_eq!(record("ref:g.14del", &mut fasta), r(13, "AA", "A"));
+        assert_eq!(record("ref:g.1_2del", &mut fasta), r(1, "AGC", "C"));
+        assert_eq!(record("ref:g.10_12dup", &mut fasta), r(12, "T", "TGAT"));
+        assert_eq!(record("ref:g.12_13insCCC", &mut fasta), r(12, "T", "TCCC"));
+        assert_eq!(record("ref:g.10_12delinsTGG", &mut fasta), r(10, "GAT", "TGG"));
+        assert_eq!(record("ref:g.10_11inv", &mut fasta), r(10, "GA", "TC"));
+
+        assert!(HgvsVariant::from_str("ref:g.10A>C").unwrap().to_vcf_record(&mut fasta).is_err());
+        assert!(HgvsVariant::from_str("g.10G>C").unwrap().to_vcf_record(&mut fasta).is_err());
+        assert!(HgvsVariant::from_str("c.10G>C").unwrap().to_vcf_record(&mut fasta).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use model::{Region, RegionIndex};
+use std::cmp;
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+/// The regions of a single template sorted by their offset. The array is the
+/// in-order traversal of an implicit balanced binary tree in which every node
+/// stores the maximal end of its subtree.
+#[derive(Clone, Debug)]
+struct TemplateTree<R: Region> {
+    regions: Vec<R>,
+    /// The maximal end of the subtree rooted at each position
+    max_end: Vec<usize>,
+    /// The position of the region with the largest end among the regions up to and
+    /// including each position
+    prefix_max_end: Vec<usize>,
+}
+
+impl<R: Region> TemplateTree<R> {
+    fn new(mut regions: Vec<R>) -> Self {
+        regions.sort_by(|a, b| a.compare(b));
+        let mut max_end = vec![0; regions.len()];
+        TemplateTree::build_max_end(&regions, &mut max_end, 0, regions.len());
+
+        let mut prefix_max_end: Vec<usize> = Vec::with_capacity(regions.len());
+        for (i, region) in regions.iter().enumerate() {
+            let best = match prefix_max_end.last() {
+                Some(&j) if regions[j].end() >= region.end() => j,
+                _ => i,
+            };
+            prefix_max_end.push(best);
+        }
+
+        TemplateTree {
+            regions: regions,
+            max_end: max_end,
+            prefix_max_end: prefix_max_end,
+        }
+    }
+
+    fn build_max_end(regions: &Vec<R>, max_end: &mut Vec<usize>, lo: usize, hi: usize) -> usize {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = (lo + hi) / 2;
+        let left = TemplateTree::build_max_end(regions, max_end, lo, mid);
+        let right = TemplateTree::build_max_end(regions, max_end, mid + 1, hi);
+        max_end[mid] = cmp::max(regions[mid].end(), cmp::max(left, right));
+        max_end[mid]
+    }
+
+    /// Collects the regions in `lo..hi` that start at or before `max_offset` and end
+    /// after (or at, if `inclusive_end`) `min_end`.
+    fn collect<'a>(&'a self, lo: usize, hi: usize, max_offset: usize, min_end: usize, inclusive_end: bool, found: &mut Vec<&'a R>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let ends_late_enough = |end: usize| match inclusive_end {
+            true => end >= min_end,
+            false => end > min_end,
+        };
+        if !ends_late_enough(self.max_end[mid]) {
+            return;
+        }
+        self.collect(lo, mid, max_offset, min_end, inclusive_end, found);
+        if self.regions[mid].offset() <= max_offset {
+            if ends_late_enough(self.regions[mid].end()) {
+                found.push(&self.regions[mid]);
+            }
+            self.collect(mid + 1, hi, max_offset, min_end, inclusive_end, found);
+        }
+    }
+
+    /// Returns the position of the first region with an offset not less than `offset`
+    fn lower_bound(&self, offset: usize) -> usize {
+        let (mut lo, mut hi) = (0, self.regions.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.regions[mid].offset() < offset {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+
+/// An in-memory interval tree over regions of any type. The regions are grouped
+/// by template and each group is stored as an augmented, implicit binary tree, so
+/// that queries need logarithmic time plus the number of reported regions.
+#[derive(Clone, Debug)]
+pub struct IntervalTree<R: Region> {
+    templates: BTreeMap<String, TemplateTree<R>>,
+}
+
+impl<R: Region> IntervalTree<R> {
+    pub fn new(regions: Vec<R>) -> Self {
+        let mut grouped: BTreeMap<String, Vec<R>> = BTreeMap::new();
+        for region in regions {
+            grouped.entry(region.template()).or_insert(Vec::new()).push(region);
+        }
+        IntervalTree {
+            templates: grouped.into_iter().map(|(t, r)| (t, TemplateTree::new(r))).collect(),
+        }
+    }
+
+    /// Returns the number of regions in the tree
+    pub fn len(&self) -> usize {
+        self.templates.values().map(|t| t.regions.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the names of the templates with at least one region
+    pub fn templates(&self) -> Vec<String> {
+        self.templates.keys().cloned().collect()
+    }
+
+    /// Returns all regions ordered by template name and offset
+    pub fn regions(&self) -> Vec<&R> {
+        self.templates.values().flat_map(|t| t.regions.iter()).collect()
+    }
+}
+
+impl<R: Region> RegionIndex<R> for IntervalTree<R> {
+    fn overlapping<O: Region>(&self, region: &O) -> Vec<&R> {
+        let mut found = Vec::new();
+        if region.length() == 0 {
+            return found;
+        }
+        if let Some(tree) = self.templates.get(&region.template()) {
+            tree.collect(0, tree.regions.len(), region.end() - 1, region.offset(), false, &mut found);
+        }
+        found
+    }
+
+    fn containing<O: Region>(&self, region: &O) -> Vec<&R> {
+        let mut found = Vec::new();
+        if let Some(tree) = self.templates.get(&region.template()) {
+            tree.collect(0, tree.regions.len(), region.offset(), region.end(), true, &mut found);
+        }
+        found
+    }
+
+    fn contained_in<O: Region>(&self, region: &O) -> Vec<&R> {
+        match self.templates.get(&region.template()) {
+            Some(tree) => tree.regions[tree.lower_bound(region.offset())..]
+                .iter()
+                .take_while(|r| r.offset() <= region.end())
+                .filter(|r| r.end() <= region.end())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn nearest<O: Region>(&self, region: &O) -> Option<&R> {
+        if let Some(r) = self.overlapping(region).into_iter().next() {
+            return Some(r);
+        }
+        let tree = match self.templates.get(&region.template()) {
+            Some(t) => t,
+            None => return None,
+        };
+
+        // Without overlaps, all regions starting before the query end before it
+        let first_downstream = tree.lower_bound(region.end());
+        let downstream = tree.regions.get(first_downstream);
+        let last_upstream = tree.lower_bound(region.offset());
+        let upstream = match last_upstream {
+            0 => None,
+            i => Some(&tree.regions[tree.prefix_max_end[i - 1]]),
+        };
+
+        match (upstream, downstream) {
+            (Some(u), Some(d)) => {
+                let upstream_distance = region.offset().saturating_sub(u.end());
+                let downstream_distance = d.offset() - region.end();
+                match upstream_distance <= downstream_distance {
+                    true => Some(u),
+                    false => Some(d),
+                }
+            }
+            (Some(u), None) => Some(u),
+            (None, d) => d,
+        }
+    }
+}
+
+impl<R: Region> From<Vec<R>> for IntervalTree<R> {
+    fn from(regions: Vec<R>) -> Self {
+        IntervalTree::new(regions)
+    }
+}
+
+impl<R: Region> FromIterator<R> for IntervalTree<R> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        IntervalTree::new(iter.into_iter().collect())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use model::{IntervalTree, Region, RegionIndex, SimpleRegion};
+
+    fn offsets(regions: Vec<&SimpleRegion>) -> Vec<usize> {
+        regions.iter().map(|r| r.offset()).collect()
+    }
+
+    fn tree() -> IntervalTree<SimpleRegion> {
+        vec![
+            SimpleRegion::new("ref", 50, 10),
+            SimpleRegion::new("ref", 0, 100),
+            SimpleRegion::new("ref", 10, 5),
+            SimpleRegion::new("ref", 20, 20),
+            SimpleRegion::new("ref", 70, 5),
+            SimpleRegion::new("ref2", 10, 5),
+        ].into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let tree = tree();
+        assert_eq!(tree.len(), 6);
+        assert_eq!(offsets(tree.overlapping(&SimpleRegion::new("ref", 14, 7))), vec![0, 10, 20]);
+        assert_eq!(offsets(tree.overlapping(&SimpleRegion::new("ref", 15, 5))), vec![0]);
+        assert_eq!(offsets(tree.overlapping(&SimpleRegion::new("ref", 100, 5))), Vec::<usize>::new());
+        assert_eq!(offsets(tree.overlapping(&SimpleRegion::new("ref2", 0, 100))), vec![10]);
+        assert!(tree.overlapping(&SimpleRegion::new("ref3", 0, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_containment() {
+        let tree = tree();
+        assert_eq!(offsets(tree.containing(&SimpleRegion::new("ref", 20, 20))), vec![0, 20]);
+        assert_eq!(offsets(tree.containing(&SimpleRegion::new("ref", 45, 10))), vec![0]);
+        assert_eq!(offsets(tree.contained_in(&SimpleRegion::new("ref", 10, 50))), vec![10, 20, 50]);
+        assert_eq!(offsets(tree.contained_in(&SimpleRegion::new("ref", 11, 50))), vec![20, 50]);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let tree: IntervalTree<SimpleRegion> = vec![
+            SimpleRegion::new("ref", 10, 5),
+            SimpleRegion::new("ref", 0, 30),
+            SimpleRegion::new("ref", 50, 10),
+        ].into();
+        assert_eq!(tree.nearest(&SimpleRegion::new("ref", 12, 1)).unwrap().offset(), 0);
+        assert_eq!(tree.nearest(&SimpleRegion::new("ref", 35, 5)).unwrap().offset(), 0);
+        // Ties are resolved in favour of the upstream region
+        assert_eq!(tree.nearest(&SimpleRegion::new("ref", 39, 2)).unwrap().offset(), 0);
+        assert_eq!(tree.nearest(&SimpleRegion::new("ref", 40, 1)).unwrap().offset(), 50);
+        assert_eq!(tree.nearest(&SimpleRegion::new("ref", 100, 1)).unwrap().offset(), 50);
+        assert!(tree.nearest(&SimpleRegion::new("ref2", 0, 1)).is_none());
+    }
+}
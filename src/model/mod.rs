@@ -1,6 +1,8 @@
 pub mod region;
 pub use self::region::Region;
 pub use self::region::SimpleRegion;
+pub mod coordinates;
+pub use self::coordinates::{OneBasedInterval, ZeroBasedInterval};
 pub mod strand;
 pub use self::strand::Strand;
 pub mod variant;
@@ -8,3 +10,15 @@ pub use self::variant::VariantType;
 pub use self::variant::Variant;
 pub use self::variant::GenomicVariant;
 pub use self::variant::PeptideVariant;
+pub mod transcript;
+pub use self::transcript::Transcript;
+pub mod gene;
+pub mod regionindex;
+pub use self::regionindex::RegionIndex;
+pub mod intervaltree;
+pub use self::intervaltree::IntervalTree;
+pub mod regionset;
+pub mod sequencedictionary;
+pub use self::sequencedictionary::SequenceDictionary;
+pub mod hgvs;
+pub use self::hgvs::{CodingPosition, HgvsVariant};
@@ -1,3 +1,6 @@
+use model::Strand;
+use model::coordinates::{OneBasedInterval, ZeroBasedInterval};
+use std::cmp;
 use std::cmp::Ordering;
 use std::fmt;
 
@@ -16,8 +19,8 @@ pub trait Region {
     /// Returns the length of the sequence, that is the number of 
     fn length(&self) -> usize;
 
-    /// Returns the end position of the region. This is the last
-    /// position being part of the region.
+    /// Returns the end of the region. This is the offset of the first
+    /// position after the region, i.e., the end is exclusive.
     fn end(&self) -> usize {
         self.offset() + self.length()
     }
@@ -33,46 +36,21 @@ pub trait Region {
             return 0
         }
 
-        let self_offset = self.offset();
-        let self_end    = self.end();
-        let other_offset = other.offset();
-        let other_end    = other.end();
-
-        if self_offset == other_offset {
-            if self_end < other_end {
-                self.length()
-            }
-            else {
-                other.length()
-            }
-        }    
-        else if self_offset < other_offset {
-            if self_end < other_offset {
-                0
-            }
-            else if self_end <= other_end {
-                self_end - other_offset + 1
-            }
-            else {
-                other.length()
-            }
+        let offset = cmp::max(self.offset(), other.offset());
+        let end    = cmp::min(self.end(), other.end());
+        if offset < end {
+            end - offset
         }
-        else if self_offset < other_end { // && self_offset > other_offset
-            if self_end <= other_end {
-                self.length()
-            } 
-            else {
-                other_end - self_offset
-            }
-        } 
-        else { // self_offset >= other_end
+        else {
             0
         }
     }    
 
-    /// Returns string that resembles the 
+    /// Returns string that resembles the region in 1-based, closed
+    /// coordinates (e.g., `chr1:100-200`).
     fn display_string(&self) -> String {
-        format!("{}:{}-{}", self.template(), self.offset()+1, self.end()+1)
+        let interval = OneBasedInterval::from(ZeroBasedInterval::from_region(self));
+        format!("{}:{}-{}", self.template(), interval.start(), interval.end())
     }
 
     /// Compare two regions by 
@@ -124,6 +102,12 @@ impl SimpleRegion {
             length: length,
         }
     }
+
+    /// Creates the region from an interval in either coordinate system
+    pub fn from_interval<S: ToString, I: Into<ZeroBasedInterval>>(name: S, interval: I) -> Self {
+        let interval = interval.into();
+        SimpleRegion::new(name, interval.start(), interval.length())
+    }
 }
 
 impl Region for SimpleRegion {
@@ -147,12 +131,89 @@ impl Region for SimpleRegion {
 
 impl fmt::Display for SimpleRegion {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}:{}-{}", self.template(), self.offset()+1, self.end() + 1 )
+        write!(f, "{}", self.display_string())
+    }
+}
+
+
+/// A region that is located on a specific strand of the template.
+#[derive(Clone, Debug)]
+pub struct StrandedRegion {
+    name: String,
+    offset: usize,
+    length: usize,
+    strand: Strand,
+}
+
+impl StrandedRegion {
+    pub fn new<S: ToString>(name: S, offset: usize, length: usize, strand: Strand) -> Self {
+        StrandedRegion {
+            name: name.to_string(),
+            offset: offset,
+            length: length,
+            strand: strand,
+        }
+    }
+
+    pub fn from_region<R: Region>(region: &R, strand: Strand) -> Self {
+        StrandedRegion::new(region.template(), region.offset(), region.length(), strand)
+    }
+
+    pub fn strand(&self) -> Strand {
+        self.strand.clone()
+    }
+
+    pub fn with_strand(mut self, new_strand: Strand) -> Self {
+        self.strand = new_strand;
+        self
+    }
+
+    /// Returns the offset of the first position in reading direction
+    pub fn five_prime_offset(&self) -> usize {
+        match self.strand {
+            Strand::Forward => self.offset,
+            Strand::Backward => self.end() - 1,
+        }
+    }
+
+    /// Returns the offset of the last position in reading direction
+    pub fn three_prime_offset(&self) -> usize {
+        match self.strand {
+            Strand::Forward => self.end() - 1,
+            Strand::Backward => self.offset,
+        }
+    }
+
+    /// Calculate if two regions overlap on the same strand
+    pub fn overlaps_stranded(&self, other: &StrandedRegion) -> bool {
+        self.strand == other.strand && self.overlaps(other)
+    }
+}
+
+impl Region for StrandedRegion {
+    fn template(&self) -> String {
+        self.name.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn length(&self) -> usize {
+        self.length
+    }
+}
+
+impl fmt::Display for StrandedRegion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({})", self.display_string(), self.strand)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use model::Strand;
+    use model::coordinates::{OneBasedInterval, ZeroBasedInterval};
     use model::region::*;
 
     #[test]
@@ -161,6 +222,39 @@ mod tests {
         assert_eq!(r.template(), "ref");
         assert_eq!(r.offset(), 10);
         assert_eq!(r.length(), 5);
+        assert_eq!(r.end(), 15);
+        assert_eq!(r.to_string(), "ref:11-15");
+    }
+
+    #[test]
+    fn test_from_interval() {
+        let r = SimpleRegion::from_interval("ref", OneBasedInterval::new(11, 15).unwrap());
+        assert_eq!((r.offset(), r.length()), (10, 5));
+        assert_eq!(OneBasedInterval::from(ZeroBasedInterval::from_region(&r)), OneBasedInterval::new(11, 15).unwrap());
+    }
+
+    #[test]
+    fn test_overlap() {
+        let r = SimpleRegion::new("ref", 10, 10);
+        assert!( ! SimpleRegion::new("ref",  0, 10).overlaps(&r), "Two regions right after each other");
+        assert!(   SimpleRegion::new("ref",  0, 11).overlaps(&r), "Two regions right after each other but 1bp overlap");
+        assert!(   SimpleRegion::new("ref", 10, 10).overlaps(&r), "First region is second region");
+        assert!(   SimpleRegion::new("ref", 11,  9).overlaps(&r), "First region in second region");
+        assert!(   SimpleRegion::new("ref", 19, 10).overlaps(&r), "Region one at end of second region but 1bp overlap");
+        assert!( ! SimpleRegion::new("ref", 20, 10).overlaps(&r), "Region one at end of second region no overlap");
+        assert!( ! SimpleRegion::new("ref2", 10, 10).overlaps(&r), "Different templates");
+        assert_eq!(SimpleRegion::new("ref", 5, 10).overlap_length(&r), 5);
+        assert_eq!(r.overlap_length(&SimpleRegion::new("ref", 5, 10)), 5);
+    }
+
+    #[test]
+    fn test_stranded_region() {
+        let r = StrandedRegion::new("ref", 10, 5, Strand::Backward);
+        assert_eq!(r.five_prime_offset(), 14);
+        assert_eq!(r.three_prime_offset(), 10);
+        assert_eq!(r.to_string(), "ref:11-15(-)");
+        assert!(!r.overlaps_stranded(&StrandedRegion::from_region(&r, Strand::Forward)));
+        assert!(r.overlaps_stranded(&r.clone()));
     }
 /*
     #[test]
@@ -193,19 +287,5 @@ mod tests {
     }
 
 
-    #[test]
-    fn test_overlap() {
-        assert!( ! Region::new_with_coordinates(&"ref" ,  0, 10).overlaps(&Region::new_with_coordinates(&"ref", 10, 10)), "Two regions right after each other");
-        assert!(   Region::new_with_coordinates(&"ref" ,  0, 11).overlaps(&Region::new_with_coordinates(&"ref", 10, 10)), "Two regions right after each other but 1bp overlap");
-        assert!(   Region::new_with_coordinates(&"ref" , 10, 10).overlaps(&Region::new_with_coordinates(&"ref", 10, 10)), "First region is second region");
-        assert!(   Region::new_with_coordinates(&"ref" , 11,  9).overlaps(&Region::new_with_coordinates(&"ref", 10, 10)), "First region in second region");
-
-        assert!(   Region::new_with_coordinates(&"ref" , 19,  10).overlaps(&Region::new_with_coordinates(&"ref", 10, 10)), "Region one at end of second region but 1bp overlap");
-        assert!( ! Region::new_with_coordinates(&"ref" , 20,  10).overlaps(&Region::new_with_coordinates(&"ref", 10, 10)), "Region one at end of second region no overlap");
-
-
-    }
-
-
 */    
 }
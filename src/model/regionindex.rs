@@ -1,10 +1,20 @@
+use model::Region;
 
+/// An index over regions that answers positional queries without scanning all
+/// regions. All queries use half-open coordinates, i.e., two regions overlap if they
+/// share at least one position.
+pub trait RegionIndex<R: Region> {
+    /// Returns the regions that overlap the given region
+    fn overlapping<O: Region>(&self, region: &O) -> Vec<&R>;
 
+    /// Returns the regions that completely contain the given region
+    fn containing<O: Region>(&self, region: &O) -> Vec<&R>;
 
-pub trait RegionIndex {
+    /// Returns the regions that are completely contained in the given region
+    fn contained_in<O: Region>(&self, region: &O) -> Vec<&R>;
 
-	fn file_offset_for_template<S: ToString>(s: &S) -> Option<usize>;
-
-	fn file_offset_for_region<R: Region>(r: &R) -> Option<usize>;
-
-}
\ No newline at end of file
+    /// Returns the region closest to the given region on the same template. An
+    /// overlapping region has a distance of zero. If an upstream and a downstream
+    /// region have the same distance, the upstream region is returned.
+    fn nearest<O: Region>(&self, region: &O) -> Option<&R>;
+}
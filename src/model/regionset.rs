@@ -0,0 +1,214 @@
+// Set operations on collections of regions, similar to the ones provided by
+// bedtools. All operations use half-open coordinates and report their results
+// ordered by template name and offset.
+
+use model::{IntervalTree, Region, RegionIndex, SimpleRegion};
+use std::cmp;
+use std::collections::BTreeMap;
+
+/// Returns references to the regions ordered by template name and offset
+fn sorted<R: Region>(regions: &[R]) -> Vec<&R> {
+    let mut sorted: Vec<&R> = regions.iter().collect();
+    sorted.sort_by(|a, b| a.compare(*b));
+    sorted
+}
+
+/// Returns the length of each template of the genome
+fn template_lengths<G: Region>(genome: &[G]) -> BTreeMap<String, usize> {
+    genome.iter().map(|g| (g.template(), g.end())).collect()
+}
+
+/// Merges regions that overlap or that are at most `distance` positions apart.
+/// Book-ended regions are merged with a distance of zero.
+pub fn merge<R: Region>(regions: &[R], distance: usize) -> Vec<SimpleRegion> {
+    let mut merged: Vec<SimpleRegion> = Vec::new();
+    for region in sorted(regions) {
+        let extends_last = match merged.last() {
+            Some(last) => last.template() == region.template() && region.offset() <= last.end() + distance,
+            None => false,
+        };
+        if extends_last {
+            let last = merged.pop().unwrap();
+            let end = cmp::max(last.end(), region.end());
+            merged.push(SimpleRegion::new(last.template(), last.offset(), end - last.offset()));
+        } else {
+            merged.push(SimpleRegion::new(region.template(), region.offset(), region.length()));
+        }
+    }
+    merged
+}
+
+/// Returns each pair of overlapping regions of `a` and `b` together with the
+/// overlapping part.
+pub fn intersect<'a, 'b, A: Region, B: Region>(a: &'a [A], b: &'b IntervalTree<B>) -> Vec<(&'a A, &'b B, SimpleRegion)> {
+    let mut pairs = Vec::new();
+    for region in sorted(a) {
+        for other in b.overlapping(region) {
+            let offset = cmp::max(region.offset(), other.offset());
+            let end = cmp::min(region.end(), other.end());
+            pairs.push((region, other, SimpleRegion::new(region.template(), offset, end - offset)));
+        }
+    }
+    pairs
+}
+
+/// Returns the regions of `a` that do not overlap any region of `b`
+pub fn without_overlap<'a, A: Region, B: Region>(a: &'a [A], b: &IntervalTree<B>) -> Vec<&'a A> {
+    sorted(a).into_iter().filter(|r| b.overlapping(*r).is_empty()).collect()
+}
+
+/// Removes the parts of the regions of `a` that are covered by `b`. A region of
+/// `a` may be split into multiple parts.
+pub fn subtract<'a, A: Region, B: Region>(a: &'a [A], b: &IntervalTree<B>) -> Vec<(&'a A, SimpleRegion)> {
+    let mut parts = Vec::new();
+    for region in sorted(a) {
+        let overlapping: Vec<SimpleRegion> = b.overlapping(region)
+            .into_iter()
+            .map(|o| SimpleRegion::new(o.template(), o.offset(), o.length()))
+            .collect();
+        let covered = merge(&overlapping, 0);
+        let mut offset = region.offset();
+        for c in covered {
+            if c.offset() > offset {
+                parts.push((region, SimpleRegion::new(region.template(), offset, c.offset() - offset)));
+            }
+            offset = cmp::max(offset, c.end());
+        }
+        if offset < region.end() {
+            parts.push((region, SimpleRegion::new(region.template(), offset, region.end() - offset)));
+        }
+    }
+    parts
+}
+
+/// Returns the parts of the genome that are not covered by any region. The
+/// genome is given as one region per template that spans the whole template.
+pub fn complement<R: Region, G: Region>(regions: &[R], genome: &[G]) -> Vec<SimpleRegion> {
+    let covered = IntervalTree::new(merge(regions, 0));
+    subtract(genome, &covered).into_iter().map(|(_, r)| r).collect()
+}
+
+/// Extends the regions by `left` positions before and `right` positions after
+/// them. The regions are clipped at the template ends given by the genome.
+pub fn slop<'a, R: Region, G: Region>(regions: &'a [R], left: usize, right: usize, genome: &[G]) -> Result<Vec<(&'a R, SimpleRegion)>, String> {
+    let lengths = template_lengths(genome);
+    let mut extended = Vec::new();
+    for region in sorted(regions) {
+        let length = match lengths.get(&region.template()) {
+            Some(l) => *l,
+            None => return Err(format!("Can not find template '{}' in genome", region.template())),
+        };
+        let offset = region.offset().saturating_sub(left);
+        let end = cmp::max(cmp::min(region.end() + right, length), offset);
+        extended.push((region, SimpleRegion::new(region.template(), offset, end - offset)));
+    }
+    Ok(extended)
+}
+
+/// Returns the `left` positions before and the `right` positions after each region
+/// clipped at the template ends. Empty flanks are not reported.
+pub fn flank<'a, R: Region, G: Region>(regions: &'a [R], left: usize, right: usize, genome: &[G]) -> Result<Vec<(&'a R, SimpleRegion)>, String> {
+    let mut flanks = Vec::new();
+    for (region, extended) in slop(regions, left, right, genome)? {
+        if extended.offset() < region.offset() {
+            flanks.push((region, SimpleRegion::new(region.template(), extended.offset(), region.offset() - extended.offset())));
+        }
+        if extended.end() > region.end() {
+            flanks.push((region, SimpleRegion::new(region.template(), region.end(), extended.end() - region.end())));
+        }
+    }
+    Ok(flanks)
+}
+
+/// Splits the regions into windows of `size` positions that start every `step`
+/// positions. The last window of a region may be shorter.
+pub fn windows<R: Region>(regions: &[R], size: usize, step: usize) -> Vec<SimpleRegion> {
+    assert!(size > 0 && step > 0, "Window size and step must be larger than zero");
+    let mut windows = Vec::new();
+    for region in sorted(regions) {
+        let mut offset = region.offset();
+        while offset < region.end() {
+            let end = cmp::min(offset + size, region.end());
+            windows.push(SimpleRegion::new(region.template(), offset, end - offset));
+            if end == region.end() {
+                break;
+            }
+            offset += step;
+        }
+    }
+    windows
+}
+
+
+#[cfg(test)]
+mod tests {
+    use model::regionset::*;
+
+    fn coordinates(regions: &Vec<SimpleRegion>) -> Vec<(String, usize, usize)> {
+        regions.iter().map(|r| (r.template(), r.offset(), r.end())).collect()
+    }
+
+    fn regions(v: Vec<(&str, usize, usize)>) -> Vec<SimpleRegion> {
+        v.into_iter().map(|(t, s, e)| SimpleRegion::new(t, s, e - s)).collect()
+    }
+
+    fn genome() -> Vec<SimpleRegion> {
+        regions(vec![("chr1", 0, 100), ("chr2", 0, 50)])
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = regions(vec![("chr1", 30, 40), ("chr1", 10, 20), ("chr1", 20, 25), ("chr1", 15, 18), ("chr2", 0, 5)]);
+        assert_eq!(
+            coordinates(&merge(&a, 0)),
+            coordinates(&regions(vec![("chr1", 10, 25), ("chr1", 30, 40), ("chr2", 0, 5)]))
+        );
+        assert_eq!(coordinates(&merge(&a, 5))[0], ("chr1".to_string(), 10, 40));
+    }
+
+    #[test]
+    fn test_intersect_and_subtract() {
+        let a = regions(vec![("chr1", 10, 50), ("chr1", 60, 70), ("chr2", 0, 10)]);
+        let b = IntervalTree::new(regions(vec![("chr1", 0, 15), ("chr1", 20, 30), ("chr1", 25, 35)]));
+
+        let pairs = intersect(&a, &b);
+        let overlaps: Vec<SimpleRegion> = pairs.iter().map(|p| p.2.clone()).collect();
+        assert_eq!(coordinates(&overlaps), coordinates(&regions(vec![("chr1", 10, 15), ("chr1", 20, 30), ("chr1", 25, 35)])));
+
+        let unique: Vec<SimpleRegion> = without_overlap(&a, &b).into_iter().cloned().collect();
+        assert_eq!(coordinates(&unique), coordinates(&regions(vec![("chr1", 60, 70), ("chr2", 0, 10)])));
+
+        let parts: Vec<SimpleRegion> = subtract(&a, &b).into_iter().map(|p| p.1).collect();
+        assert_eq!(
+            coordinates(&parts),
+            coordinates(&regions(vec![("chr1", 15, 20), ("chr1", 35, 50), ("chr1", 60, 70), ("chr2", 0, 10)]))
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        let a = regions(vec![("chr1", 10, 20), ("chr1", 15, 30), ("chr1", 90, 100)]);
+        assert_eq!(
+            coordinates(&complement(&a, &genome())),
+            coordinates(&regions(vec![("chr1", 0, 10), ("chr1", 30, 90), ("chr2", 0, 50)]))
+        );
+    }
+
+    #[test]
+    fn test_slop_and_flank() {
+        let a = regions(vec![("chr1", 5, 20), ("chr2", 40, 45)]);
+        let extended: Vec<SimpleRegion> = slop(&a, 10, 10, &genome()).unwrap().into_iter().map(|p| p.1).collect();
+        assert_eq!(coordinates(&extended), coordinates(&regions(vec![("chr1", 0, 30), ("chr2", 30, 50)])));
+
+        let flanks: Vec<SimpleRegion> = flank(&a, 0, 10, &genome()).unwrap().into_iter().map(|p| p.1).collect();
+        assert_eq!(coordinates(&flanks), coordinates(&regions(vec![("chr1", 20, 30), ("chr2", 45, 50)])));
+
+        assert!(slop(&regions(vec![("chr3", 0, 1)]), 1, 1, &genome()).is_err());
+    }
+
+    #[test]
+    fn test_windows() {
+        let w = windows(&regions(vec![("chr2", 0, 50)]), 20, 15);
+        assert_eq!(coordinates(&w), coordinates(&regions(vec![("chr2", 0, 20), ("chr2", 15, 35), ("chr2", 30, 50)])));
+    }
+}
@@ -0,0 +1,275 @@
+use io::compress::DecompressReader;
+use io::fai::FaiIndex;
+use io::vcf::VcfHeader;
+use model::{Region, SimpleRegion};
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// The names and lengths of the templates (contigs) of a genome in the order
+/// they are defined in. Templates can be looked up by their name, by a registered
+/// alias, or by the common alternative name that adds or removes the `chr` prefix
+/// (e.g., `chr1` and `1` or `chrM` and `MT`).
+#[derive(Clone, Debug)]
+pub struct SequenceDictionary {
+    sequences: Vec<(String, usize)>,
+    index: HashMap<String, usize>,
+    aliases: BTreeMap<String, String>,
+}
+
+impl SequenceDictionary {
+    /// Creates an empty dictionary
+    pub fn new() -> Self {
+        SequenceDictionary {
+            sequences: Vec::new(),
+            index: HashMap::new(),
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a template with given name and length. A template that is already
+    /// defined is updated with the new length.
+    pub fn with_sequence<S: ToString>(mut self, name: S, length: usize) -> Self {
+        let name = name.to_string();
+        match self.index.get(&name) {
+            Some(&i) => self.sequences[i].1 = length,
+            None => {
+                self.index.insert(name.clone(), self.sequences.len());
+                self.sequences.push((name, length));
+            }
+        }
+        self
+    }
+
+    /// Registers `alias` as an alternative name for the template `name`
+    pub fn with_alias<A: ToString, S: ToString>(mut self, alias: A, name: S) -> Self {
+        self.aliases.insert(alias.to_string(), name.to_string());
+        self
+    }
+
+    /// Creates the dictionary from the records of a FASTA index
+    pub fn from_fai(index: &FaiIndex) -> Self {
+        index
+            .records()
+            .iter()
+            .fold(SequenceDictionary::new(), |d, r| d.with_sequence(r.name(), r.length()))
+    }
+
+    /// Creates the dictionary for a FASTA file from its `.fai` index. If the index
+    /// does not exist, it is built by scanning the FASTA file.
+    pub fn for_fasta<P: AsRef<Path> + Display>(fasta_filename: &P) -> Result<Self, String> {
+        let fai_filename = format!("{}.fai", fasta_filename);
+        let index = match Path::new(&fai_filename).exists() {
+            true => FaiIndex::read_fai(&fai_filename).map_err(|e| format!("Can not read FASTA index '{}': {}", fai_filename, e))?,
+            false => FaiIndex::build_for(fasta_filename)?,
+        };
+        Ok(SequenceDictionary::from_fai(&index))
+    }
+
+    /// Creates the dictionary from the `##contig` lines of a VCF header. Contigs
+    /// without a length are skipped.
+    pub fn from_vcf_header(header: &VcfHeader) -> Self {
+        let mut dictionary = SequenceDictionary::new();
+        for contig in header.contigs() {
+            match contig.length() {
+                Some(l) => dictionary = dictionary.with_sequence(contig.id(), l),
+                None => warn!("Skipping contig '{}' without length", contig.id()),
+            }
+        }
+        dictionary
+    }
+
+    /// Creates the dictionary from the `@SQ` lines of a SAM header text
+    pub fn from_sam_header(text: &str) -> Result<Self, String> {
+        let mut dictionary = SequenceDictionary::new();
+        for (i, line) in text.lines().enumerate() {
+            if !line.starts_with("@SQ\t") {
+                continue;
+            }
+            let mut name = None;
+            let mut length = None;
+            for field in line.split('\t').skip(1) {
+                if field.starts_with("SN:") {
+                    name = Some(&field[3..]);
+                } else if field.starts_with("LN:") {
+                    length = match field[3..].parse::<usize>() {
+                        Ok(l) => Some(l),
+                        Err(e) => return Err(format!("Line {}: Can not parse length '{}': {}", i + 1, &field[3..], e)),
+                    };
+                }
+            }
+            match (name, length) {
+                (Some(n), Some(l)) => dictionary = dictionary.with_sequence(n, l),
+                _ => return Err(format!("Line {}: Expecting SN and LN in @SQ line", i + 1)),
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Reads a genome file with the template name and length in the first two tab
+    /// separated columns. This covers `.genome` and `chrom.sizes` files but also
+    /// FASTA index (`.fai`) files.
+    pub fn read_genome_file<P: AsRef<Path> + Display>(filename: &P) -> Result<Self, String> {
+        let fh = match DecompressReader::open(filename) {
+            Ok(fh) => fh,
+            Err(e) => return Err(format!("Can not open genome file '{}': {}", filename, e)),
+        };
+        let mut dictionary = SequenceDictionary::new();
+        for (i, line) in BufReader::new(fh).lines().enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => return Err(format!("Can not read genome file '{}': {}", filename, e)),
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cells: Vec<&str> = line.split('\t').collect();
+            match cells.get(1).map(|c| c.trim().parse::<usize>()) {
+                Some(Ok(l)) => dictionary = dictionary.with_sequence(cells[0], l),
+                _ => return Err(format!("Line {}: Expecting template name and length in '{}'", i + 1, filename)),
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Returns the number of templates
+    pub fn len(&self) -> usize {
+        self.sequences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    /// Returns the template names in the order they are defined in
+    pub fn names(&self) -> Vec<String> {
+        self.sequences.iter().map(|s| s.0.clone()).collect()
+    }
+
+    /// Returns the alternative names that are tried for a template name that is
+    /// not part of the dictionary
+    fn alternative_names(name: &str) -> Vec<String> {
+        let stripped = match name.starts_with("chr") {
+            true => &name[3..],
+            false => name,
+        };
+        match stripped {
+            "M" | "MT" => vec!["chrM", "chrMT", "M", "MT"].into_iter().map(|n| n.to_string()).collect(),
+            _ if stripped.len() < name.len() => vec![stripped.to_string()],
+            _ => vec![format!("chr{}", name)],
+        }
+    }
+
+    /// Returns the name of the template as defined in the dictionary for a name or
+    /// an alias of the template.
+    pub fn resolve<S: AsRef<str>>(&self, name: S) -> Option<String> {
+        let name = name.as_ref();
+        let name = match self.aliases.get(name) {
+            Some(n) => n.as_str(),
+            None => name,
+        };
+        let is_defined = |n: &str| self.index.contains_key(n);
+        if is_defined(name) {
+            return Some(name.to_string());
+        }
+        SequenceDictionary::alternative_names(name).into_iter().find(|n| is_defined(n))
+    }
+
+    /// Returns the length of the template with given name or alias
+    pub fn length<S: AsRef<str>>(&self, name: S) -> Option<usize> {
+        self.resolve(name).map(|n| self.sequences[self.index[&n]].1)
+    }
+
+    /// Checks that the template of the region is defined and that the region lies
+    /// within the template.
+    pub fn validate<R: Region>(&self, region: &R) -> Result<(), String> {
+        match self.length(region.template()) {
+            None => Err(format!("Can not find template '{}' in sequence dictionary", region.template())),
+            Some(l) if region.end() > l => Err(format!(
+                "Region {} exceeds the length {} of template '{}'",
+                region.display_string(),
+                l,
+                region.template()
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Returns the part of the region that lies within its template. The returned
+    /// region uses the template name as defined in the dictionary.
+    pub fn clamp<R: Region>(&self, region: &R) -> Result<SimpleRegion, String> {
+        let name = match self.resolve(region.template()) {
+            Some(n) => n,
+            None => return Err(format!("Can not find template '{}' in sequence dictionary", region.template())),
+        };
+        let length = self.length(&name).unwrap();
+        let offset = cmp::min(region.offset(), length);
+        let end = cmp::min(region.end(), length);
+        Ok(SimpleRegion::new(name, offset, end - offset))
+    }
+
+    /// Returns one region per template that spans the whole template
+    pub fn regions(&self) -> Vec<SimpleRegion> {
+        self.sequences.iter().map(|s| SimpleRegion::new(s.0.as_str(), 0, s.1)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fai::FaiIndex;
+    use io::vcf::VcfStream;
+    use model::{Region, SequenceDictionary, SimpleRegion};
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn test_loaders() {
+        let fai = SequenceDictionary::from_fai(&FaiIndex::read_fai(&"testdata/toy.fasta.fai").unwrap());
+        assert_eq!(fai.names(), vec!["ref", "ref2"]);
+        assert_eq!(fai.length("ref2"), Some(40));
+        assert_eq!(SequenceDictionary::for_fasta(&"testdata/toy_blocks.fasta.gz").unwrap().len(), 2);
+
+        let genome = SequenceDictionary::read_genome_file(&"testdata/toy.fasta.fai").unwrap();
+        assert_eq!(genome.regions().iter().map(|r| r.length()).collect::<Vec<usize>>(), vec![45, 40]);
+
+        let mut sam = String::new();
+        File::open("testdata/toy.sam").unwrap().read_to_string(&mut sam).unwrap();
+        let sam = SequenceDictionary::from_sam_header(&sam).unwrap();
+        assert_eq!((sam.len(), sam.length("ref")), (2, Some(45)));
+        assert!(SequenceDictionary::from_sam_header("@SQ\tSN:ref\tLN:x").is_err());
+
+        let vcf = VcfStream::open(&"testdata/toy.vcf").unwrap();
+        let vcf = SequenceDictionary::from_vcf_header(vcf.header());
+        assert_eq!(vcf.names(), fai.names());
+    }
+
+    #[test]
+    fn test_aliases() {
+        let dictionary = SequenceDictionary::new()
+            .with_sequence("chr1", 100)
+            .with_sequence("MT", 16)
+            .with_sequence("scaffold_1", 10)
+            .with_alias("contig1", "scaffold_1");
+        assert_eq!(dictionary.resolve("chr1"), Some("chr1".to_string()));
+        assert_eq!(dictionary.resolve("1"), Some("chr1".to_string()));
+        assert_eq!(dictionary.resolve("chrM"), Some("MT".to_string()));
+        assert_eq!(dictionary.resolve("contig1"), Some("scaffold_1".to_string()));
+        assert_eq!(dictionary.resolve("2"), None);
+    }
+
+    #[test]
+    fn test_validate_and_clamp() {
+        let dictionary = SequenceDictionary::new().with_sequence("chr1", 100);
+        assert!(dictionary.validate(&SimpleRegion::new("1", 90, 10)).is_ok());
+        assert!(dictionary.validate(&SimpleRegion::new("1", 90, 11)).is_err());
+        assert!(dictionary.validate(&SimpleRegion::new("2", 0, 1)).is_err());
+
+        let clamped = dictionary.clamp(&SimpleRegion::new("1", 90, 20)).unwrap();
+        assert_eq!((clamped.template(), clamped.offset(), clamped.end()), ("chr1".to_string(), 90, 100));
+        let clamped = dictionary.clamp(&SimpleRegion::new("chr1", 120, 20)).unwrap();
+        assert_eq!((clamped.offset(), clamped.length()), (100, 0));
+    }
+}
@@ -0,0 +1,540 @@
+use io::fasta::FastaReader;
+use io::gtf::{GtfFeature, GtfFormat, GtfHierarchy, GtfRecord};
+use model::{Region, SimpleRegion, Strand};
+use sequence::{DnaSequence, GeneticCode, Peptide, Sequence};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A part of the coding sequence of a transcript that is located on a single exon.
+#[derive(Clone, Debug)]
+pub struct CdsSegment {
+    region: SimpleRegion,
+    phase: usize,
+}
+
+impl CdsSegment {
+    pub fn new(region: SimpleRegion, phase: usize) -> Self {
+        assert!(phase < 3);
+        CdsSegment {
+            region: region,
+            phase: phase,
+        }
+    }
+
+    /// Returns the number of nucleotides that have to be skipped from the 5' end
+    /// of the segment (in reading direction) to reach the first complete codon.
+    pub fn phase(&self) -> usize {
+        self.phase
+    }
+
+    pub fn region(&self) -> &SimpleRegion {
+        &self.region
+    }
+}
+
+impl Region for CdsSegment {
+    fn template(&self) -> String {
+        self.region.template()
+    }
+
+    fn offset(&self) -> usize {
+        self.region.offset()
+    }
+
+    fn length(&self) -> usize {
+        self.region.length()
+    }
+}
+
+
+/// A transcript consisting of exons and (if it is protein coding) the coding
+/// segments. The region of the transcript spans from the first to the last exon.
+///
+/// All regions are kept on the forward strand of the template but the accessors
+/// return them in transcription order, i.e., in descending order for transcripts on
+/// the reverse strand.
+#[derive(Clone, Debug)]
+pub struct Transcript {
+    id: String,
+    gene_id: Option<String>,
+    name: Option<String>,
+    template: String,
+    strand: Strand,
+    exons: Vec<SimpleRegion>,
+    cds: Vec<CdsSegment>,
+    five_prime_utrs: Vec<SimpleRegion>,
+    three_prime_utrs: Vec<SimpleRegion>,
+    start_codon: Vec<SimpleRegion>,
+    stop_codon: Vec<SimpleRegion>,
+}
+
+impl Transcript {
+    pub fn new<S: ToString, T: ToString>(id: S, template: T, strand: Strand) -> Self {
+        Transcript {
+            id: id.to_string(),
+            gene_id: None,
+            name: None,
+            template: template.to_string(),
+            strand: strand,
+            exons: Vec::new(),
+            cds: Vec::new(),
+            five_prime_utrs: Vec::new(),
+            three_prime_utrs: Vec::new(),
+            start_codon: Vec::new(),
+            stop_codon: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn gene_id(&self) -> Option<String> {
+        self.gene_id.clone()
+    }
+
+    pub fn with_gene_id<S: ToString>(mut self, gene_id: S) -> Self {
+        self.gene_id = Some(gene_id.to_string());
+        self
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn with_name<S: ToString>(mut self, name: S) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn strand(&self) -> Strand {
+        self.strand.clone()
+    }
+
+    pub fn with_exon(mut self, exon: SimpleRegion) -> Self {
+        self.exons.push(exon);
+        self.exons.sort_by(|a, b| a.compare(b));
+        self
+    }
+
+    /// Adds a coding segment. The phases of all segments are recalculated from the
+    /// segment lengths, only the phase of the first segment in reading direction is
+    /// kept.
+    pub fn with_cds(mut self, cds: SimpleRegion, phase: usize) -> Self {
+        self.cds.push(CdsSegment::new(cds, phase));
+        self.cds.sort_by(|a, b| a.compare(b));
+        self.update_phases();
+        self
+    }
+
+    pub fn with_five_prime_utr(mut self, utr: SimpleRegion) -> Self {
+        self.five_prime_utrs.push(utr);
+        self.five_prime_utrs.sort_by(|a, b| a.compare(b));
+        self
+    }
+
+    pub fn with_three_prime_utr(mut self, utr: SimpleRegion) -> Self {
+        self.three_prime_utrs.push(utr);
+        self.three_prime_utrs.sort_by(|a, b| a.compare(b));
+        self
+    }
+
+    pub fn with_start_codon(mut self, codon: SimpleRegion) -> Self {
+        self.start_codon.push(codon);
+        self.start_codon.sort_by(|a, b| a.compare(b));
+        self
+    }
+
+    /// Adds (a part of) the stop codon. GTF files exclude the stop codon from the
+    /// CDS records, so it is appended to the coding segments if necessary.
+    pub fn with_stop_codon(mut self, codon: SimpleRegion) -> Self {
+        self.stop_codon.push(codon.clone());
+        self.stop_codon.sort_by(|a, b| a.compare(b));
+        if !self.cds.is_empty() && !self.cds.iter().any(|c| c.overlaps(&codon)) {
+            self.extend_cds(codon);
+        }
+        self
+    }
+
+    /// Adds the region to the coding segments by extending an adjacent segment.
+    fn extend_cds(&mut self, codon: SimpleRegion) {
+        let adjacent = self.cds.iter().position(|c| c.end() == codon.offset() || codon.end() == c.offset());
+        match adjacent {
+            Some(i) => {
+                let offset = if self.cds[i].offset() < codon.offset() { self.cds[i].offset() } else { codon.offset() };
+                let region = SimpleRegion::new(self.template.clone(), offset, self.cds[i].length() + codon.length());
+                self.cds[i] = CdsSegment::new(region, self.cds[i].phase());
+            }
+            None => self.cds.push(CdsSegment::new(codon, 0)),
+        }
+        self.cds.sort_by(|a, b| a.compare(b));
+        self.update_phases();
+    }
+
+    /// Recalculates the phases of all segments but the first in reading direction.
+    fn update_phases(&mut self) {
+        let mut order: Vec<usize> = (0..self.cds.len()).collect();
+        if self.strand == Strand::Backward {
+            order.reverse();
+        }
+        let mut coding_length = 0;
+        for (n, i) in order.into_iter().enumerate() {
+            if n > 0 {
+                let phase = (3 - coding_length % 3) % 3;
+                self.cds[i] = CdsSegment::new(self.cds[i].region().clone(), phase);
+            }
+            coding_length += self.cds[i].length() - self.cds[i].phase();
+        }
+    }
+
+    /// Orders the regions by transcription direction
+    fn oriented<R: Clone>(&self, regions: &Vec<R>) -> Vec<R> {
+        let mut regions = regions.clone();
+        if self.strand == Strand::Backward {
+            regions.reverse();
+        }
+        regions
+    }
+
+    /// Returns the exons in transcription order
+    pub fn exons(&self) -> Vec<SimpleRegion> {
+        self.oriented(&self.exons)
+    }
+
+    /// Returns the introns, i.e., the gaps between the exons, in transcription order
+    pub fn introns(&self) -> Vec<SimpleRegion> {
+        let introns = self.exons.windows(2)
+            .filter(|w| w[0].end() < w[1].offset())
+            .map(|w| SimpleRegion::new(self.template.clone(), w[0].end(), w[1].offset() - w[0].end()))
+            .collect();
+        self.oriented(&introns)
+    }
+
+    /// Returns the coding segments (including the stop codon) in reading order
+    pub fn cds(&self) -> Vec<CdsSegment> {
+        self.oriented(&self.cds)
+    }
+
+    pub fn is_coding(&self) -> bool {
+        !self.cds.is_empty()
+    }
+
+    pub fn five_prime_utrs(&self) -> Vec<SimpleRegion> {
+        self.oriented(&self.five_prime_utrs)
+    }
+
+    pub fn three_prime_utrs(&self) -> Vec<SimpleRegion> {
+        self.oriented(&self.three_prime_utrs)
+    }
+
+    pub fn start_codon(&self) -> Vec<SimpleRegion> {
+        self.oriented(&self.start_codon)
+    }
+
+    pub fn stop_codon(&self) -> Vec<SimpleRegion> {
+        self.oriented(&self.stop_codon)
+    }
+
+    /// Returns the length of the mature (spliced) transcript
+    pub fn spliced_length(&self) -> usize {
+        self.exons.iter().map(|e| e.length()).sum()
+    }
+
+    /// Returns the number of coding nucleotides
+    pub fn cds_length(&self) -> usize {
+        self.cds.iter().map(|c| c.length()).sum()
+    }
+
+    /// Converts an offset on the template into the position within the mature
+    /// transcript (counted from the 5' end). Returns `None` if the offset is not
+    /// located within an exon.
+    pub fn spliced_position(&self, offset: usize) -> Option<usize> {
+        let mut position = 0;
+        for exon in self.exons() {
+            if exon.offset() <= offset && offset < exon.end() {
+                return match self.strand {
+                    Strand::Forward => Some(position + offset - exon.offset()),
+                    Strand::Backward => Some(position + exon.end() - 1 - offset),
+                };
+            }
+            position += exon.length();
+        }
+        None
+    }
+
+    /// Converts a position within the mature transcript into the offset on the template.
+    /// This is the inverse of `spliced_position()`.
+    pub fn template_offset(&self, position: usize) -> Option<usize> {
+        let mut remaining = position;
+        for exon in self.exons() {
+            if remaining < exon.length() {
+                return match self.strand {
+                    Strand::Forward => Some(exon.offset() + remaining),
+                    Strand::Backward => Some(exon.end() - 1 - remaining),
+                };
+            }
+            remaining -= exon.length();
+        }
+        None
+    }
+
+    /// Returns the sequence of the mature transcript in transcription direction,
+    /// i.e., the exons are joined and reverse-complemented for the reverse strand.
+    pub fn spliced_sequence<F: FastaReader>(&self, fasta: &mut F) -> Result<DnaSequence, String> {
+        let exons = self.exons.clone();
+        self.fetch_joined(fasta, &exons)
+    }
+
+    /// Returns the coding sequence in reading direction. The nucleotides before the
+    /// first complete codon (see `CdsSegment::phase()`) are skipped.
+    pub fn cds_sequence<F: FastaReader>(&self, fasta: &mut F) -> Result<DnaSequence, String> {
+        let first_phase = match self.cds().first() {
+            Some(c) => c.phase(),
+            None => return Err(format!("Transcript '{}' is not protein coding", self.id)),
+        };
+        let regions = self.cds.iter().map(|c| c.region().clone()).collect();
+        let sequence = self.fetch_joined(fasta, &regions)?;
+        Ok(sequence.subsequence(first_phase, sequence.length() - first_phase))
+    }
+
+    /// Translates the coding sequence. An (alternative) start codon is translated
+    /// into methionine if the coding sequence is complete at its 5' end.
+    pub fn peptide<F: FastaReader>(&self, fasta: &mut F, code: &GeneticCode) -> Result<Peptide, String> {
+        let sequence = self.cds_sequence(fasta)?;
+        match self.cds()[0].phase() {
+            0 => Ok(Peptide::translate_cds(&sequence, code)),
+            _ => Ok(Peptide::translate(&sequence, code)),
+        }
+    }
+
+    /// Fetches and joins the regions (in forward strand order) and orients the result
+    fn fetch_joined<F: FastaReader>(&self, fasta: &mut F, regions: &Vec<SimpleRegion>) -> Result<DnaSequence, String> {
+        let mut joined = String::new();
+        for region in regions {
+            let record = match fasta.search_region(&self.template, region.offset(), region.length()) {
+                Some(r) => r,
+                None => return Err(format!("Can not fetch sequence of '{}' for transcript '{}'", self.template, self.id)),
+            };
+            let sequence = record.sequence();
+            if sequence.len() != region.length() {
+                return Err(format!(
+                    "Region {}:{}-{} of transcript '{}' exceeds the template sequence",
+                    self.template,
+                    region.offset() + 1,
+                    region.end(),
+                    self.id
+                ));
+            }
+            joined.push_str(&sequence);
+        }
+        let sequence = joined.parse::<DnaSequence>()?;
+        match self.strand {
+            Strand::Forward => Ok(sequence),
+            Strand::Backward => Ok(sequence.reverse_strand()),
+        }
+    }
+
+    /// Assembles the transcripts from GTF or GFF3 records (see `from_gtf_hierarchy()`).
+    pub fn from_gtf_records(records: &[GtfRecord]) -> Result<Vec<Transcript>, String> {
+        Transcript::from_gtf_hierarchy(&GtfHierarchy::new(records.to_vec()))
+    }
+
+    /// Assembles the transcripts from the children in the GTF or GFF3 hierarchy. The
+    /// exons, coding segments, UTRs, and start and stop codons are grouped by their
+    /// parent and the transcripts are returned in the order of their first record.
+    /// Transcripts without exon records use the coding segments as exons.
+    pub fn from_gtf_hierarchy(hierarchy: &GtfHierarchy) -> Result<Vec<Transcript>, String> {
+        let mut order: Vec<String> = Vec::new();
+        let mut seen: BTreeSet<String> = BTreeSet::new();
+        for record in hierarchy.records().iter().filter(|r| is_transcript_part(r)) {
+            for id in record.parent_ids() {
+                if seen.insert(id.clone()) {
+                    order.push(id);
+                }
+            }
+        }
+
+        let mut transcripts = Vec::new();
+        for id in order {
+            let parts: Vec<&GtfRecord> = hierarchy.children(&id).into_iter().filter(|r| is_transcript_part(r)).collect();
+            let transcript = Transcript::from_parts(&id, hierarchy.find(&id).into_iter().next(), &parts)?;
+            transcripts.push(transcript);
+        }
+        Ok(transcripts)
+    }
+
+    fn from_parts(id: &String, record: Option<&GtfRecord>, parts: &Vec<&GtfRecord>) -> Result<Transcript, String> {
+        let first = match record {
+            Some(r) => r,
+            None => parts[0],
+        };
+        let strand = match first.strand() {
+            Some(s) => s,
+            None => return Err(format!("Transcript '{}' has no strand", id)),
+        };
+        for part in parts {
+            if part.seqname() != first.seqname() || part.strand() != Some(strand.clone()) {
+                return Err(format!(
+                    "Transcript '{}' has features on different templates or strands",
+                    id
+                ));
+            }
+        }
+
+        let mut transcript = Transcript::new(id, first.seqname(), strand);
+        let gene_id = match first.format() {
+            GtfFormat::Gtf => first.gene_id(),
+            GtfFormat::Gff3 => record.and_then(|r| r.parent_ids().into_iter().next()),
+        };
+        if let Some(g) = gene_id {
+            transcript = transcript.with_gene_id(g);
+        }
+        if let Some(n) = first.annotation("transcript_name").or(first.annotation("Name")) {
+            transcript = transcript.with_name(n);
+        }
+
+        let region = |r: &GtfRecord| SimpleRegion::new(r.seqname(), r.offset(), r.length());
+        let mut stop_codon = Vec::new();
+        for part in parts {
+            transcript = match part.feature() {
+                Some(GtfFeature::Exon) => transcript.with_exon(region(part)),
+                Some(GtfFeature::CDS) => transcript.with_cds(region(part), part.frame().unwrap_or(0) % 3),
+                Some(GtfFeature::FivePrimeUTR) => transcript.with_five_prime_utr(region(part)),
+                Some(GtfFeature::ThreePrimeUTR) => transcript.with_three_prime_utr(region(part)),
+                Some(GtfFeature::StartCodon) => transcript.with_start_codon(region(part)),
+                Some(GtfFeature::StopCodon) => {
+                    stop_codon.push(region(part));
+                    transcript
+                }
+                _ => transcript,
+            };
+        }
+        // The stop codon is added last because it extends the coding segments
+        for codon in stop_codon {
+            transcript = transcript.with_stop_codon(codon);
+        }
+        if transcript.exons.is_empty() {
+            transcript.exons = transcript.cds.iter().map(|c| c.region().clone()).collect();
+        }
+        if transcript.exons.is_empty() {
+            return Err(format!("Transcript '{}' has neither exons nor coding segments", id));
+        }
+        Ok(transcript)
+    }
+}
+
+/// Returns `true` if the record describes a part of a transcript
+fn is_transcript_part(record: &GtfRecord) -> bool {
+    match record.feature() {
+        Some(GtfFeature::Exon) | Some(GtfFeature::CDS) | Some(GtfFeature::FivePrimeUTR) |
+        Some(GtfFeature::ThreePrimeUTR) | Some(GtfFeature::StartCodon) | Some(GtfFeature::StopCodon) => true,
+        _ => false,
+    }
+}
+
+impl Region for Transcript {
+    fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.exons.first().map(|e| e.offset()).unwrap_or(0)
+    }
+
+    fn length(&self) -> usize {
+        match self.exons.last() {
+            Some(e) => e.end() - self.offset(),
+            None => 0,
+        }
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}:{}-{}({})", self.id, self.template(), self.offset() + 1, self.end(), self.strand)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fasta::IndexedFastaFile;
+    use io::gtf::GtfStream;
+    use model::{Region, Strand, Transcript};
+    use sequence::GeneticCode;
+
+    fn load(filename: &str) -> Vec<Transcript> {
+        let records = GtfStream::open(&filename).unwrap().read_records().unwrap();
+        Transcript::from_gtf_records(&records).unwrap()
+    }
+
+    #[test]
+    fn test_coding_transcript() {
+        for filename in &["testdata/toy.gtf", "testdata/toy.gff3"] {
+            let transcripts = load(filename);
+            assert_eq!(transcripts.len(), 3);
+
+            let t = &transcripts[0];
+            assert_eq!(t.id(), "t1");
+            assert_eq!(t.gene_id(), Some("g1".to_string()));
+            assert_eq!(t.strand(), Strand::Forward);
+            assert_eq!((t.offset(), t.length()), (1, 39));
+            assert_eq!(t.spliced_length(), 32);
+            assert_eq!(t.introns().len(), 1);
+            assert_eq!((t.introns()[0].offset(), t.introns()[0].length()), (20, 7));
+
+            // The stop codon is part of the CDS for GTF and GFF3
+            let cds = t.cds();
+            assert_eq!(cds.len(), 2);
+            assert_eq!(t.cds_length(), 21);
+            assert_eq!((cds[1].offset(), cds[1].length(), cds[1].phase()), (27, 4, 1));
+        }
+    }
+
+    #[test]
+    fn test_reverse_strand() {
+        let transcripts = load("testdata/toy.gtf");
+        let t = &transcripts[1];
+        assert_eq!(t.id(), "t2");
+        assert_eq!(t.strand(), Strand::Backward);
+        assert!(!t.is_coding());
+
+        let exons = t.exons();
+        assert_eq!(exons[0].offset(), 19);
+        assert_eq!(exons[1].offset(), 4);
+        assert_eq!(t.spliced_length(), 27);
+
+        assert_eq!(t.spliced_position(37), Some(0));
+        assert_eq!(t.spliced_position(19), Some(18));
+        assert_eq!(t.spliced_position(11), Some(19));
+        assert_eq!(t.spliced_position(15), None);
+        assert_eq!(t.template_offset(19), Some(11));
+        assert_eq!(t.template_offset(27), None);
+    }
+
+    #[test]
+    fn test_sequences() {
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let transcripts = load("testdata/toy.gtf");
+
+        let t1 = &transcripts[0];
+        assert_eq!(t1.spliced_sequence(&mut fasta).unwrap().to_string(), "GCATGTTAGATAAGATAGCGTAGGCAGTCAGC");
+        assert_eq!(t1.cds_sequence(&mut fasta).unwrap().to_string(), "ATGTTAGATAAGATAGCGTAG");
+        assert_eq!(t1.peptide(&mut fasta, &GeneticCode::standard()).unwrap().to_string(), "MLDKIA*");
+
+        let t2 = &transcripts[1];
+        assert_eq!(t2.spliced_sequence(&mut fasta).unwrap().to_string(), "CGTAGTTGCTCTGTAGACTTTTATAAA");
+        assert!(t2.cds_sequence(&mut fasta).is_err());
+    }
+
+    #[test]
+    fn test_phases() {
+        let t = Transcript::new("t", "ref", Strand::Backward)
+            .with_cds(::model::SimpleRegion::new("ref", 30, 10), 2)
+            .with_cds(::model::SimpleRegion::new("ref", 0, 5), 0)
+            .with_cds(::model::SimpleRegion::new("ref", 10, 10), 0);
+        let phases: Vec<usize> = t.cds().iter().map(|c| c.phase()).collect();
+        // 8 and 17 coding nucleotides leave one nucleotide to complete the codon
+        assert_eq!(phases, vec![2, 1, 1]);
+    }
+}
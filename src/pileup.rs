@@ -1,52 +1,505 @@
-use std::collections::HashMap;
-use dna::*;
-use read::Read;
+use alignment::CigarOperation;
+use io::bam::{AlignmentRecord, IndexedBamReader};
+use io::fasta::FastaReader;
+use model::{Region, SimpleRegion};
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 
-#[derive(Clone,Debug)]
+/// A single read at a pileup position. The read either has a base aligned to
+/// the position, a deletion, or skips the position (like an intron).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PileupBase {
+    name: String,
+    base: char,
+    quality: u8,
+    mapping_quality: u8,
+    is_reverse: bool,
+    is_deletion: bool,
+    is_skip: bool,
+    is_read_start: bool,
+    is_read_end: bool,
+    insertion: Option<String>,
+    deletion: Option<String>,
+}
+
+impl PileupBase {
+    /// Returns the name of the read
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the upper case read base or `*` for deleted and skipped positions
+    pub fn base(&self) -> char {
+        self.base
+    }
+
+    /// Returns the base quality. Deleted and skipped positions have the quality
+    /// of the preceding read base.
+    pub fn quality(&self) -> u8 {
+        self.quality
+    }
+
+    pub fn mapping_quality(&self) -> u8 {
+        self.mapping_quality
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        self.is_reverse
+    }
+
+    pub fn is_deletion(&self) -> bool {
+        self.is_deletion
+    }
+
+    pub fn is_skip(&self) -> bool {
+        self.is_skip
+    }
+
+    /// Returns `true` if this is the first template position of the read
+    pub fn is_read_start(&self) -> bool {
+        self.is_read_start
+    }
+
+    /// Returns `true` if this is the last template position of the read
+    pub fn is_read_end(&self) -> bool {
+        self.is_read_end
+    }
+
+    /// Returns the bases inserted after this position
+    pub fn insertion(&self) -> Option<String> {
+        self.insertion.clone()
+    }
+
+    /// Returns the template bases deleted after this position. Unknown template
+    /// bases are reported as `N`.
+    pub fn deletion(&self) -> Option<String> {
+        self.deletion.clone()
+    }
+
+    /// Writes the base in the notation of `samtools mpileup`
+    fn write_mpileup(&self, reference: Option<char>, out: &mut String) {
+        let strand = |s: &str| match self.is_reverse {
+            true => s.to_lowercase(),
+            false => s.to_uppercase(),
+        };
+        if self.is_read_start {
+            out.push('^');
+            out.push((cmp::min(self.mapping_quality, 93) + 33) as char);
+        }
+        if self.is_skip {
+            out.push(if self.is_reverse { '<' } else { '>' });
+        } else if self.is_deletion {
+            out.push('*');
+        } else if reference.map(|r| r.to_ascii_uppercase()) == Some(self.base) {
+            out.push(if self.is_reverse { ',' } else { '.' });
+        } else {
+            out.push_str(&strand(&self.base.to_string()));
+        }
+        if let Some(ref i) = self.insertion {
+            out.push_str(&format!("+{}{}", i.len(), strand(i)));
+        }
+        if let Some(ref d) = self.deletion {
+            out.push_str(&format!("-{}{}", d.len(), strand(d)));
+        }
+        if self.is_read_end {
+            out.push('$');
+        }
+    }
+}
+
+
+/// All reads covering a single template position
+#[derive(Clone, Debug, PartialEq)]
+pub struct PileupColumn {
+    template: String,
+    position: usize,
+    reference: Option<char>,
+    bases: Vec<PileupBase>,
+}
+
+impl PileupColumn {
+    pub fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    /// Returns the 0-based template position
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the template base if a reference sequence was given
+    pub fn reference(&self) -> Option<char> {
+        self.reference
+    }
+
+    pub fn bases(&self) -> &[PileupBase] {
+        &self.bases
+    }
+
+    /// Returns the number of reads at the position including deletions and
+    /// skipped positions
+    pub fn depth(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Returns the number of reads with the given base on the forward and on
+    /// the reverse strand. Deletions are counted as base `*`.
+    pub fn strand_counts(&self, base: char) -> (usize, usize) {
+        let base = base.to_ascii_uppercase();
+        self.bases
+            .iter()
+            .filter(|b| !b.is_skip && b.base == base)
+            .fold((0, 0), |(f, r), b| match b.is_reverse {
+                true => (f, r + 1),
+                false => (f + 1, r),
+            })
+    }
+
+    /// Returns the number of reads with the given base on both strands
+    pub fn count(&self, base: char) -> usize {
+        let (f, r) = self.strand_counts(base);
+        f + r
+    }
+
+    /// Returns the number of reads for all observed bases including `*` for
+    /// deletions
+    pub fn counts(&self) -> BTreeMap<char, usize> {
+        let mut counts = BTreeMap::new();
+        for b in self.bases.iter().filter(|b| !b.is_skip) {
+            *counts.entry(b.base).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Returns the number of reads with an insertion after the position for
+    /// each inserted sequence
+    pub fn insertions(&self) -> BTreeMap<String, usize> {
+        let mut insertions = BTreeMap::new();
+        for i in self.bases.iter().filter_map(|b| b.insertion.clone()) {
+            *insertions.entry(i.to_uppercase()).or_insert(0) += 1;
+        }
+        insertions
+    }
+
+    /// Returns the number of reads with a deletion after the position for each
+    /// deleted sequence
+    pub fn deletions(&self) -> BTreeMap<String, usize> {
+        let mut deletions = BTreeMap::new();
+        for d in self.bases.iter().filter_map(|b| b.deletion.clone()) {
+            *deletions.entry(d.to_uppercase()).or_insert(0) += 1;
+        }
+        deletions
+    }
+}
+
+impl fmt::Display for PileupColumn {
+    /// Writes the column as line of `samtools mpileup` with template, 1-based
+    /// position, reference base, depth, bases, and base qualities
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut bases = String::new();
+        for b in self.bases.iter() {
+            b.write_mpileup(self.reference, &mut bases);
+        }
+        let qualities: String = self.bases
+            .iter()
+            .map(|b| (cmp::min(b.quality, 93) + 33) as char)
+            .collect();
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.template,
+            self.position + 1,
+            self.reference.unwrap_or('N'),
+            self.depth(),
+            match bases.is_empty() {
+                true => "*".to_string(),
+                false => bases,
+            },
+            match qualities.is_empty() {
+                true => "*".to_string(),
+                false => qualities,
+            }
+        )
+    }
+}
+
+
+/// Builds pileup columns from alignment records. By default, reads that are
+/// unmapped, secondary, duplicates, or fail quality checks are skipped, bases
+/// with a quality below 13 are ignored, and overlapping mates of a pair are
+/// counted only once.
+#[derive(Clone, Debug)]
 pub struct Pileup {
-    /// Stores the data mapping a position toward
-    /// a tuple of nucleotide counts. The keys are 
-    positions: HashMap<usize, usize>
+    min_base_quality: u8,
+    min_mapping_quality: u8,
+    detect_overlaps: bool,
 }
 
 impl Pileup {
-    fn get_key(&self, p: usize, n: &DnaNucleotide) -> usize {
-        p * 10 + (u8::from(n)) as usize
+    pub fn new() -> Self {
+        Pileup {
+            min_base_quality: 13,
+            min_mapping_quality: 0,
+            detect_overlaps: true,
+        }
     }
 
-    /// Internal method to add a nucleotide to the pileup at a specific position
-    fn add_pileup(&mut self, p: usize, n: &DnaNucleotide){
-        let pn = self.get_key(p, n);
+    /// Ignores aligned bases with a lower base quality. Deletions are not filtered.
+    pub fn with_min_base_quality(mut self, quality: u8) -> Self {
+        self.min_base_quality = quality;
+        self
+    }
 
-        let counts : usize = match self.positions.get(&pn) {
-            Some(c) => *c,
-            None => 0
-        };
-        self.positions.insert(pn, counts + 1usize);
+    /// Ignores reads with a lower mapping quality
+    pub fn with_min_mapping_quality(mut self, quality: u8) -> Self {
+        self.min_mapping_quality = quality;
+        self
+    }
+
+    /// Enables or disables counting overlapping mates only once. If both mates
+    /// cover a position, only the base with the higher quality is kept.
+    pub fn with_overlap_detection(mut self, detect_overlaps: bool) -> Self {
+        self.detect_overlaps = detect_overlaps;
+        self
     }
 
-    /// Returns the nucleotide count of a specific nucleotide `n` at position `p`.
-    pub fn nucleotide_count(&self, p: usize, n: &DnaNucleotide) -> usize {
-        match self.positions.get(&self.get_key(p, n)) {
-            Some(c) => *c,
-            None => 0
+    /// Returns `true` if the record is used for the pileup
+    fn accepts(&self, record: &AlignmentRecord) -> bool {
+        !record.is_unmapped() && !record.is_secondary() && !record.is_duplicate() && !record.is_qc_fail() &&
+            record.position().is_some() && record.mapping_quality() >= self.min_mapping_quality &&
+            !record.cigar().is_empty() && !record.sequence().is_empty()
+    }
+
+    /// Computes one column per position of the region. The `reference` is the
+    /// template sequence starting at `reference_offset` and is used for the
+    /// reference bases and the deleted bases.
+    pub fn columns<R: Region>(
+        &self,
+        records: &[AlignmentRecord],
+        region: &R,
+        reference: Option<(usize, &str)>,
+    ) -> Vec<PileupColumn> {
+        let reference: Option<(usize, Vec<char>)> = reference.map(|(o, s)| (o, s.chars().map(|c| c.to_ascii_uppercase()).collect()));
+        let reference_base = |p: usize| match reference {
+            Some((o, ref s)) if p >= o => s.get(p - o).cloned(),
+            _ => None,
+        };
+
+        let mut columns: Vec<PileupColumn> = (region.offset()..region.end())
+            .map(|p| PileupColumn {
+                template: region.template(),
+                position: p,
+                reference: reference_base(p),
+                bases: Vec::new(),
+            })
+            .collect();
+        let mut add = |p: usize, base: PileupBase| if p >= region.offset() && p < region.end() {
+            columns[p - region.offset()].bases.push(base);
+        };
+
+        for record in records.iter().filter(|r| self.accepts(r) && r.template() == region.template()) {
+            let sequence: Vec<char> = record.sequence().chars().map(|c| c.to_ascii_uppercase()).collect();
+            let qualities = record.qualities().map(|q| q.scores());
+            let quality = |i: usize| qualities.as_ref().map(|q| q[i]).unwrap_or(255);
+            let (start, end) = (record.offset(), record.end());
+            let base = |p: usize, q: usize, c: char| PileupBase {
+                name: record.name(),
+                base: c,
+                quality: quality(q),
+                mapping_quality: record.mapping_quality(),
+                is_reverse: record.is_reverse(),
+                is_deletion: false,
+                is_skip: false,
+                is_read_start: p == start,
+                is_read_end: p + 1 == end,
+                insertion: None,
+                deletion: None,
+            };
+
+            let operations = record.cigar().operations().to_vec();
+            let (mut p, mut q) = (start, 0usize);
+            for (i, &(l, op)) in operations.iter().enumerate() {
+                match op {
+                    _ if op.is_aligned() => for j in 0..l {
+                        let mut b = base(p + j, q + j, sequence[q + j]);
+                        if j + 1 == l {
+                            let (insertion, deletion) = Pileup::indels_after(&operations[i + 1..], q + l, p + l);
+                            b.insertion = insertion.map(|(o, n)| record.sequence()[o..o + n].to_string());
+                            b.deletion = deletion.map(|(o, n)| (o..o + n).map(|d| reference_base(d).unwrap_or('N')).collect());
+                        }
+                        if b.quality >= self.min_base_quality {
+                            add(p + j, b);
+                        }
+                    },
+                    CigarOperation::Deletion | CigarOperation::Skip => for j in 0..l {
+                        let mut b = base(p + j, cmp::max(q, 1) - 1, '*');
+                        b.is_deletion = op == CigarOperation::Deletion;
+                        b.is_skip = op == CigarOperation::Skip;
+                        add(p + j, b);
+                    },
+                    _ => {}
+                }
+                if op.consumes_query() {
+                    q += l;
+                }
+                if op.consumes_reference() {
+                    p += l;
+                }
+            }
         }
+
+        if self.detect_overlaps {
+            for column in columns.iter_mut() {
+                column.bases = Pileup::remove_overlaps(column.bases.drain(..).collect());
+            }
+        }
+        columns
     }
 
-    pub fn nucleotide_counts(&self, p: usize, nucleotides: &Vec<DnaNucleotide>) -> Vec<usize> {
-        nucleotides.iter().map(|n| self.nucleotide_count(p, n)).collect::<Vec<usize>>()
+    /// Returns the read offset and length of the insertion and the template offset
+    /// and length of the deletion that directly follow an aligned base. They are
+    /// reported at the aligned base before them. Insertions interrupted by padding
+    /// are joined.
+    fn indels_after(
+        operations: &[(usize, CigarOperation)],
+        read_offset: usize,
+        template_offset: usize,
+    ) -> (Option<(usize, usize)>, Option<(usize, usize)>) {
+        let mut inserted = 0;
+        for &(l, op) in operations.iter() {
+            match op {
+                CigarOperation::Insertion => inserted += l,
+                CigarOperation::Padding => {}
+                CigarOperation::Deletion => {
+                    let insertion = if inserted > 0 { Some((read_offset, inserted)) } else { None };
+                    return (insertion, Some((template_offset, l)));
+                }
+                _ => break,
+            }
+        }
+        match inserted {
+            0 => (None, None),
+            n => (Some((read_offset, n)), None),
+        }
     }
 
-    /// Add a read to the pileup
-    pub fn add_read(&mut self, r: &Read) {
-        if r.is_aligned() {
-            let p = r.position().unwrap();
-            for rs in r.segments().iter().filter( |rs| rs.is_aligned() ) {
-                for (o,n) in rs.sequence().iter().enumerate() {
-                    self.add_pileup(p+o, n);
+    /// Keeps only the base with the highest quality of reads with the same name
+    fn remove_overlaps(bases: Vec<PileupBase>) -> Vec<PileupBase> {
+        let mut kept: Vec<PileupBase> = Vec::with_capacity(bases.len());
+        let mut index: HashMap<String, usize> = HashMap::with_capacity(bases.len());
+        for b in bases.into_iter() {
+            match index.get(&b.name) {
+                Some(&i) => {
+                    if kept[i].quality < b.quality {
+                        kept[i] = b;
+                    }
+                }
+                None => {
+                    index.insert(b.name.clone(), kept.len());
+                    kept.push(b);
                 }
             }
         }
+        kept
+    }
+
+    /// Reads the records overlapping the region from the BAM file and computes
+    /// the pileup columns. The template sequence is read from the FASTA file if
+    /// it is given.
+    pub fn from_bam<R: Region, F: FastaReader>(
+        &self,
+        bam: &mut IndexedBamReader,
+        region: &R,
+        fasta: Option<&mut F>,
+    ) -> Result<Vec<PileupColumn>, String> {
+        let region: SimpleRegion = bam.dictionary().clamp(region)?;
+        let records = bam.query(&region)?;
+
+        // Deletions may extend beyond the region, so the reference covers all reads
+        let offset = records.iter().map(|r| r.offset()).fold(region.offset(), cmp::min);
+        let end = records.iter().map(|r| r.end()).fold(region.end(), cmp::max);
+        let reference = match fasta {
+            Some(f) => match f.search_region_as_sequence(region.template(), offset, end - offset) {
+                Some(s) => Some(s),
+                None => return Err(format!("Can not find template '{}' in FASTA file", region.template())),
+            },
+            None => None,
+        };
+        Ok(self.columns(&records, &region, reference.as_ref().map(|s| (offset, s.as_str()))))
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use io::bam::{AlignmentRecord, IndexedBamReader};
+    use io::fasta::IndexedFastaFile;
+    use model::SimpleRegion;
+    use pileup::Pileup;
+    use sequence::quality::{PhredEncoding, QualitySequence};
+
+    #[test]
+    fn test_pileup_bam() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let columns = Pileup::new()
+            .from_bam(&mut bam, &SimpleRegion::new("ref", 0, 45), Some(&mut fasta))
+            .unwrap();
+        assert_eq!(columns.len(), 45);
+
+        // r001 starts at position 7, r002 and r003 at position 9
+        assert_eq!(columns[6].to_string(), "ref\t7\tT\t1\t^?.\t~");
+        assert_eq!(columns[8].depth(), 3);
+        assert_eq!(columns[8].count('A'), 3);
+
+        // r001 and r002 have insertions after position 14, where the insertion
+        // of r002 is interrupted by padding
+        assert_eq!(columns[13].to_string(), "ref\t14\tA\t3\t.+4AGAG.+2GG.$\t~~~");
+        assert_eq!(columns[13].insertions().get("AGAG"), Some(&1));
+
+        // r001 has a deletion at position 19
+        assert_eq!(columns[17].deletions().get("G"), Some(&1));
+        assert_eq!(columns[18].counts().get(&'*'), Some(&1));
+
+        // r004 skips positions 22 to 35 and the reverse read r003 starts at 29
+        assert_eq!(columns[21].to_string(), "ref\t22\tG\t2\t.$>\t~~");
+        assert_eq!(columns[28].to_string(), "ref\t29\tT\t2\t>^?,\t~~");
+        assert_eq!(columns[28].strand_counts('T'), (0, 1));
+    }
+
+    #[test]
+    fn test_filters_and_overlaps() {
+        let quality = |s: &str| Some(QualitySequence::from_ascii(s, PhredEncoding::Phred33).unwrap());
+        let read = |name: &str, flags: u16, offset: usize, q: &str| {
+            AlignmentRecord::new(name)
+                .with_flags(flags)
+                .with_position("ref", offset)
+                .with_mapping_quality(20)
+                .with_cigar("4M")
+                .unwrap()
+                .with_sequence("ACGT", quality(q))
+                .unwrap()
+        };
+        let records = vec![
+            read("a", 0x1 | 0x40, 0, "IIII"),
+            read("a", 0x1 | 0x80 | 0x10, 2, "5III"),
+            read("b", 0x400, 0, "IIII"),
+            read("c", 0, 1, "I#II"),
+        ];
+        let region = SimpleRegion::new("ref", 0, 6);
+        let columns = Pileup::new().columns(&records, &region, Some((0, "ACGTGT")));
+        let depths: Vec<usize> = columns.iter().map(|c| c.depth()).collect();
+        assert_eq!(depths, vec![1, 2, 1, 2, 2, 1]);
+        assert!(columns[2].bases().iter().all(|b| !b.is_reverse()));
+        assert_eq!(columns[4].to_string(), "ref\t5\tG\t2\t,T$\tII");
+
+        let columns = Pileup::new()
+            .with_min_base_quality(0)
+            .with_overlap_detection(false)
+            .with_min_mapping_quality(30)
+            .columns(&records, &region, None);
+        assert!(columns.iter().all(|c| c.depth() == 0));
+        assert_eq!(columns[0].to_string(), "ref\t1\tN\t0\t*\t*");
+    }
+}
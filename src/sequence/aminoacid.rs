@@ -30,7 +30,8 @@ pub enum Aminoacid {
     Unknown,
     Stop,
 }
-#[derive(Clone, Debug)]
+/// The three letter codes of the amino acids as used by HGVS descriptions
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Aminoacid3 {
     Ala,
     Arg,
@@ -120,7 +121,7 @@ impl From<Aminoacid> for char {
             Aminoacid::N => 'N',
             Aminoacid::D => 'D',
             Aminoacid::C => 'C',
-            Aminoacid::E => 'G',
+            Aminoacid::E => 'E',
             Aminoacid::Q => 'Q',
             Aminoacid::G => 'G',
             Aminoacid::H => 'H',
@@ -229,8 +230,42 @@ impl fmt::Display for Aminoacid3 {
             Aminoacid3::Trp => write!(f, "Trp"),
             Aminoacid3::Tyr => write!(f, "Tyr"),
             Aminoacid3::Val => write!(f, "Val"),
-            Aminoacid3::Stop => write!(f, " * "),
-            _ => write!(f, " ? "),
+            Aminoacid3::Stop => write!(f, "Ter"),
+            Aminoacid3::Unknown => write!(f, "Xaa"),
+        }
+    }
+}
+
+impl FromStr for Aminoacid3 {
+    type Err = String;
+
+    /// Parses the three letter code. The stop codon is `Ter` (or `*`) and an
+    /// unknown amino acid is `Xaa`.
+    fn from_str(s: &str) -> Result<Aminoacid3, Self::Err> {
+        match s {
+            "Ala" => Ok(Aminoacid3::Ala),
+            "Arg" => Ok(Aminoacid3::Arg),
+            "Asn" => Ok(Aminoacid3::Asn),
+            "Asp" => Ok(Aminoacid3::Asp),
+            "Cys" => Ok(Aminoacid3::Cys),
+            "Glu" => Ok(Aminoacid3::Glu),
+            "Gln" => Ok(Aminoacid3::Gln),
+            "Gly" => Ok(Aminoacid3::Gly),
+            "His" => Ok(Aminoacid3::His),
+            "Ile" => Ok(Aminoacid3::Ile),
+            "Leu" => Ok(Aminoacid3::Leu),
+            "Lys" => Ok(Aminoacid3::Lys),
+            "Met" => Ok(Aminoacid3::Met),
+            "Phe" => Ok(Aminoacid3::Phe),
+            "Pro" => Ok(Aminoacid3::Pro),
+            "Ser" => Ok(Aminoacid3::Ser),
+            "Thr" => Ok(Aminoacid3::Thr),
+            "Trp" => Ok(Aminoacid3::Trp),
+            "Tyr" => Ok(Aminoacid3::Tyr),
+            "Val" => Ok(Aminoacid3::Val),
+            "Ter" | "*" => Ok(Aminoacid3::Stop),
+            "Xaa" => Ok(Aminoacid3::Unknown),
+            _ => Err(format!("Unknown amino acid '{}'", s)),
         }
     }
 }
@@ -265,52 +300,258 @@ impl From<Aminoacid> for Aminoacid3 {
 }
 
 impl<'a> From<&'a DnaCodon> for Aminoacid {
+    /// Translates the codon using the standard genetic code.
+    /// See `GeneticCode::translate` for the handling of ambiguity codes.
     fn from(c: &DnaCodon) -> Aminoacid {
-        match *c {
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::C, _) => Aminoacid::A,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::G, _) => Aminoacid::R,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::G, DnaNucleotide::A) => Aminoacid::R,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::G, DnaNucleotide::G) => Aminoacid::R,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::A, DnaNucleotide::T) => Aminoacid::N,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::A, DnaNucleotide::C) => Aminoacid::N,
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::A, DnaNucleotide::T) => Aminoacid::D,
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::A, DnaNucleotide::C) => Aminoacid::D,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::G, DnaNucleotide::T) => Aminoacid::C,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::G, DnaNucleotide::C) => Aminoacid::C,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::A, DnaNucleotide::A) => Aminoacid::Q,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::A, DnaNucleotide::G) => Aminoacid::Q,
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::A, DnaNucleotide::A) => Aminoacid::E,
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::A, DnaNucleotide::G) => Aminoacid::E,
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::G, _) => Aminoacid::G,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::A, DnaNucleotide::T) => Aminoacid::H,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::A, DnaNucleotide::C) => Aminoacid::H,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::T, DnaNucleotide::T) => Aminoacid::I,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::T, DnaNucleotide::C) => Aminoacid::I,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::T, DnaNucleotide::A) => Aminoacid::I,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::T, _) => Aminoacid::L,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::T, DnaNucleotide::A) => Aminoacid::L,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::T, DnaNucleotide::G) => Aminoacid::L,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::A, DnaNucleotide::A) => Aminoacid::K,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::A, DnaNucleotide::G) => Aminoacid::K,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::T, DnaNucleotide::G) => Aminoacid::M,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::T, DnaNucleotide::T) => Aminoacid::F,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::T, DnaNucleotide::C) => Aminoacid::F,
-            DnaCodon(DnaNucleotide::C, DnaNucleotide::C, _) => Aminoacid::P,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::C, _) => Aminoacid::S,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::G, DnaNucleotide::T) => Aminoacid::S,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::G, DnaNucleotide::C) => Aminoacid::S,
-            DnaCodon(DnaNucleotide::A, DnaNucleotide::C, _) => Aminoacid::T,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::G, DnaNucleotide::G) => Aminoacid::W,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::A, DnaNucleotide::T) => Aminoacid::Y,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::A, DnaNucleotide::C) => Aminoacid::Y,
-            DnaCodon(DnaNucleotide::G, DnaNucleotide::T, _) => Aminoacid::V,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::A, DnaNucleotide::A) => Aminoacid::Stop,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::A, DnaNucleotide::G) => Aminoacid::Stop,
-            DnaCodon(DnaNucleotide::T, DnaNucleotide::G, DnaNucleotide::A) => Aminoacid::Stop,
-            _ => Aminoacid::Unknown,
+        GeneticCode::standard().translate(c)
+    }
+}
+
+/// The NCBI translation tables as (id, name, amino acids, start codons). The
+/// 64 characters of each string correspond to the codons in the order `TTT`,
+/// `TTC`, `TTA`, `TTG`, `TCT`, ..., `GGG` where `M` in the start codon string
+/// marks a codon that can initiate translation.
+/// See https://www.ncbi.nlm.nih.gov/Taxonomy/Utils/wprintgc.cgi
+const NCBI_TRANSLATION_TABLES: [(usize, &'static str, &'static str, &'static str); 26] = [
+    (1, "Standard",
+        "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**--*----M---------------M----------------------------"),
+    (2, "Vertebrate Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        "----------**--------------------MMMM----------**---M------------"),
+    (3, "Yeast Mitochondrial",
+        "FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**----------------------MM---------------M------------"),
+    (4, "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--MM------**-------M------------MMMM---------------M------------"),
+    (5, "Invertebrate Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        "---M------**--------------------MMMM---------------M------------"),
+    (6, "Ciliate, Dasycladacean and Hexamita Nuclear",
+        "FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------"),
+    (9, "Echinoderm and Flatworm Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        "----------**-----------------------M---------------M------------"),
+    (10, "Euplotid Nuclear",
+        "FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**-----------------------M----------------------------"),
+    (11, "Bacterial, Archaeal and Plant Plastid",
+        "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**--*----M------------MMMM---------------M------------"),
+    (12, "Alternative Yeast Nuclear",
+        "FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**--*----M---------------M----------------------------"),
+    (13, "Ascidian Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        "---M------**----------------------MM---------------M------------"),
+    (14, "Alternative Flatworm Mitochondrial",
+        "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        "-----------*-----------------------M----------------------------"),
+    (16, "Chlorophycean Mitochondrial",
+        "FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------*---*--------------------M----------------------------"),
+    (21, "Trematode Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        "----------**-----------------------M---------------M------------"),
+    (22, "Scenedesmus obliquus Mitochondrial",
+        "FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "------*---*---*--------------------M----------------------------"),
+    (23, "Thraustochytrium Mitochondrial",
+        "FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--*-------**--*-----------------M--M---------------M------------"),
+    (24, "Rhabdopleuridae Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        "---M------**-------M---------------M---------------M------------"),
+    (25, "Candidate Division SR1 and Gracilibacteria",
+        "FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**-----------------------M---------------M------------"),
+    (26, "Pachysolen tannophilus Nuclear",
+        "FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**--*----M---------------M----------------------------"),
+    (27, "Karyorelict Nuclear",
+        "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------"),
+    (28, "Condylostoma Nuclear",
+        "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**--*--------------------M----------------------------"),
+    (29, "Mesodinium Nuclear",
+        "FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------"),
+    (30, "Peritrich Nuclear",
+        "FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------"),
+    (31, "Blastocrithidia Nuclear",
+        "FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**-----------------------M----------------------------"),
+    (32, "Balanophoraceae Plastid",
+        "FFLLSSSSYY*WCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------*---*----M------------MMMM---------------M------------"),
+    (33, "Cephalodiscidae Mitochondrial",
+        "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        "---M-------*-------M---------------M---------------M------------"),
+];
+
+/// A genetic code that defines how codons are translated into amino acids.
+/// All translation tables defined by the NCBI are available via their id.
+#[derive(Clone, Debug)]
+pub struct GeneticCode {
+    id: usize,
+    name: &'static str,
+    aminoacids: &'static str,
+    starts: &'static str,
+}
+
+impl GeneticCode {
+    /// Returns the standard genetic code (NCBI translation table 1).
+    pub fn standard() -> GeneticCode {
+        GeneticCode::from_table(&NCBI_TRANSLATION_TABLES[0])
+    }
+
+    /// Returns the genetic code for the NCBI translation table with the given id.
+    pub fn from_ncbi_id(id: usize) -> Result<GeneticCode, String> {
+        match NCBI_TRANSLATION_TABLES.iter().find(|t| t.0 == id) {
+            Some(t) => Ok(GeneticCode::from_table(t)),
+            None => Err(format!("No NCBI translation table with id {}", id)),
         }
     }
+
+    /// Returns all available genetic codes ordered by their NCBI id.
+    pub fn all() -> Vec<GeneticCode> {
+        NCBI_TRANSLATION_TABLES.iter().map(|t| GeneticCode::from_table(t)).collect()
+    }
+
+    fn from_table(t: &(usize, &'static str, &'static str, &'static str)) -> GeneticCode {
+        GeneticCode {
+            id: t.0,
+            name: t.1,
+            aminoacids: t.2,
+            starts: t.3,
+        }
+    }
+
+    /// Returns the NCBI id of the translation table
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the name of the translation table
+    pub fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    /// Translates the codon into an amino acid. A codon containing
+    /// ambiguity codes is resolved if all codons it stands for translate into
+    /// the same amino acid. Otherwise, `Aminoacid::Unknown` is returned.
+    pub fn translate(&self, c: &DnaCodon) -> Aminoacid {
+        let mut translations = c.expand()
+            .into_iter()
+            .map(|e| match GeneticCode::codon_index(&e) {
+                Some(i) => Aminoacid::from(self.aminoacids.as_bytes()[i] as char),
+                None => Aminoacid::Unknown,
+            });
+
+        match translations.next() {
+            None => Aminoacid::Unknown,
+            Some(first) => {
+                if translations.all(|aa| aa == first) {
+                    first
+                } else {
+                    Aminoacid::Unknown
+                }
+            }
+        }
+    }
+
+    /// Translates the codon as the first codon of a coding sequence. If the
+    /// codon is a start codon, it is translated into methionine.
+    pub fn translate_initiator(&self, c: &DnaCodon) -> Aminoacid {
+        if self.is_start(c) {
+            Aminoacid::M
+        } else {
+            self.translate(c)
+        }
+    }
+
+    /// Returns `true` if the codon can initiate translation. An ambiguous codon
+    /// is a start codon if all codons it stands for are start codons.
+    pub fn is_start(&self, c: &DnaCodon) -> bool {
+        c.expand().iter().all(|e| match GeneticCode::codon_index(e) {
+            Some(i) => self.starts.as_bytes()[i] == b'M',
+            None => false,
+        })
+    }
+
+    /// Returns `true` if the codon terminates translation.
+    pub fn is_stop(&self, c: &DnaCodon) -> bool {
+        self.translate(c) == Aminoacid::Stop
+    }
+
+    /// Returns all codons that can initiate translation.
+    pub fn start_codons(&self) -> Vec<DnaCodon> {
+        GeneticCode::codons()
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, _)| self.starts.as_bytes()[i] == b'M')
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    /// Returns all codons that terminate translation.
+    pub fn stop_codons(&self) -> Vec<DnaCodon> {
+        GeneticCode::codons()
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, _)| self.aminoacids.as_bytes()[i] == b'*')
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    /// Returns the 64 unambiguous codons in the order of the NCBI tables.
+    fn codons() -> Vec<DnaCodon> {
+        let bases = [DnaNucleotide::T, DnaNucleotide::C, DnaNucleotide::A, DnaNucleotide::G];
+        let mut codons = Vec::with_capacity(64);
+        for b1 in bases.iter() {
+            for b2 in bases.iter() {
+                for b3 in bases.iter() {
+                    codons.push(DnaCodon(b1.clone(), b2.clone(), b3.clone()));
+                }
+            }
+        }
+        codons
+    }
+
+    /// Calculates the position of an unambiguous codon in the NCBI tables.
+    fn codon_index(c: &DnaCodon) -> Option<usize> {
+        fn base_index(n: &DnaNucleotide) -> Option<usize> {
+            match *n {
+                DnaNucleotide::T => Some(0),
+                DnaNucleotide::C => Some(1),
+                DnaNucleotide::A => Some(2),
+                DnaNucleotide::G => Some(3),
+                _ => None,
+            }
+        }
+        match (base_index(&c.0), base_index(&c.1), base_index(&c.2)) {
+            (Some(b1), Some(b2), Some(b3)) => Some(16 * b1 + 4 * b2 + b3),
+            _ => None,
+        }
+    }
+}
+
+impl Default for GeneticCode {
+    fn default() -> GeneticCode {
+        GeneticCode::standard()
+    }
 }
+
+impl fmt::Display for GeneticCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}. {}", self.id, self.name)
+    }
+}
+
 impl From<DnaCodon> for Aminoacid {
     fn from(c: DnaCodon) -> Aminoacid {
         Aminoacid::from(&c)
@@ -329,7 +570,26 @@ pub struct Peptide {
     elements: Vec<Aminoacid>,
 }
 
-impl Peptide {}
+impl Peptide {
+    /// Translates the DNA sequence codon by codon using the given genetic code.
+    pub fn translate(dna: &DnaSequence, code: &GeneticCode) -> Peptide {
+        Peptide::from(dna.codons().iter().map(|c| code.translate(c)).collect::<Vec<Aminoacid>>())
+    }
+
+    /// Translates the DNA sequence as a coding sequence. In contrast to `translate`,
+    /// an (alternative) start codon at the first position is translated into methionine.
+    pub fn translate_cds(dna: &DnaSequence, code: &GeneticCode) -> Peptide {
+        let v: Vec<Aminoacid> = dna.codons()
+            .iter()
+            .enumerate()
+            .map(|(i, c)| match i {
+                0 => code.translate_initiator(c),
+                _ => code.translate(c),
+            })
+            .collect();
+        Peptide::from(v)
+    }
+}
 
 
 impl Sequence<Aminoacid> for Peptide {
@@ -409,8 +669,7 @@ impl From<DnaSequence> for Peptide {
 }
 impl<'a> From<&'a DnaSequence> for Peptide {
     fn from(d: &'a DnaSequence) -> Peptide {
-        let v: Vec<Aminoacid> = d.codons().iter().map(|n| Aminoacid::from(n)).collect();
-        Peptide::from(v)
+        Peptide::translate(d, &GeneticCode::standard())
     }
 }
 impl From<Vec<DnaCodon>> for Peptide {
@@ -424,3 +683,78 @@ impl<'a> From<&'a Vec<DnaCodon>> for Peptide {
         Peptide::from(v)
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use sequence::aminoacid::*;
+
+    #[test]
+    fn test_translate_ambiguous_codon() {
+        // GCN: all four codons encode alanine
+        assert_eq!(Aminoacid::from(DnaCodon::from(&vec![DnaNucleotide::G, DnaNucleotide::C, DnaNucleotide::N])), Aminoacid::A);
+        // AAR: both codons encode lysine
+        assert_eq!(Aminoacid::from(DnaCodon::from(&vec![DnaNucleotide::A, DnaNucleotide::A, DnaNucleotide::R])), Aminoacid::K);
+        // TRA: TAA (stop) and TGA (stop)
+        assert_eq!(Aminoacid::from(DnaCodon::from(&vec![DnaNucleotide::T, DnaNucleotide::R, DnaNucleotide::A])), Aminoacid::Stop);
+        // AAY (asparagine) vs. AAR (lysine) differs
+        assert_eq!(Aminoacid::from(DnaCodon::from(&vec![DnaNucleotide::A, DnaNucleotide::A, DnaNucleotide::N])), Aminoacid::Unknown);
+    }
+
+    #[test]
+    fn test_genetic_code_tables() {
+        assert_eq!(GeneticCode::all().len(), 26);
+        assert_eq!(GeneticCode::standard().id(), 1);
+        assert!(GeneticCode::from_ncbi_id(7).is_err());
+        assert!(GeneticCode::from_ncbi_id(33).is_ok());
+    }
+
+    #[test]
+    fn test_vertebrate_mitochondrial_code() {
+        let standard = GeneticCode::standard();
+        let mito = GeneticCode::from_ncbi_id(2).unwrap();
+        let tga = DnaCodon(DnaNucleotide::T, DnaNucleotide::G, DnaNucleotide::A);
+        let aga = DnaCodon(DnaNucleotide::A, DnaNucleotide::G, DnaNucleotide::A);
+        let ata = DnaCodon(DnaNucleotide::A, DnaNucleotide::T, DnaNucleotide::A);
+
+        assert_eq!(standard.translate(&tga), Aminoacid::Stop);
+        assert_eq!(mito.translate(&tga), Aminoacid::W);
+        assert_eq!(standard.translate(&aga), Aminoacid::R);
+        assert_eq!(mito.translate(&aga), Aminoacid::Stop);
+        assert_eq!(mito.translate(&ata), Aminoacid::M);
+        assert_eq!(mito.stop_codons().len(), 4);
+    }
+
+    #[test]
+    fn test_start_codons() {
+        let standard = GeneticCode::standard();
+        let bacterial = GeneticCode::from_ncbi_id(11).unwrap();
+        let gtg = DnaCodon(DnaNucleotide::G, DnaNucleotide::T, DnaNucleotide::G);
+        let atg = DnaCodon(DnaNucleotide::A, DnaNucleotide::T, DnaNucleotide::G);
+
+        assert!(standard.is_start(&atg));
+        assert!(!standard.is_start(&gtg));
+        assert!(bacterial.is_start(&gtg));
+        assert_eq!(standard.start_codons().len(), 3);
+
+        let dna = DnaSequence::from_str("GTGAAATAA").unwrap();
+        assert_eq!(Peptide::translate(&dna, &bacterial).to_string(), "VK*");
+        assert_eq!(Peptide::translate_cds(&dna, &bacterial).to_string(), "MK*");
+    }
+
+    #[test]
+    fn test_translate_sequence_with_ambiguity() {
+        let dna = DnaSequence::from_str("ATGGCNAARTAR").unwrap();
+        assert_eq!(Peptide::from(dna).to_string(), "MAK*");
+    }
+
+    #[test]
+    fn test_glutamate() {
+        // Amino acids are compared by their one letter code
+        assert!(Aminoacid::E != Aminoacid::G);
+        assert_eq!(char::from(Aminoacid::E), 'E');
+        assert_eq!(Aminoacid::from(DnaCodon::from(&vec![DnaNucleotide::G, DnaNucleotide::A, DnaNucleotide::A])), Aminoacid::E);
+        let dna = DnaSequence::from_str("GAAGGA").unwrap();
+        assert_eq!(Peptide::from(dna).to_string(), "EG");
+    }
+}
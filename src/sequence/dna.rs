@@ -1,26 +1,133 @@
 
 
 pub use sequence::{SequenceElement, Sequence};
+use std::cmp;
 use std::cmp::{Ord, Ordering};
 use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
 pub use std::str::FromStr;
 
+/// A DNA nucleotide including the IUPAC ambiguity codes.
 #[derive(Clone, Debug)]
 pub enum DnaNucleotide {
     A,
     C,
     G,
     T,
+    /// Purine: A or G
+    R,
+    /// Pyrimidine: C or T
+    Y,
+    /// Strong: C or G
+    S,
+    /// Weak: A or T
+    W,
+    /// Keto: G or T
+    K,
+    /// Amino: A or C
+    M,
+    /// Not A: C, G, or T
+    B,
+    /// Not C: A, G, or T
+    D,
+    /// Not G: A, C, or T
+    H,
+    /// Not T: A, C, or G
+    V,
+    /// Any nucleotide
     N,
 }
 
 impl DnaNucleotide {
+    /// Returns the complementary nucleotide. Ambiguity codes are complemented
+    /// such that the complement represents the complements of all bases
+    /// the code stands for (e.g., `R` (A/G) becomes `Y` (T/C)).
     pub fn complement(&self) -> DnaNucleotide {
         match *self {
             DnaNucleotide::A => DnaNucleotide::T,
             DnaNucleotide::C => DnaNucleotide::G,
             DnaNucleotide::G => DnaNucleotide::C,
             DnaNucleotide::T => DnaNucleotide::A,
+            DnaNucleotide::R => DnaNucleotide::Y,
+            DnaNucleotide::Y => DnaNucleotide::R,
+            DnaNucleotide::S => DnaNucleotide::S,
+            DnaNucleotide::W => DnaNucleotide::W,
+            DnaNucleotide::K => DnaNucleotide::M,
+            DnaNucleotide::M => DnaNucleotide::K,
+            DnaNucleotide::B => DnaNucleotide::V,
+            DnaNucleotide::D => DnaNucleotide::H,
+            DnaNucleotide::H => DnaNucleotide::D,
+            DnaNucleotide::V => DnaNucleotide::B,
+            DnaNucleotide::N => DnaNucleotide::N,
+        }
+    }
+
+    /// Returns `true` if the nucleotide is an ambiguity code, i.e.,
+    /// it stands for more than one base.
+    pub fn is_ambiguous(&self) -> bool {
+        self.bits().count_ones() > 1
+    }
+
+    /// Returns the unambiguous bases (`A`, `C`, `G`, `T`) this
+    /// nucleotide stands for.
+    pub fn expand(&self) -> Vec<DnaNucleotide> {
+        let bits = self.bits();
+        vec![DnaNucleotide::A, DnaNucleotide::C, DnaNucleotide::G, DnaNucleotide::T]
+            .into_iter()
+            .filter(|n| bits & n.bits() > 0)
+            .collect()
+    }
+
+    /// Returns `true` if the two nucleotides may represent the same base,
+    /// i.e., the sets of bases they stand for intersect. For example,
+    /// `R` matches `A` and `G` but not `C`.
+    pub fn matches(&self, other: &DnaNucleotide) -> bool {
+        self.bits() & other.bits() > 0
+    }
+
+    /// Returns the ambiguity code that represents all the given nucleotides.
+    pub fn ambiguity_code(nucleotides: &[DnaNucleotide]) -> DnaNucleotide {
+        DnaNucleotide::from_bits(nucleotides.iter().fold(0u8, |b, n| b | n.bits()))
+    }
+
+    /// Encodes the set of bases as bit mask with A=1, C=2, G=4, and T=8.
+    fn bits(&self) -> u8 {
+        match *self {
+            DnaNucleotide::A => 0b0001,
+            DnaNucleotide::C => 0b0010,
+            DnaNucleotide::G => 0b0100,
+            DnaNucleotide::T => 0b1000,
+            DnaNucleotide::R => 0b0101,
+            DnaNucleotide::Y => 0b1010,
+            DnaNucleotide::S => 0b0110,
+            DnaNucleotide::W => 0b1001,
+            DnaNucleotide::K => 0b1100,
+            DnaNucleotide::M => 0b0011,
+            DnaNucleotide::B => 0b1110,
+            DnaNucleotide::D => 0b1101,
+            DnaNucleotide::H => 0b1011,
+            DnaNucleotide::V => 0b0111,
+            DnaNucleotide::N => 0b1111,
+        }
+    }
+
+    fn from_bits(bits: u8) -> DnaNucleotide {
+        match bits & 0b1111 {
+            0b0001 => DnaNucleotide::A,
+            0b0010 => DnaNucleotide::C,
+            0b0100 => DnaNucleotide::G,
+            0b1000 => DnaNucleotide::T,
+            0b0101 => DnaNucleotide::R,
+            0b1010 => DnaNucleotide::Y,
+            0b0110 => DnaNucleotide::S,
+            0b1001 => DnaNucleotide::W,
+            0b1100 => DnaNucleotide::K,
+            0b0011 => DnaNucleotide::M,
+            0b1110 => DnaNucleotide::B,
+            0b1101 => DnaNucleotide::D,
+            0b1011 => DnaNucleotide::H,
+            0b0111 => DnaNucleotide::V,
             _ => DnaNucleotide::N,
         }
     }
@@ -57,14 +164,20 @@ impl Ord for DnaNucleotide {
 impl From<char> for DnaNucleotide {
     fn from(c: char) -> DnaNucleotide {
         match c {
-            'a' => DnaNucleotide::A,
-            'A' => DnaNucleotide::A,
-            'c' => DnaNucleotide::C,
-            'C' => DnaNucleotide::C,
-            'g' => DnaNucleotide::G,
-            'G' => DnaNucleotide::G,
-            't' => DnaNucleotide::T,
-            'T' => DnaNucleotide::T,
+            'a' | 'A' => DnaNucleotide::A,
+            'c' | 'C' => DnaNucleotide::C,
+            'g' | 'G' => DnaNucleotide::G,
+            't' | 'T' => DnaNucleotide::T,
+            'r' | 'R' => DnaNucleotide::R,
+            'y' | 'Y' => DnaNucleotide::Y,
+            's' | 'S' => DnaNucleotide::S,
+            'w' | 'W' => DnaNucleotide::W,
+            'k' | 'K' => DnaNucleotide::K,
+            'm' | 'M' => DnaNucleotide::M,
+            'b' | 'B' => DnaNucleotide::B,
+            'd' | 'D' => DnaNucleotide::D,
+            'h' | 'H' => DnaNucleotide::H,
+            'v' | 'V' => DnaNucleotide::V,
             _ => DnaNucleotide::N,
         }
     }
@@ -72,13 +185,7 @@ impl From<char> for DnaNucleotide {
 
 impl From<DnaNucleotide> for char {
     fn from(n: DnaNucleotide) -> char {
-        match n {
-            DnaNucleotide::A => 'A',
-            DnaNucleotide::C => 'C',
-            DnaNucleotide::G => 'G',
-            DnaNucleotide::T => 'T',
-            _ => 'N',
-        }
+        char::from(&n)
     }
 }
 
@@ -89,6 +196,16 @@ impl From<u8> for DnaNucleotide {
             2 => DnaNucleotide::C,
             3 => DnaNucleotide::G,
             4 => DnaNucleotide::T,
+            5 => DnaNucleotide::R,
+            6 => DnaNucleotide::Y,
+            7 => DnaNucleotide::S,
+            8 => DnaNucleotide::W,
+            9 => DnaNucleotide::K,
+            10 => DnaNucleotide::M,
+            11 => DnaNucleotide::B,
+            12 => DnaNucleotide::D,
+            13 => DnaNucleotide::H,
+            14 => DnaNucleotide::V,
             _ => DnaNucleotide::N,
         }
     }
@@ -96,13 +213,7 @@ impl From<u8> for DnaNucleotide {
 
 impl From<DnaNucleotide> for u8 {
     fn from(n: DnaNucleotide) -> u8 {
-        match n {
-            DnaNucleotide::A => 1,
-            DnaNucleotide::C => 2,
-            DnaNucleotide::G => 3,
-            DnaNucleotide::T => 4,
-            _ => 0,
-        }
+        u8::from(&n)
     }
 }
 
@@ -113,7 +224,17 @@ impl<'a> From<&'a DnaNucleotide> for u8 {
             DnaNucleotide::C => 2,
             DnaNucleotide::G => 3,
             DnaNucleotide::T => 4,
-            _ => 0,
+            DnaNucleotide::R => 5,
+            DnaNucleotide::Y => 6,
+            DnaNucleotide::S => 7,
+            DnaNucleotide::W => 8,
+            DnaNucleotide::K => 9,
+            DnaNucleotide::M => 10,
+            DnaNucleotide::B => 11,
+            DnaNucleotide::D => 12,
+            DnaNucleotide::H => 13,
+            DnaNucleotide::V => 14,
+            DnaNucleotide::N => 0,
         }
     }
 }
@@ -125,7 +246,17 @@ impl<'a> From<&'a DnaNucleotide> for char {
             DnaNucleotide::C => 'C',
             DnaNucleotide::G => 'G',
             DnaNucleotide::T => 'T',
-            _ => 'N',
+            DnaNucleotide::R => 'R',
+            DnaNucleotide::Y => 'Y',
+            DnaNucleotide::S => 'S',
+            DnaNucleotide::W => 'W',
+            DnaNucleotide::K => 'K',
+            DnaNucleotide::M => 'M',
+            DnaNucleotide::B => 'B',
+            DnaNucleotide::D => 'D',
+            DnaNucleotide::H => 'H',
+            DnaNucleotide::V => 'V',
+            DnaNucleotide::N => 'N',
         }
     }
 }
@@ -134,6 +265,26 @@ impl<'a> From<&'a DnaNucleotide> for char {
 #[derive(Clone, Debug)]
 pub struct DnaCodon(pub DnaNucleotide, pub DnaNucleotide, pub DnaNucleotide);
 
+impl DnaCodon {
+    /// Returns `true` if any of the three nucleotides is an ambiguity code.
+    pub fn is_ambiguous(&self) -> bool {
+        self.0.is_ambiguous() || self.1.is_ambiguous() || self.2.is_ambiguous()
+    }
+
+    /// Returns all unambiguous codons this codon stands for.
+    pub fn expand(&self) -> Vec<DnaCodon> {
+        let mut codons = Vec::new();
+        for n0 in self.0.expand() {
+            for n1 in self.1.expand() {
+                for n2 in self.2.expand() {
+                    codons.push(DnaCodon(n0.clone(), n1.clone(), n2.clone()));
+                }
+            }
+        }
+        codons
+    }
+}
+
 impl From<(DnaNucleotide, DnaNucleotide, DnaNucleotide)> for DnaCodon {
     fn from(c: (DnaNucleotide, DnaNucleotide, DnaNucleotide)) -> DnaCodon {
         DnaCodon(c.0, c.1, c.2)
@@ -163,42 +314,79 @@ impl<'a> From<&'a Vec<DnaNucleotide>> for DnaCodon {
 }
 
 
-#[derive(Clone, Debug)]
+/// A DNA sequence.
+///
+/// The nucleotides are stored packed with four bits per nucleotide (which covers
+/// all IUPAC codes). The packed buffer is shared between a sequence and all
+/// subsequences derived from it, such that `subsequence()` does not copy any
+/// nucleotides but only creates a view into the buffer.
+#[derive(Clone)]
 pub struct DnaSequence {
-    elements: Vec<DnaNucleotide>,
+    data: Rc<Vec<u8>>,
+    offset: usize,
+    length: usize,
 }
 
 impl Sequence<DnaNucleotide> for DnaSequence {
     type SubsequenceType = DnaSequence;
     
     fn length(&self) -> usize {
-        self.elements.len()
+        self.length
     }
 
     fn vec(&self) -> Vec<DnaNucleotide> {
-        self.elements.clone()
+        self.iter().collect()
     }
 
+    /// Returns a view on the subsequence without copying the nucleotides.
+    /// The subsequence is truncated at the end of the sequence.
     fn subsequence(&self, offset:usize, length: usize) -> DnaSequence {
-        let v : Vec<DnaNucleotide> = self.elements.iter().skip(offset).take(length).cloned().collect();
+        let offset = cmp::min(offset, self.length);
         DnaSequence {
-            elements: v
+            data: self.data.clone(),
+            offset: self.offset + offset,
+            length: cmp::min(length, self.length - offset),
         }
     }
 }
 
 impl DnaSequence {
+    /// Returns the nucleotide at the given position or `None` if
+    /// the position is beyond the end of the sequence.
+    pub fn get(&self, index: usize) -> Option<DnaNucleotide> {
+        match index < self.length {
+            true => Some(self.get_unchecked(index)),
+            false => None,
+        }
+    }
+
+    fn get_unchecked(&self, index: usize) -> DnaNucleotide {
+        let position = self.offset + index;
+        let byte = self.data[position / 2];
+        match position % 2 {
+            0 => DnaNucleotide::from(byte & 0x0f),
+            _ => DnaNucleotide::from(byte >> 4),
+        }
+    }
+
+    /// Returns an iterator over the nucleotides that borrows the sequence.
+    pub fn iter<'a>(&'a self) -> DnaSequenceIter<'a> {
+        DnaSequenceIter {
+            sequence: self,
+            front: 0,
+            back: self.length,
+        }
+    }
+
     /// Returns the complementary strand sequence in reversed direction (i.e., the actual sequence
     /// that is read by DNA or RNA polymerase).
     pub fn reverse_strand(&self) -> Self {
-        let r: Vec<DnaNucleotide> = self.vec().iter().map(|n| n.complement()).rev().collect();
-        Self::from(r)
+        self.iter().rev().map(|n| n.complement()).collect()
     }
 
     /// Returns the complementary strand sequence in forward direction.
     pub fn complement(&self) -> Self {
-        let r: Vec<DnaNucleotide> = self.vec().iter().map(|n| n.complement()).collect();
-        Self::from(r)
+        self.iter().map(|n| n.complement()).collect()
     }
 
     /// Returns an iterator on the codons. This is identical
@@ -211,26 +399,95 @@ impl DnaSequence {
     /// at `offset`. If the sequence is not a multiple of 3, the
     /// last codon will be filled with `DnaNucleotide::N`.
     pub fn frame(&self, offset: usize) -> Vec<DnaCodon> {
-        let v: Vec<DnaNucleotide> = self.vec().iter().skip(offset).map(|n| n.clone()).collect();
+        let v: Vec<DnaNucleotide> = self.iter().skip(offset).collect();
         v.chunks(3usize).map(|c| DnaCodon::from(c)).collect()
     }
 }
 
+/// An iterator over the nucleotides of a `DnaSequence`.
+pub struct DnaSequenceIter<'a> {
+    sequence: &'a DnaSequence,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for DnaSequenceIter<'a> {
+    type Item = DnaNucleotide;
+
+    fn next(&mut self) -> Option<DnaNucleotide> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.sequence.get_unchecked(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for DnaSequenceIter<'a> {
+    fn next_back(&mut self) -> Option<DnaNucleotide> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.sequence.get_unchecked(self.back))
+    }
+}
+
+impl<'a> ExactSizeIterator for DnaSequenceIter<'a> {}
+
+impl<'a> IntoIterator for &'a DnaSequence {
+    type Item = DnaNucleotide;
+    type IntoIter = DnaSequenceIter<'a>;
+
+    fn into_iter(self) -> DnaSequenceIter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<DnaNucleotide> for DnaSequence {
+    fn from_iter<I: IntoIterator<Item = DnaNucleotide>>(iter: I) -> DnaSequence {
+        let mut data: Vec<u8> = Vec::new();
+        let mut length = 0usize;
+        for n in iter {
+            let code = u8::from(&n);
+            match length % 2 {
+                0 => data.push(code),
+                _ => {
+                    let last = data.len() - 1;
+                    data[last] |= code << 4
+                }
+            }
+            length += 1;
+        }
+
+        DnaSequence {
+            data: Rc::new(data),
+            offset: 0,
+            length: length,
+        }
+    }
+}
+
 impl PartialEq for DnaSequence {
     fn eq(&self, other: &DnaSequence) -> bool {
-        self.elements.eq(&other.elements)
+        self.length == other.length && self.iter().eq(other.iter())
     }
 }
 impl Eq for DnaSequence {}
 
 impl PartialOrd for DnaSequence {
     fn partial_cmp(&self, other: &DnaSequence) -> Option<Ordering> {
-        self.elements.partial_cmp(&other.elements)
+        Some(self.cmp(other))
     }
 }
 impl Ord for DnaSequence {
     fn cmp(&self, other: &DnaSequence) -> Ordering {
-        self.elements.cmp(&other.elements)
+        self.iter().cmp(other.iter())
     }
 }
 impl Default for DnaSequence {
@@ -241,33 +498,37 @@ impl Default for DnaSequence {
 impl FromStr for DnaSequence {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Vec<DnaNucleotide> = s.chars()
+        Ok(s.chars()
             .filter(|n| !n.is_whitespace())
             .map(|n| DnaNucleotide::from(n))
-            .collect();
-        Ok(DnaSequence::from(v))
+            .collect())
     }
 }
 
 impl From<Vec<DnaNucleotide>> for DnaSequence {
     fn from(v: Vec<DnaNucleotide>) -> DnaSequence {
-        DnaSequence { elements: v }
+        v.into_iter().collect()
     }
 }
 impl fmt::Display for DnaSequence {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let s: String = self.vec().iter().map(|n| char::from(n)).collect();
+        let s: String = self.iter().map(|n| char::from(n)).collect();
         write!(f, "{}", s)
     }
 }
+impl fmt::Debug for DnaSequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "DnaSequence({})", self)
+    }
+}
 impl From<DnaSequence> for Vec<DnaNucleotide> {
     fn from(seq: DnaSequence) -> Vec<DnaNucleotide> {
-        seq.vec().iter().map(|n| n.clone()).collect()
+        seq.vec()
     }
 }
 impl<'a> From<&'a DnaSequence> for Vec<DnaNucleotide> {
     fn from(seq: &'a DnaSequence) -> Vec<DnaNucleotide> {
-        seq.vec().iter().map(|n| n.clone()).collect()
+        seq.vec()
     }
 }
 
@@ -318,8 +579,42 @@ mod tests {
     fn test_others() {
         assert_eq!(DnaNucleotide::from('u'), DnaNucleotide::N);
         assert_eq!(DnaNucleotide::from('U'), DnaNucleotide::N);
-        assert_eq!(DnaNucleotide::from('B'), DnaNucleotide::N);
-        assert_eq!(DnaNucleotide::from('H'), DnaNucleotide::N);
+        assert_eq!(DnaNucleotide::from('X'), DnaNucleotide::N);
+        assert_eq!(DnaNucleotide::from('-'), DnaNucleotide::N);
+    }
+
+    #[test]
+    fn test_iupac_roundtrip() {
+        let codes = "RYSWKMBDHVN";
+        let seq = DnaSequence::from_str(codes).unwrap();
+        assert_eq!(seq.to_string(), codes);
+        assert_eq!(DnaSequence::from_str("rysw").unwrap().to_string(), "RYSW");
+        for c in codes.chars() {
+            let n = DnaNucleotide::from(c);
+            assert_eq!(DnaNucleotide::from(u8::from(&n)), n);
+        }
+    }
+
+    #[test]
+    fn test_iupac_complement() {
+        let seq = DnaSequence::from_str("ACGTRYSWKMBDHVN").unwrap();
+        assert_eq!(seq.complement().to_string(), "TGCAYRSWMKVHDBN");
+        assert_eq!(seq.complement().complement(), seq);
+    }
+
+    #[test]
+    fn test_iupac_matches() {
+        assert!(DnaNucleotide::R.matches(&DnaNucleotide::A));
+        assert!(DnaNucleotide::R.matches(&DnaNucleotide::G));
+        assert!(!DnaNucleotide::R.matches(&DnaNucleotide::C));
+        assert!(DnaNucleotide::R.matches(&DnaNucleotide::S));
+        assert!(!DnaNucleotide::R.matches(&DnaNucleotide::Y));
+        assert!(DnaNucleotide::N.matches(&DnaNucleotide::T));
+        assert!(!DnaNucleotide::A.matches(&DnaNucleotide::C));
+        assert!(!DnaNucleotide::A.is_ambiguous());
+        assert!(DnaNucleotide::B.is_ambiguous());
+        assert_eq!(DnaNucleotide::B.expand(), vec![DnaNucleotide::C, DnaNucleotide::G, DnaNucleotide::T]);
+        assert_eq!(DnaNucleotide::ambiguity_code(&[DnaNucleotide::C, DnaNucleotide::T]), DnaNucleotide::Y);
     }
 
 
@@ -350,6 +645,31 @@ mod tests {
         // assert_eq!(s3.to_string(), "ACGTTGCA");
     }*/
 
+    #[test]
+    fn test_dna_packed_odd_length() {
+        let s = DnaSequence::from_str(&"ACGTRYSWKMBDHVN").expect("Can not parse DNA sequence string");
+        assert_eq!(s.length(), 15);
+        assert_eq!(s.get(0), Some(DnaNucleotide::A));
+        assert_eq!(s.get(3), Some(DnaNucleotide::T));
+        assert_eq!(s.get(14), Some(DnaNucleotide::N));
+        assert_eq!(s.get(15), None);
+        assert_eq!(s.iter().rev().take(2).collect::<Vec<DnaNucleotide>>(), vec![DnaNucleotide::N, DnaNucleotide::V]);
+        assert_eq!(DnaSequence::from(s.vec()), s);
+    }
+
+    #[test]
+    fn test_dna_subsequence_view() {
+        let s1 = DnaSequence::from_str(&"AACCGGTT").expect("Can not parse DNA sequence string");
+        let s2 = s1.subsequence(1, 6);
+        assert_eq!(s2.to_string(), "ACCGGT");
+        assert_eq!(s2.subsequence(1, 3).to_string(), "CCG");
+        assert_eq!(s2.subsequence(4, 10).to_string(), "GT");
+        assert_eq!(s2.subsequence(10, 10).to_string(), "");
+        assert_eq!(s2.reverse_strand().to_string(), "ACCGGT");
+        assert_eq!(s2, DnaSequence::from_str(&"ACCGGT").unwrap());
+        assert!(s2 < DnaSequence::from_str(&"ACCGTT").unwrap());
+    }
+
     #[test]
     fn test_dna_subsequence() {
         let s1 = DnaSequence::from_str(&"ACGT").expect("Can not parse DNA sequence string");
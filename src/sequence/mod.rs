@@ -3,13 +3,17 @@ use std::fmt;
 pub mod dna;
 pub mod rna;
 pub mod aminoacid;
+pub mod orf;
+pub mod quality;
 
 pub use self::dna::DnaNucleotide;
 pub use self::dna::DnaSequence;
 pub use self::rna::RnaNucleotide;
 pub use self::rna::RnaSequence;
 pub use self::aminoacid::Aminoacid;
+pub use self::aminoacid::Aminoacid3;
 pub use self::aminoacid::Peptide;
+pub use self::aminoacid::GeneticCode;
 
 
 /// An element that can be part of a sequence.
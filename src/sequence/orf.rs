@@ -0,0 +1,335 @@
+use model::{Region, Strand};
+use sequence::*;
+use sequence::dna::DnaCodon;
+use std::fmt;
+
+/// Translates the sequence in all six reading frames, i.e., the three frames on the
+/// forward strand followed by the three frames on the reverse strand. Only complete
+/// codons are translated.
+pub fn six_frame_translation(sequence: &DnaSequence, code: &GeneticCode) -> Vec<(Strand, usize, Peptide)> {
+    let reverse = sequence.reverse_strand();
+    let mut frames = Vec::new();
+
+    for &(ref strand, ref seq) in [(Strand::Forward, sequence), (Strand::Backward, &reverse)].iter() {
+        for frame in 0..3usize {
+            let peptide: Vec<Aminoacid> = complete_codons(seq, frame)
+                .iter()
+                .map(|c| code.translate(c))
+                .collect();
+            frames.push((strand.clone(), frame, Peptide::from(peptide)));
+        }
+    }
+
+    frames
+}
+
+/// Returns the complete codons of the frame starting at `offset`.
+fn complete_codons(sequence: &DnaSequence, offset: usize) -> Vec<DnaCodon> {
+    if sequence.length() < offset {
+        return Vec::new();
+    }
+    let n_codons = (sequence.length() - offset) / 3;
+    sequence.frame(offset).into_iter().take(n_codons).collect()
+}
+
+
+/// An open reading frame that has been identified on a template sequence.
+///
+/// The region (i.e., `offset()` and `length()`) is always given with respect to
+/// the forward strand of the template while `sequence()` returns the nucleotides
+/// in reading direction.
+#[derive(Clone, Debug)]
+pub struct OpenReadingFrame {
+    template: String,
+    offset: usize,
+    strand: Strand,
+    frame: usize,
+    sequence: DnaSequence,
+    has_start_codon: bool,
+    has_stop_codon: bool,
+}
+
+impl OpenReadingFrame {
+    /// Returns the strand on which the reading frame is located
+    pub fn strand(&self) -> Strand {
+        self.strand.clone()
+    }
+
+    /// Returns the frame (0, 1, or 2) with respect to the start of the
+    /// template sequence in reading direction of the strand.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Returns the nucleotide sequence in reading direction.
+    pub fn sequence(&self) -> DnaSequence {
+        self.sequence.clone()
+    }
+
+    /// Returns `true` if the reading frame begins with a start codon. This
+    /// is `false` for reading frames that run off the beginning of the template.
+    pub fn has_start_codon(&self) -> bool {
+        self.has_start_codon
+    }
+
+    /// Returns `true` if the reading frame is terminated by a stop codon. This
+    /// is `false` for reading frames that run off the end of the template.
+    pub fn has_stop_codon(&self) -> bool {
+        self.has_stop_codon
+    }
+
+    /// Translates the reading frame into a peptide. A start codon is
+    /// translated into methionine.
+    pub fn peptide(&self, code: &GeneticCode) -> Peptide {
+        match self.has_start_codon {
+            true => Peptide::translate_cds(&self.sequence, code),
+            false => Peptide::translate(&self.sequence, code),
+        }
+    }
+}
+
+impl Region for OpenReadingFrame {
+    fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn length(&self) -> usize {
+        self.sequence.length()
+    }
+}
+
+impl fmt::Display for OpenReadingFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}({}) frame={}",
+            self.template(),
+            self.offset() + 1,
+            self.end(),
+            self.strand,
+            self.frame
+        )
+    }
+}
+
+
+/// Searches open reading frames in all six frames of a DNA sequence.
+#[derive(Clone, Debug)]
+pub struct OrfFinder {
+    code: GeneticCode,
+    min_length: usize,
+    allow_partial: bool,
+}
+
+impl OrfFinder {
+    /// Create a new ORF finder that uses the start and stop codons of the given genetic code.
+    pub fn new(code: GeneticCode) -> Self {
+        OrfFinder {
+            code: code,
+            min_length: 75,
+            allow_partial: false,
+        }
+    }
+
+    pub fn genetic_code(&self) -> GeneticCode {
+        self.code.clone()
+    }
+
+    /// Returns the minimal number of nucleotides (including the stop codon)
+    /// of a reported ORF.
+    pub fn min_length(&self) -> usize {
+        self.min_length
+    }
+
+    pub fn with_min_length(mut self, new_min_length: usize) -> Self {
+        self.min_length = new_min_length;
+        self
+    }
+
+    /// Returns `true` if ORFs running off the sequence ends are reported.
+    pub fn allow_partial(&self) -> bool {
+        self.allow_partial
+    }
+
+    pub fn with_partial(mut self, allow_partial: bool) -> Self {
+        self.allow_partial = allow_partial;
+        self
+    }
+
+    /// Searches all six frames of the sequence and returns the identified ORFs
+    /// ordered by their position on the template.
+    pub fn find<S: ToString>(&self, template: &S, sequence: &DnaSequence) -> Vec<OpenReadingFrame> {
+        let mut orfs = Vec::new();
+        let reverse = sequence.reverse_strand();
+
+        for frame in 0..3usize {
+            orfs.append(&mut self.find_in_frame(template, sequence, Strand::Forward, frame));
+            orfs.append(&mut self.find_in_frame(template, &reverse, Strand::Backward, frame));
+        }
+
+        orfs.sort_by(|a, b| a.compare(b));
+        orfs
+    }
+
+    /// Searches a single frame of the (already strand-oriented) sequence.
+    fn find_in_frame<S: ToString>(
+        &self,
+        template: &S,
+        sequence: &DnaSequence,
+        strand: Strand,
+        frame: usize,
+    ) -> Vec<OpenReadingFrame> {
+        let codons = complete_codons(sequence, frame);
+        let mut orfs = Vec::new();
+
+        // The codon index where the current ORF starts (if any)
+        let mut start: Option<usize> = match self.allow_partial {
+            true => Some(0),
+            false => None,
+        };
+
+        for (i, codon) in codons.iter().enumerate() {
+            if self.code.is_stop(codon) {
+                if let Some(s) = start {
+                    orfs.push(self.build_orf(template, sequence, &strand, frame, s, i + 1, true));
+                }
+                start = None;
+            } else if start.is_none() && self.code.is_start(codon) {
+                start = Some(i);
+            }
+        }
+
+        if self.allow_partial {
+            if let Some(s) = start {
+                orfs.push(self.build_orf(template, sequence, &strand, frame, s, codons.len(), false));
+            }
+        }
+
+        orfs.into_iter()
+            .filter(|o| o.length() >= self.min_length && o.length() > 0)
+            .collect()
+    }
+
+    /// Creates an ORF from codon `first` to codon `last` (exclusive).
+    fn build_orf<S: ToString>(
+        &self,
+        template: &S,
+        sequence: &DnaSequence,
+        strand: &Strand,
+        frame: usize,
+        first: usize,
+        last: usize,
+        has_stop_codon: bool,
+    ) -> OpenReadingFrame {
+        let oriented_offset = frame + 3 * first;
+        let length = 3 * (last - first);
+        let orf_sequence = sequence.subsequence(oriented_offset, length);
+        let has_start_codon = match orf_sequence.codons().first() {
+            Some(c) => self.code.is_start(c),
+            None => false,
+        };
+
+        let offset = match *strand {
+            Strand::Forward => oriented_offset,
+            Strand::Backward => sequence.length() - oriented_offset - length,
+        };
+
+        OpenReadingFrame {
+            template: template.to_string(),
+            offset: offset,
+            strand: strand.clone(),
+            frame: frame,
+            sequence: orf_sequence,
+            has_start_codon: has_start_codon,
+            has_stop_codon: has_stop_codon,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use model::{Region, Strand};
+    use sequence::orf::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_six_frame_translation() {
+        let dna = DnaSequence::from_str("ATGGCCTAA").unwrap();
+        let frames = six_frame_translation(&dna, &GeneticCode::standard());
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[0].0, Strand::Forward);
+        assert_eq!(frames[0].2.to_string(), "MA*");
+        assert_eq!(frames[1].2.to_string(), "WP");
+        assert_eq!(frames[3].0, Strand::Backward);
+        assert_eq!(frames[3].2.to_string(), "LGH");
+    }
+
+    #[test]
+    fn test_find_forward() {
+        let dna = DnaSequence::from_str("CCATGAAACCCTAGCC").unwrap();
+        let orfs = OrfFinder::new(GeneticCode::standard())
+            .with_min_length(6)
+            .find(&"ref", &dna);
+
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].template(), "ref");
+        assert_eq!(orfs[0].offset(), 2);
+        assert_eq!(orfs[0].length(), 12);
+        assert_eq!(orfs[0].strand(), Strand::Forward);
+        assert_eq!(orfs[0].frame(), 2);
+        assert_eq!(orfs[0].sequence().to_string(), "ATGAAACCCTAG");
+        assert_eq!(orfs[0].peptide(&GeneticCode::standard()).to_string(), "MKP*");
+    }
+
+    #[test]
+    fn test_find_reverse() {
+        // reverse strand of "CCATGAAACCCTAGCC"
+        let dna = DnaSequence::from_str("GGCTAGGGTTTCATGG").unwrap();
+        let orfs = OrfFinder::new(GeneticCode::standard())
+            .with_min_length(6)
+            .find(&"ref", &dna);
+
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].offset(), 2);
+        assert_eq!(orfs[0].end(), 14);
+        assert_eq!(orfs[0].strand(), Strand::Backward);
+        assert_eq!(orfs[0].sequence().to_string(), "ATGAAACCCTAG");
+    }
+
+    #[test]
+    fn test_find_partial() {
+        let dna = DnaSequence::from_str("AAACCCTAGATGCCC").unwrap();
+        let finder = OrfFinder::new(GeneticCode::standard()).with_min_length(6);
+        assert_eq!(finder.find(&"ref", &dna).len(), 0);
+
+        let orfs: Vec<OpenReadingFrame> = finder.with_partial(true)
+            .find(&"ref", &dna)
+            .into_iter()
+            .filter(|o| o.strand() == Strand::Forward && o.frame() == 0)
+            .collect();
+        assert_eq!(orfs.len(), 2);
+        assert_eq!(orfs[0].sequence().to_string(), "AAACCCTAG");
+        assert!(!orfs[0].has_start_codon());
+        assert!(orfs[0].has_stop_codon());
+        assert_eq!(orfs[1].sequence().to_string(), "ATGCCC");
+        assert!(orfs[1].has_start_codon());
+        assert!(!orfs[1].has_stop_codon());
+    }
+
+    #[test]
+    fn test_find_alternative_start() {
+        let dna = DnaSequence::from_str("GTGAAATAA").unwrap();
+        let standard = OrfFinder::new(GeneticCode::standard()).with_min_length(6);
+        assert_eq!(standard.find(&"ref", &dna).len(), 0);
+
+        let bacterial = OrfFinder::new(GeneticCode::from_ncbi_id(11).unwrap()).with_min_length(6);
+        let orfs = bacterial.find(&"ref", &dna);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].peptide(&bacterial.genetic_code()).to_string(), "MK*");
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+
+/// The encoding of Phred quality scores as ASCII characters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhredEncoding {
+    /// Sanger / Illumina 1.8+ encoding with an offset of 33
+    Phred33,
+    /// Illumina 1.3 to 1.7 encoding with an offset of 64
+    Phred64,
+}
+
+impl PhredEncoding {
+    /// Returns the ASCII value of a quality score of zero.
+    pub fn offset(&self) -> u8 {
+        match *self {
+            PhredEncoding::Phred33 => 33,
+            PhredEncoding::Phred64 => 64,
+        }
+    }
+
+    /// Decodes a single quality character into a Phred score.
+    pub fn decode(&self, c: char) -> Result<u8, String> {
+        let v = c as u32;
+        if v < self.offset() as u32 || v > 126 {
+            return Err(format!(
+                "Quality character '{}' is out of range for {:?} encoding",
+                c,
+                self
+            ));
+        }
+        Ok(v as u8 - self.offset())
+    }
+
+    /// Encodes a Phred score into its quality character.
+    pub fn encode(&self, score: u8) -> char {
+        let max = 126 - self.offset();
+        (if score > max { max } else { score } + self.offset()) as char
+    }
+}
+
+impl Default for PhredEncoding {
+    fn default() -> PhredEncoding {
+        PhredEncoding::Phred33
+    }
+}
+
+
+/// A sequence of Phred-scaled base quality scores.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct QualitySequence {
+    scores: Vec<u8>,
+}
+
+impl QualitySequence {
+    /// Parses the ASCII representation of the qualities using the given encoding.
+    pub fn from_ascii<S: AsRef<str>>(s: S, encoding: PhredEncoding) -> Result<QualitySequence, String> {
+        let mut scores = Vec::with_capacity(s.as_ref().len());
+        for c in s.as_ref().chars() {
+            scores.push(encoding.decode(c)?);
+        }
+        Ok(QualitySequence::from(scores))
+    }
+
+    /// Returns the ASCII representation of the qualities using the given encoding.
+    pub fn to_ascii(&self, encoding: PhredEncoding) -> String {
+        self.scores.iter().map(|q| encoding.encode(*q)).collect()
+    }
+
+    /// Returns the number of quality scores
+    pub fn length(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Returns the Phred scores
+    pub fn scores(&self) -> Vec<u8> {
+        self.scores.clone()
+    }
+
+    /// Returns the Phred score at the given position
+    pub fn score(&self, index: usize) -> Option<u8> {
+        self.scores.get(index).cloned()
+    }
+
+    /// Converts the Phred scores into error probabilities, i.e., `10^(-Q/10)`.
+    pub fn error_probabilities(&self) -> Vec<f64> {
+        self.scores.iter().map(|q| phred_to_probability(*q)).collect()
+    }
+
+    /// Returns the mean Phred score or `None` if the sequence is empty.
+    pub fn mean(&self) -> Option<f64> {
+        if self.scores.is_empty() {
+            return None;
+        }
+        Some(self.scores.iter().map(|q| *q as f64).sum::<f64>() / self.scores.len() as f64)
+    }
+
+    /// Extracts the qualities with a given offset and length.
+    pub fn subsequence(&self, offset: usize, length: usize) -> QualitySequence {
+        QualitySequence::from(self.scores.iter().skip(offset).take(length).cloned().collect::<Vec<u8>>())
+    }
+
+    /// Returns the qualities in reversed order.
+    pub fn reverse(&self) -> QualitySequence {
+        QualitySequence::from(self.scores.iter().rev().cloned().collect::<Vec<u8>>())
+    }
+}
+
+/// Converts a Phred score into an error probability.
+pub fn phred_to_probability(q: u8) -> f64 {
+    10f64.powf(-(q as f64) / 10f64)
+}
+
+/// Converts an error probability into the (rounded) Phred score.
+pub fn probability_to_phred(p: f64) -> u8 {
+    if p <= 0f64 {
+        return u8::max_value();
+    }
+    let q = (-10f64 * p.log10()).round();
+    if q > u8::max_value() as f64 { u8::max_value() } else { q as u8 }
+}
+
+impl From<Vec<u8>> for QualitySequence {
+    fn from(scores: Vec<u8>) -> QualitySequence {
+        QualitySequence { scores: scores }
+    }
+}
+
+impl fmt::Display for QualitySequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_ascii(PhredEncoding::Phred33))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use sequence::quality::*;
+
+    #[test]
+    fn test_phred33() {
+        let q = QualitySequence::from_ascii("!+5?I", PhredEncoding::Phred33).unwrap();
+        assert_eq!(q.scores(), vec![0, 10, 20, 30, 40]);
+        assert_eq!(q.to_ascii(PhredEncoding::Phred33), "!+5?I");
+        assert_eq!(q.to_ascii(PhredEncoding::Phred64), "@JT^h");
+    }
+
+    #[test]
+    fn test_phred64() {
+        let q = QualitySequence::from_ascii("@JT^h", PhredEncoding::Phred64).unwrap();
+        assert_eq!(q.scores(), vec![0, 10, 20, 30, 40]);
+        assert!(QualitySequence::from_ascii("!", PhredEncoding::Phred64).is_err());
+    }
+
+    #[test]
+    fn test_error_probabilities() {
+        let q = QualitySequence::from(vec![0, 10, 20, 30]);
+        let p = q.error_probabilities();
+        assert!((p[0] - 1.0).abs() < 1e-9);
+        assert!((p[1] - 0.1).abs() < 1e-9);
+        assert!((p[2] - 0.01).abs() < 1e-9);
+        assert!((p[3] - 0.001).abs() < 1e-9);
+        assert_eq!(probability_to_phred(0.001), 30);
+        assert_eq!(q.mean(), Some(15f64));
+    }
+}
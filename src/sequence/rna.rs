@@ -5,15 +5,108 @@ use std::cmp::{Ord, Ordering};
 use std::fmt;
 pub use std::str::FromStr;
 
+/// A RNA nucleotide including the IUPAC ambiguity codes.
 #[derive(Clone, Debug)]
 pub enum RnaNucleotide {
     A,
     C,
     G,
     U,
+    /// Purine: A or G
+    R,
+    /// Pyrimidine: C or U
+    Y,
+    /// Strong: C or G
+    S,
+    /// Weak: A or U
+    W,
+    /// Keto: G or U
+    K,
+    /// Amino: A or C
+    M,
+    /// Not A: C, G, or U
+    B,
+    /// Not C: A, G, or U
+    D,
+    /// Not G: A, C, or U
+    H,
+    /// Not U: A, C, or G
+    V,
+    /// Any nucleotide
     N,
 }
 
+impl RnaNucleotide {
+    /// Returns the complementary nucleotide. Ambiguity codes are complemented
+    /// such that the complement represents the complements of all bases
+    /// the code stands for.
+    pub fn complement(&self) -> RnaNucleotide {
+        RnaNucleotide::from_dna(&self.as_dna().complement())
+    }
+
+    /// Returns `true` if the nucleotide is an ambiguity code, i.e.,
+    /// it stands for more than one base.
+    pub fn is_ambiguous(&self) -> bool {
+        self.as_dna().is_ambiguous()
+    }
+
+    /// Returns the unambiguous bases (`A`, `C`, `G`, `U`) this
+    /// nucleotide stands for.
+    pub fn expand(&self) -> Vec<RnaNucleotide> {
+        self.as_dna().expand().iter().map(|n| RnaNucleotide::from_dna(n)).collect()
+    }
+
+    /// Returns `true` if the two nucleotides may represent the same base,
+    /// i.e., the sets of bases they stand for intersect.
+    pub fn matches(&self, other: &RnaNucleotide) -> bool {
+        self.as_dna().matches(&other.as_dna())
+    }
+
+    /// Maps the nucleotide onto the DNA nucleotide with identical
+    /// base set (i.e., `U` becomes `T`).
+    fn as_dna(&self) -> DnaNucleotide {
+        match *self {
+            RnaNucleotide::A => DnaNucleotide::A,
+            RnaNucleotide::C => DnaNucleotide::C,
+            RnaNucleotide::G => DnaNucleotide::G,
+            RnaNucleotide::U => DnaNucleotide::T,
+            RnaNucleotide::R => DnaNucleotide::R,
+            RnaNucleotide::Y => DnaNucleotide::Y,
+            RnaNucleotide::S => DnaNucleotide::S,
+            RnaNucleotide::W => DnaNucleotide::W,
+            RnaNucleotide::K => DnaNucleotide::K,
+            RnaNucleotide::M => DnaNucleotide::M,
+            RnaNucleotide::B => DnaNucleotide::B,
+            RnaNucleotide::D => DnaNucleotide::D,
+            RnaNucleotide::H => DnaNucleotide::H,
+            RnaNucleotide::V => DnaNucleotide::V,
+            RnaNucleotide::N => DnaNucleotide::N,
+        }
+    }
+
+    /// Maps a DNA nucleotide onto the RNA nucleotide with identical
+    /// base set (i.e., `T` becomes `U`).
+    fn from_dna(n: &DnaNucleotide) -> RnaNucleotide {
+        match *n {
+            DnaNucleotide::A => RnaNucleotide::A,
+            DnaNucleotide::C => RnaNucleotide::C,
+            DnaNucleotide::G => RnaNucleotide::G,
+            DnaNucleotide::T => RnaNucleotide::U,
+            DnaNucleotide::R => RnaNucleotide::R,
+            DnaNucleotide::Y => RnaNucleotide::Y,
+            DnaNucleotide::S => RnaNucleotide::S,
+            DnaNucleotide::W => RnaNucleotide::W,
+            DnaNucleotide::K => RnaNucleotide::K,
+            DnaNucleotide::M => RnaNucleotide::M,
+            DnaNucleotide::B => RnaNucleotide::B,
+            DnaNucleotide::D => RnaNucleotide::D,
+            DnaNucleotide::H => RnaNucleotide::H,
+            DnaNucleotide::V => RnaNucleotide::V,
+            DnaNucleotide::N => RnaNucleotide::N,
+        }
+    }
+}
+
 impl SequenceElement for RnaNucleotide {}
 
 impl fmt::Display for RnaNucleotide {
@@ -45,14 +138,20 @@ impl Ord for RnaNucleotide {
 impl From<char> for RnaNucleotide {
     fn from(c: char) -> RnaNucleotide {
         match c {
-            'a' => RnaNucleotide::A,
-            'A' => RnaNucleotide::A,
-            'c' => RnaNucleotide::C,
-            'C' => RnaNucleotide::C,
-            'g' => RnaNucleotide::G,
-            'G' => RnaNucleotide::G,
-            'u' => RnaNucleotide::U,
-            'U' => RnaNucleotide::U,
+            'a' | 'A' => RnaNucleotide::A,
+            'c' | 'C' => RnaNucleotide::C,
+            'g' | 'G' => RnaNucleotide::G,
+            'u' | 'U' => RnaNucleotide::U,
+            'r' | 'R' => RnaNucleotide::R,
+            'y' | 'Y' => RnaNucleotide::Y,
+            's' | 'S' => RnaNucleotide::S,
+            'w' | 'W' => RnaNucleotide::W,
+            'k' | 'K' => RnaNucleotide::K,
+            'm' | 'M' => RnaNucleotide::M,
+            'b' | 'B' => RnaNucleotide::B,
+            'd' | 'D' => RnaNucleotide::D,
+            'h' | 'H' => RnaNucleotide::H,
+            'v' | 'V' => RnaNucleotide::V,
             _ => RnaNucleotide::N,
         }
     }
@@ -60,60 +159,33 @@ impl From<char> for RnaNucleotide {
 
 impl From<RnaNucleotide> for char {
     fn from(n: RnaNucleotide) -> char {
-        match n {
-            RnaNucleotide::A => 'A',
-            RnaNucleotide::C => 'C',
-            RnaNucleotide::G => 'G',
-            RnaNucleotide::U => 'U',
-            _ => 'N',
-        }
+        char::from(&n)
     }
 }
 
 impl From<u8> for RnaNucleotide {
     fn from(n: u8) -> RnaNucleotide {
-        match n {
-            1 => RnaNucleotide::A,
-            2 => RnaNucleotide::C,
-            3 => RnaNucleotide::G,
-            4 => RnaNucleotide::U,
-            _ => RnaNucleotide::N,
-        }
+        RnaNucleotide::from_dna(&DnaNucleotide::from(n))
     }
 }
 
 impl From<RnaNucleotide> for u8 {
     fn from(n: RnaNucleotide) -> u8 {
-        match n {
-            RnaNucleotide::A => 1,
-            RnaNucleotide::C => 2,
-            RnaNucleotide::G => 3,
-            RnaNucleotide::U => 4,
-            _ => 0,
-        }
+        u8::from(&n)
     }
 }
 
 impl<'a> From<&'a RnaNucleotide> for u8 {
     fn from(n: &'a RnaNucleotide) -> u8 {
-        match n.clone() {
-            RnaNucleotide::A => 1,
-            RnaNucleotide::C => 2,
-            RnaNucleotide::G => 3,
-            RnaNucleotide::U => 4,
-            _ => 0,
-        }
+        u8::from(n.as_dna())
     }
 }
 
 impl<'a> From<&'a RnaNucleotide> for char {
     fn from(n: &RnaNucleotide) -> char {
         match *n {
-            RnaNucleotide::A => 'A',
-            RnaNucleotide::C => 'C',
-            RnaNucleotide::G => 'G',
             RnaNucleotide::U => 'U',
-            _ => 'N',
+            _ => char::from(n.as_dna()),
         }
     }
 }
@@ -121,25 +193,13 @@ impl<'a> From<&'a RnaNucleotide> for char {
 
 impl From<DnaNucleotide> for RnaNucleotide {
     fn from(n: DnaNucleotide) -> RnaNucleotide {
-        match n {
-            DnaNucleotide::A => RnaNucleotide::U,
-            DnaNucleotide::C => RnaNucleotide::G,
-            DnaNucleotide::G => RnaNucleotide::C,
-            DnaNucleotide::T => RnaNucleotide::A,
-            DnaNucleotide::N => RnaNucleotide::N,
-        }
+        RnaNucleotide::from(&n)
     }
 }
 
 impl<'a> From<&'a DnaNucleotide> for RnaNucleotide {
     fn from(n: &DnaNucleotide) -> RnaNucleotide {
-        match *n {
-            DnaNucleotide::A => RnaNucleotide::U,
-            DnaNucleotide::C => RnaNucleotide::G,
-            DnaNucleotide::G => RnaNucleotide::C,
-            DnaNucleotide::T => RnaNucleotide::A,
-            DnaNucleotide::N => RnaNucleotide::N,
-        }
+        RnaNucleotide::from_dna(&n.complement())
     }
 }
 
@@ -236,6 +296,7 @@ impl<'a> From<&'a DnaSequence> for RnaSequence {
 #[cfg(test)]
 mod tests {
 
+    use sequence::dna::DnaNucleotide;
     use sequence::rna::RnaNucleotide;
 
     #[test]
@@ -275,7 +336,19 @@ mod tests {
 
     #[test]
     fn test_others() {
-        assert_eq!(RnaNucleotide::from('B'), RnaNucleotide::N);
-        assert_eq!(RnaNucleotide::from('H'), RnaNucleotide::N);
+        assert_eq!(RnaNucleotide::from('T'), RnaNucleotide::N);
+        assert_eq!(RnaNucleotide::from('X'), RnaNucleotide::N);
+    }
+
+    #[test]
+    fn test_iupac() {
+        assert_eq!(RnaNucleotide::from('r'), RnaNucleotide::R);
+        assert_eq!(char::from(RnaNucleotide::Y), 'Y');
+        assert_eq!(RnaNucleotide::R.complement(), RnaNucleotide::Y);
+        assert_eq!(RnaNucleotide::A.complement(), RnaNucleotide::U);
+        assert_eq!(RnaNucleotide::W.expand(), vec![RnaNucleotide::A, RnaNucleotide::U]);
+        assert!(RnaNucleotide::Y.matches(&RnaNucleotide::U));
+        assert!(!RnaNucleotide::Y.matches(&RnaNucleotide::G));
+        assert_eq!(RnaNucleotide::from(DnaNucleotide::R), RnaNucleotide::Y);
     }
 }
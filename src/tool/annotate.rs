@@ -0,0 +1,217 @@
+extern crate clap;
+
+use effect::{CSQ_FORMAT, EffectPredictor};
+use io::fasta::IndexedFastaFile;
+use io::gtf::{GtfHierarchy, GtfStream};
+use io::vcf::{VcfField, VcfRecord, VcfStream, VcfWriter};
+use model::Transcript;
+use sequence::GeneticCode;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::io::stdin;
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+
+/// The INFO field with the consequence annotations
+const CSQ_KEY: &'static str = "CSQ";
+
+pub struct Annotate {}
+
+impl Tool for Annotate {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        s.about("Predicts the effects of the variants of a VCF file on GTF/GFF3 transcripts")
+            .arg(
+                clap::Arg::with_name("vcf")
+                    .index(1)
+                    .required(true)
+                    .help("The (possibly compressed) VCF file (use '-' to read from standard input)"),
+            )
+            .arg(
+                clap::Arg::with_name("annotation")
+                    .long("annotation")
+                    .short("a")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The GTF or GFF3 file with the transcript annotation"),
+            )
+            .arg(
+                clap::Arg::with_name("fasta-ref")
+                    .long("fasta-ref")
+                    .short("f")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The faidx-indexed FASTA file with the reference sequence"),
+            )
+            .arg(
+                clap::Arg::with_name("table")
+                    .long("table")
+                    .takes_value(true)
+                    .value_name("N")
+                    .help("The NCBI translation table (defaults to 1)"),
+            )
+            .arg(
+                clap::Arg::with_name("distance")
+                    .long("distance")
+                    .short("d")
+                    .takes_value(true)
+                    .help("The maximal distance of up- and downstream variants to a transcript (defaults to 5000)"),
+            )
+            .arg(
+                clap::Arg::with_name("out")
+                    .long("out")
+                    .short("o")
+                    .takes_value(true)
+                    .help("The output file (use standard output if not given)"),
+            )
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let code = match args.value_of("table").unwrap_or("1").parse::<usize>() {
+            Ok(id) => match GeneticCode::from_ncbi_id(id) {
+                Ok(code) => code,
+                Err(e) => { error!("{}", e); process::exit(1) }
+            },
+            Err(e) => { error!("Can not parse translation table: {}", e); process::exit(1) }
+        };
+        let distance = match args.value_of("distance").unwrap_or("5000").parse::<usize>() {
+            Ok(d) => d,
+            Err(e) => { error!("Can not parse distance: {}", e); process::exit(1) }
+        };
+
+        let annotation = args.value_of("annotation").unwrap();
+        let transcripts = match GtfStream::open(&annotation).and_then(|mut s| s.read_records()) {
+            Ok(records) => match Transcript::from_gtf_hierarchy(&GtfHierarchy::new(records)) {
+                Ok(t) => t,
+                Err(e) => { error!("{}", e); process::exit(1) }
+            },
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+        let predictor = EffectPredictor::new(transcripts)
+            .with_genetic_code(code)
+            .with_upstream_distance(distance);
+
+        let fasta_filename = args.value_of("fasta-ref").unwrap();
+        let mut fasta = match IndexedFastaFile::open(&fasta_filename) {
+            Ok(f) => f,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let filename = args.value_of("vcf").unwrap();
+        let result = match filename {
+            "-" => VcfStream::new(stdin()).and_then(|s| Annotate::with_input(args, &predictor, &mut fasta, s)),
+            _ => VcfStream::open(&filename).and_then(|s| Annotate::with_input(args, &predictor, &mut fasta, s)),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Annotate {
+    fn with_input<R: Read>(
+        args: &clap::ArgMatches,
+        predictor: &EffectPredictor,
+        fasta: &mut IndexedFastaFile,
+        stream: VcfStream<R>,
+    ) -> Result<(), String> {
+        match args.value_of("out") {
+            Some(out) => match File::create(out) {
+                Ok(fh) => Annotate::write(predictor, fasta, stream, fh),
+                Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+            },
+            None => Annotate::write(predictor, fasta, stream, stdout()),
+        }
+    }
+
+    /// Adds the consequences of each alternative allele on all nearby transcripts
+    /// to the INFO field `CSQ`. Symbolic alleles are not annotated. Records whose
+    /// effects can not be predicted, e.g., because the reference allele does not
+    /// match, are written without annotation.
+    fn write<R: Read, W: Write>(
+        predictor: &EffectPredictor,
+        fasta: &mut IndexedFastaFile,
+        mut stream: VcfStream<R>,
+        writer: W,
+    ) -> Result<(), String> {
+        let error = |e: ::std::io::Error| format!("Can not write VCF: {}", e);
+        let header = stream.header().clone().with_info(VcfField::new(
+            CSQ_KEY,
+            ".",
+            "String",
+            format!("Consequence annotations. Format: {}", CSQ_FORMAT),
+        ));
+        let mut writer = VcfWriter::from(writer);
+        writer.write_header(&header).map_err(&error)?;
+
+        while let Some(record) = stream.next() {
+            let record = record?;
+            let record = match Annotate::annotate(predictor, fasta, &record) {
+                Ok(ref values) if values.is_empty() => record,
+                Ok(values) => record.with_info(CSQ_KEY, values.join(",")),
+                Err(e) => {
+                    warn!("Skipping annotation of {}:{}: {}", record.chrom(), record.position(), e);
+                    record
+                }
+            };
+            writer.append(&record).map_err(&error)?;
+        }
+        writer.flush().map_err(&error)
+    }
+
+    /// Returns the CSQ values of all alternative alleles of the record
+    fn annotate(predictor: &EffectPredictor, fasta: &mut IndexedFastaFile, record: &VcfRecord) -> Result<Vec<String>, String> {
+        let mut values = Vec::new();
+        for alternative in record.alternative_alleles().iter() {
+            if VcfRecord::is_symbolic_allele(alternative) || alternative == "*" {
+                continue;
+            }
+            let variant = record.clone().with_alternative_alleles(vec![alternative.clone()]);
+            for effect in predictor.predict(&variant, fasta)?.iter() {
+                values.push(effect.to_vcf_value(alternative));
+            }
+        }
+        Ok(values)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use effect::EffectPredictor;
+    use io::fasta::IndexedFastaFile;
+    use io::gtf::GtfStream;
+    use io::vcf::VcfStream;
+    use model::Transcript;
+    use tool::annotate::Annotate;
+
+    #[test]
+    fn test_write() {
+        let records = GtfStream::open(&"testdata/toy.gtf").unwrap().read_records().unwrap();
+        let predictor = EffectPredictor::new(Transcript::from_gtf_records(&records).unwrap());
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let vcf = "##fileformat=VCFv4.2\n\
+                   #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+                   ref\t10\t.\tG\tC,T\t.\t.\tDP=10\n\
+                   ref\t11\t.\tG\tC\t.\t.\t.\n\
+                   ref2\t1\t.\tA\t<DEL>\t.\t.\t.\n";
+        let mut out = Vec::new();
+        Annotate::write(&predictor, &mut fasta, VcfStream::new(vcf.as_bytes()).unwrap(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[1],
+            "##INFO=<ID=CSQ,Number=.,Type=String,Description=\"Consequence annotations. \
+             Format: Allele|Consequence|IMPACT|Gene|Feature|Codons|Amino_acids|Protein_position|HGVSc|HGVSp\">"
+        );
+        assert_eq!(
+            lines[3],
+            "ref\t10\t.\tG\tC,T\t.\t.\tDP=10;CSQ=C|missense_variant|MODERATE|g1|t1|Gat/Cat|D/H|3|t1:c.7G>C|p.(Asp3His),\
+             T|missense_variant|MODERATE|g1|t1|Gat/Tat|D/Y|3|t1:c.7G>T|p.(Asp3Tyr)"
+        );
+        // The reference allele does not match the coding sequence
+        assert_eq!(lines[4], "ref\t11\t.\tG\tC\t.\t.\t.");
+        assert_eq!(lines[5], "ref2\t1\t.\tA\t<DEL>\t.\t.\t.");
+    }
+}
@@ -0,0 +1,309 @@
+extern crate clap;
+
+use io::bed::{BedRecord, BedStream};
+use model::{IntervalTree, Region, SequenceDictionary, SimpleRegion};
+use model::regionset;
+use std::fs::File;
+use std::io::Write;
+use std::io::stdin;
+use std::io::stdout;
+use std::process;
+use std::ptr;
+use tool::Tool;
+
+pub struct Bed {}
+
+impl Tool for Bed {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        let input = |name: &'a str, short: &'a str, help: &'a str| {
+            clap::Arg::with_name(name)
+                .long(name)
+                .short(short)
+                .takes_value(true)
+                .required(true)
+                .help(help)
+        };
+        let genome = || input("genome", "g", "The genome file with template names and lengths (e.g., a chrom.sizes or .fai file)");
+        let out = || {
+            clap::Arg::with_name("out")
+                .long("out")
+                .short("o")
+                .takes_value(true)
+                .help("The output file (use standard output if not given)")
+        };
+        let number = |name: &'a str, short: &'a str, help: &'a str| {
+            clap::Arg::with_name(name)
+                .long(name)
+                .short(short)
+                .takes_value(true)
+                .help(help)
+        };
+
+        s.about("Set operations on BED files (use '-' to read from standard input)")
+            .setting(clap::AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                clap::SubCommand::with_name("merge")
+                    .about("Merges overlapping and book-ended regions")
+                    .arg(input("in", "i", "The input BED file"))
+                    .arg(number("distance", "d", "Merge regions that are at most this far apart (defaults to 0)"))
+                    .arg(out()),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("intersect")
+                    .about("Reports the overlaps between the regions of two BED files")
+                    .arg(input("a", "a", "The BED file whose regions are reported"))
+                    .arg(input("b", "b", "The BED file with the regions to overlap with"))
+                    .arg(clap::Arg::with_name("wa").long("wa").help("Write the original region of A for each overlap"))
+                    .arg(clap::Arg::with_name("wb").long("wb").help("Write the region of B after the region of A for each overlap"))
+                    .arg(clap::Arg::with_name("u").short("u").help("Write each overlapping region of A only once"))
+                    .arg(
+                        clap::Arg::with_name("v")
+                            .short("v")
+                            .conflicts_with_all(&["wa", "wb", "u"])
+                            .help("Write the regions of A without overlap"),
+                    )
+                    .arg(out()),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("subtract")
+                    .about("Removes the parts of the regions of A that overlap B")
+                    .arg(input("a", "a", "The BED file whose regions are reported"))
+                    .arg(input("b", "b", "The BED file with the regions to remove"))
+                    .arg(out()),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("complement")
+                    .about("Reports the parts of the genome not covered by any region")
+                    .arg(input("in", "i", "The input BED file"))
+                    .arg(genome())
+                    .arg(out()),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("slop")
+                    .about("Extends the regions on both sides")
+                    .arg(input("in", "i", "The input BED file"))
+                    .arg(genome())
+                    .arg(number("both", "b", "The number of positions to add on both sides"))
+                    .arg(number("left", "l", "The number of positions to add before each region"))
+                    .arg(number("right", "r", "The number of positions to add after each region"))
+                    .arg(out()),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("flank")
+                    .about("Reports the flanking regions on both sides")
+                    .arg(input("in", "i", "The input BED file"))
+                    .arg(genome())
+                    .arg(number("both", "b", "The length of the flanks on both sides"))
+                    .arg(number("left", "l", "The length of the flank before each region"))
+                    .arg(number("right", "r", "The length of the flank after each region"))
+                    .arg(out()),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("windows")
+                    .about("Splits the genome or the regions of a BED file into windows")
+                    .arg(input("genome", "g", "The genome file with template names and lengths").required(false))
+                    .arg(input("in", "i", "Split the regions of this BED file instead of the genome").required(false))
+                    .arg(number("size", "w", "The window size").required(true))
+                    .arg(number("step", "s", "The distance between window starts (defaults to the window size)"))
+                    .group(clap::ArgGroup::with_name("source").args(&["genome", "in"]).required(true))
+                    .arg(out()),
+            )
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let (name, sub_args) = args.subcommand();
+        let sub_args = match sub_args {
+            Some(a) => a,
+            None => return,
+        };
+        let lines = match name {
+            "merge" => Bed::merge(sub_args),
+            "intersect" => Bed::intersect(sub_args),
+            "subtract" => Bed::subtract(sub_args),
+            "complement" => Bed::complement(sub_args),
+            "slop" => Bed::slop(sub_args, false),
+            "flank" => Bed::slop(sub_args, true),
+            "windows" => Bed::windows(sub_args),
+            _ => Err(format!("Unknown subcommand '{}'", name)),
+        };
+        let lines = match lines {
+            Ok(l) => l,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let result = match sub_args.value_of("out") {
+            Some(filename) => match File::create(filename) {
+                Ok(fh) => Bed::write(&lines, fh),
+                Err(e) => { error!("Can not open '{}' for write: {}", filename, e); process::exit(1) }
+            },
+            None => Bed::write(&lines, stdout()),
+        };
+        if let Err(e) = result {
+            error!("Can not write output: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Bed {
+    fn write<W: Write>(lines: &Vec<String>, mut writer: W) -> Result<(), String> {
+        for line in lines {
+            if let Err(e) = writeln!(writer, "{}", line) {
+                return Err(e.to_string());
+            }
+        }
+        writer.flush().map_err(|e| e.to_string())
+    }
+
+    /// Reads the BED records from the file or from standard input for `-`
+    fn read_records(filename: &str) -> Result<Vec<BedRecord>, String> {
+        match filename {
+            "-" => Ok(BedStream::new(stdin()).read_records()),
+            _ => match BedStream::open(&filename) {
+                Ok(mut s) => Ok(s.read_records()),
+                Err(e) => Err(format!("Can not open '{}' for read: {}", filename, e)),
+            },
+        }
+    }
+
+    /// Reads a genome file with the template name and length in the first two
+    /// columns and returns one region per template. This covers chrom.sizes files
+    /// as well as FASTA indexes.
+    fn read_genome(filename: &str) -> Result<Vec<SimpleRegion>, String> {
+        SequenceDictionary::read_genome_file(&filename).map(|d| d.regions())
+    }
+
+    fn parse_number(args: &clap::ArgMatches, name: &str, default: usize) -> Result<usize, String> {
+        match args.value_of(name) {
+            Some(v) => v.parse::<usize>().map_err(|e| format!("Can not parse --{} '{}': {}", name, v, e)),
+            None => Ok(default),
+        }
+    }
+
+    /// Returns the record with the coordinates of the region
+    fn with_region<R: Region>(record: &BedRecord, region: &R) -> BedRecord {
+        record.clone()
+            .with_chrom_start(region.offset())
+            .with_chrom_end(region.end())
+    }
+
+    fn as_bed<R: Region>(region: &R) -> String {
+        BedRecord::new(&region.template(), region.offset(), region.end()).to_string()
+    }
+
+    fn merge(args: &clap::ArgMatches) -> Result<Vec<String>, String> {
+        let records = Bed::read_records(args.value_of("in").unwrap())?;
+        let distance = Bed::parse_number(args, "distance", 0)?;
+        Ok(regionset::merge(&records, distance).iter().map(Bed::as_bed).collect())
+    }
+
+    fn intersect(args: &clap::ArgMatches) -> Result<Vec<String>, String> {
+        let a = Bed::read_records(args.value_of("a").unwrap())?;
+        let b = IntervalTree::new(Bed::read_records(args.value_of("b").unwrap())?);
+        Ok(Bed::intersect_lines(&a, &b, args.is_present("wa"), args.is_present("wb"), args.is_present("u"), args.is_present("v")))
+    }
+
+    fn intersect_lines(a: &[BedRecord], b: &IntervalTree<BedRecord>, wa: bool, wb: bool, unique: bool, without: bool) -> Vec<String> {
+        if without {
+            return regionset::without_overlap(a, b).iter().map(|r| r.to_string()).collect();
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut last_a: Option<&BedRecord> = None;
+        for (record, other, overlap) in regionset::intersect(a, b) {
+            if unique {
+                if last_a.map(|l| ptr::eq(l, record)).unwrap_or(false) {
+                    continue;
+                }
+                last_a = Some(record);
+            }
+            let reported = match wa || unique {
+                true => record.clone(),
+                false => Bed::with_region(record, &overlap),
+            };
+            lines.push(match wb && !unique {
+                true => format!("{}\t{}", reported, other),
+                false => reported.to_string(),
+            });
+        }
+        lines
+    }
+
+    fn subtract(args: &clap::ArgMatches) -> Result<Vec<String>, String> {
+        let a = Bed::read_records(args.value_of("a").unwrap())?;
+        let b = IntervalTree::new(Bed::read_records(args.value_of("b").unwrap())?);
+        Ok(regionset::subtract(&a, &b)
+            .iter()
+            .map(|&(record, ref part)| Bed::with_region(record, part).to_string())
+            .collect())
+    }
+
+    fn complement(args: &clap::ArgMatches) -> Result<Vec<String>, String> {
+        let records = Bed::read_records(args.value_of("in").unwrap())?;
+        let genome = Bed::read_genome(args.value_of("genome").unwrap())?;
+        Ok(regionset::complement(&records, &genome).iter().map(Bed::as_bed).collect())
+    }
+
+    /// Runs `slop` or, if `flank` is set, `flank`
+    fn slop(args: &clap::ArgMatches, flank: bool) -> Result<Vec<String>, String> {
+        let both = Bed::parse_number(args, "both", 0)?;
+        let left = Bed::parse_number(args, "left", both)?;
+        let right = Bed::parse_number(args, "right", both)?;
+        let records = Bed::read_records(args.value_of("in").unwrap())?;
+        let genome = Bed::read_genome(args.value_of("genome").unwrap())?;
+        let regions = match flank {
+            true => regionset::flank(&records, left, right, &genome)?,
+            false => regionset::slop(&records, left, right, &genome)?,
+        };
+        Ok(regions
+            .iter()
+            .map(|&(record, ref region)| Bed::with_region(record, region).to_string())
+            .collect())
+    }
+
+    fn windows(args: &clap::ArgMatches) -> Result<Vec<String>, String> {
+        let size = Bed::parse_number(args, "size", 0)?;
+        let step = Bed::parse_number(args, "step", size)?;
+        if size == 0 || step == 0 {
+            return Err(format!("Window size and step must be larger than zero"));
+        }
+        let regions = match args.value_of("genome") {
+            Some(g) => Bed::read_genome(g)?,
+            None => Bed::read_records(args.value_of("in").unwrap())?
+                .iter()
+                .map(|r| SimpleRegion::new(r.template(), r.offset(), r.length()))
+                .collect(),
+        };
+        Ok(regionset::windows(&regions, size, step).iter().map(Bed::as_bed).collect())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use model::{IntervalTree, Region};
+    use tool::bed::Bed;
+
+    #[test]
+    fn test_read_genome() {
+        let genome = Bed::read_genome("testdata/toy.fasta.fai").unwrap();
+        assert_eq!(genome.len(), 2);
+        assert_eq!((genome[1].template(), genome[1].length()), ("ref2".to_string(), 40));
+    }
+
+    #[test]
+    fn test_intersect_lines() {
+        let a = Bed::read_records("testdata/toy.bed").unwrap();
+        let b = IntervalTree::new(Bed::read_records("testdata/toy.bed.gz").unwrap());
+
+        let lines = Bed::intersect_lines(&a[2..3], &b, false, false, false, false);
+        assert_eq!(lines, vec!["ref\t34\t37\tGeneB\t0\t-", "ref\t34\t41\tGeneB\t0\t-"]);
+
+        let lines = Bed::intersect_lines(&a[2..3], &b, true, true, false, false);
+        assert_eq!(lines[0], "ref\t34\t41\tGeneB\t0\t-\tref\t29\t37\tGeneA_Exon2\t0\t+");
+
+        let lines = Bed::intersect_lines(&a[2..3], &b, false, false, true, false);
+        assert_eq!(lines, vec!["ref\t34\t41\tGeneB\t0\t-"]);
+        assert!(Bed::intersect_lines(&a, &b, false, false, false, true).is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+extern crate clap;
+
+use caller::VariantCaller;
+use io::bam::IndexedBamReader;
+use io::fasta::IndexedFastaFile;
+use io::vcf::{VcfContig, VcfField, VcfHeader, VcfWriter};
+use model::{Region, SimpleRegion};
+use pileup::Pileup;
+use std::cmp;
+use std::fs::File;
+use std::io::Write;
+use std::io::stdout;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+use tool::Tool;
+use util;
+
+/// The number of positions that are piled up at once
+const WINDOW_SIZE: usize = 100000;
+
+pub struct Call {}
+
+impl Tool for Call {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        let value = |name: &'a str, short: &'a str, help: &'a str| {
+            clap::Arg::with_name(name)
+                .long(name)
+                .short(short)
+                .takes_value(true)
+                .help(help)
+        };
+        s.about("Calls germline SNVs and short indels of a diploid sample from an indexed BAM file and writes them as VCF")
+            .arg(
+                clap::Arg::with_name("bam")
+                    .index(1)
+                    .required(true)
+                    .help("The indexed BAM file"),
+            )
+            .arg(value("fasta-ref", "f", "The faidx-indexed FASTA file with the reference sequence").required(true))
+            .arg(
+                value("region", "r", "Call variants in the region 'name' or 'name:start-end' (1-based, inclusive; defaults to all templates)")
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(value("sample", "s", "The sample name (defaults to the name of the BAM file)"))
+            .arg(value("min-BQ", "Q", "Skip bases with a lower base quality (defaults to 13)"))
+            .arg(value("min-MQ", "q", "Skip reads with a lower mapping quality (defaults to 0)"))
+            .arg(value("min-depth", "d", "Skip positions with fewer reads (defaults to 3)"))
+            .arg(value("min-alt-count", "c", "Skip alleles supported by fewer reads (defaults to 2)"))
+            .arg(value("min-alt-fraction", "a", "Skip alleles supported by a lower fraction of reads (defaults to 0.1)"))
+            .arg(value("min-quality", "m", "Skip calls with a lower variant quality (defaults to 20)"))
+            .arg(
+                clap::Arg::with_name("ignore-overlaps")
+                    .long("ignore-overlaps")
+                    .short("x")
+                    .help("Count both mates of a pair where they overlap"),
+            )
+            .arg(value("out", "o", "The output VCF file (use standard output if not given)"))
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let (engine, caller) = match Call::configure(args) {
+            Ok(c) => c,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let filename = args.value_of("bam").unwrap();
+        let mut bam = match IndexedBamReader::open(&filename) {
+            Ok(b) => b,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+        let fasta_filename = args.value_of("fasta-ref").unwrap();
+        let mut fasta = match IndexedFastaFile::open(&fasta_filename) {
+            Ok(f) => f,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let regions = match args.values_of("region") {
+            Some(r) => match r.map(|r| util::parse_region(r).map(|(name, interval)| SimpleRegion::from_interval(name, interval))).collect::<Result<Vec<SimpleRegion>, String>>() {
+                Ok(r) => r,
+                Err(e) => { error!("{}", e); process::exit(1) }
+            },
+            None => bam.dictionary().regions(),
+        };
+        let sample = match args.value_of("sample") {
+            Some(s) => s.to_string(),
+            None => Path::new(filename)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or(filename.to_string()),
+        };
+
+        let result = match args.value_of("out") {
+            Some(out) => match File::create(out) {
+                Ok(fh) => Call::write(&engine, &caller, &mut bam, &mut fasta, &regions, &sample, fh),
+                Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+            },
+            None => Call::write(&engine, &caller, &mut bam, &mut fasta, &regions, &sample, stdout()),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Call {
+    /// Creates the pileup engine and the variant caller from the arguments
+    fn configure(args: &clap::ArgMatches) -> Result<(Pileup, VariantCaller), String> {
+        fn parse<T: FromStr>(args: &clap::ArgMatches, name: &str, default: T) -> Result<T, String>
+        where
+            T::Err: ToString,
+        {
+            match args.value_of(name) {
+                Some(v) => v.parse::<T>().map_err(|e| format!("Can not parse --{} '{}': {}", name, v, e.to_string())),
+                None => Ok(default),
+            }
+        }
+
+        let engine = Pileup::new()
+            .with_min_base_quality(parse(args, "min-BQ", 13u8)?)
+            .with_min_mapping_quality(parse(args, "min-MQ", 0u8)?)
+            .with_overlap_detection(!args.is_present("ignore-overlaps"));
+        let caller = VariantCaller::new()
+            .with_min_depth(parse(args, "min-depth", 3usize)?)
+            .with_min_alt_count(parse(args, "min-alt-count", 2usize)?)
+            .with_min_alt_fraction(parse(args, "min-alt-fraction", 0.1f64)?)
+            .with_min_quality(parse(args, "min-quality", 20f64)?);
+        Ok((engine, caller))
+    }
+
+    /// Returns the VCF header with the templates of the BAM file as contigs
+    fn header(bam: &IndexedBamReader, sample: &str) -> VcfHeader {
+        let mut header = VcfHeader::new()
+            .with_info(VcfField::new("DP", 1, "Integer", "Number of reads used for the call"))
+            .with_info(VcfField::new("AF", "A", "Float", "Fraction of reads supporting the alternative allele"))
+            .with_format(VcfField::new("GT", 1, "String", "Genotype"))
+            .with_format(VcfField::new("GQ", 1, "Integer", "Genotype quality"))
+            .with_format(VcfField::new("DP", 1, "Integer", "Number of reads used for the call"))
+            .with_format(VcfField::new("AD", "R", "Integer", "Number of reads supporting each allele"));
+        for name in bam.dictionary().names() {
+            let length = bam.dictionary().length(&name);
+            header = header.with_contig(VcfContig::new(name, length));
+        }
+        header.with_sample(sample)
+    }
+
+    /// Calls the variants of the regions window by window
+    fn write<W: Write>(
+        engine: &Pileup,
+        caller: &VariantCaller,
+        bam: &mut IndexedBamReader,
+        fasta: &mut IndexedFastaFile,
+        regions: &[SimpleRegion],
+        sample: &str,
+        writer: W,
+    ) -> Result<(), String> {
+        let error = |e: ::std::io::Error| format!("Can not write variants: {}", e);
+        let mut writer = VcfWriter::from(writer);
+        writer.write_header(&Call::header(bam, sample)).map_err(&error)?;
+        for region in regions.iter() {
+            let region = bam.dictionary().clamp(region)?;
+            let mut offset = region.offset();
+            while offset < region.end() {
+                let window = SimpleRegion::new(region.template(), offset, cmp::min(WINDOW_SIZE, region.end() - offset));
+                let columns = engine.from_bam(bam, &window, Some(&mut *fasta))?;
+                for call in caller.call_all(&columns).iter() {
+                    writer.append(&call.to_vcf_record()).map_err(&error)?;
+                }
+                offset = window.end();
+            }
+        }
+        writer.flush().map_err(&error)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use caller::VariantCaller;
+    use io::bam::IndexedBamReader;
+    use io::fasta::IndexedFastaFile;
+    use pileup::Pileup;
+    use model::SimpleRegion;
+    use std::usize;
+    use tool::call::Call;
+
+    #[test]
+    fn test_write() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let regions = vec![SimpleRegion::new("ref2", 0, usize::MAX), SimpleRegion::new("ref", 0, 20)];
+        let mut out = Vec::new();
+        Call::write(&Pileup::new(), &VariantCaller::new(), &mut bam, &mut fasta, &regions, "toy", &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.contains(&"##contig=<ID=ref2,length=40>"));
+        assert!(lines.contains(&"##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Number of reads supporting each allele\">"));
+        assert!(lines.contains(&"#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ttoy"));
+        let records: Vec<&str> = lines.into_iter().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(records.len(), 1);
+        assert!(records[0].starts_with("ref2\t14\t.\tC\tT\t"));
+        assert!(records[0].ends_with("\t0/1:47:6:3,3"));
+    }
+}
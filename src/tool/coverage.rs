@@ -0,0 +1,234 @@
+extern crate clap;
+
+use coverage::{CoverageCalculator, CoverageSummary};
+use io::bam::IndexedBamReader;
+use io::bed::BedStream;
+use model::{Region, SimpleRegion};
+use std::cmp;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+use util;
+
+/// The number of positions whose depth is computed at once
+const WINDOW_SIZE: usize = 1000000;
+
+pub struct Coverage {}
+
+impl Tool for Coverage {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        let value = |name: &'a str, short: &'a str, help: &'a str| {
+            clap::Arg::with_name(name)
+                .long(name)
+                .short(short)
+                .takes_value(true)
+                .help(help)
+        };
+        s.about("Computes the per-base depth of an indexed BAM file and writes summary statistics per target")
+            .arg(
+                clap::Arg::with_name("bam")
+                    .index(1)
+                    .required(true)
+                    .help("The indexed BAM file"),
+            )
+            .arg(
+                value("region", "r", "The target region 'name' or 'name:start-end' (1-based, inclusive)")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .conflicts_with("targets"),
+            )
+            .arg(value("targets", "b", "The BED file with the target regions (defaults to all templates)"))
+            .arg(value("thresholds", "t", "Comma separated depths to report the breadth for (defaults to 1,10,20,30)"))
+            .arg(value("min-MQ", "q", "Skip reads with a lower mapping quality (defaults to 0)"))
+            .arg(
+                clap::Arg::with_name("ignore-overlaps")
+                    .long("ignore-overlaps")
+                    .short("x")
+                    .help("Count both mates of a pair where they overlap"),
+            )
+            .arg(value("bedgraph", "g", "Write the per-base depth of the targets to this bedGraph file"))
+            .arg(value("out", "o", "The output file for the summary table (use standard output if not given)"))
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let min_mapping_quality = match args.value_of("min-MQ").unwrap_or("0").parse::<u8>() {
+            Ok(q) => q,
+            Err(e) => { error!("Can not parse --min-MQ: {}", e); process::exit(1) }
+        };
+        let calculator = CoverageCalculator::new()
+            .with_min_mapping_quality(min_mapping_quality)
+            .with_overlap_detection(!args.is_present("ignore-overlaps"));
+        let thresholds = match Coverage::parse_thresholds(args.value_of("thresholds").unwrap_or("1,10,20,30")) {
+            Ok(t) => t,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let filename = args.value_of("bam").unwrap();
+        let mut bam = match IndexedBamReader::open(&filename) {
+            Ok(b) => b,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+        let targets = match (args.values_of("region"), args.value_of("targets")) {
+            (Some(r), _) => r
+                .map(|r| util::parse_region(r).map(|(name, interval)| (SimpleRegion::from_interval(name, interval), None)))
+                .collect(),
+            (None, Some(t)) => Coverage::read_targets(t),
+            (None, None) => Ok(bam.dictionary().regions().into_iter().map(|r| (r, None)).collect()),
+        };
+        let targets = match targets {
+            Ok(t) => t,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let bedgraph = match args.value_of("bedgraph").map(|f| (f, File::create(f))) {
+            Some((_, Ok(fh))) => Some(fh),
+            Some((f, Err(e))) => { error!("Can not open '{}' for write: {}", f, e); process::exit(1) }
+            None => None,
+        };
+        let result = match args.value_of("out") {
+            Some(out) => match File::create(out) {
+                Ok(fh) => Coverage::write(&calculator, &mut bam, &targets, &thresholds, fh, bedgraph),
+                Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+            },
+            None => Coverage::write(&calculator, &mut bam, &targets, &thresholds, stdout(), bedgraph),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Coverage {
+    fn parse_thresholds(s: &str) -> Result<Vec<u32>, String> {
+        s.split(',')
+            .map(|t| t.trim().parse::<u32>().map_err(|e| format!("Can not parse threshold '{}': {}", t, e)))
+            .collect()
+    }
+
+    /// Reads the target regions and their names from a BED file
+    fn read_targets(filename: &str) -> Result<Vec<(SimpleRegion, Option<String>)>, String> {
+        match BedStream::open(&filename) {
+            Ok(mut s) => Ok(s.read_records()
+                .iter()
+                .map(|r| (SimpleRegion::new(r.template(), r.offset(), r.length()), r.name()))
+                .collect()),
+            Err(e) => Err(format!("Can not open '{}' for read: {}", filename, e)),
+        }
+    }
+
+    /// Writes one summary line per target followed by the summary of all targets
+    /// and the run-length merged depths of the targets as bedGraph
+    fn write<S: Write, B: Write>(
+        calculator: &CoverageCalculator,
+        bam: &mut IndexedBamReader,
+        targets: &[(SimpleRegion, Option<String>)],
+        thresholds: &[u32],
+        summary: S,
+        bedgraph: Option<B>,
+    ) -> Result<(), String> {
+        let error = |e: ::std::io::Error| format!("Can not write coverage: {}", e);
+        let mut summary = BufWriter::new(summary);
+        let mut bedgraph = bedgraph.map(BufWriter::new);
+
+        let header: Vec<String> = thresholds.iter().map(|t| format!("\t{}x", t)).collect();
+        writeln!(summary, "#template\tstart\tend\tname\tlength\tmean\tmedian{}", header.concat()).map_err(&error)?;
+        // Writes the coordinates (template, start, end) and the name of a target
+        // followed by its statistics
+        let write_summary = |w: &mut BufWriter<S>, target: [String; 4], s: &CoverageSummary| {
+            let breadths: Vec<String> = thresholds.iter().map(|t| format!("\t{:.4}", s.breadth(*t))).collect();
+            writeln!(
+                w,
+                "{}\t{}\t{:.2}\t{:.2}{}",
+                target.join("\t"),
+                s.length(),
+                s.mean(),
+                s.median(),
+                breadths.concat()
+            )
+        };
+
+        let mut total = CoverageSummary::new();
+        for &(ref target, ref name) in targets.iter() {
+            let target = bam.dictionary().clamp(target)?;
+            let mut target_summary = CoverageSummary::new();
+
+            // The bedGraph run that may continue in the next window
+            let mut run: Option<(SimpleRegion, u32)> = None;
+            let mut offset = target.offset();
+            while offset < target.end() {
+                let window = SimpleRegion::new(target.template(), offset, cmp::min(WINDOW_SIZE, target.end() - offset));
+                let coverage = calculator.from_bam(bam, &window)?;
+                target_summary.merge(&coverage.summary());
+                if let Some(ref mut w) = bedgraph {
+                    for (region, depth) in coverage.runs().into_iter() {
+                        run = match run {
+                            Some((ref r, d)) if d == depth => Some((SimpleRegion::new(r.template(), r.offset(), region.end() - r.offset()), d)),
+                            Some((ref r, d)) => {
+                                writeln!(w, "{}\t{}\t{}\t{}", r.template(), r.offset(), r.end(), d).map_err(&error)?;
+                                Some((region, depth))
+                            }
+                            None => Some((region, depth)),
+                        };
+                    }
+                }
+                offset = window.end();
+            }
+            if let (Some(ref mut w), Some((ref r, d))) = (bedgraph.as_mut(), run) {
+                writeln!(w, "{}\t{}\t{}\t{}", r.template(), r.offset(), r.end(), d).map_err(&error)?;
+            }
+
+            let columns = [
+                target.template(),
+                target.offset().to_string(),
+                target.end().to_string(),
+                name.clone().unwrap_or(".".to_string()),
+            ];
+            write_summary(&mut summary, columns, &target_summary).map_err(&error)?;
+            total.merge(&target_summary);
+        }
+        let columns = ["total".to_string(), ".".to_string(), ".".to_string(), ".".to_string()];
+        write_summary(&mut summary, columns, &total).map_err(&error)?;
+
+        if let Some(ref mut w) = bedgraph {
+            w.flush().map_err(&error)?;
+        }
+        summary.flush().map_err(&error)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use coverage::CoverageCalculator;
+    use io::bam::IndexedBamReader;
+    use model::SimpleRegion;
+    use tool::coverage::Coverage;
+
+    #[test]
+    fn test_write() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let targets = vec![
+            (SimpleRegion::new("ref", 15, 9), Some("a".to_string())),
+            (SimpleRegion::new("ref2", 0, 4), None),
+        ];
+        let (mut summary, mut bedgraph) = (Vec::new(), Vec::new());
+        Coverage::write(&CoverageCalculator::new(), &mut bam, &targets, &[1, 3], &mut summary, Some(&mut bedgraph)).unwrap();
+        assert_eq!(
+            String::from_utf8(summary).unwrap(),
+            "#template\tstart\tend\tname\tlength\tmean\tmedian\t1x\t3x\n\
+             ref\t15\t24\ta\t9\t1.67\t2.00\t0.7778\t0.3333\n\
+             ref2\t0\t4\t.\t4\t1.75\t2.00\t1.0000\t0.0000\n\
+             total\t.\t.\t.\t13\t1.69\t2.00\t0.8462\t0.2308\n"
+        );
+        assert_eq!(
+            String::from_utf8(bedgraph).unwrap(),
+            "ref\t15\t18\t3\nref\t18\t19\t1\nref\t19\t21\t2\nref\t21\t22\t1\nref\t22\t24\t0\nref2\t0\t1\t1\nref2\t1\t4\t2\n"
+        );
+
+        assert_eq!(Coverage::parse_thresholds("1, 5").unwrap(), vec![1, 5]);
+        assert!(Coverage::parse_thresholds("1,x").is_err());
+    }
+}
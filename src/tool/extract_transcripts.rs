@@ -0,0 +1,189 @@
+extern crate clap;
+
+use io::fasta::{FastaWriter, IndexedFastaFile};
+use io::gtf::{GtfHierarchy, GtfStream};
+use model::Transcript;
+use sequence::GeneticCode;
+use std::fs::File;
+use std::io::Write;
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+
+pub struct ExtractTranscripts {}
+
+impl Tool for ExtractTranscripts {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        s.about("Extracts the spliced transcript, coding or protein sequences of GTF/GFF3 transcripts")
+            .arg(
+                clap::Arg::with_name("fasta")
+                    .index(1)
+                    .required(true)
+                    .help("The (indexed) FASTA file with the template sequences"),
+            )
+            .arg(
+                clap::Arg::with_name("annotation")
+                    .index(2)
+                    .required(true)
+                    .help("The GTF or GFF3 file with the transcript annotation"),
+            )
+            .arg(
+                clap::Arg::with_name("type")
+                    .long("type")
+                    .short("t")
+                    .takes_value(true)
+                    .possible_values(&["mrna", "cds", "protein"])
+                    .help("The sequence to extract (defaults to mrna)"),
+            )
+            .arg(
+                clap::Arg::with_name("table")
+                    .long("table")
+                    .takes_value(true)
+                    .value_name("N")
+                    .help("The NCBI translation table used for proteins (defaults to 1)"),
+            )
+            .arg(
+                clap::Arg::with_name("out")
+                    .long("out")
+                    .short("o")
+                    .takes_value(true)
+                    .help("The output FASTA file (use standard output if not given)"),
+            )
+            .arg(
+                clap::Arg::with_name("length")
+                    .long("length")
+                    .short("n")
+                    .takes_value(true)
+                    .help("The length of the sequence lines in the output (defaults to 60)"),
+            )
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let code = match args.value_of("table").unwrap_or("1").parse::<usize>() {
+            Ok(id) => {
+                match GeneticCode::from_ncbi_id(id) {
+                    Ok(code) => code,
+                    Err(e) => { error!("{}", e); process::exit(1) }
+                }
+            }
+            Err(e) => { error!("Can not parse translation table: {}", e); process::exit(1) }
+        };
+        let linelength = match args.value_of("length").unwrap_or("60").parse::<usize>() {
+            Ok(l) if l > 0 => l,
+            Ok(_) => { error!("Line length must be larger than zero"); process::exit(1) }
+            Err(e) => { error!("Can not parse line length: {}", e); process::exit(1) }
+        };
+
+        let annotation = args.value_of("annotation").unwrap();
+        let transcripts = match GtfStream::open(&annotation).and_then(|mut s| s.read_records()) {
+            Ok(records) => match Transcript::from_gtf_hierarchy(&GtfHierarchy::new(records)) {
+                Ok(t) => t,
+                Err(e) => { error!("{}", e); process::exit(1) }
+            },
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let filename = args.value_of("fasta").unwrap();
+        let mut fasta = match IndexedFastaFile::open(&filename) {
+            Ok(f) => f,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let sequence_type = args.value_of("type").unwrap_or("mrna");
+        let result = match args.value_of("out") {
+            Some(out) => {
+                match File::create(out) {
+                    Ok(fh) => ExtractTranscripts::extract(&transcripts, &mut fasta, sequence_type, &code, linelength, fh),
+                    Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+                }
+            }
+            None => ExtractTranscripts::extract(&transcripts, &mut fasta, sequence_type, &code, linelength, stdout()),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl ExtractTranscripts {
+    /// Writes the requested sequence of each transcript. Non-coding transcripts are
+    /// skipped for coding and protein sequences. Transcripts whose sequence can not be
+    /// extracted are reported and make the extraction fail once all others are written.
+    fn extract<W: Write>(
+        transcripts: &Vec<Transcript>,
+        fasta: &mut IndexedFastaFile,
+        sequence_type: &str,
+        code: &GeneticCode,
+        linelength: usize,
+        writer: W,
+    ) -> Result<(), String> {
+        let mut writer = FastaWriter::from(writer);
+        writer.set_linelength(linelength);
+
+        let mut failed = 0;
+        for transcript in transcripts {
+            if sequence_type != "mrna" && !transcript.is_coding() {
+                debug!("Skipping non-coding transcript '{}'", transcript.id());
+                continue;
+            }
+            let sequence = match sequence_type {
+                "cds" => transcript.cds_sequence(fasta).map(|s| s.to_string()),
+                "protein" => transcript.peptide(fasta, code).map(|p| p.to_string()),
+                _ => transcript.spliced_sequence(fasta).map(|s| s.to_string()),
+            };
+            let sequence = match sequence {
+                Ok(s) => s,
+                Err(e) => { error!("{}", e); failed += 1; continue }
+            };
+
+            let header = match transcript.gene_id() {
+                Some(g) => format!("{} gene={}", transcript.id(), g),
+                None => transcript.id(),
+            };
+            if let Err(e) = writer.append(header, sequence) {
+                return Err(format!("Can not write transcript '{}': {}", transcript.id(), e));
+            }
+        }
+        writer.flush();
+        match failed {
+            0 => Ok(()),
+            n => Err(format!("Can not extract the sequence of {} transcripts", n)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fasta::IndexedFastaFile;
+    use io::gtf::GtfStream;
+    use model::Transcript;
+    use sequence::GeneticCode;
+    use tool::extract_transcripts::ExtractTranscripts;
+
+    fn extract(sequence_type: &str) -> String {
+        let records = GtfStream::open(&"testdata/toy.gff3").unwrap().read_records().unwrap();
+        let transcripts = Transcript::from_gtf_records(&records).unwrap();
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta.gz").unwrap();
+        let mut out = Vec::new();
+        ExtractTranscripts::extract(&transcripts, &mut fasta, sequence_type, &GeneticCode::standard(), 20, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_extract_mrna() {
+        assert_eq!(
+            extract("mrna"),
+            ">t1 gene=g1\nGCATGTTAGATAAGATAGCG\nTAGGCAGTCAGC\n\
+             >t2 gene=g2\nCGTAGTTGCTCTGTAGACTT\nTTATAAA\n\
+             >t3 gene=g2\nCGTAGTTGCTCTGTAGACT\n"
+        );
+    }
+
+    #[test]
+    fn test_extract_protein() {
+        assert_eq!(extract("cds"), ">t1 gene=g1\nATGTTAGATAAGATAGCGTA\nG\n");
+        assert_eq!(extract("protein"), ">t1 gene=g1\nMLDKIA*\n");
+    }
+}
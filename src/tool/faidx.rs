@@ -0,0 +1,200 @@
+extern crate clap;
+
+use io::compress::{Compression, DecompressReader, GziIndex};
+use io::fai::FaiIndex;
+use io::fasta::{FastaReader, FastaWriter, IndexedFastaFile};
+use std::cmp;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Write;
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+use util;
+
+pub struct Faidx {}
+
+impl Tool for Faidx {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        s.about("Indexes a (BGZF compressed) FASTA file and extracts regions from it")
+            .arg(
+                clap::Arg::with_name("fasta")
+                    .index(1)
+                    .required(true)
+                    .help("The FASTA file to index"),
+            )
+            .arg(
+                clap::Arg::with_name("regions")
+                    .index(2)
+                    .multiple(true)
+                    .help("Regions to extract in the form 'name', 'name:start' or 'name:start-end' (1-based, inclusive)"),
+            )
+            .arg(
+                clap::Arg::with_name("out")
+                    .long("out")
+                    .short("o")
+                    .takes_value(true)
+                    .help("The output file for extracted regions (use standard output if not given)"),
+            )
+            .arg(
+                clap::Arg::with_name("length")
+                    .long("length")
+                    .short("n")
+                    .takes_value(true)
+                    .help("The length of the sequence lines in the output (defaults to 60)"),
+            )
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let filename = args.value_of("fasta").unwrap();
+
+        let index = match Faidx::load_or_build_index(filename) {
+            Ok(index) => index,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+
+        let regions: Vec<&str> = match args.values_of("regions") {
+            Some(r) => r.collect(),
+            None => return,
+        };
+
+        let linelength = match args.value_of("length").unwrap_or("60").parse::<usize>() {
+            Ok(l) if l > 0 => l,
+            Ok(_) => { error!("Line length must be larger than zero"); process::exit(1) }
+            Err(e) => { error!("Can not parse line length: {}", e); process::exit(1) }
+        };
+
+        let result = match args.value_of("out") {
+            Some(out) => {
+                match File::create(out) {
+                    Ok(fh) => Faidx::extract(filename, &index, &regions, linelength, fh),
+                    Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+                }
+            }
+            None => Faidx::extract(filename, &index, &regions, linelength, stdout()),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Faidx {
+    /// Reads the existing index or builds and writes a new one next to the FASTA file.
+    /// For BGZF files, a missing `.gzi` index is written as well.
+    fn load_or_build_index(filename: &str) -> Result<FaiIndex, String> {
+        let compression = match DecompressReader::open(&filename) {
+            Ok(r) => r.compression(),
+            Err(e) => return Err(format!("Can not open '{}' for read: {}", filename, e)),
+        };
+        if compression == Compression::Gzip {
+            return Err(format!("Can not index gzip compressed file '{}', please use bgzip", filename));
+        }
+
+        if let Some(fh) = FaiIndex::discover(&filename) {
+            return Ok(FaiIndex::from(BufReader::new(fh)));
+        }
+
+        debug!("Building FASTA index for '{}'", filename);
+        let index = FaiIndex::build_for(&filename)?;
+        let fai_filename = format!("{}.fai", filename);
+        if let Err(e) = index.write_fai(&fai_filename) {
+            return Err(format!("Can not write FASTA index '{}': {}", fai_filename, e));
+        }
+
+        if compression == Compression::Bgzf && GziIndex::discover(&filename).is_none() {
+            let gzi_filename = format!("{}.gzi", filename);
+            let result = GziIndex::build_for(&filename).and_then(|gzi| gzi.write_gzi(&gzi_filename));
+            if let Err(e) = result {
+                return Err(format!("Can not write BGZF index '{}': {}", gzi_filename, e));
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Writes the sequence of each region. Regions that can not be resolved or fetched
+    /// are reported and skipped, and make the extraction fail once all others are written.
+    fn extract<W: Write>(filename: &str, index: &FaiIndex, regions: &Vec<&str>, linelength: usize, writer: W) -> Result<(), String> {
+        let mut fasta = IndexedFastaFile::open(&filename)?;
+        let mut writer = FastaWriter::from(writer);
+        writer.set_linelength(linelength);
+
+        let mut failed = 0;
+        for region in regions {
+            let (name, offset, length) = match Faidx::parse_region(region, index) {
+                Ok(r) => r,
+                Err(e) => { error!("{}", e); failed += 1; continue }
+            };
+            match fasta.search_region(&name, offset, length) {
+                Some(record) => {
+                    if let Err(e) = writer.append(region, record.sequence()) {
+                        return Err(format!("Can not write region '{}': {}", region, e));
+                    }
+                }
+                None => { error!("Failed to fetch sequence in {}", region); failed += 1 }
+            }
+        }
+        writer.flush();
+        match failed {
+            0 => Ok(()),
+            n => Err(format!("Can not extract {} of {} regions", n, regions.len())),
+        }
+    }
+
+    /// Resolves a region against the index. Sequence names that contain a colon are
+    /// matched as a whole before the region is parsed with `util::parse_region`.
+    /// Returns the sequence name, the 0-based offset and the length clamped to the sequence.
+    fn parse_region(region: &str, index: &FaiIndex) -> Result<(String, usize, usize), String> {
+        if let Some(record) = index.find_record(region) {
+            return Ok((record.name(), 0, record.length()));
+        }
+
+        let (name, interval) = util::parse_region(region)?;
+        let record = match index.find_record(&name) {
+            Some(r) => r,
+            None => return Err(format!("Can not find sequence '{}' in index", name)),
+        };
+
+        let (offset, end) = (interval.start() - 1, cmp::min(interval.end(), record.length()));
+        if end < offset {
+            return Err(format!("Region start beyond the end of '{}' in '{}'", record.name(), region));
+        }
+        Ok((record.name(), offset, end - offset))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fai::FaiIndex;
+    use tool::faidx::Faidx;
+
+    #[test]
+    fn test_parse_region() {
+        let index = FaiIndex::read_fai(&"testdata/toy.fasta.fai").unwrap();
+        assert_eq!(Faidx::parse_region("ref", &index), Ok(("ref".to_string(), 0, 45)));
+        assert_eq!(Faidx::parse_region("ref:10", &index), Ok(("ref".to_string(), 9, 36)));
+        assert_eq!(Faidx::parse_region("ref:10-20", &index), Ok(("ref".to_string(), 9, 11)));
+        assert_eq!(Faidx::parse_region("ref:1,0-2,0", &index), Ok(("ref".to_string(), 9, 11)));
+        assert_eq!(Faidx::parse_region("ref2:30-100", &index), Ok(("ref2".to_string(), 29, 11)));
+        assert!(Faidx::parse_region("ref3:1-10", &index).is_err());
+        assert!(Faidx::parse_region("ref:20-10", &index).is_err());
+    }
+
+    #[test]
+    fn test_extract() {
+        let index = FaiIndex::read_fai(&"testdata/toy.fasta.fai").unwrap();
+        let mut out = Vec::new();
+        assert!(Faidx::extract("testdata/toy.fasta.gz", &index, &vec!["ref2:5-20", "ref:44"], 10, &mut out).is_ok());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            ">ref2:5-20\ntttataaaac\naattaa\n>ref:44\nAT\n"
+        );
+
+        let mut out = Vec::new();
+        assert!(Faidx::extract("testdata/toy.fasta.gz", &index, &vec!["ref3", "ref:44"], 10, &mut out).is_err());
+        assert_eq!(String::from_utf8(out).unwrap(), ">ref:44\nAT\n");
+    }
+}
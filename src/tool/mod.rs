@@ -6,6 +6,24 @@ mod sketch;
 pub use self::sketch::Sketch;
 mod fasta_format;
 pub use self::fasta_format::FastaFormat;
+mod orfs;
+pub use self::orfs::Orfs;
+mod faidx;
+pub use self::faidx::Faidx;
+mod extract_transcripts;
+pub use self::extract_transcripts::ExtractTranscripts;
+mod bed;
+pub use self::bed::Bed;
+mod pileup;
+pub use self::pileup::Pileup;
+mod coverage;
+pub use self::coverage::Coverage;
+mod call;
+pub use self::call::Call;
+mod norm;
+pub use self::norm::Norm;
+mod annotate;
+pub use self::annotate::Annotate;
 
 pub trait Tool {
 
@@ -0,0 +1,267 @@
+extern crate clap;
+
+use io::fasta::IndexedFastaFile;
+use io::vcf::{VcfNormalizer, VcfRecord, VcfStream, VcfWriter};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::io::stdin;
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+
+/// The maximal distance a record is expected to move by left-alignment. Records
+/// are buffered and sorted within this distance (see the `--help` of the tool).
+const SORT_WINDOW: usize = 1000;
+
+/// How records whose reference allele does not match the reference sequence
+/// are handled
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReferenceCheck {
+    Error,
+    Warn,
+    Exclude,
+}
+
+pub struct Norm {}
+
+impl Tool for Norm {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        s.about("Left-aligns and trims the variants of a VCF file against the reference sequence")
+            .arg(
+                clap::Arg::with_name("vcf")
+                    .index(1)
+                    .required(true)
+                    .help("The (possibly compressed) VCF file (use '-' to read from standard input)"),
+            )
+            .arg(
+                clap::Arg::with_name("fasta-ref")
+                    .long("fasta-ref")
+                    .short("f")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The faidx-indexed FASTA file with the reference sequence"),
+            )
+            .arg(
+                clap::Arg::with_name("split")
+                    .long("split")
+                    .short("m")
+                    .help("Split multi-allelic records into one record per alternative allele"),
+            )
+            .arg(
+                clap::Arg::with_name("remove-duplicates")
+                    .long("remove-duplicates")
+                    .short("d")
+                    .help("Write records with the same position and alleles only once"),
+            )
+            .arg(
+                clap::Arg::with_name("check-ref")
+                    .long("check-ref")
+                    .short("c")
+                    .takes_value(true)
+                    .possible_values(&["e", "w", "x"])
+                    .help("Fail (e), warn and keep the record (w), or exclude the record (x) if the reference allele does not match (defaults to e)"),
+            )
+            .arg(
+                clap::Arg::with_name("out")
+                    .long("out")
+                    .short("o")
+                    .takes_value(true)
+                    .help("The output file (use standard output if not given)"),
+            )
+            .after_help(
+                "The input must be sorted by position. Records are sorted again after left-alignment \
+                 only within 1000 positions; records that move further are written out of order and \
+                 reported with a warning.",
+            )
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let fasta_filename = args.value_of("fasta-ref").unwrap();
+        let fasta = match IndexedFastaFile::open(&fasta_filename) {
+            Ok(f) => f,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+        let check = match args.value_of("check-ref").unwrap_or("e") {
+            "w" => ReferenceCheck::Warn,
+            "x" => ReferenceCheck::Exclude,
+            _ => ReferenceCheck::Error,
+        };
+
+        let filename = args.value_of("vcf").unwrap();
+        let result = match filename {
+            "-" => VcfStream::new(stdin()).and_then(|s| Norm::with_input(args, fasta, check, s)),
+            _ => VcfStream::open(&filename).and_then(|s| Norm::with_input(args, fasta, check, s)),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Norm {
+    fn with_input<R: Read>(args: &clap::ArgMatches, fasta: IndexedFastaFile, check: ReferenceCheck, stream: VcfStream<R>) -> Result<(), String> {
+        let normalizer = VcfNormalizer::new(fasta, stream.header()).with_multiallelic_splitting(args.is_present("split"));
+        let remove_duplicates = args.is_present("remove-duplicates");
+        match args.value_of("out") {
+            Some(out) => match File::create(out) {
+                Ok(fh) => Norm::write(normalizer, stream, check, remove_duplicates, SORT_WINDOW, fh),
+                Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+            },
+            None => Norm::write(normalizer, stream, check, remove_duplicates, SORT_WINDOW, stdout()),
+        }
+    }
+
+    /// Normalizes all records of the stream. Records are sorted again after
+    /// left-alignment if they moved less than `window` positions. Records that
+    /// moved further are written out of order with a warning.
+    fn write<R: Read, W: Write>(
+        mut normalizer: VcfNormalizer<IndexedFastaFile>,
+        mut stream: VcfStream<R>,
+        check: ReferenceCheck,
+        remove_duplicates: bool,
+        window: usize,
+        writer: W,
+    ) -> Result<(), String> {
+        let error = |e: ::std::io::Error| format!("Can not write VCF: {}", e);
+        let mut writer = VcfWriter::from(writer);
+        writer.write_header(stream.header()).map_err(&error)?;
+
+        let (mut total, mut changed, mut excluded, mut unsorted) = (0, 0, 0, 0);
+        let mut buffer: Vec<VcfRecord> = Vec::new();
+        let mut last: Option<(String, usize)> = None;
+        let mut append = |writer: &mut VcfWriter<W>, r: &VcfRecord| -> Result<(), String> {
+            if let Some((ref chrom, position)) = last {
+                if *chrom == r.chrom() && r.position() < position {
+                    warn!("Record {}:{} moved more than {} positions and is written out of order", r.chrom(), r.position(), window);
+                    unsorted += 1;
+                }
+            }
+            last = Some((r.chrom(), r.position()));
+            writer.append(r).map_err(&error)
+        };
+        while let Some(record) = stream.next() {
+            let record = record?;
+            total += 1;
+            let normalized = match (normalizer.normalize(&record), check) {
+                (Ok(n), _) => n,
+                (Err(e), ReferenceCheck::Error) => return Err(e),
+                (Err(e), ReferenceCheck::Warn) => {
+                    warn!("{}", e);
+                    vec![record.clone()]
+                }
+                (Err(_), ReferenceCheck::Exclude) => {
+                    excluded += 1;
+                    continue;
+                }
+            };
+            if normalized.len() != 1 || normalized[0] != record {
+                changed += 1;
+            }
+
+            // Records can only move left, so buffered records far enough before
+            // the current input record are final
+            let flush = buffer
+                .iter()
+                .take_while(|r| r.chrom() != record.chrom() || r.position() + window < record.position())
+                .count();
+            for r in buffer.drain(..flush) {
+                append(&mut writer, &r)?;
+            }
+            for r in normalized.into_iter() {
+                let duplicate = remove_duplicates && buffer.iter().any(|b| Norm::is_duplicate(b, &r));
+                if !duplicate {
+                    // Inserts the record after all records on the same template with
+                    // a lower or equal position
+                    let index = buffer
+                        .iter()
+                        .rposition(|b| b.chrom() != r.chrom() || b.position() <= r.position())
+                        .map(|i| i + 1)
+                        .unwrap_or(0);
+                    buffer.insert(index, r);
+                }
+            }
+        }
+        for r in buffer.iter() {
+            append(&mut writer, r)?;
+        }
+        if unsorted > 0 {
+            warn!("{} records are not sorted by position", unsorted);
+        }
+        info!("Normalized {} records: {} changed or split, {} excluded", total, changed, excluded);
+        writer.flush().map_err(&error)
+    }
+
+    /// Returns `true` if both records describe the same variant
+    fn is_duplicate(a: &VcfRecord, b: &VcfRecord) -> bool {
+        a.chrom() == b.chrom() && a.position() == b.position() &&
+            a.reference_allele().to_uppercase() == b.reference_allele().to_uppercase() &&
+            a.alternative_alleles().iter().map(|a| a.to_uppercase()).collect::<Vec<String>>() ==
+                b.alternative_alleles().iter().map(|a| a.to_uppercase()).collect::<Vec<String>>()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::fasta::IndexedFastaFile;
+    use io::vcf::{VcfNormalizer, VcfStream};
+    use tool::norm::{Norm, ReferenceCheck, SORT_WINDOW};
+
+    fn normalize(vcf: &str, check: ReferenceCheck, remove_duplicates: bool) -> Result<Vec<String>, String> {
+        normalize_within(vcf, check, remove_duplicates, SORT_WINDOW)
+    }
+
+    fn normalize_within(vcf: &str, check: ReferenceCheck, remove_duplicates: bool, window: usize) -> Result<Vec<String>, String> {
+        let stream = VcfStream::new(vcf.as_bytes()).unwrap();
+        let fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let normalizer = VcfNormalizer::new(fasta, stream.header()).with_multiallelic_splitting(true);
+        let mut out = Vec::new();
+        Norm::write(normalizer, stream, check, remove_duplicates, window, &mut out)?;
+        Ok(String::from_utf8(out).unwrap().lines().map(|l| l.to_string()).collect())
+    }
+
+    #[test]
+    fn test_write() {
+        let vcf = "##fileformat=VCFv4.2\n\
+                   #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+                   ref\t12\t.\tTA\tT\t.\t.\t.\n\
+                   ref\t13\t.\tAAG\tAG,ACG\t.\t.\t.\n\
+                   ref\t16\t.\tC\tT\t.\t.\t.\n\
+                   ref\t22\t.\tG\tGTG\t.\t.\t.\n\
+                   ref2\t1\t.\tA\tT\t.\t.\t.\n";
+        let lines = normalize(vcf, ReferenceCheck::Warn, true).unwrap();
+        let records: Vec<&str> = lines.iter().skip(2).map(|l| l.as_str()).collect();
+        assert_eq!(
+            records,
+            vec![
+                "ref\t12\t.\tTA\tT\t.\t.\t.",
+                "ref\t14\t.\tA\tC\t.\t.\t.",
+                "ref\t16\t.\tC\tT\t.\t.\t.",
+                "ref\t20\t.\tC\tCTG\t.\t.\t.",
+                "ref2\t1\t.\tA\tT\t.\t.\t.",
+            ]
+        );
+        assert_eq!(lines[1], "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO");
+
+        let lines = normalize(vcf, ReferenceCheck::Exclude, false).unwrap();
+        assert_eq!(lines.len(), 2 + 5);
+        assert!(lines.iter().all(|l| !l.starts_with("ref\t16")));
+        assert!(normalize(vcf, ReferenceCheck::Error, false).is_err());
+    }
+
+    #[test]
+    fn test_sort_window() {
+        // The insertion moves from 22 to 20 and is sorted only within a window of 2
+        let vcf = "##fileformat=VCFv4.2\n\
+                   #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+                   ref\t21\t.\tT\tA\t.\t.\t.\n\
+                   ref\t22\t.\tG\tGTG\t.\t.\t.\n";
+        let positions = |window: usize| -> Vec<String> {
+            let lines = normalize_within(vcf, ReferenceCheck::Error, false, window).unwrap();
+            lines.iter().skip(2).map(|l| l.split('\t').nth(1).unwrap().to_string()).collect()
+        };
+        assert_eq!(positions(2), vec!["20", "21"]);
+        assert_eq!(positions(0), vec!["21", "20"]);
+    }
+}
@@ -0,0 +1,178 @@
+extern crate clap;
+
+use io::bed::BedRecord;
+use io::fasta::{FastaStream, FastaWriter};
+use model::{Region, Strand};
+use sequence::GeneticCode;
+use sequence::orf::{OpenReadingFrame, OrfFinder};
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::io::stdin;
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+
+pub struct Orfs {}
+
+impl Tool for Orfs {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        s.about("Searches open reading frames in all six frames of FASTA sequences")
+            .arg(
+                clap::Arg::with_name("in")
+                    .long("in")
+                    .short("i")
+                    .takes_value(true)
+                    .help("The input FASTA file (use standard input if not given)"),
+            )
+            .arg(
+                clap::Arg::with_name("out")
+                    .long("out")
+                    .short("o")
+                    .takes_value(true)
+                    .help("The output file (use standard output if not given)"),
+            )
+            .arg(
+                clap::Arg::with_name("format")
+                    .long("format")
+                    .short("f")
+                    .takes_value(true)
+                    .possible_values(&["bed", "fasta"])
+                    .help("The output format (defaults to bed)"),
+            )
+            .arg(
+                clap::Arg::with_name("min-length")
+                    .long("min-length")
+                    .short("l")
+                    .takes_value(true)
+                    .help("The minimal ORF length in nucleotides including the stop codon (defaults to 75)"),
+            )
+            .arg(
+                clap::Arg::with_name("table")
+                    .long("table")
+                    .short("t")
+                    .takes_value(true)
+                    .value_name("N")
+                    .help("The NCBI translation table defining start and stop codons (defaults to 1)"),
+            )
+            .arg(
+                clap::Arg::with_name("partial")
+                    .long("partial")
+                    .help("Report ORFs that run off the sequence ends"),
+            )
+            .arg(
+                clap::Arg::with_name("translate")
+                    .long("translate")
+                    .help("Write the translated peptides instead of nucleotides (FASTA only)"),
+            )
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let code = match args.value_of("table").unwrap_or("1").parse::<usize>() {
+            Ok(id) => {
+                match GeneticCode::from_ncbi_id(id) {
+                    Ok(code) => code,
+                    Err(e) => { error!("{}", e); process::exit(1) }
+                }
+            }
+            Err(e) => { error!("Can not parse translation table: {}", e); process::exit(1) }
+        };
+        let min_length = match args.value_of("min-length").unwrap_or("75").parse::<usize>() {
+            Ok(l) => l,
+            Err(e) => { error!("Can not parse minimal length: {}", e); process::exit(1) }
+        };
+        let finder = OrfFinder::new(code)
+            .with_min_length(min_length)
+            .with_partial(args.is_present("partial"));
+
+        match args.value_of("in") {
+            Some(filename) => {
+                match File::open(filename) {
+                    Ok(fh) => Orfs::with_input(args, &finder, fh),
+                    Err(e) => { error!("Can not open '{}' for read: {}", filename, e); process::exit(1) }
+                }
+            }
+            None => Orfs::with_input(args, &finder, stdin()),
+        }
+    }
+}
+
+impl Orfs {
+    fn with_input<R: Read>(args: &clap::ArgMatches, finder: &OrfFinder, reader: R) {
+        match args.value_of("out") {
+            Some(filename) => {
+                match File::create(filename) {
+                    Ok(fh) => Orfs::with_input_and_output(args, finder, reader, fh),
+                    Err(e) => { error!("Can not open '{}' for write: {}", filename, e); process::exit(1) }
+                }
+            }
+            None => Orfs::with_input_and_output(args, finder, reader, stdout()),
+        }
+    }
+
+    fn with_input_and_output<R: Read, W: Write>(
+        args: &clap::ArgMatches,
+        finder: &OrfFinder,
+        reader: R,
+        writer: W,
+    ) {
+        let records = FastaStream::from(reader);
+        match args.value_of("format") {
+            Some("fasta") => {
+                Orfs::write_fasta(finder, records, FastaWriter::from(writer), args.is_present("translate"))
+            }
+            _ => Orfs::write_bed(finder, records, writer),
+        }
+    }
+
+    fn write_bed<R: Read, W: Write>(finder: &OrfFinder, records: FastaStream<R>, mut writer: W) {
+        for record in records {
+            let orfs = finder.find(&record.name(), &record.as_dna());
+            debug!("Found {} ORFs on '{}'", orfs.len(), record.name());
+
+            for (i, orf) in orfs.iter().enumerate() {
+                let name = format!("{}_orf{}", record.name(), i + 1);
+                if let Err(e) = write!(writer, "{}\n", Orfs::as_bed_record(&name, orf)) {
+                    error!("Can not write ORF: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    fn write_fasta<R: Read, W: Write>(
+        finder: &OrfFinder,
+        records: FastaStream<R>,
+        mut writer: FastaWriter<W>,
+        translate: bool,
+    ) {
+        for record in records {
+            let orfs = finder.find(&record.name(), &record.as_dna());
+            debug!("Found {} ORFs on '{}'", orfs.len(), record.name());
+
+            for (i, orf) in orfs.iter().enumerate() {
+                let header = format!("{}_orf{} {}", record.name(), i + 1, orf);
+                let result = match translate {
+                    true => writer.append(header, orf.peptide(&finder.genetic_code())),
+                    false => writer.append(header, orf.sequence()),
+                };
+                if let Err(e) = result {
+                    error!("Can not write ORF: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        writer.flush();
+    }
+
+    fn as_bed_record(name: &String, orf: &OpenReadingFrame) -> BedRecord {
+        let strand = match orf.strand() {
+            Strand::Forward => '+',
+            Strand::Backward => '-',
+        };
+        BedRecord::new(&orf.template(), orf.offset(), orf.end())
+            .with_name(name)
+            .with_score(0f64)
+            .with_strand(strand)
+    }
+}
@@ -0,0 +1,158 @@
+extern crate clap;
+
+use io::bam::IndexedBamReader;
+use io::fasta::IndexedFastaFile;
+use model::{Region, SimpleRegion};
+use pileup::Pileup as PileupEngine;
+use std::cmp;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::io::stdout;
+use std::process;
+use tool::Tool;
+use util;
+
+/// The number of positions that are piled up at once
+const WINDOW_SIZE: usize = 100000;
+
+pub struct Pileup {}
+
+impl Tool for Pileup {
+    fn args<'a, 'b>(s: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+        let value = |name: &'a str, short: &'a str, help: &'a str| {
+            clap::Arg::with_name(name)
+                .long(name)
+                .short(short)
+                .takes_value(true)
+                .help(help)
+        };
+        s.about("Writes the pileup of an indexed BAM file in the format of samtools mpileup")
+            .arg(
+                clap::Arg::with_name("bam")
+                    .index(1)
+                    .required(true)
+                    .help("The indexed BAM file"),
+            )
+            .arg(
+                value("region", "r", "Pile up the region 'name' or 'name:start-end' (1-based, inclusive; defaults to all templates)")
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .arg(value("fasta-ref", "f", "The faidx-indexed FASTA file with the reference sequence"))
+            .arg(value("min-BQ", "Q", "Skip bases with a lower base quality (defaults to 13)"))
+            .arg(value("min-MQ", "q", "Skip reads with a lower mapping quality (defaults to 0)"))
+            .arg(
+                clap::Arg::with_name("ignore-overlaps")
+                    .long("ignore-overlaps")
+                    .short("x")
+                    .help("Count both mates of a pair where they overlap"),
+            )
+            .arg(
+                clap::Arg::with_name("all")
+                    .long("all")
+                    .short("a")
+                    .help("Write positions without reads as well"),
+            )
+            .arg(value("out", "o", "The output file (use standard output if not given)"))
+    }
+
+    fn run(args: &clap::ArgMatches) {
+        let quality = |name: &str, default: u8| match args.value_of(name) {
+            Some(v) => v.parse::<u8>().map_err(|e| format!("Can not parse --{} '{}': {}", name, v, e)),
+            None => Ok(default),
+        };
+        let engine = match (quality("min-BQ", 13), quality("min-MQ", 0)) {
+            (Ok(bq), Ok(mq)) => PileupEngine::new()
+                .with_min_base_quality(bq)
+                .with_min_mapping_quality(mq)
+                .with_overlap_detection(!args.is_present("ignore-overlaps")),
+            (Err(e), _) | (_, Err(e)) => { error!("{}", e); process::exit(1) }
+        };
+
+        let filename = args.value_of("bam").unwrap();
+        let mut bam = match IndexedBamReader::open(&filename) {
+            Ok(b) => b,
+            Err(e) => { error!("{}", e); process::exit(1) }
+        };
+        let mut fasta = match args.value_of("fasta-ref").map(|f| IndexedFastaFile::open(&f)) {
+            Some(Ok(f)) => Some(f),
+            Some(Err(e)) => { error!("{}", e); process::exit(1) }
+            None => None,
+        };
+
+        let regions = match args.values_of("region") {
+            Some(r) => match r.map(|r| util::parse_region(r).map(|(name, interval)| SimpleRegion::from_interval(name, interval))).collect::<Result<Vec<SimpleRegion>, String>>() {
+                Ok(r) => r,
+                Err(e) => { error!("{}", e); process::exit(1) }
+            },
+            None => bam.dictionary().regions(),
+        };
+
+        let result = match args.value_of("out") {
+            Some(out) => match File::create(out) {
+                Ok(fh) => Pileup::write(&engine, &mut bam, fasta.as_mut(), &regions, args.is_present("all"), fh),
+                Err(e) => Err(format!("Can not open '{}' for write: {}", out, e)),
+            },
+            None => Pileup::write(&engine, &mut bam, fasta.as_mut(), &regions, args.is_present("all"), stdout()),
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+impl Pileup {
+    /// Writes the pileup of the regions window by window
+    fn write<W: Write>(
+        engine: &PileupEngine,
+        bam: &mut IndexedBamReader,
+        mut fasta: Option<&mut IndexedFastaFile>,
+        regions: &[SimpleRegion],
+        all: bool,
+        writer: W,
+    ) -> Result<(), String> {
+        let mut writer = BufWriter::new(writer);
+        for region in regions.iter() {
+            let region = bam.dictionary().clamp(region)?;
+            let mut offset = region.offset();
+            while offset < region.end() {
+                let window = SimpleRegion::new(region.template(), offset, cmp::min(WINDOW_SIZE, region.end() - offset));
+                let columns = match fasta {
+                    Some(ref mut f) => engine.from_bam(bam, &window, Some(&mut **f))?,
+                    None => engine.from_bam::<_, IndexedFastaFile>(bam, &window, None)?,
+                };
+                for column in columns.iter().filter(|c| all || c.depth() > 0) {
+                    if let Err(e) = writeln!(writer, "{}", column) {
+                        return Err(format!("Can not write pileup: {}", e));
+                    }
+                }
+                offset = window.end();
+            }
+        }
+        writer.flush().map_err(|e| format!("Can not write pileup: {}", e))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use io::bam::IndexedBamReader;
+    use io::fasta::IndexedFastaFile;
+    use model::SimpleRegion;
+    use pileup::Pileup as PileupEngine;
+    use tool::pileup::Pileup;
+
+    #[test]
+    fn test_write() {
+        let mut bam = IndexedBamReader::open(&"testdata/toy.bam").unwrap();
+        let mut fasta = IndexedFastaFile::open(&"testdata/toy.fasta").unwrap();
+        let regions = vec![SimpleRegion::new("ref2", 0, 3), SimpleRegion::new("ref", 43, 10)];
+        let mut out = Vec::new();
+        Pileup::write(&PileupEngine::new(), &mut bam, Some(&mut fasta), &regions, false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ref2\t1\tA\t1\t^?.\t?\nref2\t2\tG\t2\t.^?.\t??\nref2\t3\tG\t2\t..\t??\nref\t44\tA\t1\t,\t~\nref\t45\tT\t1\t,$\t~\n"
+        );
+    }
+}
@@ -7,9 +7,11 @@ use std::fs::File;
 use std::fmt::Display;
 
 
+use io::bam::{AlignmentRecord,IndexedBamReader,SamStream};
 use io::bed::*;
-use model::{Region,SimpleRegion};
+use model::{Region,SequenceDictionary,SimpleRegion};
 use io::fasta::{FastaReader,IndexedFastaFile};
+use io::vcf::{IndexedVcfFile,VcfRecord,VcfStream};
 use sequence::aminoacid::*;
 use sequence::dna::*;
 
@@ -76,11 +78,11 @@ impl Tool for Sketch {
 
     fn run(args: &clap::ArgMatches) {
         // Check for a given region
-        let (template, offset, length) = match util::parse_region_string(args.value_of("region").unwrap()) {
+        let (template, interval) = match util::parse_region(args.value_of("region").unwrap()) {
                 Ok(a) => a,
                 Err(e) => { error!("Can not parse region string '{}': {}", args.value_of("region").unwrap(), e); return },
         };
-        let mut region = SimpleRegion::new(template, offset, length);
+        let mut region = SimpleRegion::from_interval(template, interval);
         debug!("Start visualization of region: {}", region);
 
         // Load the reference sequence
@@ -88,14 +90,18 @@ impl Tool for Sketch {
             None => { error!("Did not found reference file parameter"); return; },
             Some(s) => s
         };
+        let dictionary = match SequenceDictionary::for_fasta(&reference_filename) {
+                Err(e) => { error!("{}", e); return }
+                Ok(d) => d
+        };
+        region = match dictionary.clamp(&region) {
+                Err(e) => { error!("{}", e); return }
+                Ok(r) => r
+        };
         let reference = match Self::load_reference_sequence(&reference_filename, &region) {
                 Err(e) => { error!("{}", e); return }
                 Ok(r) => r
         };
-
-        if reference.length() < region.length() {
-            region = SimpleRegion::new(region.template(), region.offset(), reference.length())
-        }
        
         //moz-extension://e94c6c94-63b8-4a7f-aadf-380f4931c605/main-blocked.html?details=eyJ1cmwiOiJodHRwOi8vYml0LmRvLzNEQmlvbG9neSIsImhuIjoiYml0LmRvIiwid2h5IjoiPyJ9 Create the drawing
         let mut drawing = sketch::Sketch::new(sketch::canvas::Svg::new(region.clone()));
@@ -169,11 +175,66 @@ impl Sketch {
         return Ok(seq);
     }
 
+    /// Reads the VCF records in the region using the tabix index if available.
+    fn load_vcf_records<P: AsRef<Path> + Display, R: Region>(filename: &P, region: &R) -> Result<Vec<VcfRecord>,String> {
+        if let Ok(vcf) = IndexedVcfFile::open(filename) {
+            return vcf.query(region);
+        }
+
+        let mut records = Vec::new();
+        for record in VcfStream::open(filename)? {
+            let record = record?;
+            if region.overlaps(&record) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Converts a VCF record into a BED record named by its ID or alleles.
+    fn vcf_as_bed_record(record: &VcfRecord) -> BedRecord {
+        let name = match record.ids().first() {
+            Some(id) => id.clone(),
+            None => format!("{}>{}", record.reference_allele(), record.alternative_alleles().join(",")),
+        };
+        BedRecord::new(&record.chrom(), Region::offset(record), Region::end(record)).with_name(&name)
+    }
+
+    /// Reads the aligned reads in the region from an indexed BAM file or a SAM file.
+    fn load_alignment_records<P: AsRef<Path> + Display, R: Region>(filename: &P, region: &R) -> Result<Vec<AlignmentRecord>,String> {
+        if filename.to_string().ends_with("bam") {
+            return IndexedBamReader::open(filename)?.query(region);
+        }
+
+        let mut records = Vec::new();
+        for record in SamStream::open(filename)? {
+            let record = record?;
+            if !record.is_unmapped() && region.overlaps(&record) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Converts an aligned read into a BED record named by the read name.
+    fn alignment_as_bed_record(record: &AlignmentRecord) -> BedRecord {
+        let strand = match record.is_reverse() {
+            true => '-',
+            false => '+',
+        };
+        BedRecord::new(&record.template(), Region::offset(record), Region::end(record))
+            .with_name(&record.name())
+            .with_strand(strand)
+    }
+
     fn draw_from_file<P: AsRef<Path> + Display, C: sketch::Canvas, R: Region>(mut drawing: sketch::Sketch<C>, region: &R, filename: &P) -> sketch::Sketch<C> {
         let fss = filename.to_string();
 
-        if fss.ends_with("bam") {
-            error!("BAM visualization not yet implemented: {}", fss);
+        if fss.ends_with("bam") || fss.ends_with("sam") || fss.ends_with("sam.gz") {
+            match Sketch::load_alignment_records(filename, region) {
+                Ok(records) => drawing.append_bed_records(records.iter().map(|r| Sketch::alignment_as_bed_record(r)).collect()),
+                Err(e) => error!("Can not read alignments from '{}': {}", fss, e)
+            }
         }
         else if fss.ends_with("bed") || fss.ends_with("bed.gz") {
             match BedStream::open(fss.clone()) {
@@ -182,7 +243,10 @@ impl Sketch {
             }
         }
         else if fss.ends_with("vcf") || fss.ends_with("vcf.gz") {
-            error!("VCF visualization not yet implemented: {}", fss);
+            match Sketch::load_vcf_records(filename, region) {
+                Ok(records) => drawing.append_bed_records(records.iter().map(|r| Sketch::vcf_as_bed_record(r)).collect()),
+                Err(e) => error!("Can not read VCF records from '{}': {}", fss, e)
+            }
         }
         else {
             error!("Don't know how to visualize file: {}", fss);
@@ -36,6 +36,23 @@ impl Tool for Translate {
                         "The output destination. If omitted or \"-\" will write to stdout",
                     ),
             )
+            .arg(
+                clap::Arg::with_name("table")
+                    .long("table")
+                    .short("t")
+                    .takes_value(true)
+                    .value_name("N")
+                    .help(
+                        "The NCBI translation table to use (defaults to 1, the standard code)",
+                    ),
+            )
+            .arg(
+                clap::Arg::with_name("cds")
+                    .long("cds")
+                    .help(
+                        "Translate an alternative start codon at the first position into methionine",
+                    ),
+            )
     }
 
     fn run(matches: &clap::ArgMatches) {
@@ -58,6 +75,26 @@ impl Tool for Translate {
 
 impl Translate {
     fn run_with_output<W: Write>(matches: &clap::ArgMatches, output: W) {
+        let code = match matches.value_of("table") {
+            None => GeneticCode::standard(),
+            Some(s) => {
+                match s.parse::<usize>() {
+                    Ok(id) => {
+                        match GeneticCode::from_ncbi_id(id) {
+                            Ok(code) => code,
+                            Err(e) => panic!("Can not use translation table: {}", e),
+                        }
+                    }
+                    Err(e) => panic!("Can not parse translation table '{}': {}", s, e),
+                }
+            }
+        };
+        debug!("Using translation table: {}", code);
+        let translator = Translator {
+            code: code,
+            is_cds: matches.is_present("cds"),
+        };
+
         // Check for input sequence first
         match matches.value_of("in") {
             Some(filename) => {
@@ -65,12 +102,12 @@ impl Translate {
                 match filename {
                     "-" => {
                         debug!("Reading from standard input");
-                        Translate::translate_read_write(stdin(), output);
+                        translator.translate_read_write(stdin(), output);
                     }
                     _ => {
                         debug!("Try to read from file: {}", filename);
                         match File::open(filename) {
-                            Ok(fh) => Translate::translate_read_write(fh, output),
+                            Ok(fh) => translator.translate_read_write(fh, output),
                             Err(e) => panic!("Can not open '{}' for read: {}", filename, e),
                         }
                     }
@@ -82,20 +119,35 @@ impl Translate {
                     Some(seqstring) => {
                         debug!("Found sequence with value {}", seqstring);
                         match seqstring {
-                            "-" => Translate::translate_read_write(stdin(), output),
-                            _ => Translate::translate_string(&seqstring, output),
+                            "-" => translator.translate_read_write(stdin(), output),
+                            _ => translator.translate_string(&seqstring, output),
                         }
                     }
-                    None => Translate::translate_read_write(stdin(), output),
+                    None => translator.translate_read_write(stdin(), output),
                 }
             }
         }
     }
+}
+
+/// Translates DNA sequences using a given genetic code.
+struct Translator {
+    code: GeneticCode,
+    is_cds: bool,
+}
+
+impl Translator {
+    fn translate(&self, dna: &DnaSequence) -> Peptide {
+        match self.is_cds {
+            true => Peptide::translate_cds(dna, &self.code),
+            false => Peptide::translate(dna, &self.code),
+        }
+    }
 
-    fn translate_string<S: ToString, W: Write>(sequence: &S, mut output: W) {
+    fn translate_string<S: ToString, W: Write>(&self, sequence: &S, mut output: W) {
         match DnaSequence::from_str(sequence.to_string().as_ref()) {
             Ok(dna) => {
-                match write!(output, "{}\n", Peptide::from(dna)) {
+                match write!(output, "{}\n", self.translate(&dna)) {
                     Ok(_) => {}
                     Err(e) => panic!("Error: {}", e),
                 }
@@ -104,8 +156,8 @@ impl Translate {
         }
     }
 
-    fn translate_read_write<R: Read, W: Write>(input: R, output: W) {
-        Translate::translate_fasta(
+    fn translate_read_write<R: Read, W: Write>(&self, input: R, output: W) {
+        self.translate_fasta(
             fasta::FastaStream::from(input),
             fasta::FastaWriter::from(output),
         );
@@ -113,11 +165,12 @@ impl Translate {
 
     // Translates a FASTA source into a FASTA destination
     fn translate_fasta<R: Read, W: Write>(
+        &self,
         input: fasta::FastaStream<R>,
         mut output: fasta::FastaWriter<W>,
     ) {
         for record in input {
-            match output.append(record.header(), Peptide::from(record.as_dna())) {
+            match output.append(record.header(), self.translate(&record.as_dna())) {
                 Ok(_) => {}
                 Err(e) => panic!("Can not write: {}", e),
             }
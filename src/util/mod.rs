@@ -1,5 +1,8 @@
 pub mod matrix;
 
+use model::OneBasedInterval;
+use std::usize;
+
 pub fn split<S: ToString, P: ToString>(record: S, cell_separator: P) -> Vec<String> {
     record
         .to_string()
@@ -17,75 +20,55 @@ pub fn join<S1: ToString, S2: ToString>(l: Vec<S1>, sep: S2) -> String {
 }
 
 
-/// Parses a region identifer string in the form of
-/// ```
-///  template_name:position
-///  template_name:start.position-end.postion
-/// ```
-/// Thereby, the start and end region 
-pub fn parse_region_string<S: ToString>(s: S) -> Result<(String, usize, usize), String> {
-    let parts = s.to_string()
-        .split(|c| c == ':' || c == '-')
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>();
-
-    let name = parts[0].to_string();
-
-    let offset = match parts.len() > 1 {
-        true => match parts[1].parse::<usize>() {
-            Ok(o) => o - 1,
-            Err(e) => return Err(format!("Can not parse start part '{}': {}", parts[1], e))
-        }, 
-        false => 0
+/// Parses a region like samtools: a plain template name for the whole template,
+/// `name:start` up to the end of the template or `name:start-end`. Coordinates
+/// are 1-based and inclusive and may contain commas. Regions that reach to the
+/// end of the template end at `usize::MAX` and need to be clamped to its length.
+pub fn parse_region(s: &str) -> Result<(String, OneBasedInterval), String> {
+    let (name, range) = match s.rfind(':') {
+        Some(pos) => (&s[..pos], s[pos + 1..].replace(",", "")),
+        None => return OneBasedInterval::new(1, usize::MAX).map(|interval| (s.to_string(), interval)),
     };
 
-    let length = match parts.len() > 2 {
-        true  => match parts[2].parse::<usize>() {
-            Ok(end) => match offset < end {
-                true => end - offset,
-                false => return Err(format!("Region end after start '{}'", parts[2])),
-            },
-            Err(e) => return Err(format!("Can not parse start part '{}': {}", parts[2], e))
-        },
-        false => 1usize
+    let parse = |c: &str, default: usize| -> Result<usize, String> {
+        match c.is_empty() {
+            true => Ok(default),
+            false => c.parse::<usize>().map_err(|e| format!("Can not parse coordinate '{}' in region '{}': {}", c, s, e)),
+        }
+    };
+    let (start, end) = match range.find('-') {
+        Some(pos) => (parse(&range[..pos], 1)?, parse(&range[pos + 1..], usize::MAX)?),
+        None => (parse(&range, 1)?, usize::MAX),
     };
 
-    Ok((name, offset, length))
+    if start == 0 {
+        return Err(format!("Region start must be at least 1 in '{}'", s));
+    }
+    if end < start {
+        return Err(format!("Region end before start in '{}'", s));
+    }
+    OneBasedInterval::new(start, end).map(|interval| (name.to_string(), interval))
 }
 
 
-
-
 #[cfg(test)]
 mod tests {
-    use util::parse_region_string;
-
-    #[test]
-    fn test_parse_region_string_single_coordinate() {
-        let (r,o,l) = parse_region_string("chr1:100").expect("Can not parse");
-        assert_eq!(r, "chr1", "Correct template name");
-        assert_eq!(o, 99    , "Correct offset");
-        assert_eq!(l,  1    , "Correct length");
-    }
-
-    #[test]
-    fn test_parse_region_string_two_coordinate() {
-        let (r,o,l) = parse_region_string("chr1:200-300").expect("Can not parse");
-        assert_eq!(r, "chr1", "Correct template name");
-        assert_eq!(o,    199, "Correct offset");
-        assert_eq!(l,    101, "Correct length");
-    }
-
-    #[test]
-    fn test_parse_region_string_end_equal_start() {
-        let (r,o,l) = parse_region_string("chr1:200-200").expect("Can not parse");
-        assert_eq!(r, "chr1", "Correct template name");
-        assert_eq!(o,    199, "Correct offset");
-        assert_eq!(l,      1, "Correct length");
-    }
+    use std::usize;
+    use util::parse_region;
 
     #[test]
-    fn test_parse_region_string_end_before_start() {
-        assert!( parse_region_string("chr1:200-199").is_err() );
+    fn test_parse_region() {
+        let region = |s: &str| parse_region(s).map(|(name, i)| (name, i.start(), i.end())).unwrap();
+        assert_eq!(region("chr1"), ("chr1".to_string(), 1, usize::MAX));
+        assert_eq!(region("chr1:100"), ("chr1".to_string(), 100, usize::MAX));
+        assert_eq!(region("chr1:200-300"), ("chr1".to_string(), 200, 300));
+        assert_eq!(region("chr1:200-200"), ("chr1".to_string(), 200, 200));
+        assert_eq!(region("chr1:1,000-2,000"), ("chr1".to_string(), 1000, 2000));
+        assert_eq!(region("HLA-A*01:01:1-10"), ("HLA-A*01:01".to_string(), 1, 10));
+
+        assert!(parse_region("chr1:0-10").is_err());
+        assert!(parse_region("chr1:20-10").is_err());
+        assert!(parse_region("chr1:200-199").is_err());
+        assert!(parse_region("chr1:x-10").is_err());
     }
 }